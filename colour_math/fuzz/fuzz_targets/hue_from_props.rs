@@ -0,0 +1,19 @@
+#![no_main]
+use std::convert::TryFrom;
+
+use libfuzzer_sys::fuzz_target;
+
+use colour_math::{fdrn::Prop, hcv::HCV, ColourBasics, Hue};
+
+fuzz_target!(|data: [u64; 3]| {
+    let props = [
+        Prop::from(data[0]),
+        Prop::from(data[1]),
+        Prop::from(data[2]),
+    ];
+    let _ = Hue::try_from(props);
+    let hcv = HCV::from(&props);
+    let _ = hcv.rgb::<u8>();
+    let _ = hcv.hue_angle();
+    let _ = hcv.chroma();
+});
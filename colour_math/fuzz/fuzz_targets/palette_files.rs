@@ -0,0 +1,23 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use colour_math::palette_io::{aco, ase, gif, png, session};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = png::palette_from_bytes(data);
+    let _ = gif::palette_from_bytes(data);
+    // whatever was read must survive being written and read back
+    if let Ok(palette) = ase::palette_from_bytes(data) {
+        assert!(ase::palette_from_bytes(&ase::palette_to_bytes(&palette)).is_ok());
+    }
+    if let Ok(palette) = aco::palette_from_bytes(data) {
+        assert!(aco::palette_from_bytes(&aco::palette_to_bytes(&palette)).is_ok());
+    }
+    if let Ok(text) = std::str::from_utf8(data) {
+        if let Ok(palettes) = session::session_from_str(text) {
+            // whatever was read must survive being written and read back
+            let text = session::session_to_string(&palettes);
+            assert!(session::session_from_str(&text).is_ok());
+        }
+    }
+});
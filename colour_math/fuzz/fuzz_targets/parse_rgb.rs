@@ -0,0 +1,18 @@
+#![no_main]
+use std::str::FromStr;
+
+use libfuzzer_sys::fuzz_target;
+
+use colour_math::{hcv::HCV, ColourBasics, RGB};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        if let Ok(rgb) = RGB::<u8>::from_str(text) {
+            let hcv = HCV::from(rgb);
+            assert_eq!(hcv.rgb::<u8>(), rgb);
+        }
+        if let Ok(rgb) = RGB::<u16>::from_str(text) {
+            let _ = HCV::from(rgb).pango_string();
+        }
+    }
+});
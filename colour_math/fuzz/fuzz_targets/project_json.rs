@@ -0,0 +1,22 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use colour_math::{project::Project, ColourBasics};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        if let Ok(project) = Project::from_json(text) {
+            for series in project.series() {
+                for paint in series.paints() {
+                    let _ = paint.colour().rgb::<u16>();
+                }
+            }
+            for mixture in project.mixtures() {
+                let _ = project.mixed_colour(mixture);
+            }
+            for target in project.targets() {
+                let _ = target.colour().rgb::<u16>();
+            }
+        }
+    }
+});
@@ -0,0 +1,258 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    attributes::{Chroma, Value},
+    fdrn::Prop,
+    hcv::HCV,
+    hue::Hue,
+    rgb::RGB,
+    ColourBasics, LightLevel,
+};
+
+// Porter-Duff "over" on straight (i.e. not premultiplied) f64 components
+fn over(source: ([f64; 3], f64), background: ([f64; 3], f64)) -> ([f64; 3], f64) {
+    let (source_rgb, source_alpha) = source;
+    let (background_rgb, background_alpha) = background;
+    let alpha = source_alpha + background_alpha * (1.0 - source_alpha);
+    if alpha <= 0.0 {
+        return ([0.0; 3], 0.0);
+    }
+    let rgb = [0, 1, 2].map(|i| {
+        let component = source_rgb[i] * source_alpha
+            + background_rgb[i] * background_alpha * (1.0 - source_alpha);
+        (component / alpha).clamp(0.0, 1.0)
+    });
+    (rgb, alpha.clamp(0.0, 1.0))
+}
+
+// Interpolate premultiplied components so that fully transparent colours
+// don't contribute their (invisible) colour.
+fn blend(lhs: ([f64; 3], f64), rhs: ([f64; 3], f64), prop: f64) -> ([f64; 3], f64) {
+    let alpha = lhs.1 + (rhs.1 - lhs.1) * prop;
+    if alpha <= 0.0 {
+        return ([0.0; 3], 0.0);
+    }
+    let rgb = [0, 1, 2].map(|i| {
+        let lhs_c = lhs.0[i] * lhs.1;
+        let rhs_c = rhs.0[i] * rhs.1;
+        ((lhs_c + (rhs_c - lhs_c) * prop) / alpha).clamp(0.0, 1.0)
+    });
+    (rgb, alpha.clamp(0.0, 1.0))
+}
+
+/// An `RGB` with an alpha (opacity) channel. The colour components are not
+/// premultiplied and the `ColourBasics` attributes are those of the opaque
+/// colour.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Default)]
+pub struct RGBA<L: LightLevel> {
+    pub rgb: RGB<L>,
+    pub alpha: Prop,
+}
+
+impl<L: LightLevel> Eq for RGBA<L> where L: Eq {}
+
+impl<L: LightLevel> RGBA<L> {
+    pub fn new(rgb: RGB<L>, alpha: Prop) -> Self {
+        Self { rgb, alpha }
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        self.alpha == Prop::ONE
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.alpha == Prop::ZERO
+    }
+
+    fn components(&self) -> ([f64; 3], f64) {
+        let rgb = self.rgb.rgb::<f64>();
+        ([rgb[0], rgb[1], rgb[2]], f64::from(self.alpha))
+    }
+
+    fn from_components(components: ([f64; 3], f64)) -> Self {
+        Self {
+            rgb: RGB::<f64>::from(components.0).rgb::<L>(),
+            alpha: Prop::from(components.1),
+        }
+    }
+
+    /// `self` composited over `background`.
+    pub fn over(&self, background: &Self) -> Self {
+        Self::from_components(over(self.components(), background.components()))
+    }
+
+    /// The opaque colour seen when `self` is composited over `background`.
+    pub fn over_opaque(&self, background: &impl ColourBasics) -> RGB<L> {
+        let background = Self::new(background.rgb::<L>(), Prop::ONE);
+        self.over(&background).rgb
+    }
+
+    /// Linear interpolation from `self` (`prop` zero) to `other` (`prop` one).
+    pub fn blend(&self, other: &Self, prop: Prop) -> Self {
+        Self::from_components(blend(
+            self.components(),
+            other.components(),
+            f64::from(prop),
+        ))
+    }
+}
+
+impl<L: LightLevel> ColourBasics for RGBA<L> {
+    fn hue(&self) -> Option<Hue> {
+        self.rgb.hue()
+    }
+
+    fn chroma(&self) -> Chroma {
+        self.rgb.chroma()
+    }
+
+    fn chroma_prop(&self) -> Prop {
+        self.rgb.chroma_prop()
+    }
+
+    fn value(&self) -> Value {
+        self.rgb.value()
+    }
+
+    fn hcv(&self) -> HCV {
+        self.rgb.hcv()
+    }
+
+    fn rgb<M: LightLevel>(&self) -> RGB<M> {
+        self.rgb.rgb::<M>()
+    }
+}
+
+impl<L: LightLevel> From<RGB<L>> for RGBA<L> {
+    fn from(rgb: RGB<L>) -> Self {
+        Self::new(rgb, Prop::ONE)
+    }
+}
+
+impl<L: LightLevel> From<RGBA<L>> for RGB<L> {
+    fn from(rgba: RGBA<L>) -> Self {
+        rgba.rgb
+    }
+}
+
+impl<L: LightLevel> From<&HCVA> for RGBA<L> {
+    fn from(hcva: &HCVA) -> Self {
+        Self::new(hcva.hcv.rgb::<L>(), hcva.alpha)
+    }
+}
+
+/// An `HCV` with an alpha (opacity) channel.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HCVA {
+    pub hcv: HCV,
+    pub alpha: Prop,
+}
+
+impl HCVA {
+    pub fn new(hcv: HCV, alpha: Prop) -> Self {
+        Self { hcv, alpha }
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        self.alpha == Prop::ONE
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.alpha == Prop::ZERO
+    }
+
+    /// `self` composited over `background`.
+    pub fn over(&self, background: &Self) -> Self {
+        (&RGBA::<f64>::from(self).over(&background.into())).into()
+    }
+
+    /// The opaque colour seen when `self` is composited over `background`.
+    pub fn over_opaque(&self, background: &impl ColourBasics) -> HCV {
+        RGBA::<f64>::from(self).over_opaque(background).hcv()
+    }
+
+    /// Linear interpolation from `self` (`prop` zero) to `other` (`prop` one).
+    pub fn blend(&self, other: &Self, prop: Prop) -> Self {
+        (&RGBA::<f64>::from(self).blend(&other.into(), prop)).into()
+    }
+}
+
+impl ColourBasics for HCVA {
+    fn hue(&self) -> Option<Hue> {
+        self.hcv.hue()
+    }
+
+    fn chroma(&self) -> Chroma {
+        self.hcv.chroma()
+    }
+
+    fn chroma_prop(&self) -> Prop {
+        self.hcv.chroma_prop()
+    }
+
+    fn value(&self) -> Value {
+        self.hcv.value()
+    }
+
+    fn hcv(&self) -> HCV {
+        self.hcv
+    }
+
+    fn rgb<L: LightLevel>(&self) -> RGB<L> {
+        self.hcv.rgb::<L>()
+    }
+}
+
+impl From<HCV> for HCVA {
+    fn from(hcv: HCV) -> Self {
+        Self::new(hcv, Prop::ONE)
+    }
+}
+
+impl From<HCVA> for HCV {
+    fn from(hcva: HCVA) -> Self {
+        hcva.hcv
+    }
+}
+
+impl<L: LightLevel> From<&RGBA<L>> for HCVA {
+    fn from(rgba: &RGBA<L>) -> Self {
+        Self::new(rgba.rgb.hcv(), rgba.alpha)
+    }
+}
+
+#[cfg(test)]
+mod alpha_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    #[test]
+    fn over() {
+        let half_red = RGBA::new(RGB::<f64>::RED, Prop::HALF);
+        let blue: RGBA<f64> = RGB::<f64>::BLUE.into();
+        let result = half_red.over(&blue);
+        assert!(result.is_opaque());
+        let rgb = RGB::<u8>::rounded_from(&result);
+        assert_eq!((rgb[0], rgb[1], rgb[2]), (rgb[2], 0, rgb[0]));
+        assert!(rgb[0] == 127 || rgb[0] == 128);
+        let clear = RGBA::new(RGB::<f64>::WHITE, Prop::ZERO);
+        assert_eq!(clear.over(&blue), blue);
+        assert!(clear.over(&clear).is_transparent());
+        assert_eq!(blue.over(&half_red), blue);
+        let hcva = HCVA::new(HCV::RED, Prop::HALF);
+        assert_eq!(hcva.over_opaque(&HCV::RED), HCV::RED);
+        assert_eq!(hcva.hue(), HCV::RED.hue());
+    }
+
+    #[test]
+    fn blend() {
+        let red: RGBA<f64> = RGB::<f64>::RED.into();
+        let clear = RGBA::new(RGB::<f64>::BLUE, Prop::ZERO);
+        let result = red.blend(&clear, Prop::HALF);
+        assert_eq!(result.rgb, RGB::<f64>::RED);
+        assert!(!result.is_opaque() && !result.is_transparent());
+        assert_eq!(red.blend(&clear, Prop::ZERO), red);
+        let white = HCVA::from(HCV::WHITE);
+        let black = HCVA::from(HCV::BLACK);
+        assert_eq!(white.blend(&black, Prop::ONE), black);
+    }
+}
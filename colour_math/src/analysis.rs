@@ -0,0 +1,182 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! Colour statistics for images (or any other collection of pixels) e.g.
+//! to populate a hue wheel with an image's dominant colours.
+use crate::{
+    hcv::HCV,
+    quantise::{median_cut, ColourCounts},
+    rgb::RGB,
+    ColourBasics, LightLevel,
+};
+
+/// Counts of items in equal width bins across a range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// At least one bin.
+    pub fn new(bins: usize) -> Self {
+        Self {
+            counts: vec![0; bins.max(1)],
+        }
+    }
+
+    /// Count `proportion` (of the way across the range) in its bin.
+    pub fn add(&mut self, proportion: f64) {
+        let bins = self.counts.len();
+        let bin = (proportion.clamp(0.0, 1.0) * bins as f64) as usize;
+        self.counts[bin.min(bins - 1)] += 1;
+    }
+
+    pub fn counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// The proportion of the total in each bin (all zero if empty).
+    pub fn proportions(&self) -> Vec<f64> {
+        let total = self.total().max(1) as f64;
+        self.counts
+            .iter()
+            .map(|count| *count as f64 / total)
+            .collect()
+    }
+
+    /// The index of the (first) fullest bin or `None` if empty.
+    pub fn peak(&self) -> Option<usize> {
+        let max = *self.counts.iter().max()?;
+        if max > 0 {
+            self.counts.iter().position(|count| *count == max)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColourStatistics {
+    /// Of hue angles (anticlockwise from red) for colours that aren't grey.
+    pub hues: Histogram,
+    /// Of chroma (as a proportion of the maximum for the hue).
+    pub chromas: Histogram,
+    pub values: Histogram,
+    pub greys: usize,
+    /// `None` if there were no pixels.
+    pub mean: Option<HCV>,
+}
+
+impl ColourStatistics {
+    pub fn new<L: LightLevel>(pixels: &[RGB<L>], bins: usize) -> Self {
+        let mut hues = Histogram::new(bins);
+        let mut chromas = Histogram::new(bins);
+        let mut values = Histogram::new(bins);
+        let mut greys = 0;
+        for pixel in pixels.iter() {
+            let hcv = pixel.hcv();
+            match hcv.hue_angle() {
+                Some(angle) if !hcv.is_grey() => {
+                    hues.add(f64::from(angle).rem_euclid(360.0) / 360.0)
+                }
+                _ => greys += 1,
+            }
+            chromas.add(f64::from(hcv.chroma_prop()));
+            values.add(f64::from(hcv.value()));
+        }
+        Self {
+            hues,
+            chromas,
+            values,
+            greys,
+            mean: mean_colour(pixels),
+        }
+    }
+}
+
+/// The mean of the pixels' (encoded) RGB components or `None` if there
+/// aren't any.
+pub fn mean_colour<L: LightLevel>(pixels: &[RGB<L>]) -> Option<HCV> {
+    if pixels.is_empty() {
+        return None;
+    }
+    let mut sums = [0.0_f64; 3];
+    for pixel in pixels.iter() {
+        let rgb = pixel.rgb::<f64>();
+        for (sum, component) in sums.iter_mut().zip([rgb[0], rgb[1], rgb[2]]) {
+            *sum += component;
+        }
+    }
+    let count = pixels.len() as f64;
+    Some(HCV::from(RGB::<f64>::from(sums.map(|sum| sum / count))))
+}
+
+/// Up to `count` colours representative of `pixels` with the number of
+/// pixels each represents (most first).  See `quantise::median_cut()`.
+pub fn dominant_colours<L: LightLevel>(pixels: &[RGB<L>], count: usize) -> ColourCounts {
+    median_cut(pixels, count)
+}
+
+#[cfg(test)]
+mod analysis_tests {
+    use super::*;
+    use crate::{fdrn::Prop, HueConstants, RGBConstants};
+
+    #[test]
+    fn histograms() {
+        let mut histogram = Histogram::new(4);
+        assert_eq!(histogram.peak(), None);
+        for proportion in [0.0, 0.1, 0.3, 0.99, 1.0, 1.5] {
+            histogram.add(proportion);
+        }
+        assert_eq!(histogram.counts(), &[2, 1, 0, 3]);
+        assert_eq!(histogram.total(), 6);
+        assert_eq!(histogram.peak(), Some(3));
+        assert_eq!(histogram.proportions()[3], 0.5);
+        assert_eq!(Histogram::new(0).counts().len(), 1);
+    }
+
+    #[test]
+    fn statistics() {
+        let pixels = [
+            RGB::<u8>::RED,
+            RGB::<u8>::RED,
+            RGB::<u8>::CYAN,
+            RGB::<u8>::WHITE,
+        ];
+        let statistics = ColourStatistics::new(&pixels, 12);
+        assert_eq!(statistics.greys, 1);
+        assert_eq!(statistics.hues.total(), 3);
+        assert_eq!(statistics.hues.counts()[0], 2);
+        assert_eq!(statistics.hues.counts()[6], 1);
+        assert_eq!(statistics.chromas.counts()[11], 3);
+        assert_eq!(statistics.values.counts()[11], 1);
+        let mean = statistics.mean.unwrap().rgb::<f64>();
+        assert!(mean.approx_eq(
+            &RGB::<f64>::from([0.75, 0.5, 0.5]),
+            Some(Prop::from(0.000_001))
+        ));
+        assert_eq!(mean_colour::<u8>(&[]), None);
+    }
+
+    #[test]
+    fn dominant() {
+        let mut pixels = vec![RGB::<u8>::RED; 6];
+        pixels.extend([RGB::<u8>::BLUE; 3]);
+        pixels.push(RGB::from([250, 5, 5]));
+        let colours = dominant_colours(&pixels, 3);
+        assert_eq!(colours.len(), 3);
+        assert_eq!(colours[0], (HCV::RED, 6));
+        assert_eq!(colours[1], (HCV::BLUE, 3));
+        assert_eq!(colours[2].1, 1);
+        assert_eq!(colours[2].0.hue(), Some(crate::Hue::RED));
+        // can't have more colours than there are
+        assert_eq!(
+            dominant_colours(&[RGB::<u8>::RED; 5], 3),
+            vec![(HCV::RED, 5)]
+        );
+        assert!(dominant_colours::<u8>(&[], 3).is_empty());
+    }
+}
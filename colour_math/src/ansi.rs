@@ -0,0 +1,170 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{fdrn::Prop, rgb::RGB, ColourAttributes, ColourBasics, ScalarAttribute};
+
+pub const RESET: &str = "\x1b[0m";
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColourMode {
+    #[default]
+    TrueColour,
+    Ansi256,
+}
+
+fn nearest_cube_index(level: u8) -> usize {
+    match level {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (level as usize - 35) / 40,
+    }
+}
+
+fn distance(lhs: [u8; 3], rhs: [u8; 3]) -> u32 {
+    (0..3)
+        .map(|i| (lhs[i] as i32 - rhs[i] as i32).pow(2) as u32)
+        .sum()
+}
+
+/// The index of the entry in the xterm 256 colour palette's colour cube
+/// or grey ramp (i.e. 16 to 255) nearest to `colour`.
+pub fn ansi256_index(colour: &impl ColourBasics) -> u8 {
+    let rgb = colour.rgb::<u8>().0;
+    let cube = rgb.map(nearest_cube_index);
+    let cube_rgb = cube.map(|i| CUBE_LEVELS[i]);
+    let cube_index = 16 + 36 * cube[0] + 6 * cube[1] + cube[2];
+    let mean = rgb.iter().map(|c| *c as usize).sum::<usize>() / 3;
+    let grey_step = (mean.saturating_sub(3) / 10).min(23);
+    let grey_level = (8 + 10 * grey_step) as u8;
+    if distance(rgb, [grey_level; 3]) < distance(rgb, cube_rgb) {
+        (232 + grey_step) as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+pub fn foreground(colour: &impl ColourBasics, mode: ColourMode) -> String {
+    match mode {
+        ColourMode::TrueColour => {
+            let [red, green, blue] = colour.rgb::<u8>().0;
+            format!("\x1b[38;2;{red};{green};{blue}m")
+        }
+        ColourMode::Ansi256 => format!("\x1b[38;5;{}m", ansi256_index(colour)),
+    }
+}
+
+pub fn background(colour: &impl ColourBasics, mode: ColourMode) -> String {
+    match mode {
+        ColourMode::TrueColour => {
+            let [red, green, blue] = colour.rgb::<u8>().0;
+            format!("\x1b[48;2;{red};{green};{blue}m")
+        }
+        ColourMode::Ansi256 => format!("\x1b[48;5;{}m", ansi256_index(colour)),
+    }
+}
+
+/// A block of `width` character cells in `colour`.
+pub fn swatch(colour: &impl ColourBasics, width: usize, mode: ColourMode) -> String {
+    format!("{}{}{RESET}", background(colour, mode), " ".repeat(width))
+}
+
+/// `text` in the best foreground colour on a `colour` background.
+pub fn label(colour: &impl ColourBasics, text: &str, mode: ColourMode) -> String {
+    format!(
+        "{}{}{text}{RESET}",
+        background(colour, mode),
+        foreground(&colour.best_foreground(), mode)
+    )
+}
+
+/// A horizontal bar `width` cells long filled in proportion to the
+/// colour's value for `attr` e.g. "████░░░░".
+pub fn attribute_bar(
+    colour: &impl ColourAttributes,
+    attr: ScalarAttribute,
+    width: usize,
+    mode: ColourMode,
+) -> String {
+    let filled = (f64::from(colour.scalar_attribute(attr)) * width as f64).round() as usize;
+    let filled = filled.min(width);
+    let bar_colour = colour.scalar_attribute_rgb::<u8>(attr);
+    format!(
+        "{}{}{}{RESET}",
+        foreground(&bar_colour, mode),
+        "\u{2588}".repeat(filled),
+        "\u{2591}".repeat(width - filled)
+    )
+}
+
+/// A line showing a swatch of the colour followed by bars for its
+/// chroma, value and warmth.
+pub fn summary(colour: &impl ColourAttributes, mode: ColourMode) -> String {
+    let mut line = swatch(colour, 4, mode);
+    for attr in [
+        ScalarAttribute::Chroma,
+        ScalarAttribute::Value,
+        ScalarAttribute::Warmth,
+    ] {
+        let prop: Prop = colour.scalar_attribute(attr);
+        line.push_str(&format!(
+            " {}:{} {:.2}",
+            &attr.to_string()[..1],
+            attribute_bar(colour, attr, 10, mode),
+            f64::from(prop)
+        ));
+    }
+    line
+}
+
+/// The ANSI 256 colour palette entry (16 to 255) as RGB.
+pub fn ansi256_rgb(index: u8) -> Option<RGB<u8>> {
+    match index {
+        16..=231 => {
+            let i = index as usize - 16;
+            Some(RGB([
+                CUBE_LEVELS[i / 36],
+                CUBE_LEVELS[(i / 6) % 6],
+                CUBE_LEVELS[i % 6],
+            ]))
+        }
+        232..=255 => Some(RGB([8 + 10 * (index - 232); 3])),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod ansi_tests {
+    use super::*;
+    use crate::{hcv::HCV, HueConstants, RGBConstants};
+
+    #[test]
+    fn ansi256() {
+        assert_eq!(ansi256_index(&HCV::RED), 196);
+        assert_eq!(ansi256_index(&HCV::BLUE), 21);
+        assert_eq!(ansi256_index(&HCV::WHITE), 231);
+        assert_eq!(ansi256_index(&HCV::BLACK), 16);
+        assert_eq!(ansi256_index(&RGB::<u8>::from([0x80, 0x80, 0x80])), 244);
+        for index in 16..=255 {
+            let rgb = ansi256_rgb(index).unwrap();
+            assert_eq!(ansi256_rgb(ansi256_index(&rgb)), Some(rgb));
+        }
+        assert!(ansi256_rgb(15).is_none());
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            foreground(&HCV::RED, ColourMode::TrueColour),
+            "\x1b[38;2;255;0;0m"
+        );
+        assert_eq!(background(&HCV::BLUE, ColourMode::Ansi256), "\x1b[48;5;21m");
+        assert_eq!(
+            swatch(&HCV::GREEN, 2, ColourMode::Ansi256),
+            "\x1b[48;5;46m  \x1b[0m"
+        );
+        let bar = attribute_bar(&HCV::WHITE, ScalarAttribute::Value, 4, ColourMode::Ansi256);
+        assert!(bar.contains("\u{2588}\u{2588}\u{2588}\u{2588}"));
+        assert!(!bar.contains('\u{2591}'));
+        assert!(summary(&HCV::RED, ColourMode::TrueColour).contains("V:"));
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::fmt;
+
+/// How much notice downstream users get before a module's public items change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Stability {
+    /// Only changed (with deprecation first where practical) in a semver
+    /// breaking release.  Everything in the `prelude` is stable.
+    Stable,
+    /// Usable but still settling: may be reorganised in any minor release.
+    Experimental,
+    /// Exposed for testing and diagnosis only.  No guarantees.
+    Unstable,
+}
+
+impl fmt::Display for Stability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stability::Stable => write!(f, "stable"),
+            Stability::Experimental => write!(f, "experimental"),
+            Stability::Unstable => write!(f, "unstable"),
+        }
+    }
+}
+
+/// The stability tier of each of the crate's public top level modules.
+pub const MODULES: &[(&str, Stability)] = &[
+    ("alpha", Stability::Experimental),
+    ("analysis", Stability::Experimental),
+    ("ansi", Stability::Experimental),
+    ("api", Stability::Stable),
+    ("attributes", Stability::Stable),
+    ("beigui", Stability::Experimental),
+    ("bulk", Stability::Experimental),
+    ("cmyk", Stability::Experimental),
+    ("code", Stability::Experimental),
+    ("colour_vision", Stability::Experimental),
+    ("conformance", Stability::Experimental),
+    ("debug", Stability::Unstable),
+    ("diagnostics", Stability::Unstable),
+    ("distance", Stability::Experimental),
+    ("dyn_colour", Stability::Experimental),
+    ("fdrn", Stability::Stable),
+    ("gamut", Stability::Experimental),
+    ("gradient", Stability::Experimental),
+    ("harmony", Stability::Experimental),
+    ("hcv", Stability::Stable),
+    ("hue", Stability::Stable),
+    ("image", Stability::Experimental),
+    ("interop", Stability::Experimental),
+    ("lab", Stability::Stable),
+    ("manipulator", Stability::Stable),
+    ("matching", Stability::Experimental),
+    ("mixing", Stability::Stable),
+    ("names", Stability::Experimental),
+    ("palette", Stability::Stable),
+    ("palette_io", Stability::Stable),
+    ("pigments", Stability::Experimental),
+    ("preferences", Stability::Experimental),
+    ("prelude", Stability::Stable),
+    ("progress", Stability::Stable),
+    ("project", Stability::Experimental),
+    ("proof", Stability::Experimental),
+    ("quantise", Stability::Experimental),
+    ("ramps", Stability::Experimental),
+    ("recording", Stability::Experimental),
+    ("rgb", Stability::Stable),
+    ("rgb_space", Stability::Experimental),
+    ("schemes", Stability::Experimental),
+    ("serde_human", Stability::Experimental),
+    ("standards", Stability::Stable),
+    ("transfer", Stability::Experimental),
+    ("transform", Stability::Experimental),
+    ("xyz", Stability::Experimental),
+];
+
+/// The stability of the named top level module (e.g. "beigui" or
+/// "colour_math::beigui").
+pub fn stability(module: &str) -> Option<Stability> {
+    let module = module.strip_prefix("colour_math::").unwrap_or(module);
+    let module = module.split("::").next().unwrap_or(module);
+    MODULES
+        .iter()
+        .find(|(name, _)| *name == module)
+        .map(|(_, stability)| *stability)
+}
+
+#[cfg(test)]
+mod api_tests {
+    use super::*;
+
+    #[test]
+    fn every_public_module_has_a_tier() {
+        let declared: Vec<&str> = include_str!("lib.rs")
+            .lines()
+            .filter_map(|line| line.strip_prefix("pub mod "))
+            .filter_map(|line| line.strip_suffix(';'))
+            .collect();
+        assert!(!declared.is_empty());
+        for module in declared.iter() {
+            assert!(stability(module).is_some(), "{module} has no tier");
+        }
+        for (module, _) in MODULES.iter() {
+            assert!(declared.contains(module), "{module} is not a module");
+        }
+        assert_eq!(
+            stability("colour_math::beigui::hue_wheel"),
+            Some(Stability::Experimental)
+        );
+        assert_eq!(stability("nonesuch"), None);
+    }
+}
@@ -100,30 +100,30 @@ impl From<(Prop, Hue, UFDRNumber)> for Chroma {
     }
 }
 
-impl PartialOrd for Chroma {
-    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+impl Ord for Chroma {
+    fn cmp(&self, rhs: &Self) -> Ordering {
         use Chroma::*;
         match self {
             Shade(proportion) => match rhs {
-                Shade(other_proportion) => proportion.partial_cmp(other_proportion),
-                _ => Some(Ordering::Less),
+                Shade(other_proportion) => proportion.cmp(other_proportion),
+                _ => Ordering::Less,
             },
             Tint(proportion) => match rhs {
-                Tint(other_proportion) => proportion.partial_cmp(other_proportion),
-                Shade(_) => Some(Ordering::Greater),
-                Neither(_) => Some(Ordering::Less),
+                Tint(other_proportion) => proportion.cmp(other_proportion),
+                Shade(_) => Ordering::Greater,
+                Neither(_) => Ordering::Less,
             },
             Neither(proportion) => match rhs {
-                Neither(other_proportion) => proportion.partial_cmp(other_proportion),
-                _ => Some(Ordering::Greater),
+                Neither(other_proportion) => proportion.cmp(other_proportion),
+                _ => Ordering::Greater,
             },
         }
     }
 }
 
-impl Ord for Chroma {
-    fn cmp(&self, rhs: &Self) -> Ordering {
-        self.partial_cmp(rhs).unwrap()
+impl PartialOrd for Chroma {
+    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+        Some(self.cmp(rhs))
     }
 }
 
@@ -198,30 +198,30 @@ impl Default for Greyness {
     }
 }
 
-impl PartialOrd for Greyness {
-    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+impl Ord for Greyness {
+    fn cmp(&self, rhs: &Self) -> Ordering {
         use Greyness::*;
         match self {
             Shade(proportion) => match rhs {
-                Shade(other_proportion) => proportion.partial_cmp(other_proportion),
-                _ => Some(Ordering::Less),
+                Shade(other_proportion) => proportion.cmp(other_proportion),
+                _ => Ordering::Less,
             },
             Tint(proportion) => match rhs {
-                Tint(other_proportion) => proportion.partial_cmp(other_proportion),
-                Shade(_) => Some(Ordering::Greater),
-                Neither(_) => Some(Ordering::Less),
+                Tint(other_proportion) => proportion.cmp(other_proportion),
+                Shade(_) => Ordering::Greater,
+                Neither(_) => Ordering::Less,
             },
             Neither(proportion) => match rhs {
-                Neither(other_proportion) => proportion.partial_cmp(other_proportion),
-                _ => Some(Ordering::Greater),
+                Neither(other_proportion) => proportion.cmp(other_proportion),
+                _ => Ordering::Greater,
             },
         }
     }
 }
 
-impl Ord for Greyness {
-    fn cmp(&self, rhs: &Self) -> Ordering {
-        self.partial_cmp(rhs).unwrap()
+impl PartialOrd for Greyness {
+    fn partial_cmp(&self, rhs: &Self) -> Option<Ordering> {
+        Some(self.cmp(rhs))
     }
 }
 
@@ -338,6 +338,11 @@ impl Value {
     pub const ZERO: Self = Self(0);
     pub const ONE: Self = Self(u64::MAX);
 
+    /// As `Value::from(prop)` but usable in constants.
+    pub const fn from_prop(prop: Prop) -> Self {
+        Self(prop.0)
+    }
+
     pub fn abs_diff(&self, other: &Self) -> Value {
         match self.cmp(other) {
             Ordering::Greater => Value(self.0 - other.0),
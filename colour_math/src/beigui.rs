@@ -12,7 +12,12 @@ use crate::{
 mod test_beigui;
 
 pub mod attr_display;
+pub mod hue_dial;
 pub mod hue_wheel;
+pub mod layout;
+pub mod sparkline;
+pub mod sv_box;
+pub mod value_scale;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct Point {
@@ -101,6 +106,7 @@ impl From<[UFDRNumber; 2]> for Size {
 }
 
 /// Direction in which to draw isosceles triangle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Dirn {
     Down,
     Up,
@@ -116,6 +122,56 @@ pub enum TextPosn {
     Centre(Point),
 }
 
+/// How a drawer renders text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextRendering {
+    #[default]
+    Glyphs,
+    /// Draw nothing.
+    Suppressed,
+    /// Outline a box approximating the text's extent (see `text_box()`)
+    /// which, unlike glyphs, doesn't depend on the fonts available and so
+    /// is the same on every machine.
+    Boxes,
+}
+
+/// A deterministic stand in for the extent of `text` (assuming y
+/// increases downwards): each character is 0.6 of `font_size` wide and
+/// the box is 0.7 of `font_size` high.
+pub fn text_box(text: &str, posn: &TextPosn, font_size: UFDRNumber) -> [Point; 4] {
+    let font_size = f64::from(font_size);
+    let width = FDRNumber::from(font_size * 0.6 * text.chars().count() as f64);
+    let height = FDRNumber::from(font_size * 0.7);
+    let top_left = match posn {
+        TextPosn::TopLeftCorner(point) => *point,
+        TextPosn::TopRightCorner(point) => [point.x - width, point.y].into(),
+        TextPosn::BottomLeftCorner(point) => [point.x, point.y - height].into(),
+        TextPosn::BottomRightCorner(point) => [point.x - width, point.y - height].into(),
+        TextPosn::Centre(point) => [point.x - width / 2, point.y - height / 2].into(),
+    };
+    [
+        top_left,
+        [top_left.x + width, top_left.y].into(),
+        [top_left.x + width, top_left.y + height].into(),
+        [top_left.x, top_left.y + height].into(),
+    ]
+}
+
+/// A short description of `colour` (value first) for when it's being
+/// displayed in monochrome e.g. for printing or value studies.
+pub fn monochrome_annotation(colour: &impl ColourBasics) -> String {
+    let percent = |proportion: f64| (proportion * 100.0).round() as u8;
+    let value = percent(f64::from(colour.value()));
+    match colour.hue_angle() {
+        Some(angle) if !colour.is_grey() => {
+            let degrees = (f64::from(angle).round() as i16).rem_euclid(360);
+            let chroma = percent(f64::from(colour.chroma_prop()));
+            format!("V {value}% C {chroma}% H {degrees}\u{b0}")
+        }
+        _ => format!("V {value}% Grey"),
+    }
+}
+
 pub trait Draw {
     fn size(&self) -> Size;
     fn draw_polygon(&self, polygon: &[Point], fill: bool);
@@ -129,7 +185,67 @@ pub trait Draw {
     fn draw_line(&self, line: &[Point]);
     fn draw_text(&self, text: &str, posn: TextPosn, font_size: UFDRNumber);
 
+    fn text_rendering(&self) -> TextRendering {
+        TextRendering::Glyphs
+    }
+
+    /// Draw `text` as `text_rendering()` dictates with boxes being outlined
+    /// in the line colour.
+    fn draw_text_as_configured(&self, text: &str, posn: TextPosn, font_size: UFDRNumber) {
+        match self.text_rendering() {
+            TextRendering::Glyphs => self.draw_text(text, posn, font_size),
+            TextRendering::Suppressed => (),
+            TextRendering::Boxes => {
+                if !text.is_empty() {
+                    self.draw_polygon(&text_box(text, &posn, font_size), false)
+                }
+            }
+        }
+    }
+
     fn paint_linear_gradient(&self, posn: Point, size: Size, colour_stops: &[(HCV, Prop)]);
+
+    /// Plot `values` (oldest first) as a line across the full width with
+    /// `range.0` at the bottom and `range.1` at the top.
+    fn draw_time_series(&self, values: &[f64], range: (f64, f64)) {
+        let size = self.size();
+        let line: Vec<Point> =
+            time_series_xy(f64::from(size.width), f64::from(size.height), values, range)
+                .iter()
+                .map(|(x, y)| [FDRNumber::from(*x), FDRNumber::from(*y)].into())
+                .collect();
+        self.draw_line(&line);
+    }
+}
+
+/// Coordinates (with y increasing downwards) for plotting `values` evenly
+/// spaced across `width` with `range.0` at the bottom and `range.1` at the
+/// top.  Values outside the range are clamped and a degenerate range puts
+/// everything half way up.
+pub fn time_series_xy(
+    width: f64,
+    height: f64,
+    values: &[f64],
+    range: (f64, f64),
+) -> Vec<(f64, f64)> {
+    let (low, high) = range;
+    let step = if values.len() > 1 {
+        width / (values.len() - 1) as f64
+    } else {
+        0.0
+    };
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let prop = if high > low {
+                ((value - low) / (high - low)).clamp(0.0, 1.0)
+            } else {
+                0.5
+            };
+            (i as f64 * step, height * (1.0 - prop))
+        })
+        .collect()
 }
 
 pub trait DrawIsosceles: Draw {
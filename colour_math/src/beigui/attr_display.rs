@@ -2,13 +2,54 @@
 
 use crate::{
     attributes::{Chroma, Greyness, Value, Warmth},
-    beigui::{Dirn, Draw, DrawIsosceles, Point, TextPosn},
+    beigui::{
+        layout::{BarLayout, MarkerGeometry},
+        Dirn, Draw, DrawIsosceles, Point, Size, TextPosn,
+    },
     fdrn::{FDRNumber, IntoProp, Prop, UFDRNumber},
     hcv::HCV,
     hue::{Hue, HueIfce},
+    manipulator::{ColourManipulator, Outcome, SetScalar},
+    rgb::RGB,
     ColourBasics, HueConstants, RGBConstants,
 };
 
+/// Draw the (current) value indicator i.e. triangles pointing inwards from
+/// the top and bottom edges at `attr_value` across the display.
+pub fn draw_value_marker(drawer: &impl DrawIsosceles, attr_value: Prop, colour: &HCV) {
+    let bar = BarLayout::new(drawer.size());
+    let geometry = MarkerGeometry::default();
+    drawer.set_fill_colour(colour);
+    drawer.set_line_colour(colour);
+    for (centre, dirn) in geometry.value_triangles(&bar, attr_value) {
+        drawer.draw_isosceles(centre, dirn, geometry.base, geometry.height, true);
+    }
+}
+
+/// Draw the target value indicator i.e. a vertical line at
+/// `attr_target_value` across the display with arrows (on the side of the
+/// current value) pointing towards it if `dirn` isn't `None`.
+pub fn draw_target_marker(
+    drawer: &impl DrawIsosceles,
+    attr_target_value: Prop,
+    colour: &HCV,
+    dirn: Option<Dirn>,
+) {
+    let bar = BarLayout::new(drawer.size());
+    let geometry = MarkerGeometry::default();
+    drawer.set_line_width(geometry.line_width);
+    drawer.set_line_colour(colour);
+    drawer.draw_line(&geometry.target_line(&bar, attr_target_value));
+    if let Some(arrows) =
+        dirn.and_then(|dirn| geometry.target_arrows(&bar, attr_target_value, dirn))
+    {
+        drawer.set_fill_colour(colour);
+        for (centre, dirn) in arrows {
+            drawer.draw_isosceles(centre, dirn, geometry.base, geometry.height, true);
+        }
+    }
+}
+
 pub trait ColourAttributeDisplayIfce {
     const LABEL: &'static str;
 
@@ -36,41 +77,52 @@ pub trait ColourAttributeDisplayIfce {
         vec![(HCV::BLACK, Prop::ZERO), (HCV::WHITE, Prop::ONE)]
     }
 
+    /// The attribute value indicated by position `x` in a display `width`
+    /// wide i.e. the inverse of the indicator's placement when drawn.
+    fn attr_value_at(x: f64, width: f64) -> Prop {
+        let bar = BarLayout::new(Size {
+            width: UFDRNumber::from(width.max(0.0)),
+            height: UFDRNumber::ZERO,
+        });
+        bar.attr_value_at(FDRNumber::from(x))
+    }
+
+    /// Adjust the manipulator's colour so that this attribute has the given
+    /// value. Displays that can't be used for editing return `None`.
+    fn set_attr_value(_manipulator: &mut ColourManipulator, _attr_value: Prop) -> Option<Outcome> {
+        None
+    }
+
+    /// The direction (along the display) in which the value has to move
+    /// to reach the target.  NB: this is derived from the indicators'
+    /// positions so it is correct for attributes (e.g. greyness) whose
+    /// scale runs in the opposite direction to a related attribute's.
+    fn target_dirn(&self) -> Option<Dirn> {
+        let attr_value = self.attr_value()?;
+        let attr_target_value = self.attr_target_value()?;
+        if attr_value < attr_target_value {
+            Some(Dirn::Right)
+        } else if attr_value > attr_target_value {
+            Some(Dirn::Left)
+        } else {
+            None
+        }
+    }
+
     fn draw_attr_value_indicator(&self, drawer: &impl DrawIsosceles) {
         if let Some(attr_value) = self.attr_value() {
-            let size = drawer.size();
-            let indicator_x = size.width * attr_value;
-            drawer.set_fill_colour(&self.attr_value_fg_colour());
-            drawer.set_line_colour(&self.attr_value_fg_colour());
-            let base: UFDRNumber = UFDRNumber::ONE * 8;
-            let height: UFDRNumber = UFDRNumber::ONE * 6;
-            drawer.draw_isosceles(
-                [indicator_x.into(), (height / 2).into()].into(),
-                Dirn::Up,
-                base,
-                height,
-                true,
-            );
-            drawer.draw_isosceles(
-                [indicator_x.into(), (size.height - height / 2).into()].into(),
-                Dirn::Down,
-                base,
-                height,
-                true,
-            );
+            draw_value_marker(drawer, attr_value, &self.attr_value_fg_colour());
         }
     }
 
-    fn draw_target_attr_value_indicator(&self, drawer: &impl Draw) {
-        if let Some(attr_value) = self.attr_target_value() {
-            let size = drawer.size();
-            let indicator_x: FDRNumber = (size.width * attr_value).into();
-            drawer.set_line_width(UFDRNumber::ONE * 2);
-            drawer.set_line_colour(&self.attr_target_value_fg_colour());
-            drawer.draw_line(&[
-                [indicator_x, FDRNumber::ONE].into(),
-                [indicator_x, FDRNumber::from(size.height) - FDRNumber::ONE].into(),
-            ]);
+    fn draw_target_attr_value_indicator(&self, drawer: &impl DrawIsosceles) {
+        if let Some(attr_target_value) = self.attr_target_value() {
+            draw_target_marker(
+                drawer,
+                attr_target_value,
+                &self.attr_target_value_fg_colour(),
+                self.target_dirn(),
+            );
         }
     }
 
@@ -79,7 +131,8 @@ pub trait ColourAttributeDisplayIfce {
             let posn = TextPosn::Centre(drawer.size().centre());
             let font_size = UFDRNumber::ONE * 15;
             drawer.set_text_colour(&self.label_colour());
-            drawer.draw_text(Self::LABEL, posn, font_size);
+            drawer.set_line_colour(&self.label_colour());
+            drawer.draw_text_as_configured(Self::LABEL, posn, font_size);
         }
     }
 
@@ -89,10 +142,14 @@ pub trait ColourAttributeDisplayIfce {
         drawer.paint_linear_gradient(posn, size, &self.colour_stops());
     }
 
-    fn draw_all(&self, drawer: &impl DrawIsosceles) {
+    fn draw_all_unlabelled(&self, drawer: &impl DrawIsosceles) {
         self.draw_background(drawer);
         self.draw_target_attr_value_indicator(drawer);
         self.draw_attr_value_indicator(drawer);
+    }
+
+    fn draw_all(&self, drawer: &impl DrawIsosceles) {
+        self.draw_all_unlabelled(drawer);
         self.draw_label(drawer);
     }
 }
@@ -353,6 +410,10 @@ impl ColourAttributeDisplayIfce for ChromaCAD {
     fn colour_stops(&self) -> Vec<(HCV, Prop)> {
         self.colour_stops.clone()
     }
+
+    fn set_attr_value(manipulator: &mut ColourManipulator, attr_value: Prop) -> Option<Outcome> {
+        Some(manipulator.set_chroma(attr_value, SetScalar::Clamp))
+    }
 }
 
 // VALUE
@@ -414,6 +475,10 @@ impl ColourAttributeDisplayIfce for ValueCAD {
     fn label_colour(&self) -> HCV {
         HCV::WHITE
     }
+
+    fn set_attr_value(manipulator: &mut ColourManipulator, attr_value: Prop) -> Option<Outcome> {
+        Some(manipulator.set_sum(attr_value * 3, SetScalar::Clamp))
+    }
 }
 
 // Greyness
@@ -532,6 +597,19 @@ pub struct WarmthCAD {
     target_warmth: Option<Warmth>,
     warmth_fg_colour: HCV,
     target_warmth_fg_colour: HCV,
+    warmth_gradient: bool,
+}
+
+impl WarmthCAD {
+    /// Paint the background with the actual warmth gradient (cool to warm)
+    /// rather than the default stops.
+    pub fn set_warmth_gradient(&mut self, warmth_gradient: bool) {
+        self.warmth_gradient = warmth_gradient
+    }
+
+    pub fn warmth_gradient(&self) -> bool {
+        self.warmth_gradient
+    }
 }
 
 impl ColourAttributeDisplayIfce for WarmthCAD {
@@ -543,6 +621,7 @@ impl ColourAttributeDisplayIfce for WarmthCAD {
             target_warmth: None,
             warmth_fg_colour: HCV::BLACK,
             target_warmth_fg_colour: HCV::BLACK,
+            warmth_gradient: false,
         }
     }
 
@@ -587,6 +666,14 @@ impl ColourAttributeDisplayIfce for WarmthCAD {
     }
 
     fn colour_stops(&self) -> Vec<(HCV, Prop)> {
+        if self.warmth_gradient {
+            return (0..=4)
+                .map(|i| {
+                    let prop = Prop::from(i as f64 / 4.0);
+                    (RGB::<f64>::new_warmth_rgb(prop.into()).hcv(), prop)
+                })
+                .collect();
+        }
         vec![
             (HCV::WHITE, Prop::ZERO),
             (HCV::CYAN, Prop::ONE / 4),
@@ -0,0 +1,124 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    beigui::{DrawShapes, Point},
+    fdrn::{FDRNumber, UFDRNumber},
+    hue::{angle::Angle, Hue, HueIfce},
+    ColourBasics,
+};
+
+/// A ring of maximum chroma hues (drawn in cartesian coordinates with an
+/// outer radius of one) with an indicator showing the current hue.
+#[derive(Debug, Clone, Default)]
+pub struct HueDial {
+    hue: Option<Hue>,
+    snap: Option<Angle>,
+}
+
+impl HueDial {
+    const SEGMENTS: i16 = 72;
+    const INNER_RADIUS: f64 = 0.7;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn hue(&self) -> Option<Hue> {
+        self.hue
+    }
+
+    pub fn set_hue(&mut self, hue: Option<Hue>) {
+        self.hue = hue
+    }
+
+    pub fn set_colour(&mut self, colour: Option<&impl ColourBasics>) {
+        self.hue = colour.and_then(|colour| colour.hue())
+    }
+
+    pub fn snap(&self) -> Option<Angle> {
+        self.snap
+    }
+
+    /// Restrict selections to multiples of `snap` e.g. `Angle::STEP_12`.
+    pub fn set_snap(&mut self, snap: Option<Angle>) {
+        self.snap = snap
+    }
+
+    pub fn snapped(&self, angle: Angle) -> Angle {
+        match self.snap {
+            Some(step) => angle.nearest_step(step),
+            None => angle,
+        }
+    }
+
+    /// The angle of `point` around the dial's centre (`None` at the centre).
+    pub fn angle_for_point(point: Point) -> Option<Angle> {
+        let x = f64::from(point.x);
+        let y = f64::from(point.y);
+        if x == 0.0 && y == 0.0 {
+            None
+        } else {
+            Some(Angle::from(y.atan2(x).to_degrees()))
+        }
+    }
+
+    pub fn draw(&self, draw_shapes: &impl DrawShapes) {
+        let inner = UFDRNumber::from(Self::INNER_RADIUS);
+        let outer = UFDRNumber::ONE;
+        let step = 360 / Self::SEGMENTS;
+        for segment in 0..Self::SEGMENTS {
+            let start = Angle::from(segment * step - 180);
+            let end = start + Angle::from(step);
+            let middle = start + Angle::from(f64::from(step) / 2.0);
+            let colour = Hue::from(middle).max_chroma_hcv();
+            draw_shapes.set_fill_colour(&colour);
+            draw_shapes.set_line_colour(&colour);
+            draw_shapes.set_line_width(UFDRNumber::from(0.005));
+            let polygon: Vec<Point> = vec![
+                (start, inner).into(),
+                (start, outer).into(),
+                (end, outer).into(),
+                (end, inner).into(),
+            ];
+            draw_shapes.draw_polygon(&polygon, true);
+            draw_shapes.draw_polygon(&polygon, false);
+        }
+        if let Some(hue) = self.hue {
+            let colour = hue.max_chroma_hcv();
+            let angle = hue.angle();
+            draw_shapes.set_fill_colour(&colour);
+            draw_shapes.draw_circle(Point::default(), inner / 2, true);
+            draw_shapes.set_line_colour(&colour.best_foreground());
+            draw_shapes.set_line_width(UFDRNumber::from(0.02));
+            draw_shapes.draw_line(&[
+                (angle, inner - UFDRNumber::from(0.05)).into(),
+                (angle, outer + UFDRNumber::from(0.05)).into(),
+            ]);
+            let knob: Point = (angle, (inner + outer) / 2).into();
+            draw_shapes.draw_circle(knob, UFDRNumber::from(0.06), false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod hue_dial_tests {
+    use super::*;
+    use crate::{hcv::HCV, HueConstants};
+
+    #[test]
+    fn angle_for_point() {
+        let point = |x: f64, y: f64| Point {
+            x: FDRNumber::from(x),
+            y: FDRNumber::from(y),
+        };
+        assert_eq!(HueDial::angle_for_point(point(0.0, 0.0)), None);
+        assert_eq!(HueDial::angle_for_point(point(0.8, 0.0)), Some(Angle::RED));
+        let angle = HueDial::angle_for_point(point(-0.5, 0.5 * 3.0_f64.sqrt())).unwrap();
+        assert!(angle.abs_diff(&Angle::GREEN) < Angle::from(0.001));
+        let mut dial = HueDial::new();
+        dial.set_colour(Some(&HCV::BLUE));
+        assert_eq!(dial.hue(), Some(Hue::BLUE));
+        assert_eq!(dial.snapped(angle), angle);
+        dial.set_snap(Some(Angle::STEP_12));
+        assert_eq!(dial.snapped(angle), Angle::GREEN);
+    }
+}
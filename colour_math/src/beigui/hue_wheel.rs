@@ -1,12 +1,12 @@
 // Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::BTreeSet};
 
 use crate::{
     attributes::Value,
-    beigui::{DrawShapes, Point},
-    fdrn::{FDRNumber, UFDRNumber},
+    beigui::{DrawShapes, Point, TextPosn},
+    fdrn::{FDRNumber, Prop, UFDRNumber},
     hcv::HCV,
-    hue::{Hue, HueIfce},
+    hue::{angle::Angle, Hue, HueIfce},
     rgb::RGB,
     ColourAttributes, ColourBasics, HueConstants, LightLevel, RGBConstants, ScalarAttribute,
 };
@@ -31,6 +31,10 @@ impl Zoom {
     pub fn scale(&self) -> UFDRNumber {
         self.scale
     }
+
+    pub fn set_scale(&mut self, scale: UFDRNumber) {
+        self.scale = scale.max(UFDRNumber::ONE).min(Self::MAX);
+    }
 }
 
 impl Default for Zoom {
@@ -41,7 +45,7 @@ impl Default for Zoom {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
 pub enum Shape {
     Circle,
     Diamond,
@@ -55,24 +59,24 @@ pub enum Proximity {
     NotEnclosed(UFDRNumber),
 }
 
-impl PartialOrd for Proximity {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+impl Ord for Proximity {
+    fn cmp(&self, other: &Self) -> Ordering {
         match self {
             Self::Enclosed(mine) => match other {
-                Self::Enclosed(other) => mine.partial_cmp(other),
-                Self::NotEnclosed(_) => Some(Ordering::Less),
+                Self::Enclosed(other) => mine.cmp(other),
+                Self::NotEnclosed(_) => Ordering::Less,
             },
             Self::NotEnclosed(mine) => match other {
-                Self::Enclosed(_) => Some(Ordering::Greater),
-                Self::NotEnclosed(other) => mine.partial_cmp(other),
+                Self::Enclosed(_) => Ordering::Greater,
+                Self::NotEnclosed(other) => mine.cmp(other),
             },
         }
     }
 }
 
-impl Ord for Proximity {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).expect("should never get None")
+impl PartialOrd for Proximity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -97,12 +101,84 @@ impl ShapeConsts for FDRNumber {
     const SHAPE_RADIUS: Self = Self::SHAPE_HALF_SIDE;
 }
 
+/// How much detail to use when drawing a shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detail {
+    /// A plain filled circle regardless of shape
+    Dot,
+    /// The filled shape without its outline
+    Filled,
+    /// The filled shape with its outline
+    Full,
+}
+
+/// Decides the detail used to draw the wheel's items based on their size
+/// (in pixels) on the screen and the number of items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelOfDetail {
+    dot_size: UFDRNumber,
+    outline_size: UFDRNumber,
+    max_full_items: usize,
+}
+
+impl Default for LevelOfDetail {
+    fn default() -> Self {
+        Self {
+            dot_size: UFDRNumber::from(4),
+            outline_size: UFDRNumber::from(8),
+            max_full_items: 2000,
+        }
+    }
+}
+
+impl LevelOfDetail {
+    /// Items smaller than `dot_size` are drawn as dots and only items of at
+    /// least `outline_size` are outlined (provided that there are no more
+    /// than `max_full_items` items).
+    pub fn new(dot_size: UFDRNumber, outline_size: UFDRNumber, max_full_items: usize) -> Self {
+        debug_assert!(dot_size <= outline_size);
+        Self {
+            dot_size,
+            outline_size,
+            max_full_items,
+        }
+    }
+
+    /// Always draw in full detail.
+    pub fn full() -> Self {
+        Self::new(UFDRNumber::ZERO, UFDRNumber::ZERO, usize::MAX)
+    }
+
+    pub fn detail(&self, item_size: UFDRNumber, number_of_items: usize) -> Detail {
+        if item_size < self.dot_size {
+            Detail::Dot
+        } else if item_size < self.outline_size || number_of_items > self.max_full_items {
+            Detail::Filled
+        } else {
+            Detail::Full
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum CachedPoint {
     Hued(Point),
     Grey(Point),
 }
 
+impl CachedPoint {
+    fn for_colour(colour: &impl ColourBasics) -> Self {
+        if let Some(hue_angle) = colour.hue_angle() {
+            CachedPoint::Hued(Point::from((hue_angle, UFDRNumber::ONE)))
+        } else {
+            CachedPoint::Grey(Point {
+                x: FDRNumber::from(-1.05),
+                y: FDRNumber::ONE - FDRNumber::from(colour.value()) * 2,
+            })
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ColouredShape {
     id: String,
@@ -110,24 +186,18 @@ pub struct ColouredShape {
     cached_point: CachedPoint,
     tooltip_text: String,
     shape: Shape,
+    group: Option<String>,
 }
 
 impl ColouredShape {
     pub fn new(colour: &impl ColourBasics, id: &str, tooltip_text: &str, shape: Shape) -> Self {
-        let cached_point = if let Some(hue_angle) = colour.hue_angle() {
-            CachedPoint::Hued(Point::from((hue_angle, UFDRNumber::ONE)))
-        } else {
-            CachedPoint::Grey(Point {
-                x: FDRNumber::from(-1.05),
-                y: FDRNumber::ONE - FDRNumber::from(colour.value()) * 2,
-            })
-        };
         Self {
             id: id.to_string(),
             colour: colour.hcv(),
-            cached_point,
+            cached_point: CachedPoint::for_colour(colour),
             tooltip_text: tooltip_text.to_string(),
             shape,
+            group: None,
         }
     }
 
@@ -135,6 +205,31 @@ impl ColouredShape {
         &self.id
     }
 
+    pub fn colour(&self) -> HCV {
+        self.colour
+    }
+
+    /// Change the colour in place (keeping the id, group etc.) e.g. while
+    /// the item is being edited.  Returns `true` if the colour changed.
+    pub fn set_colour(&mut self, colour: &impl ColourBasics) -> bool {
+        let colour = colour.hcv();
+        if colour == self.colour {
+            return false;
+        }
+        self.colour = colour;
+        self.cached_point = CachedPoint::for_colour(&colour);
+        true
+    }
+
+    /// The named group (e.g. paint series) that this shape belongs to.
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    pub fn set_group(&mut self, group: Option<&str>) {
+        self.group = group.map(|group| group.to_string());
+    }
+
     fn xy(&self, scalar_attribute: ScalarAttribute, zoom: &Zoom) -> Point {
         match self.cached_point {
             CachedPoint::Hued(point) => {
@@ -149,11 +244,38 @@ impl ColouredShape {
         scalar_attribute: ScalarAttribute,
         zoom: &Zoom,
         draw_shapes: &impl DrawShapes,
+    ) {
+        self.draw_shape_with_detail(scalar_attribute, zoom, Detail::Full, draw_shapes)
+    }
+
+    pub fn draw_shape_with_detail(
+        &self,
+        scalar_attribute: ScalarAttribute,
+        zoom: &Zoom,
+        detail: Detail,
+        draw_shapes: &impl DrawShapes,
     ) {
         draw_shapes.set_fill_colour(&self.colour);
+        let xy = self.xy(scalar_attribute, zoom);
+        match detail {
+            Detail::Dot => {
+                draw_shapes.draw_circle(xy, UFDRNumber::SHAPE_RADIUS, true);
+                return;
+            }
+            Detail::Filled => {
+                match self.shape {
+                    Shape::Circle | Shape::BackSight => {
+                        draw_shapes.draw_circle(xy, UFDRNumber::SHAPE_RADIUS, true)
+                    }
+                    Shape::Diamond => draw_shapes.draw_diamond(xy, UFDRNumber::SHAPE_SIDE, true),
+                    Shape::Square => draw_shapes.draw_square(xy, UFDRNumber::SHAPE_SIDE, true),
+                }
+                return;
+            }
+            Detail::Full => (),
+        }
         draw_shapes.set_line_colour(&self.colour.best_foreground());
         draw_shapes.set_line_width(UFDRNumber::from(0.01));
-        let xy = self.xy(scalar_attribute, zoom);
         match self.shape {
             Shape::Circle => {
                 draw_shapes.draw_circle(xy, UFDRNumber::SHAPE_RADIUS, true);
@@ -214,6 +336,38 @@ impl ColouredShape {
     }
 }
 
+/// The serialisable description of a `ColouredShape`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColouredShapeSpec {
+    pub id: String,
+    pub colour: HCV,
+    pub tooltip_text: String,
+    pub shape: Shape,
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+impl From<&ColouredShape> for ColouredShapeSpec {
+    fn from(coloured_shape: &ColouredShape) -> Self {
+        Self {
+            id: coloured_shape.id.clone(),
+            colour: coloured_shape.colour,
+            tooltip_text: coloured_shape.tooltip_text.clone(),
+            shape: coloured_shape.shape,
+            group: coloured_shape.group.clone(),
+        }
+    }
+}
+
+impl From<&ColouredShapeSpec> for ColouredShape {
+    fn from(spec: &ColouredShapeSpec) -> Self {
+        let mut coloured_shape =
+            ColouredShape::new(&spec.colour, &spec.id, &spec.tooltip_text, spec.shape);
+        coloured_shape.set_group(spec.group.as_deref());
+        coloured_shape
+    }
+}
+
 pub trait MakeColouredShape {
     fn coloured_shape(&self) -> ColouredShape;
 }
@@ -246,6 +400,60 @@ impl PartialEq for ColouredShape {
 
 impl Eq for ColouredShape {}
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AngularLabels {
+    #[default]
+    None,
+    /// Every 30 degrees (measured anticlockwise from red)
+    Degrees,
+    /// The primary and secondary colours
+    HueNames,
+}
+
+impl AngularLabels {
+    pub fn labels(&self) -> Vec<(Angle, String)> {
+        match self {
+            AngularLabels::None => vec![],
+            AngularLabels::Degrees => Angle::steps(Angle::STEP_12)
+                .map(|angle| {
+                    let degrees = (f64::from(angle).round() as i16).rem_euclid(360);
+                    (angle, format!("{degrees}\u{b0}"))
+                })
+                .collect(),
+            AngularLabels::HueNames => [
+                (Hue::RED, "RED"),
+                (Hue::YELLOW, "YELLOW"),
+                (Hue::GREEN, "GREEN"),
+                (Hue::CYAN, "CYAN"),
+                (Hue::BLUE, "BLUE"),
+                (Hue::MAGENTA, "MAGENTA"),
+            ]
+            .iter()
+            .map(|(hue, name)| (hue.angle(), name.to_string()))
+            .collect(),
+        }
+    }
+}
+
+/// Sizes are in wheel units (the outer ring has a radius of one).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LabelStyle {
+    pub font_size: UFDRNumber,
+    pub radius: UFDRNumber,
+    /// If `None` each label is drawn in the colour of its hue.
+    pub colour: Option<HCV>,
+}
+
+impl Default for LabelStyle {
+    fn default() -> Self {
+        Self {
+            font_size: UFDRNumber::from(0.06),
+            radius: UFDRNumber::from(0.94),
+            colour: None,
+        }
+    }
+}
+
 pub trait Graticule {
     fn draw_rings(num_rings: u8, zoom: &Zoom, draw_shapes: &impl DrawShapes) {
         draw_shapes.set_line_width(UFDRNumber::from(0.01));
@@ -259,13 +467,8 @@ pub trait Graticule {
 
     fn draw_spokes(start_ring: UFDRNumber, zoom: &Zoom, draw_shapes: &impl DrawShapes) {
         draw_shapes.set_line_width(UFDRNumber::from(0.015));
-        for hue in Hue::PRIMARIES
-            .iter()
-            .chain(Hue::SECONDARIES.iter())
-            .chain(Hue::IN_BETWEENS.iter())
-        {
-            draw_shapes.set_line_colour(&hue.max_chroma_hcv());
-            let angle = hue.angle();
+        for angle in Angle::steps(Angle::STEP_12) {
+            draw_shapes.set_line_colour(&Hue::from(angle).max_chroma_hcv());
             let start: Point = (angle, start_ring).into();
             let end: Point = (angle, UFDRNumber::ONE).into();
             draw_shapes.draw_line(&[start * zoom.scale(), end * zoom.scale()]);
@@ -279,11 +482,50 @@ pub trait Graticule {
     }
 }
 
+/// A change to the items on a hue wheel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HueWheelEvent {
+    ItemAdded(String),
+    /// The item was replaced (by one with the same id) or recoloured.
+    ItemChanged(String),
+    ItemRemoved(String),
+    /// All of the items were removed or replaced at once.
+    ItemsReplaced,
+}
+
+type EventCallback = Box<dyn Fn(&HueWheelEvent)>;
+
 #[derive(Default)]
 pub struct HueWheel {
+    // in id order (for lookup) with `order` recording the insertion order
     shapes: Vec<ColouredShape>,
+    order: Vec<String>,
+    callbacks: Vec<EventCallback>,
     target: Option<ColouredShape>,
     zoom: Zoom,
+    level_of_detail: LevelOfDetail,
+    merge_distance: Option<Prop>,
+    expanded: Option<String>,
+    angular_labels: AngularLabels,
+    label_style: LabelStyle,
+    hidden_groups: BTreeSet<String>,
+    highlight: Option<HCV>,
+}
+
+/// A line in a hue wheel's legend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegendEntry {
+    pub group: String,
+    pub number_of_items: usize,
+    pub visible: bool,
+}
+
+// Distance between the colours in RGB space normalised to the range 0 to 1
+fn colour_distance(lhs: &HCV, rhs: &HCV) -> Prop {
+    let lhs = lhs.rgb::<f64>();
+    let rhs = rhs.rgb::<f64>();
+    let sum_sq: f64 = (0..3).map(|i| (lhs[i] - rhs[i]).powi(2)).sum();
+    Prop::from((sum_sq / 3.0).sqrt().min(1.0))
 }
 
 impl Graticule for HueWheel {}
@@ -293,6 +535,49 @@ impl HueWheel {
         Self::default()
     }
 
+    fn notify(&self, event: HueWheelEvent) {
+        for callback in self.callbacks.iter() {
+            callback(&event)
+        }
+    }
+
+    /// Be told about changes to the wheel's items.  NB: `callback` is
+    /// called while the wheel is being modified.
+    pub fn connect_changed<F: Fn(&HueWheelEvent) + 'static>(&mut self, callback: F) {
+        self.callbacks.push(Box::new(callback))
+    }
+
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    /// The items in the order that they were (first) added.  Replacing an
+    /// item (with one with the same id) doesn't change its position.
+    pub fn items(&self) -> impl Iterator<Item = &ColouredShape> {
+        self.order.iter().filter_map(move |id| self.item(id))
+    }
+
+    pub fn item(&self, id: &str) -> Option<&ColouredShape> {
+        let index = self.shapes.binary_search_by_key(&id, |s| s.id()).ok()?;
+        Some(&self.shapes[index])
+    }
+
+    pub fn contains_item(&self, id: &str) -> bool {
+        self.item(id).is_some()
+    }
+
+    pub fn zoom_scale(&self) -> UFDRNumber {
+        self.zoom.scale()
+    }
+
+    pub fn set_zoom_scale(&mut self, scale: UFDRNumber) {
+        self.zoom.set_scale(scale);
+    }
+
     pub fn decr_zoom(&mut self) {
         self.zoom.decr();
     }
@@ -301,14 +586,262 @@ impl HueWheel {
         self.zoom.incr();
     }
 
+    pub fn level_of_detail(&self) -> LevelOfDetail {
+        self.level_of_detail
+    }
+
+    pub fn set_level_of_detail(&mut self, level_of_detail: LevelOfDetail) {
+        self.level_of_detail = level_of_detail;
+    }
+
+    pub fn merge_distance(&self) -> Option<Prop> {
+        self.merge_distance
+    }
+
+    /// Items whose colours are closer than `merge_distance` (in RGB space)
+    /// are drawn as a single marker showing how many items it represents.
+    pub fn set_merge_distance(&mut self, merge_distance: Option<Prop>) {
+        self.merge_distance = merge_distance;
+        self.expanded = None;
+    }
+
+    pub fn angular_labels(&self) -> AngularLabels {
+        self.angular_labels
+    }
+
+    pub fn set_angular_labels(&mut self, angular_labels: AngularLabels) {
+        self.angular_labels = angular_labels;
+    }
+
+    pub fn label_style(&self) -> LabelStyle {
+        self.label_style
+    }
+
+    pub fn set_label_style(&mut self, label_style: LabelStyle) {
+        self.label_style = label_style;
+    }
+
+    pub fn is_group_visible(&self, group: &str) -> bool {
+        !self.hidden_groups.contains(group)
+    }
+
+    /// Items that aren't in any group are always visible.
+    pub fn set_group_visible(&mut self, group: &str, visible: bool) {
+        if visible {
+            self.hidden_groups.remove(group);
+        } else {
+            self.hidden_groups.insert(group.to_string());
+        }
+        self.expanded = None;
+    }
+
+    /// The groups that the items belong to (in name order).
+    pub fn legend(&self) -> Vec<LegendEntry> {
+        let mut legend: Vec<LegendEntry> = vec![];
+        for group in self.shapes.iter().filter_map(|shape| shape.group()) {
+            match legend.binary_search_by(|entry| entry.group.as_str().cmp(group)) {
+                Ok(index) => legend[index].number_of_items += 1,
+                Err(index) => legend.insert(
+                    index,
+                    LegendEntry {
+                        group: group.to_string(),
+                        number_of_items: 1,
+                        visible: self.is_group_visible(group),
+                    },
+                ),
+            }
+        }
+        legend
+    }
+
+    fn visible_shapes(&self) -> impl Iterator<Item = &ColouredShape> {
+        self.shapes.iter().filter(move |shape| match shape.group() {
+            Some(group) => self.is_group_visible(group),
+            None => true,
+        })
+    }
+
+    fn draw_angular_labels(&self, draw_shapes: &impl DrawShapes) {
+        let style = self.label_style;
+        for (angle, text) in self.angular_labels.labels() {
+            let colour = match style.colour {
+                Some(colour) => colour,
+                None => Hue::from(angle).max_chroma_hcv(),
+            };
+            let posn: Point = (angle, style.radius).into();
+            draw_shapes.set_text_colour(&colour);
+            draw_shapes.draw_text_as_configured(
+                &text,
+                TextPosn::Centre(posn * self.zoom.scale()),
+                style.font_size,
+            );
+        }
+    }
+
+    /// Groups of items with similar colours (each led by the first of its
+    /// members in id order).  Each item is in a group of its own if merging
+    /// is off.
+    pub fn clusters(&self) -> Vec<Vec<&ColouredShape>> {
+        let mut clusters: Vec<Vec<&ColouredShape>> = vec![];
+        match self.merge_distance {
+            Some(merge_distance) => {
+                for shape in self.visible_shapes() {
+                    match clusters
+                        .iter_mut()
+                        .find(|c| colour_distance(&c[0].colour, &shape.colour) < merge_distance)
+                    {
+                        Some(cluster) => cluster.push(shape),
+                        None => clusters.push(vec![shape]),
+                    }
+                }
+            }
+            None => clusters.extend(self.visible_shapes().map(|shape| vec![shape])),
+        }
+        clusters
+    }
+
+    fn is_collapsed(&self, cluster: &[&ColouredShape]) -> bool {
+        cluster.len() > 1 && self.expanded.as_deref() != Some(cluster[0].id())
+    }
+
+    fn merged_marker_radius() -> UFDRNumber {
+        UFDRNumber::SHAPE_RADIUS * 3 / 2
+    }
+
+    fn collapsed_cluster_at_point<'a, 'b>(
+        &'a self,
+        clusters: &'b [Vec<&'a ColouredShape>],
+        point: Point,
+        scalar_attribute: ScalarAttribute,
+    ) -> Option<&'b [&'a ColouredShape]> {
+        clusters
+            .iter()
+            .filter(|cluster| cluster.len() > 1)
+            .find(|cluster| {
+                let xy = cluster[0].xy(scalar_attribute, &self.zoom);
+                (xy - point).hypot() < Self::merged_marker_radius()
+            })
+            .map(|cluster| cluster.as_slice())
+    }
+
+    /// Expand the merged marker at `point` into its members (or collapse it
+    /// if it is already expanded).  Returns `true` if anything changed.
+    pub fn toggle_expansion_at_point(
+        &mut self,
+        point: Point,
+        scalar_attribute: ScalarAttribute,
+    ) -> bool {
+        if self.merge_distance.is_none() {
+            return false;
+        }
+        let clusters = self.clusters();
+        let lead = self
+            .collapsed_cluster_at_point(&clusters, point, scalar_attribute)
+            .map(|cluster| cluster[0].id().to_string());
+        match lead {
+            Some(lead) if self.expanded.as_ref() == Some(&lead) => {
+                self.expanded = None;
+                true
+            }
+            Some(lead) => {
+                self.expanded = Some(lead);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn draw_merged_marker(
+        &self,
+        cluster: &[&ColouredShape],
+        scalar_attribute: ScalarAttribute,
+        draw_shapes: &impl DrawShapes,
+    ) {
+        let lead = cluster[0];
+        let xy = lead.xy(scalar_attribute, &self.zoom);
+        let radius = Self::merged_marker_radius();
+        draw_shapes.set_fill_colour(&lead.colour);
+        draw_shapes.draw_circle(xy, radius, true);
+        draw_shapes.set_line_colour(&lead.colour.best_foreground());
+        draw_shapes.set_line_width(UFDRNumber::from(0.01));
+        draw_shapes.draw_circle(xy, radius, false);
+        draw_shapes.set_text_colour(&lead.colour.best_foreground());
+        draw_shapes.draw_text_as_configured(
+            &cluster.len().to_string(),
+            TextPosn::Centre(xy),
+            radius,
+        );
+    }
+
     pub fn draw(&self, scalar_attribute: ScalarAttribute, draw_shapes: &impl DrawShapes) {
         self.draw_graticule(&self.zoom, draw_shapes);
-        for shape in self.shapes.iter() {
-            shape.draw_shape(scalar_attribute, &self.zoom, draw_shapes);
+        self.draw_angular_labels(draw_shapes);
+        // The wheel (radius one) approximately fills the smaller dimension
+        let size = draw_shapes.size();
+        let item_size = UFDRNumber::SHAPE_SIDE * size.width.min(size.height) / 2;
+        let clusters = self.clusters();
+        let detail = self.level_of_detail.detail(item_size, clusters.len());
+        for cluster in clusters.iter() {
+            if self.is_collapsed(cluster) {
+                self.draw_merged_marker(cluster, scalar_attribute, draw_shapes);
+            } else {
+                for shape in cluster.iter() {
+                    shape.draw_shape_with_detail(scalar_attribute, &self.zoom, detail, draw_shapes);
+                }
+            }
         }
         if let Some(ref target) = self.target {
             target.draw_shape(scalar_attribute, &self.zoom, draw_shapes)
         }
+        if let Some(ref highlight) = self.highlight {
+            let xy = self.colour_position(highlight, scalar_attribute);
+            draw_shapes.set_line_colour(&highlight.best_foreground());
+            draw_shapes.set_line_width(UFDRNumber::from(0.02));
+            draw_shapes.draw_circle(xy, UFDRNumber::SHAPE_RADIUS * 2, false);
+        }
+    }
+
+    /// Where a shape of this colour would be drawn.
+    pub fn colour_position(
+        &self,
+        colour: &impl ColourBasics,
+        scalar_attribute: ScalarAttribute,
+    ) -> Point {
+        ColouredShape::new(colour, "", "", Shape::Circle).xy(scalar_attribute, &self.zoom)
+    }
+
+    pub fn item_position(&self, id: &str, scalar_attribute: ScalarAttribute) -> Option<Point> {
+        Some(self.item(id)?.xy(scalar_attribute, &self.zoom))
+    }
+
+    /// The (visible) item whose colour is closest to `colour`.
+    pub fn nearest_item_to_colour(&self, colour: &impl ColourBasics) -> Option<&ColouredShape> {
+        let colour = colour.hcv();
+        self.visible_shapes()
+            .min_by_key(|shape| colour_distance(&shape.colour, &colour))
+    }
+
+    /// The item whose id matches `text` (ignoring case) or, failing that,
+    /// the first whose id contains it.
+    pub fn find_item(&self, text: &str) -> Option<&ColouredShape> {
+        let text = text.to_lowercase();
+        self.shapes
+            .iter()
+            .find(|shape| shape.id.to_lowercase() == text)
+            .or_else(|| {
+                self.shapes
+                    .iter()
+                    .find(|shape| shape.id.to_lowercase().contains(&text))
+            })
+    }
+
+    /// Draw attention to the position of `colour` (e.g. after a search).
+    pub fn set_highlight(&mut self, colour: Option<&impl ColourBasics>) {
+        self.highlight = colour.map(|colour| colour.hcv());
+    }
+
+    pub fn highlight(&self) -> Option<HCV> {
+        self.highlight
     }
 
     fn nearest_to(
@@ -317,7 +850,7 @@ impl HueWheel {
         scalar_attribute: ScalarAttribute,
     ) -> Option<(&ColouredShape, Proximity)> {
         let mut nearest: Option<(&ColouredShape, Proximity)> = None;
-        for shape in self.shapes.iter() {
+        for shape in self.visible_shapes() {
             let proximity = shape.proximity_to(point, scalar_attribute, &self.zoom);
             if let Some((_, nearest_so_far)) = nearest {
                 if proximity < nearest_so_far {
@@ -347,6 +880,20 @@ impl HueWheel {
         point: Point,
         scalar_attribute: ScalarAttribute,
     ) -> Option<String> {
+        if self.merge_distance.is_some() {
+            let clusters = self.clusters();
+            if let Some(cluster) =
+                self.collapsed_cluster_at_point(&clusters, point, scalar_attribute)
+            {
+                if self.is_collapsed(cluster) {
+                    return Some(format!(
+                        "{} similar colours including\n{}",
+                        cluster.len(),
+                        cluster[0].tooltip_text
+                    ));
+                }
+            }
+        }
         if let Some((shape, _)) = self.nearest_to(point, scalar_attribute) {
             return Some(shape.tooltip_text.to_string());
         }
@@ -354,7 +901,17 @@ impl HueWheel {
     }
 
     pub fn add_item(&mut self, coloured_item: ColouredShape) -> Option<ColouredShape> {
-        //self.shapes.push(coloured_item);
+        let id = coloured_item.id().to_string();
+        let old = self.insert_item(coloured_item);
+        if old.is_some() {
+            self.notify(HueWheelEvent::ItemChanged(id));
+        } else {
+            self.notify(HueWheelEvent::ItemAdded(id));
+        }
+        old
+    }
+
+    fn insert_item(&mut self, coloured_item: ColouredShape) -> Option<ColouredShape> {
         let id = coloured_item.id();
         match self.shapes.binary_search_by_key(&id, |s| s.id()) {
             Ok(index) => {
@@ -363,21 +920,53 @@ impl HueWheel {
                 Some(old)
             }
             Err(index) => {
+                self.order.push(id.to_string());
                 self.shapes.insert(index, coloured_item);
                 None
             }
         }
     }
 
-    pub fn remove_item(&mut self, id: &str) -> ColouredShape {
-        match self.shapes.binary_search_by_key(&id, |s| s.id()) {
-            Ok(index) => self.shapes.remove(index),
-            Err(_) => unreachable!("{}: shape with this id not found", id),
+    /// Change the colour of the item with `id` without removing it (so that
+    /// anything referring to it by id e.g. a selection is unaffected).
+    /// Returns `true` if the wheel needs to be redrawn.
+    pub fn set_item_colour(&mut self, id: &str, colour: &impl ColourBasics) -> bool {
+        let changed = match self.shapes.binary_search_by_key(&id, |s| s.id()) {
+            Ok(index) => self.shapes[index].set_colour(colour),
+            Err(_) => false,
+        };
+        if changed {
+            self.notify(HueWheelEvent::ItemChanged(id.to_string()));
         }
+        changed
+    }
+
+    /// Returns `None` (and changes nothing) if there's no item with `id`.
+    pub fn remove_item(&mut self, id: &str) -> Option<ColouredShape> {
+        let index = self.shapes.binary_search_by_key(&id, |s| s.id()).ok()?;
+        let removed = self.shapes.remove(index);
+        self.order.retain(|other| other != id);
+        self.notify(HueWheelEvent::ItemRemoved(id.to_string()));
+        Some(removed)
     }
 
     pub fn remove_all(&mut self) {
         self.shapes.clear();
+        self.order.clear();
+        self.expanded = None;
+        self.notify(HueWheelEvent::ItemsReplaced);
+    }
+
+    /// Replace all of the items at once (with a single notification).  If
+    /// there are duplicate ids the last of them wins.
+    pub fn replace_all(&mut self, coloured_items: impl IntoIterator<Item = ColouredShape>) {
+        self.shapes.clear();
+        self.order.clear();
+        self.expanded = None;
+        for coloured_item in coloured_items {
+            self.insert_item(coloured_item);
+        }
+        self.notify(HueWheelEvent::ItemsReplaced);
     }
 
     pub fn set_target_colour(&mut self, colour: Option<&impl ColourBasics>) {
@@ -0,0 +1,157 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! The geometry used by the built in attribute displays (see `attr_display`)
+//! so that other displays can match them exactly.
+use crate::{
+    beigui::{Dirn, Point, Size},
+    fdrn::{FDRNumber, Prop, UFDRNumber},
+};
+
+/// The rectangle occupied by an attribute display's bar (with the origin at
+/// its top left corner).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BarLayout {
+    pub origin: Point,
+    pub size: Size,
+}
+
+impl BarLayout {
+    /// The whole of a drawing area of `size` (as used by the built in
+    /// displays).
+    pub fn new(size: Size) -> Self {
+        Self {
+            origin: Point::default(),
+            size,
+        }
+    }
+
+    /// `number` bars of equal height stacked (first at the top) in a
+    /// drawing area of `size` with `gap` between them.
+    pub fn stacked(size: Size, number: usize, gap: UFDRNumber) -> Vec<Self> {
+        if number == 0 {
+            return vec![];
+        }
+        let gaps = gap * (number - 1) as i32;
+        let height = if size.height > gaps {
+            (size.height - gaps) / number as i32
+        } else {
+            UFDRNumber::ZERO
+        };
+        (0..number)
+            .map(|i| Self {
+                origin: [FDRNumber::ZERO, ((height + gap) * i as i32).into()].into(),
+                size: Size {
+                    width: size.width,
+                    height,
+                },
+            })
+            .collect()
+    }
+
+    /// Shrunk by `padding` on all sides (to nothing if it's too small).
+    pub fn padded(&self, padding: UFDRNumber) -> Self {
+        let shrink = |length: UFDRNumber| {
+            if length > padding * 2 {
+                length - padding * 2
+            } else {
+                UFDRNumber::ZERO
+            }
+        };
+        Self {
+            origin: self.origin
+                + Point {
+                    x: padding.into(),
+                    y: padding.into(),
+                },
+            size: Size {
+                width: shrink(self.size.width),
+                height: shrink(self.size.height),
+            },
+        }
+    }
+
+    pub fn top(&self) -> FDRNumber {
+        self.origin.y
+    }
+
+    pub fn bottom(&self) -> FDRNumber {
+        self.origin.y + self.size.height.into()
+    }
+
+    /// Where `attr_value` is indicated across the bar.
+    pub fn x_for(&self, attr_value: Prop) -> FDRNumber {
+        self.origin.x + (self.size.width * attr_value).into()
+    }
+
+    /// The attribute value indicated at `x` i.e. the inverse of `x_for()`.
+    pub fn attr_value_at(&self, x: FDRNumber) -> Prop {
+        let width = f64::from(self.size.width);
+        if width > 0.0 {
+            Prop::from((f64::from(x - self.origin.x) / width).clamp(0.0, 1.0))
+        } else {
+            Prop::ZERO
+        }
+    }
+}
+
+/// The sizes of the value and target indicators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkerGeometry {
+    /// The width of the triangles' bases.
+    pub base: UFDRNumber,
+    pub height: UFDRNumber,
+    pub line_width: UFDRNumber,
+}
+
+impl Default for MarkerGeometry {
+    fn default() -> Self {
+        Self {
+            base: UFDRNumber::ONE * 8,
+            height: UFDRNumber::ONE * 6,
+            line_width: UFDRNumber::ONE * 2,
+        }
+    }
+}
+
+impl MarkerGeometry {
+    /// The centres and directions of the value indicator's triangles (which
+    /// point inwards from the top and bottom edges of `bar`).
+    pub fn value_triangles(&self, bar: &BarLayout, attr_value: Prop) -> [(Point, Dirn); 2] {
+        let x = bar.x_for(attr_value);
+        let half_height = FDRNumber::from(self.height / 2);
+        [
+            ([x, bar.top() + half_height].into(), Dirn::Up),
+            ([x, bar.bottom() - half_height].into(), Dirn::Down),
+        ]
+    }
+
+    /// The end points of the target indicator's (vertical) line.
+    pub fn target_line(&self, bar: &BarLayout, attr_target_value: Prop) -> [Point; 2] {
+        let x = bar.x_for(attr_target_value);
+        [
+            [x, bar.top() + FDRNumber::ONE].into(),
+            [x, bar.bottom() - FDRNumber::ONE].into(),
+        ]
+    }
+
+    /// The centres of the arrows (a quarter of the way in from the top and
+    /// bottom of `bar`) pointing at the target from the side that the
+    /// value has to come from.  `None` unless `dirn` is horizontal.
+    pub fn target_arrows(
+        &self,
+        bar: &BarLayout,
+        attr_target_value: Prop,
+        dirn: Dirn,
+    ) -> Option<[(Point, Dirn); 2]> {
+        let x = bar.x_for(attr_target_value);
+        let arrow_x = match dirn {
+            Dirn::Right => x - self.height.into(),
+            Dirn::Left => x + self.height.into(),
+            Dirn::Up | Dirn::Down => return None,
+        };
+        let quarter = FDRNumber::from(bar.size.height / 4);
+        Some([
+            ([arrow_x, bar.top() + quarter].into(), dirn),
+            ([arrow_x, bar.bottom() - quarter].into(), dirn),
+        ])
+    }
+}
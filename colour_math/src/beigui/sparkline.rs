@@ -0,0 +1,118 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    attributes::Value,
+    beigui::{Draw, Point},
+    fdrn::{FDRNumber, UFDRNumber},
+    hcv::HCV,
+    mixing::TargetErrors,
+    HueConstants, RGBConstants,
+};
+
+/// A small plot of the size of the value, chroma and hue errors over the
+/// course of a mixing session: lines heading down towards the baseline mean
+/// that the mixture is converging on its target.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorSparkline {
+    history: Vec<TargetErrors>,
+}
+
+impl ErrorSparkline {
+    pub const VALUE_COLOUR: HCV = HCV::BLACK;
+    pub const CHROMA_COLOUR: HCV = HCV::RED;
+    pub const HUE_COLOUR: HCV = HCV::BLUE;
+
+    pub fn history(&self) -> &[TargetErrors] {
+        &self.history
+    }
+
+    pub fn set_history(&mut self, history: &[TargetErrors]) {
+        self.history = history.to_vec();
+    }
+
+    pub fn push(&mut self, errors: TargetErrors) {
+        self.history.push(errors);
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    /// Absolute value, chroma and hue errors with the hue errors scaled so
+    /// that 180 degrees is one (greys have no hue error).
+    pub fn series(&self) -> [Vec<f64>; 3] {
+        [
+            self.history.iter().map(|e| e.value.abs()).collect(),
+            self.history.iter().map(|e| e.chroma.abs()).collect(),
+            self.history
+                .iter()
+                .map(|e| e.hue.map_or(0.0, |hue| hue.abs() / 180.0))
+                .collect(),
+        ]
+    }
+
+    /// The largest error in any series (which is plotted at the top).
+    pub fn max_error(&self) -> f64 {
+        self.series()
+            .iter()
+            .flat_map(|series| series.iter())
+            .fold(0.0, |max, error| error.max(max))
+    }
+
+    pub fn draw(&self, drawer: &impl Draw) {
+        let size = drawer.size();
+        let width = FDRNumber::from(size.width);
+        let height = FDRNumber::from(size.height);
+        drawer.set_fill_colour(&HCV::new_grey(Value::from(0.95)));
+        drawer.draw_polygon(
+            &[
+                [FDRNumber::ZERO, FDRNumber::ZERO].into(),
+                [width, FDRNumber::ZERO].into(),
+                [width, height].into(),
+                [FDRNumber::ZERO, height].into(),
+            ],
+            true,
+        );
+        let baseline: [Point; 2] = [[FDRNumber::ZERO, height].into(), [width, height].into()];
+        drawer.set_line_width(UFDRNumber::ONE * 2);
+        drawer.set_line_colour(&HCV::new_grey(Value::from(0.6)));
+        drawer.draw_line(&baseline);
+        let range = (0.0, self.max_error());
+        drawer.set_line_width(UFDRNumber::ONE);
+        for (series, colour) in
+            self.series()
+                .iter()
+                .zip([Self::VALUE_COLOUR, Self::CHROMA_COLOUR, Self::HUE_COLOUR])
+        {
+            drawer.set_line_colour(&colour);
+            drawer.draw_time_series(series, range);
+        }
+    }
+}
+
+#[cfg(test)]
+mod sparkline_tests {
+    use super::*;
+
+    #[test]
+    fn series() {
+        let mut sparkline = ErrorSparkline::default();
+        assert_eq!(sparkline.max_error(), 0.0);
+        sparkline.push(TargetErrors {
+            value: -0.5,
+            chroma: 0.25,
+            hue: Some(-90.0),
+        });
+        sparkline.push(TargetErrors {
+            value: 0.1,
+            chroma: 0.0,
+            hue: None,
+        });
+        let [value, chroma, hue] = sparkline.series();
+        assert_eq!(value, vec![0.5, 0.1]);
+        assert_eq!(chroma, vec![0.25, 0.0]);
+        assert_eq!(hue, vec![0.5, 0.0]);
+        assert_eq!(sparkline.max_error(), 0.5);
+        sparkline.clear();
+        assert!(sparkline.history().is_empty());
+    }
+}
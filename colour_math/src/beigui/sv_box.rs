@@ -0,0 +1,182 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    attributes::Value,
+    beigui::{DrawShapes, Point, Size},
+    fdrn::{FDRNumber, Prop, UFDRNumber},
+    hcv::HCV,
+    hue::{Hue, HueBasics},
+    ColourBasics, HueConstants,
+};
+
+/// A square with chroma proportion (saturation) increasing from left to
+/// right and value increasing from bottom to top for a fixed hue. Those
+/// parts of the square that cannot be reached for the hue are masked.
+#[derive(Debug, Clone)]
+pub struct SVBox {
+    hue: Hue,
+    colour: Option<HCV>,
+    cells: u8,
+}
+
+impl Default for SVBox {
+    fn default() -> Self {
+        Self {
+            hue: Hue::RED,
+            colour: None,
+            cells: 32,
+        }
+    }
+}
+
+impl SVBox {
+    pub fn new(cells: u8) -> Self {
+        debug_assert!(cells > 0);
+        Self {
+            cells,
+            ..Self::default()
+        }
+    }
+
+    pub fn hue(&self) -> Hue {
+        self.hue
+    }
+
+    pub fn set_hue(&mut self, hue: Hue) {
+        self.hue = hue;
+    }
+
+    pub fn colour(&self) -> Option<HCV> {
+        self.colour
+    }
+
+    /// Greys leave the hue unchanged.
+    pub fn set_colour(&mut self, colour: Option<&impl ColourBasics>) {
+        self.colour = colour.map(|colour| colour.hcv());
+        if let Some(hue) = self.colour.and_then(|colour| colour.hue) {
+            self.hue = hue;
+        }
+    }
+
+    /// The range of values available at `c_prop` for the current hue.
+    pub fn value_range_for_chroma_prop(&self, c_prop: Prop) -> Option<(Value, Value)> {
+        if c_prop == Prop::ZERO {
+            Some((Value::ZERO, Value::ONE))
+        } else {
+            self.hue
+                .sum_range_for_chroma_prop(c_prop)
+                .map(|(min, max)| ((min / 3).into(), (max / 3).into()))
+        }
+    }
+
+    /// The colour with the current hue at the given position in the square
+    /// or `None` if it is out of gamut.
+    pub fn colour_at(&self, c_prop: Prop, value: Value) -> Option<HCV> {
+        if c_prop == Prop::ZERO {
+            Some(HCV::new_grey(value))
+        } else {
+            let sum = value * 3;
+            if self.hue.sum_and_chroma_prop_are_compatible(sum, c_prop) {
+                match HCV::try_new(Some((self.hue, c_prop)), sum) {
+                    Ok(hcv) => Some(hcv),
+                    Err(hcv) => Some(hcv),
+                }
+            } else {
+                None
+            }
+        }
+    }
+
+    fn position_for_point(point: Point, size: Size) -> (Prop, Value) {
+        let x = f64::from(point.x) / f64::from(size.width);
+        let y = f64::from(point.y) / f64::from(size.height);
+        (
+            Prop::from(x.max(0.0).min(1.0)),
+            Value::from(1.0 - y.max(0.0).min(1.0)),
+        )
+    }
+
+    fn point_for_position(c_prop: Prop, value: Value, size: Size) -> Point {
+        Point {
+            x: FDRNumber::from(f64::from(c_prop) * f64::from(size.width)),
+            y: FDRNumber::from((1.0 - f64::from(value)) * f64::from(size.height)),
+        }
+    }
+
+    /// The colour selected by a click at `point` (in a box of `size`).
+    /// Points outside the gamut give the nearest colour with the same value.
+    pub fn colour_for_point(&self, point: Point, size: Size) -> HCV {
+        let (c_prop, value) = Self::position_for_point(point, size);
+        match self.colour_at(c_prop, value) {
+            Some(hcv) => hcv,
+            None => HCV::new_approx(Some(self.hue), value * 3, c_prop),
+        }
+    }
+
+    pub fn draw(&self, draw_shapes: &impl DrawShapes) {
+        let size = draw_shapes.size();
+        let cells = self.cells as f64;
+        draw_shapes.set_background_colour(&HCV::new_grey(Value::ONE / 2));
+        for column in 0..self.cells {
+            for row in 0..self.cells {
+                let c_prop = Prop::from((column as f64 + 0.5) / cells);
+                let value = Value::from((row as f64 + 0.5) / cells);
+                if let Some(colour) = self.colour_at(c_prop, value) {
+                    let left = Prop::from(column as f64 / cells);
+                    let right = Prop::from((column as f64 + 1.0) / cells);
+                    let bottom = Value::from(row as f64 / cells);
+                    let top = Value::from((row as f64 + 1.0) / cells);
+                    draw_shapes.set_fill_colour(&colour);
+                    draw_shapes.draw_polygon(
+                        &[
+                            Self::point_for_position(left, bottom, size),
+                            Self::point_for_position(left, top, size),
+                            Self::point_for_position(right, top, size),
+                            Self::point_for_position(right, bottom, size),
+                        ],
+                        true,
+                    );
+                }
+            }
+        }
+        if let Some(colour) = self.colour {
+            let centre = Self::point_for_position(colour.c_prop, colour.value(), size);
+            draw_shapes.set_line_colour(&colour.best_foreground());
+            draw_shapes.set_line_width(UFDRNumber::from(2));
+            draw_shapes.draw_circle(centre, UFDRNumber::from(5), false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod sv_box_tests {
+    use super::*;
+    use crate::{debug::ApproxEq, RGBConstants};
+
+    #[test]
+    fn gamut() {
+        let mut sv_box = SVBox::default();
+        assert_eq!(sv_box.colour_at(Prop::ZERO, Value::ONE), Some(HCV::WHITE));
+        assert_eq!(sv_box.colour_at(Prop::ONE, Value::ONE), None);
+        assert_eq!(sv_box.colour_at(Prop::ONE, Value::ZERO), None);
+        let hcv = sv_box.colour_at(Prop::from(0.5), Value::ONE / 2).unwrap();
+        assert_eq!(hcv.hue(), Some(Hue::RED));
+        let (min, max) = sv_box.value_range_for_chroma_prop(Prop::from(0.5)).unwrap();
+        assert!(min < Value::ONE / 2 && max > Value::ONE / 2);
+        sv_box.set_colour(Some(&HCV::YELLOW));
+        assert_eq!(sv_box.hue(), Hue::YELLOW);
+        sv_box.set_colour(Some(&HCV::BLACK));
+        assert_eq!(sv_box.hue(), Hue::YELLOW);
+        let size = Size {
+            width: UFDRNumber::from(100),
+            height: UFDRNumber::from(100),
+        };
+        let hcv = sv_box.colour_for_point(
+            Point {
+                x: FDRNumber::from(100),
+                y: FDRNumber::ZERO,
+            },
+            size,
+        );
+        assert!(hcv.value().approx_eq(&Value::ONE, None));
+    }
+}
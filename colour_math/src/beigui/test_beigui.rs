@@ -7,3 +7,384 @@ fn div_by_u8() {
     assert_eq!(FDRNumber::ONE / 2, FDRNumber(u64::MAX as i128 / 2));
     assert_eq!(FDRNumber::ONE / 3, FDRNumber(u64::MAX as i128 / 3));
 }
+
+#[test]
+fn level_of_detail() {
+    use crate::{
+        beigui::hue_wheel::{Detail, LevelOfDetail},
+        fdrn::UFDRNumber,
+    };
+    let lod = LevelOfDetail::default();
+    assert_eq!(lod.detail(UFDRNumber::from(2), 10), Detail::Dot);
+    assert_eq!(lod.detail(UFDRNumber::from(6), 10), Detail::Filled);
+    assert_eq!(lod.detail(UFDRNumber::from(10), 10), Detail::Full);
+    assert_eq!(lod.detail(UFDRNumber::from(10), 5000), Detail::Filled);
+    assert_eq!(
+        LevelOfDetail::full().detail(UFDRNumber::ZERO, 5000),
+        Detail::Full
+    );
+}
+
+#[test]
+fn merge_similar_colours() {
+    use crate::{
+        beigui::{hue_wheel::HueWheel, Point},
+        fdrn::Prop,
+        rgb::RGB,
+        ScalarAttribute,
+    };
+    let mut hue_wheel = HueWheel::new();
+    for rgb in [
+        [1.0, 1.0, 1.0],
+        [0.99, 0.99, 0.99],
+        [0.98, 0.98, 0.98],
+        [0.8, 0.1, 0.1],
+    ] {
+        hue_wheel.add_item((&RGB::<f64>::from(rgb)).into());
+    }
+    assert_eq!(hue_wheel.clusters().len(), 4);
+    hue_wheel.set_merge_distance(Some(Prop::from(0.05)));
+    let clusters = hue_wheel.clusters();
+    assert_eq!(clusters.len(), 2);
+    assert!(clusters.iter().any(|c| c.len() == 3));
+    // greys are drawn to the left of the wheel and the lead is the darkest
+    let white_xy = Point {
+        x: FDRNumber::from(-1.05),
+        y: FDRNumber::from(1.0 - 2.0 * 0.98),
+    };
+    let tooltip = hue_wheel.tooltip_for_point(white_xy, ScalarAttribute::Value);
+    assert!(tooltip.unwrap().starts_with("3 similar colours"));
+    assert!(hue_wheel.toggle_expansion_at_point(white_xy, ScalarAttribute::Value));
+    let tooltip = hue_wheel.tooltip_for_point(white_xy, ScalarAttribute::Value);
+    assert!(!tooltip.unwrap().starts_with("3 similar colours"));
+    assert!(hue_wheel.toggle_expansion_at_point(white_xy, ScalarAttribute::Value));
+}
+
+#[test]
+fn angular_labels() {
+    use crate::{beigui::hue_wheel::AngularLabels, hue::angle::Angle, HueConstants};
+    assert!(AngularLabels::None.labels().is_empty());
+    let labels = AngularLabels::Degrees.labels();
+    assert_eq!(labels.len(), 12);
+    assert_eq!(labels[0], (Angle::RED, "0\u{b0}".to_string()));
+    assert_eq!(labels[6], (Angle::CYAN, "180\u{b0}".to_string()));
+    let labels = AngularLabels::HueNames.labels();
+    assert_eq!(labels.len(), 6);
+    assert_eq!(labels[2], (Angle::GREEN, "GREEN".to_string()));
+}
+
+#[test]
+fn grouped_items() {
+    use crate::{
+        beigui::hue_wheel::{ColouredShape, ColouredShapeSpec, HueWheel, Shape},
+        hcv::HCV,
+        HueConstants,
+    };
+    let mut hue_wheel = HueWheel::new();
+    for (id, colour, group) in [
+        ("red", HCV::RED, Some("Brand A")),
+        ("green", HCV::GREEN, Some("Brand B")),
+        ("blue", HCV::BLUE, Some("Brand A")),
+        ("cyan", HCV::CYAN, None),
+    ] {
+        let mut shape = ColouredShape::new(&colour, id, id, Shape::Circle);
+        shape.set_group(group);
+        hue_wheel.add_item(shape);
+    }
+    let legend = hue_wheel.legend();
+    assert_eq!(legend.len(), 2);
+    assert_eq!(legend[0].group, "Brand A");
+    assert_eq!(legend[0].number_of_items, 2);
+    assert!(legend.iter().all(|entry| entry.visible));
+    hue_wheel.set_group_visible("Brand A", false);
+    assert!(!hue_wheel.legend()[0].visible);
+    assert_eq!(hue_wheel.clusters().len(), 2);
+    hue_wheel.set_group_visible("Brand A", true);
+    assert_eq!(hue_wheel.clusters().len(), 4);
+
+    let mut shape = ColouredShape::new(&HCV::YELLOW, "yellow", "Yellow", Shape::Square);
+    shape.set_group(Some("Brand B"));
+    let spec = ColouredShapeSpec::from(&shape);
+    let json = serde_json::to_string(&spec).unwrap();
+    let spec: ColouredShapeSpec = serde_json::from_str(&json).unwrap();
+    assert_eq!(ColouredShape::from(&spec).group(), Some("Brand B"));
+    let spec: ColouredShapeSpec =
+        serde_json::from_str(&json.replace(",\"group\":\"Brand B\"", "")).unwrap();
+    assert_eq!(spec.group, None);
+}
+
+#[test]
+fn locate_items() {
+    use crate::{
+        beigui::hue_wheel::{ColouredShape, HueWheel, Shape},
+        fdrn::UFDRNumber,
+        hcv::HCV,
+        rgb::RGB,
+        HueConstants, ScalarAttribute,
+    };
+    let mut hue_wheel = HueWheel::new();
+    assert!(hue_wheel.nearest_item_to_colour(&HCV::RED).is_none());
+    for (id, colour) in [("Cadmium Red", HCV::RED), ("Leaf Green", HCV::GREEN)] {
+        hue_wheel.add_item(ColouredShape::new(&colour, id, id, Shape::Circle));
+    }
+    let orange = RGB::<f64>::from([1.0, 0.3, 0.0]);
+    let nearest = hue_wheel.nearest_item_to_colour(&orange).unwrap();
+    assert_eq!(nearest.id(), "Cadmium Red");
+    assert_eq!(
+        hue_wheel.find_item("leaf green").unwrap().id(),
+        "Leaf Green"
+    );
+    assert_eq!(hue_wheel.find_item("green").unwrap().colour(), HCV::GREEN);
+    assert!(hue_wheel.find_item("blue").is_none());
+    let attr = ScalarAttribute::Chroma;
+    assert_eq!(
+        hue_wheel.item_position("Leaf Green", attr),
+        Some(hue_wheel.colour_position(&HCV::GREEN, attr))
+    );
+    hue_wheel.set_zoom_scale(UFDRNumber::from(100));
+    assert!(hue_wheel.zoom_scale() < UFDRNumber::from(100));
+    hue_wheel.set_highlight(Some(&HCV::GREEN));
+    assert_eq!(hue_wheel.highlight(), Some(HCV::GREEN));
+}
+
+#[test]
+fn update_item_colour() {
+    use crate::{
+        beigui::hue_wheel::{ColouredShape, HueWheel, Shape},
+        hcv::HCV,
+        HueConstants, RGBConstants, ScalarAttribute,
+    };
+    let attr = ScalarAttribute::Value;
+    let mut hue_wheel = HueWheel::new();
+    let mut shape = ColouredShape::new(&HCV::RED, "paint", "Paint", Shape::Square);
+    shape.set_group(Some("Brand A"));
+    hue_wheel.add_item(shape);
+    assert!(hue_wheel.set_item_colour("paint", &HCV::BLUE));
+    assert!(!hue_wheel.set_item_colour("paint", &HCV::BLUE));
+    assert!(!hue_wheel.set_item_colour("missing", &HCV::BLUE));
+    let item = hue_wheel.find_item("paint").unwrap();
+    assert_eq!(item.colour(), HCV::BLUE);
+    assert_eq!(item.group(), Some("Brand A"));
+    assert_eq!(
+        hue_wheel.item_position("paint", attr),
+        Some(hue_wheel.colour_position(&HCV::BLUE, attr))
+    );
+    // greys are drawn beside the wheel
+    assert!(hue_wheel.set_item_colour("paint", &HCV::WHITE));
+    assert_eq!(
+        hue_wheel.item_position("paint", attr),
+        Some(hue_wheel.colour_position(&HCV::WHITE, attr))
+    );
+}
+
+#[test]
+fn ordered_items() {
+    use crate::{
+        beigui::hue_wheel::{ColouredShape, HueWheel, HueWheelEvent, Shape},
+        hcv::HCV,
+        HueConstants,
+    };
+    use std::{cell::RefCell, rc::Rc};
+    let events = Rc::new(RefCell::new(vec![]));
+    let mut hue_wheel = HueWheel::new();
+    let events_c = Rc::clone(&events);
+    hue_wheel.connect_changed(move |event| events_c.borrow_mut().push(event.clone()));
+    assert!(hue_wheel.is_empty());
+    for (id, colour) in [
+        ("zinc", HCV::CYAN),
+        ("alizarin", HCV::RED),
+        ("ochre", HCV::YELLOW),
+    ] {
+        hue_wheel.add_item(ColouredShape::new(&colour, id, id, Shape::Circle));
+    }
+    let ids = |hue_wheel: &HueWheel| -> Vec<String> {
+        hue_wheel
+            .items()
+            .map(|item| item.id().to_string())
+            .collect()
+    };
+    assert_eq!(ids(&hue_wheel), vec!["zinc", "alizarin", "ochre"]);
+    hue_wheel.add_item(ColouredShape::new(&HCV::BLUE, "zinc", "", Shape::Square));
+    assert_eq!(ids(&hue_wheel), vec!["zinc", "alizarin", "ochre"]);
+    assert_eq!(hue_wheel.item("zinc").unwrap().colour(), HCV::BLUE);
+    assert!(hue_wheel.remove_item("alizarin").is_some());
+    assert!(!hue_wheel.contains_item("alizarin"));
+    assert!(hue_wheel.remove_item("alizarin").is_none());
+    assert_eq!(ids(&hue_wheel), vec!["zinc", "ochre"]);
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            HueWheelEvent::ItemAdded("zinc".to_string()),
+            HueWheelEvent::ItemAdded("alizarin".to_string()),
+            HueWheelEvent::ItemAdded("ochre".to_string()),
+            HueWheelEvent::ItemChanged("zinc".to_string()),
+            HueWheelEvent::ItemRemoved("alizarin".to_string()),
+        ]
+    );
+    events.borrow_mut().clear();
+    hue_wheel.replace_all(
+        [("b", HCV::GREEN), ("a", HCV::MAGENTA), ("b", HCV::RED)]
+            .iter()
+            .map(|(id, colour)| ColouredShape::new(colour, id, id, Shape::Diamond)),
+    );
+    assert_eq!(hue_wheel.len(), 2);
+    assert_eq!(ids(&hue_wheel), vec!["b", "a"]);
+    assert_eq!(hue_wheel.item("b").unwrap().colour(), HCV::RED);
+    assert_eq!(*events.borrow(), vec![HueWheelEvent::ItemsReplaced]);
+}
+
+#[test]
+fn attr_display_click_to_set() {
+    use crate::{
+        beigui::attr_display::{ChromaCAD, ColourAttributeDisplayIfce, HueCAD, ValueCAD},
+        fdrn::{IntoProp, Prop},
+        hcv::HCV,
+        manipulator::ColourManipulatorBuilder,
+        ColourBasics, HueConstants, Value,
+    };
+    assert_eq!(ValueCAD::attr_value_at(0.0, 90.0), Prop::ZERO);
+    assert_eq!(ValueCAD::attr_value_at(120.0, 90.0), Prop::ONE);
+    assert_eq!(ValueCAD::attr_value_at(10.0, 0.0), Prop::ZERO);
+    let mut manipulator = ColourManipulatorBuilder::new()
+        .init_hcv(&HCV::new_grey(Value::ONE / 2))
+        .build();
+    assert!(ValueCAD::set_attr_value(&mut manipulator, Prop::ONE).is_some());
+    assert_eq!(manipulator.hcv().value(), Value::ONE);
+    assert!(HueCAD::set_attr_value(&mut manipulator, Prop::ZERO).is_none());
+    let mut manipulator = ColourManipulatorBuilder::new().init_hcv(&HCV::RED).build();
+    assert!(ChromaCAD::set_attr_value(&mut manipulator, Prop::ONE / 2).is_some());
+    assert!(manipulator.hcv().chroma().into_prop() < Prop::ONE);
+}
+
+#[test]
+fn attr_display_target_dirn() {
+    use crate::{
+        attributes::Value,
+        beigui::{
+            attr_display::{
+                ChromaCAD, ColourAttributeDisplayIfce, GreynessCAD, ValueCAD, WarmthCAD,
+            },
+            Dirn,
+        },
+        hcv::HCV,
+        HueConstants, RGBConstants,
+    };
+    fn dirn<CAD: ColourAttributeDisplayIfce>(colour: &HCV, target: &HCV) -> Option<Dirn> {
+        let mut cad = CAD::new();
+        cad.set_colour(Some(colour));
+        cad.set_target_colour(Some(target));
+        cad.target_dirn()
+    }
+    let grey = HCV::new_grey(Value::ONE / 2);
+    // greyness runs the other way to chroma
+    assert_eq!(dirn::<ChromaCAD>(&HCV::RED, &grey), Some(Dirn::Left));
+    assert_eq!(dirn::<GreynessCAD>(&HCV::RED, &grey), Some(Dirn::Right));
+    assert_eq!(
+        dirn::<ValueCAD>(&HCV::BLACK, &HCV::WHITE),
+        Some(Dirn::Right)
+    );
+    assert_eq!(dirn::<WarmthCAD>(&HCV::RED, &HCV::CYAN), Some(Dirn::Left));
+    assert_eq!(dirn::<WarmthCAD>(&HCV::RED, &HCV::RED), None);
+    let mut cad = GreynessCAD::new();
+    cad.set_target_colour(Some(&grey));
+    assert_eq!(cad.target_dirn(), None);
+}
+
+#[test]
+fn bar_and_marker_layout() {
+    use crate::{
+        beigui::{
+            layout::{BarLayout, MarkerGeometry},
+            Dirn, Point, Size,
+        },
+        fdrn::{Prop, UFDRNumber},
+    };
+    let near = |number: FDRNumber, expected: f64| (f64::from(number) - expected).abs() < 1e-9;
+    let at = |point: Point, x: f64, y: f64| near(point.x, x) && near(point.y, y);
+    let size = Size::from([UFDRNumber::from(100), UFDRNumber::from(40)]);
+    let bar = BarLayout::new(size);
+    assert_eq!(bar.x_for(Prop::ZERO), FDRNumber::ZERO);
+    assert!(near(bar.x_for(Prop::ONE), 100.0));
+    assert_eq!(bar.attr_value_at(FDRNumber::from(150.0)), Prop::ONE);
+    let geometry = MarkerGeometry::default();
+    let [top, bottom] = geometry.value_triangles(&bar, Prop::ONE / 2);
+    assert!(at(top.0, 50.0, 3.0) && top.1 == Dirn::Up);
+    assert!(at(bottom.0, 50.0, 37.0) && bottom.1 == Dirn::Down);
+    let [start, end] = geometry.target_line(&bar, Prop::ZERO);
+    assert!(at(start, 0.0, 1.0) && at(end, 0.0, 39.0));
+    let arrows = geometry
+        .target_arrows(&bar, Prop::ONE, Dirn::Right)
+        .unwrap();
+    assert!(at(arrows[0].0, 94.0, 10.0) && at(arrows[1].0, 94.0, 30.0));
+    assert!(arrows.iter().all(|(_, dirn)| *dirn == Dirn::Right));
+    assert!(geometry.target_arrows(&bar, Prop::ONE, Dirn::Up).is_none());
+
+    let bars = BarLayout::stacked(size, 3, UFDRNumber::from(5));
+    assert_eq!(bars.len(), 3);
+    assert!(near(bars[1].size.height.into(), 10.0));
+    assert!(near(bars[2].top(), 30.0) && near(bars[2].bottom(), 40.0));
+    let padded = bar.padded(UFDRNumber::from(2));
+    assert!(at(padded.origin, 2.0, 2.0));
+    assert!(near(padded.size.width.into(), 96.0));
+    assert_eq!(
+        bar.padded(UFDRNumber::from(50)).size.height,
+        UFDRNumber::ZERO
+    );
+}
+
+#[test]
+fn time_series_coordinates() {
+    use crate::beigui::time_series_xy;
+    let xy = time_series_xy(100.0, 50.0, &[0.0, 0.5, 1.0, 2.0], (0.0, 1.0));
+    assert_eq!(xy.len(), 4);
+    assert_eq!(xy[0], (0.0, 50.0));
+    assert!((xy[1].0 - 100.0 / 3.0).abs() < 1e-9);
+    assert_eq!(xy[1].1, 25.0);
+    assert_eq!(xy[2].1, 0.0);
+    assert_eq!(xy[3], (100.0, 0.0));
+    assert_eq!(
+        time_series_xy(100.0, 50.0, &[3.0], (1.0, 1.0)),
+        vec![(0.0, 25.0)]
+    );
+    assert!(time_series_xy(100.0, 50.0, &[], (0.0, 1.0)).is_empty());
+}
+
+#[test]
+fn text_boxes() {
+    use crate::{
+        beigui::{text_box, Point, TextPosn},
+        fdrn::UFDRNumber,
+    };
+    let point = |x: f64, y: f64| -> Point { [FDRNumber::from(x), FDRNumber::from(y)].into() };
+    let font_size = UFDRNumber::from(10);
+    let corners = text_box("abcd", &TextPosn::TopLeftCorner(point(5.0, 5.0)), font_size);
+    assert_eq!(corners[0], point(5.0, 5.0));
+    assert!((f64::from(corners[2].x) - 29.0).abs() < 1e-9);
+    assert!((f64::from(corners[2].y) - 12.0).abs() < 1e-9);
+    let centred = text_box("abcd", &TextPosn::Centre(point(50.0, 50.0)), font_size);
+    assert!((f64::from(centred[0].x) - 38.0).abs() < 1e-9);
+    assert!((f64::from(centred[0].y) - 46.5).abs() < 1e-9);
+    let right = text_box(
+        "abcd",
+        &TextPosn::BottomRightCorner(point(50.0, 50.0)),
+        font_size,
+    );
+    assert!((f64::from(right[2].x) - 50.0).abs() < 1e-9);
+    assert!((f64::from(right[2].y) - 50.0).abs() < 1e-9);
+}
+
+#[test]
+fn monochrome_annotations() {
+    use crate::{beigui::monochrome_annotation, hcv::HCV, HueConstants, RGBConstants, Value};
+
+    assert_eq!(monochrome_annotation(&HCV::BLACK), "V 0% Grey");
+    assert_eq!(
+        monochrome_annotation(&HCV::new_grey(Value::ONE / 2)),
+        "V 50% Grey"
+    );
+    assert_eq!(monochrome_annotation(&HCV::RED), "V 33% C 100% H 0\u{b0}");
+    assert_eq!(
+        monochrome_annotation(&HCV::BLUE),
+        "V 33% C 100% H 240\u{b0}"
+    );
+}
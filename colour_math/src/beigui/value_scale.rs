@@ -0,0 +1,139 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    attributes::Value,
+    beigui::{Dirn, DrawIsosceles, Point, TextPosn},
+    fdrn::{FDRNumber, Prop, UFDRNumber},
+    hcv::HCV,
+    ColourBasics,
+};
+
+/// A grey step wedge running from black to white with an indicator showing
+/// where a colour's value falls.
+#[derive(Debug, Clone)]
+pub struct ValueScale {
+    steps: u8,
+    value: Option<Value>,
+}
+
+impl Default for ValueScale {
+    fn default() -> Self {
+        Self {
+            steps: 11,
+            value: None,
+        }
+    }
+}
+
+impl ValueScale {
+    pub fn new(steps: u8) -> Self {
+        debug_assert!(steps > 1);
+        Self { steps, value: None }
+    }
+
+    pub fn steps(&self) -> u8 {
+        self.steps
+    }
+
+    pub fn set_steps(&mut self, steps: u8) {
+        debug_assert!(steps > 1);
+        self.steps = steps;
+    }
+
+    pub fn set_colour(&mut self, colour: Option<&impl ColourBasics>) {
+        self.value = colour.map(|colour| colour.value());
+    }
+
+    /// The grey for each step (black first and white last).
+    pub fn greys(&self) -> Vec<HCV> {
+        let last = (self.steps - 1) as f64;
+        (0..self.steps)
+            .map(|step| HCV::new_grey(Value::from(step as f64 / last)))
+            .collect()
+    }
+
+    /// The step whose grey is nearest in value to `value`.
+    pub fn step_for_value(&self, value: Value) -> u8 {
+        (f64::from(value) * (self.steps - 1) as f64).round() as u8
+    }
+
+    /// The step nearest the current colour's value.
+    pub fn current_step(&self) -> Option<u8> {
+        self.value.map(|value| self.step_for_value(value))
+    }
+
+    pub fn draw(&self, drawer: &impl DrawIsosceles) {
+        let size = drawer.size();
+        let step_width = size.width / self.steps as i32;
+        let current_step = self.current_step();
+        for (step, grey) in self.greys().iter().enumerate() {
+            let left = FDRNumber::from(step_width * step as i32);
+            let right = left + FDRNumber::from(step_width);
+            let top = FDRNumber::from(size.height);
+            drawer.set_fill_colour(grey);
+            drawer.draw_polygon(
+                &[
+                    [left, FDRNumber::ZERO].into(),
+                    [left, top].into(),
+                    [right, top].into(),
+                    [right, FDRNumber::ZERO].into(),
+                ],
+                true,
+            );
+            let centre: Point = [(left + right) / 2, top / 2].into();
+            let font_size = if current_step == Some(step as u8) {
+                UFDRNumber::ONE * 14
+            } else {
+                UFDRNumber::ONE * 10
+            };
+            drawer.set_text_colour(&grey.best_foreground());
+            drawer.draw_text_as_configured(&step.to_string(), TextPosn::Centre(centre), font_size);
+        }
+        if let Some(value) = self.value {
+            let indicator_x: FDRNumber = (size.width * Prop::from(value)).into();
+            let base = UFDRNumber::ONE * 8;
+            let height = UFDRNumber::ONE * 6;
+            let colour = if value > Value::from(0.5) {
+                HCV::new_grey(Value::ZERO)
+            } else {
+                HCV::new_grey(Value::ONE)
+            };
+            drawer.set_fill_colour(&colour);
+            drawer.set_line_colour(&colour);
+            drawer.draw_isosceles(
+                [indicator_x, (height / 2).into()].into(),
+                Dirn::Up,
+                base,
+                height,
+                true,
+            );
+            drawer.draw_isosceles(
+                [indicator_x, (size.height - height / 2).into()].into(),
+                Dirn::Down,
+                base,
+                height,
+                true,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod value_scale_tests {
+    use super::*;
+    use crate::RGBConstants;
+
+    #[test]
+    fn steps() {
+        let mut scale = ValueScale::default();
+        let greys = scale.greys();
+        assert_eq!(greys.len(), 11);
+        assert_eq!(greys[0], HCV::BLACK);
+        assert_eq!(greys[10], HCV::WHITE);
+        assert_eq!(scale.current_step(), None);
+        scale.set_colour(Some(&HCV::new_grey(Value::from(0.34))));
+        assert_eq!(scale.current_step(), Some(3));
+        scale.set_steps(5);
+        assert_eq!(scale.current_step(), Some(1));
+        assert_eq!(scale.step_for_value(Value::ONE), 4);
+    }
+}
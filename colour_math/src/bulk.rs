@@ -0,0 +1,134 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! Transformation of whole images' worth of pixels (rather than one at a
+//! time) which, with the `rayon` feature, is spread across all available
+//! processors.  The transformation has to be `Sync` either way so that
+//! turning the feature on can't break callers.
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{hcv::HCV, rgb::RGB, ColourBasics, LightLevel};
+
+fn transform_pixel<L: LightLevel>(pixel: &mut RGB<L>, transform: &impl Fn(&HCV) -> HCV) {
+    *pixel = transform(&pixel.hcv()).rgb::<L>();
+}
+
+fn transform_channels<L: LightLevel>(channels: &mut [L], transform: &impl Fn(&HCV) -> HCV) {
+    let mut pixel = RGB::<L>::from([channels[0], channels[1], channels[2]]);
+    transform_pixel(&mut pixel, transform);
+    channels[..3].copy_from_slice(&<[L; 3]>::from(pixel));
+}
+
+/// Replace each of `pixels` with the result of `transform`.
+pub fn transform_slice<L, F>(pixels: &mut [RGB<L>], transform: &F)
+where
+    L: LightLevel + Send,
+    F: Fn(&HCV) -> HCV + Sync,
+{
+    #[cfg(feature = "rayon")]
+    pixels
+        .par_iter_mut()
+        .for_each(|pixel| transform_pixel(pixel, transform));
+    #[cfg(not(feature = "rayon"))]
+    pixels
+        .iter_mut()
+        .for_each(|pixel| transform_pixel(pixel, transform));
+}
+
+/// As `transform_slice()` for a buffer of interleaved components with
+/// `n_channels` per pixel (the first three being red, green and blue).  Any
+/// extra channels (e.g. alpha) are left untouched as is an incomplete pixel
+/// at the end.  Nothing is done if `n_channels` is less than three.
+pub fn transform_interleaved<L, F>(buffer: &mut [L], n_channels: usize, transform: &F)
+where
+    L: LightLevel + Send,
+    F: Fn(&HCV) -> HCV + Sync,
+{
+    if n_channels < 3 {
+        return;
+    }
+    #[cfg(feature = "rayon")]
+    buffer
+        .par_chunks_exact_mut(n_channels)
+        .for_each(|channels| transform_channels(channels, transform));
+    #[cfg(not(feature = "rayon"))]
+    buffer
+        .chunks_exact_mut(n_channels)
+        .for_each(|channels| transform_channels(channels, transform));
+}
+
+/// As `transform_interleaved()` for an image whose rows start `rowstride`
+/// components apart (e.g. a GDK pixbuf) so that any padding at the end of
+/// each row is left untouched.  Nothing is done if `rowstride` is too
+/// small to hold `width` pixels.
+pub fn transform_strided<L, F>(
+    buffer: &mut [L],
+    width: usize,
+    rowstride: usize,
+    n_channels: usize,
+    transform: &F,
+) where
+    L: LightLevel + Send,
+    F: Fn(&HCV) -> HCV + Sync,
+{
+    let row_length = width * n_channels;
+    if n_channels < 3 || rowstride < row_length || row_length == 0 {
+        return;
+    }
+    let transform_row = |row: &mut [L]| {
+        let length = row_length.min(row.len());
+        row[..length]
+            .chunks_exact_mut(n_channels)
+            .for_each(|channels| transform_channels(channels, transform));
+    };
+    #[cfg(feature = "rayon")]
+    buffer.par_chunks_mut(rowstride).for_each(transform_row);
+    #[cfg(not(feature = "rayon"))]
+    buffer.chunks_mut(rowstride).for_each(transform_row);
+}
+
+#[cfg(test)]
+mod bulk_tests {
+    use super::*;
+    use crate::{Angle, ManipulatedColour, Prop, RGBConstants};
+
+    fn complement(hcv: &HCV) -> HCV {
+        hcv.rotated(Angle::from(180))
+    }
+
+    #[test]
+    fn slices() {
+        let original = [
+            RGB::<u8>::RED,
+            RGB::<u8>::YELLOW,
+            RGB::from([12, 200, 130]),
+            RGB::<u8>::WHITE,
+        ];
+        let mut pixels = original;
+        transform_slice(&mut pixels, &complement);
+        for (pixel, original) in pixels.iter().zip(original.iter()) {
+            assert_eq!(*pixel, complement(&original.hcv()).rgb::<u8>());
+        }
+        assert_eq!(pixels[0], RGB::<u8>::CYAN);
+        let mut pixels = [RGB::<f64>::from([0.2, 0.4, 0.6]); 3];
+        transform_slice(&mut pixels, &|hcv: &HCV| hcv.greyed(Prop::ONE));
+        assert!(pixels.iter().all(|pixel| pixel.is_grey()));
+    }
+
+    #[test]
+    fn buffers() {
+        // RGBA with an incomplete pixel at the end
+        let mut buffer: Vec<u8> = vec![255, 0, 0, 7, 0, 255, 0, 9, 1, 2];
+        transform_interleaved(&mut buffer, 4, &complement);
+        assert_eq!(buffer, vec![0, 255, 255, 7, 255, 0, 255, 9, 1, 2]);
+        let mut unchanged = buffer.clone();
+        transform_interleaved(&mut unchanged, 2, &complement);
+        assert_eq!(unchanged, buffer);
+        // two rows of one RGB pixel padded to a rowstride of four
+        let mut buffer: Vec<u8> = vec![255, 0, 0, 42, 0, 0, 255, 42];
+        transform_strided(&mut buffer, 1, 4, 3, &complement);
+        assert_eq!(buffer, vec![0, 255, 255, 42, 255, 255, 0, 42]);
+        let mut unchanged = buffer.clone();
+        transform_strided(&mut unchanged, 2, 4, 3, &complement);
+        assert_eq!(unchanged, buffer);
+    }
+}
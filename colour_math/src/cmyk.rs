@@ -0,0 +1,185 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::ops::Index;
+
+use crate::{fdrn::Prop, hcv::HCV, rgb::RGB, ColourBasics, LightLevel};
+
+/// How the achromatic (grey) component of a colour is split between black
+/// ink and the three chromatic inks.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Separation {
+    /// The proportion of the grey component to print with black ink.
+    pub black_generation: Prop,
+    /// The proportion of the black ink to remove from the chromatic inks.
+    /// Anything less than one leaves extra ink under the black (which
+    /// deepens shadows) and is reflected in the colour of the result.
+    pub undercolour_removal: Prop,
+}
+
+impl Separation {
+    /// Full grey component replacement: the conventional device conversion.
+    pub const FULL: Self = Self {
+        black_generation: Prop::ONE,
+        undercolour_removal: Prop::ONE,
+    };
+
+    /// No black ink at all.
+    pub const NO_BLACK: Self = Self {
+        black_generation: Prop::ZERO,
+        undercolour_removal: Prop::ZERO,
+    };
+}
+
+impl Default for Separation {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+/// Cyan, magenta, yellow and black ink coverage (device CMYK i.e. without
+/// any ink or paper profile).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Default)]
+pub struct CMYK<L: LightLevel>(pub(crate) [L; 4]);
+
+impl<L: LightLevel> Eq for CMYK<L> where L: Eq {}
+
+impl<L: LightLevel> CMYK<L> {
+    pub fn new(cyan: L, magenta: L, yellow: L, black: L) -> Self {
+        Self([cyan, magenta, yellow, black])
+    }
+
+    pub fn from_colour(colour: &impl ColourBasics, separation: Separation) -> Self {
+        let rgb = colour.rgb::<f64>();
+        let cmy = [0, 1, 2].map(|i| 1.0 - rgb[i]);
+        let grey = cmy[0].min(cmy[1]).min(cmy[2]);
+        let black = grey * f64::from(separation.black_generation);
+        let removed = black * f64::from(separation.undercolour_removal);
+        let inks = if removed < 1.0 {
+            cmy.map(|c| ((c - removed) / (1.0 - removed)).clamp(0.0, 1.0))
+        } else {
+            [0.0; 3]
+        };
+        Self([inks[0], inks[1], inks[2], black].map(|ink| L::from(Prop::from(ink))))
+    }
+
+    pub fn cyan(&self) -> L {
+        self.0[0]
+    }
+
+    pub fn magenta(&self) -> L {
+        self.0[1]
+    }
+
+    pub fn yellow(&self) -> L {
+        self.0[2]
+    }
+
+    pub fn black(&self) -> L {
+        self.0[3]
+    }
+
+    fn inks(&self) -> [f64; 4] {
+        self.0.map(|ink| f64::from(Into::<Prop>::into(ink)))
+    }
+
+    /// Total ink coverage (0 to 4) for checking against a press's ink limit.
+    pub fn total_ink(&self) -> f64 {
+        self.inks().iter().sum()
+    }
+
+    pub fn rgb<M: LightLevel>(&self) -> RGB<M> {
+        let [cyan, magenta, yellow, black] = self.inks();
+        let rgb = [cyan, magenta, yellow].map(|c| M::from(Prop::from((1.0 - c) * (1.0 - black))));
+        RGB::from(rgb)
+    }
+
+    pub fn hcv(&self) -> HCV {
+        HCV::from(self.rgb::<f64>())
+    }
+}
+
+impl<L: LightLevel> Index<usize> for CMYK<L> {
+    type Output = L;
+
+    fn index(&self, index: usize) -> &L {
+        &self.0[index]
+    }
+}
+
+impl<L: LightLevel> From<[L; 4]> for CMYK<L> {
+    fn from(array: [L; 4]) -> Self {
+        Self(array)
+    }
+}
+
+impl<L: LightLevel, M: LightLevel> From<&RGB<M>> for CMYK<L> {
+    fn from(rgb: &RGB<M>) -> Self {
+        CMYK::from_colour(rgb, Separation::default())
+    }
+}
+
+impl<L: LightLevel> From<&HCV> for CMYK<L> {
+    fn from(hcv: &HCV) -> Self {
+        CMYK::from_colour(hcv, Separation::default())
+    }
+}
+
+impl<L: LightLevel, M: LightLevel> From<CMYK<L>> for RGB<M> {
+    fn from(cmyk: CMYK<L>) -> Self {
+        cmyk.rgb::<M>()
+    }
+}
+
+impl<L: LightLevel> From<CMYK<L>> for HCV {
+    fn from(cmyk: CMYK<L>) -> Self {
+        cmyk.hcv()
+    }
+}
+
+#[cfg(test)]
+mod cmyk_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    #[test]
+    fn primaries_and_greys() {
+        assert_eq!(CMYK::<u8>::from(&HCV::RED), CMYK::new(0, 255, 255, 0));
+        assert_eq!(CMYK::<u8>::from(&HCV::CYAN), CMYK::new(255, 0, 0, 0));
+        assert_eq!(CMYK::<u8>::from(&HCV::BLACK), CMYK::new(0, 0, 0, 255));
+        assert_eq!(CMYK::<u8>::from(&HCV::WHITE), CMYK::new(0, 0, 0, 0));
+        let black = CMYK::<f64>::from_colour(&HCV::BLACK, Separation::NO_BLACK);
+        assert_eq!(black, CMYK::new(1.0, 1.0, 1.0, 0.0));
+        assert_eq!(black.rgb::<u8>(), RGB::<u8>::BLACK);
+    }
+
+    #[test]
+    fn round_trip() {
+        let separations = [
+            Separation::FULL,
+            Separation::NO_BLACK,
+            Separation {
+                black_generation: Prop::HALF,
+                undercolour_removal: Prop::ONE,
+            },
+        ];
+        for colour in HCV::PRIMARIES
+            .iter()
+            .chain(HCV::SECONDARIES.iter())
+            .chain(HCV::GREYS.iter())
+        {
+            let rgb = colour.rgb::<u8>();
+            for separation in separations.iter() {
+                let cmyk = CMYK::<f64>::from_colour(colour, *separation);
+                assert_eq!(RGB::<u8>::rounded_from(&cmyk.rgb::<f64>()), rgb);
+            }
+        }
+        let grey = RGB::<f64>::from([0.5, 0.5, 0.5]);
+        let partial = Separation {
+            black_generation: Prop::ONE,
+            undercolour_removal: Prop::HALF,
+        };
+        let full = CMYK::<f64>::from(&grey);
+        let cmyk = CMYK::<f64>::from_colour(&grey, partial);
+        assert!(cmyk.total_ink() > full.total_ink());
+        assert!(cmyk.rgb::<f64>().value() < grey.value());
+    }
+}
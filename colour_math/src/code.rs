@@ -0,0 +1,149 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{palette::Palette, ColourBasics};
+
+/// Convert `name` into a valid Rust constant name e.g. "Sky blue" becomes
+/// "SKY_BLUE".
+pub fn rust_const_name(name: &str) -> String {
+    let mut const_name: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if const_name.is_empty() || const_name.starts_with(|c: char| c.is_ascii_digit()) {
+        const_name.insert(0, '_');
+    }
+    const_name
+}
+
+/// Convert `name` into a CSS custom property name e.g. "Sky blue" becomes
+/// "--sky-blue".
+pub fn css_var_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("--{name}")
+}
+
+/// Code snippets for defining a colour in other contexts.
+pub trait ColourCode: ColourBasics {
+    /// e.g. `pub const SKY_BLUE: [u8; 3] = [0x87, 0xCE, 0xEB];` (for use with
+    /// `RGB::<u8>::from()`)
+    fn to_rust_code(&self, name: &str) -> String {
+        let rgb = self.rgb::<u8>();
+        format!(
+            "pub const {}: [u8; 3] = [0x{:02X}, 0x{:02X}, 0x{:02X}];",
+            rust_const_name(name),
+            rgb[0],
+            rgb[1],
+            rgb[2]
+        )
+    }
+
+    /// e.g. `--sky-blue: #87CEEB;`
+    fn to_css_var(&self, name: &str) -> String {
+        format!("{}: {};", css_var_name(name), self.pango_string())
+    }
+
+    /// e.g. `gdk::RGBA { red: 0.529412, green: 0.807843, blue: 0.921569, alpha: 1.0 }`
+    fn to_gdk_rgba_code(&self) -> String {
+        let rgb = self.rgb::<f64>();
+        format!(
+            "gdk::RGBA {{ red: {:.6}, green: {:.6}, blue: {:.6}, alpha: 1.0 }}",
+            rgb[0], rgb[1], rgb[2]
+        )
+    }
+}
+
+impl<C: ColourBasics> ColourCode for C {}
+
+pub fn palette_to_rust_code(palette: &Palette) -> String {
+    let mut code = String::new();
+    if !palette.name().is_empty() {
+        code += &format!("// {}\n", palette.name());
+    }
+    for entry in palette.iter() {
+        code += &entry.colour().to_rust_code(entry.name());
+        code.push('\n');
+    }
+    code
+}
+
+/// A `:root` rule defining a custom property for each entry.
+pub fn palette_to_css_vars(palette: &Palette) -> String {
+    let mut code = String::new();
+    if !palette.name().is_empty() {
+        code += &format!("/* {} */\n", palette.name());
+    }
+    code += ":root {\n";
+    for entry in palette.iter() {
+        code += &format!("  {}\n", entry.colour().to_css_var(entry.name()));
+    }
+    code += "}\n";
+    code
+}
+
+pub fn palette_to_gdk_rgba_code(palette: &Palette) -> String {
+    let mut code = String::new();
+    for entry in palette.iter() {
+        code += &format!(
+            "let {} = {};\n",
+            rust_const_name(entry.name()).to_lowercase(),
+            entry.colour().to_gdk_rgba_code()
+        );
+    }
+    code
+}
+
+#[cfg(test)]
+mod code_tests {
+    use super::*;
+    use crate::{hcv::HCV, rgb::RGB, HueConstants};
+
+    #[test]
+    fn names() {
+        assert_eq!(rust_const_name("Sky blue"), "SKY_BLUE");
+        assert_eq!(rust_const_name("3rd red"), "_3RD_RED");
+        assert_eq!(rust_const_name(""), "_");
+        assert_eq!(css_var_name(" Sky blue "), "--sky-blue");
+    }
+
+    #[test]
+    fn snippets() {
+        let sky_blue = RGB::<u8>::from([0x87, 0xCE, 0xEB]);
+        assert_eq!(
+            sky_blue.to_rust_code("sky blue"),
+            "pub const SKY_BLUE: [u8; 3] = [0x87, 0xCE, 0xEB];"
+        );
+        assert_eq!(sky_blue.to_css_var("sky blue"), "--sky-blue: #87CEEB;");
+        assert_eq!(
+            HCV::RED.to_gdk_rgba_code(),
+            "gdk::RGBA { red: 1.000000, green: 0.000000, blue: 0.000000, alpha: 1.0 }"
+        );
+        let mut palette = Palette::new("primaries");
+        palette.add_colour("red", &HCV::RED);
+        palette.add_colour("green", &HCV::GREEN);
+        assert_eq!(
+            palette_to_rust_code(&palette),
+            "// primaries\npub const RED: [u8; 3] = [0xFF, 0x00, 0x00];\npub const GREEN: [u8; 3] = [0x00, 0xFF, 0x00];\n"
+        );
+        assert_eq!(
+            palette_to_css_vars(&palette),
+            "/* primaries */\n:root {\n  --red: #FF0000;\n  --green: #00FF00;\n}\n"
+        );
+        assert_eq!(palette_to_gdk_rgba_code(&palette).lines().count(), 2);
+    }
+}
@@ -0,0 +1,274 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    attributes::Value,
+    beigui::Point,
+    fdrn::{FDRNumber, Prop, UFDRNumber},
+    hcv::HCV,
+    hue::{angle::Angle, Hue},
+    ColourBasics, HueConstants,
+};
+
+// Small deterministic generator so that patterns are reproducible
+#[derive(Debug, Clone)]
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+/// A Farnsworth D-15 style cap arrangement test.  The caps are equally
+/// spaced in hue (starting next to the pilot and proceeding from BLUE
+/// through GREEN, YELLOW and RED towards MAGENTA) and share the same value
+/// and chroma.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapArrangement {
+    pilot: HCV,
+    caps: Vec<HCV>,
+}
+
+impl CapArrangement {
+    pub const NUMBER_OF_CAPS: usize = 15;
+
+    pub fn farnsworth_d15(value: Value, c_prop: Prop) -> Self {
+        // leave a two step gap between the last cap and the pilot
+        let step = Angle::from(-360.0 / (Self::NUMBER_OF_CAPS + 2) as f64);
+        let colour = |angle: Angle| HCV::new_approx(Some(Hue::from(angle)), value * 3, c_prop);
+        let mut angle = Angle::BLUE;
+        let pilot = colour(angle);
+        let mut caps = vec![];
+        for _ in 0..Self::NUMBER_OF_CAPS {
+            angle = angle + step;
+            caps.push(colour(angle));
+        }
+        Self { pilot, caps }
+    }
+
+    pub fn pilot(&self) -> HCV {
+        self.pilot
+    }
+
+    /// The caps in their correct order.
+    pub fn caps(&self) -> &[HCV] {
+        &self.caps
+    }
+
+    /// The indices of the caps in a (reproducible) random order suitable for
+    /// presentation to the subject.
+    pub fn shuffled(&self, seed: u64) -> Vec<usize> {
+        let mut rng = XorShift::new(seed);
+        let mut order: Vec<usize> = (0..self.caps.len()).collect();
+        for i in (1..order.len()).rev() {
+            let j = (rng.next_f64() * (i + 1) as f64) as usize;
+            order.swap(i, j.min(i));
+        }
+        order
+    }
+
+    /// The total hue difference between consecutive caps (starting at the
+    /// pilot) in the subject's `order` relative to that for the correct
+    /// order.  A perfect arrangement scores 1.0 and larger numbers indicate
+    /// more (or larger) confusions.  Returns `None` if `order` contains an
+    /// index that is out of range or the caps are grey (e.g. because the
+    /// arrangement was made with zero chroma).
+    pub fn confusion_index(&self, order: &[usize]) -> Option<f64> {
+        let total = |colours: &mut dyn Iterator<Item = Option<HCV>>| -> Option<f64> {
+            let mut previous = self.pilot.hue_angle()?;
+            let mut total = 0.0;
+            for colour in colours {
+                let angle = colour?.hue_angle()?;
+                total += f64::from(angle.abs_diff(&previous));
+                previous = angle;
+            }
+            Some(total)
+        };
+        let perfect = total(&mut self.caps.iter().map(|cap| Some(*cap)))?;
+        let actual = total(&mut order.iter().map(|i| self.caps.get(*i).copied()))?;
+        if perfect > 0.0 {
+            Some(actual / perfect)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dot {
+    pub centre: Point,
+    pub radius: UFDRNumber,
+    pub colour: HCV,
+    pub in_figure: bool,
+}
+
+/// Builder for Ishihara style pseudo-isochromatic plates: a disc of radius
+/// 1.0 (centred on the origin) filled with non overlapping dots whose
+/// colour depends on whether they fall inside the figure.
+#[derive(Debug, Clone)]
+pub struct PlateBuilder {
+    figure_colour: HCV,
+    background_colour: HCV,
+    number_of_dots: usize,
+    dot_radii: (f64, f64),
+    value_jitter: f64,
+    seed: u64,
+}
+
+impl Default for PlateBuilder {
+    fn default() -> Self {
+        // a red/green pair of similar value and chroma
+        let colour = |degrees: i16| {
+            HCV::new_approx(
+                Some(Hue::from(Angle::from(degrees))),
+                UFDRNumber::from(1.5),
+                Prop::from(0.5),
+            )
+        };
+        Self {
+            figure_colour: colour(15),
+            background_colour: colour(75),
+            number_of_dots: 600,
+            dot_radii: (0.015, 0.045),
+            value_jitter: 0.15,
+            seed: 1,
+        }
+    }
+}
+
+impl PlateBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn figure_colour(&mut self, colour: &impl ColourBasics) -> &mut Self {
+        self.figure_colour = colour.hcv();
+        self
+    }
+
+    pub fn background_colour(&mut self, colour: &impl ColourBasics) -> &mut Self {
+        self.background_colour = colour.hcv();
+        self
+    }
+
+    pub fn number_of_dots(&mut self, number_of_dots: usize) -> &mut Self {
+        self.number_of_dots = number_of_dots;
+        self
+    }
+
+    pub fn dot_radii(&mut self, min: f64, max: f64) -> &mut Self {
+        debug_assert!(0.0 < min && min <= max && max < 0.5);
+        self.dot_radii = (min, max);
+        self
+    }
+
+    /// The maximum proportional variation in the value of the dots (used
+    /// to stop value differences giving the figure away).
+    pub fn value_jitter(&mut self, value_jitter: f64) -> &mut Self {
+        debug_assert!((0.0..1.0).contains(&value_jitter));
+        self.value_jitter = value_jitter;
+        self
+    }
+
+    pub fn seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Generate the plate's dots.  `in_figure` decides whether a point
+    /// (within the unit disc) is part of the figure to be discerned.  Fewer
+    /// dots than requested may be placed if the disc fills up.
+    pub fn build(&self, in_figure: impl Fn(Point) -> bool) -> Vec<Dot> {
+        let mut rng = XorShift::new(self.seed);
+        let mut placed: Vec<(f64, f64, f64)> = vec![];
+        let (min_radius, max_radius) = self.dot_radii;
+        let mut attempts = 0;
+        while placed.len() < self.number_of_dots && attempts < self.number_of_dots * 50 {
+            attempts += 1;
+            let radius = min_radius + rng.next_f64() * (max_radius - min_radius);
+            let x = (rng.next_f64() * 2.0 - 1.0) * (1.0 - radius);
+            let y = (rng.next_f64() * 2.0 - 1.0) * (1.0 - radius);
+            if x.hypot(y) + radius > 1.0 {
+                continue;
+            }
+            if placed
+                .iter()
+                .any(|(px, py, pr)| (x - px).hypot(y - py) < radius + pr)
+            {
+                continue;
+            }
+            placed.push((x, y, radius));
+        }
+        placed
+            .into_iter()
+            .map(|(x, y, radius)| {
+                let centre = Point::from([FDRNumber::from(x), FDRNumber::from(y)]);
+                let in_figure = in_figure(centre);
+                let base = if in_figure {
+                    self.figure_colour
+                } else {
+                    self.background_colour
+                };
+                let scale = 1.0 + self.value_jitter * (rng.next_f64() * 2.0 - 1.0);
+                let sum = (f64::from(base.sum) * scale).min(3.0);
+                let colour = HCV::new_approx(base.hue, UFDRNumber::from(sum), base.c_prop);
+                Dot {
+                    centre,
+                    radius: UFDRNumber::from(radius),
+                    colour,
+                    in_figure,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod colour_vision_tests {
+    use super::*;
+
+    #[test]
+    fn d15_arrangement() {
+        let test = CapArrangement::farnsworth_d15(Value::from(0.6), Prop::from(0.3));
+        assert_eq!(test.caps().len(), CapArrangement::NUMBER_OF_CAPS);
+        assert_eq!(test.pilot().hue(), Some(Hue::BLUE));
+        let correct: Vec<usize> = (0..CapArrangement::NUMBER_OF_CAPS).collect();
+        assert!((test.confusion_index(&correct).unwrap() - 1.0).abs() < 0.000_001);
+        let shuffled = test.shuffled(42);
+        assert_ne!(shuffled, correct);
+        let mut sorted = shuffled.clone();
+        sorted.sort();
+        assert_eq!(sorted, correct);
+        assert!(test.confusion_index(&shuffled).unwrap() > 1.0);
+        assert_eq!(
+            test.confusion_index(&[0, 1, CapArrangement::NUMBER_OF_CAPS]),
+            None
+        );
+        let grey = CapArrangement::farnsworth_d15(Value::from(0.6), Prop::ZERO);
+        assert_eq!(grey.confusion_index(&correct), None);
+    }
+
+    #[test]
+    fn plate_dots() {
+        let dots = PlateBuilder::new()
+            .number_of_dots(200)
+            .seed(7)
+            .build(|point| point.x > FDRNumber::ZERO);
+        assert!(!dots.is_empty() && dots.len() <= 200);
+        for (i, dot) in dots.iter().enumerate() {
+            let radius = f64::from(dot.radius);
+            assert!(f64::from(dot.centre.hypot()) + radius <= 1.000_001);
+            assert_eq!(dot.in_figure, dot.centre.x > FDRNumber::ZERO);
+            for other in &dots[i + 1..] {
+                let gap = f64::from((dot.centre - other.centre).hypot());
+                assert!(gap + 0.000_001 >= radius + f64::from(other.radius));
+            }
+        }
+    }
+}
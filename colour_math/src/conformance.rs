@@ -0,0 +1,241 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::fmt;
+
+use crate::{hcv::HCV, hue::angle::Angle, rgb::RGB, ColourBasics};
+
+/// Maximum acceptable differences between an implementation's answers
+/// and the reference.  Proportions (chroma and value) are in the range
+/// 0.0 to 1.0 and hue angles are in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub prop: f64,
+    pub degrees: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            prop: 1.0e-6,
+            degrees: 1.0e-3,
+        }
+    }
+}
+
+impl Tolerance {
+    fn props_agree(&self, lhs: f64, rhs: f64) -> bool {
+        (lhs - rhs).abs() <= self.prop
+    }
+
+    fn hues_agree(&self, lhs: Option<Angle>, rhs: Option<Angle>) -> bool {
+        match (lhs, rhs) {
+            (None, None) => true,
+            (Some(lhs), Some(rhs)) => f64::from(lhs - rhs).abs() <= self.degrees,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConformanceFailure {
+    /// The offending colour (or the reference that it failed to match)
+    pub colour: HCV,
+    pub check: &'static str,
+    pub detail: String,
+}
+
+impl fmt::Display for ConformanceFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} ({})",
+            self.check,
+            self.detail,
+            self.colour.pango_string()
+        )
+    }
+}
+
+impl std::error::Error for ConformanceFailure {}
+
+fn failure(colour: &impl ColourBasics, check: &'static str, detail: String) -> ConformanceFailure {
+    ConformanceFailure {
+        colour: colour.hcv(),
+        check,
+        detail,
+    }
+}
+
+/// A set of colours covering greys, primaries, secondaries and a spread
+/// of hues, chromas and values.
+pub fn reference_colours() -> Vec<HCV> {
+    const STEPS: u8 = 4;
+    let mut colours = vec![];
+    for red in 0..=STEPS {
+        for green in 0..=STEPS {
+            for blue in 0..=STEPS {
+                let rgb = RGB::<f64>::from([red, green, blue].map(|c| c as f64 / STEPS as f64));
+                colours.push(HCV::from(rgb));
+            }
+        }
+    }
+    colours
+}
+
+/// Check that the hue, chroma and value that `colour` reports are
+/// consistent with each other and with its HCV and RGB representations.
+pub fn check_consistency(
+    colour: &impl ColourBasics,
+    tolerance: &Tolerance,
+) -> Result<(), ConformanceFailure> {
+    let hue_angle = colour.hue_angle();
+    let c_prop = f64::from(colour.chroma_prop());
+    let value = f64::from(colour.value());
+
+    if colour.is_grey() != hue_angle.is_none() {
+        return Err(failure(
+            colour,
+            "grey",
+            format!("is_grey() is {} but hue is {hue_angle:?}", colour.is_grey()),
+        ));
+    }
+
+    let hcv = colour.hcv();
+    if !tolerance.hues_agree(hue_angle, hcv.hue_angle()) {
+        return Err(failure(
+            colour,
+            "hcv hue",
+            format!("{hue_angle:?} != {:?}", hcv.hue_angle()),
+        ));
+    }
+    if !tolerance.props_agree(c_prop, f64::from(hcv.chroma_prop())) {
+        return Err(failure(
+            colour,
+            "hcv chroma",
+            format!("{c_prop} != {}", f64::from(hcv.chroma_prop())),
+        ));
+    }
+    if !tolerance.props_agree(value, f64::from(hcv.value())) {
+        return Err(failure(
+            colour,
+            "hcv value",
+            format!("{value} != {}", f64::from(hcv.value())),
+        ));
+    }
+
+    let rgb = colour.rgb::<f64>();
+    let [red, green, blue] = rgb.0;
+    let rgb_value = (red + green + blue) / 3.0;
+    if !tolerance.props_agree(value, rgb_value) {
+        return Err(failure(
+            colour,
+            "rgb value",
+            format!("{value} != {rgb_value}"),
+        ));
+    }
+    let rgb_c_prop = red.max(green).max(blue) - red.min(green).min(blue);
+    if !tolerance.props_agree(c_prop, rgb_c_prop) {
+        return Err(failure(
+            colour,
+            "rgb chroma",
+            format!("{c_prop} != {rgb_c_prop}"),
+        ));
+    }
+    // hues are only meaningful where there is enough chroma to define them
+    if rgb_c_prop > tolerance.prop && !tolerance.hues_agree(hue_angle, rgb.hue_angle()) {
+        return Err(failure(
+            colour,
+            "rgb hue",
+            format!("{hue_angle:?} != {:?}", rgb.hue_angle()),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check that `colour` reports the same attributes as `reference`.
+pub fn check_agreement(
+    colour: &impl ColourBasics,
+    reference: &HCV,
+    tolerance: &Tolerance,
+) -> Result<(), ConformanceFailure> {
+    let (c_prop, ref_c_prop) = (
+        f64::from(colour.chroma_prop()),
+        f64::from(reference.chroma_prop()),
+    );
+    if !tolerance.props_agree(c_prop, ref_c_prop) {
+        return Err(failure(
+            reference,
+            "chroma",
+            format!("{c_prop} != {ref_c_prop}"),
+        ));
+    }
+    let (value, ref_value) = (f64::from(colour.value()), f64::from(reference.value()));
+    if !tolerance.props_agree(value, ref_value) {
+        return Err(failure(
+            reference,
+            "value",
+            format!("{value} != {ref_value}"),
+        ));
+    }
+    if ref_c_prop > tolerance.prop
+        && !tolerance.hues_agree(colour.hue_angle(), reference.hue_angle())
+    {
+        return Err(failure(
+            reference,
+            "hue",
+            format!("{:?} != {:?}", colour.hue_angle(), reference.hue_angle()),
+        ));
+    }
+    Ok(())
+}
+
+/// Run the full suite against an implementation.  `make` constructs the
+/// implementation's equivalent of a reference colour.
+pub fn check_implementation<C, F>(make: F, tolerance: &Tolerance) -> Result<(), ConformanceFailure>
+where
+    C: ColourBasics,
+    F: Fn(&HCV) -> C,
+{
+    for reference in reference_colours().iter() {
+        let colour = make(reference);
+        check_consistency(&colour, tolerance)?;
+        check_agreement(&colour, reference, tolerance)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod conformance_tests {
+    use super::*;
+    use crate::{attributes::Value, HueConstants};
+
+    #[test]
+    fn reference_implementations_conform() {
+        let tolerance = Tolerance::default();
+        assert_eq!(check_implementation(|hcv| *hcv, &tolerance), Ok(()));
+        assert_eq!(
+            check_implementation(|hcv| hcv.rgb::<f64>(), &tolerance),
+            Ok(())
+        );
+        assert_eq!(
+            check_implementation(
+                |hcv| hcv.rgb::<u16>(),
+                &Tolerance {
+                    prop: 1.0e-4,
+                    degrees: 0.1
+                }
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn disagreement_is_detected() {
+        let tolerance = Tolerance::default();
+        let result = check_agreement(&HCV::RED, &HCV::GREEN, &tolerance);
+        assert_eq!(result.map_err(|f| f.check), Err("hue"));
+        let dark = HCV::new_grey(Value::from(0.25));
+        let result = check_agreement(&dark, &HCV::new_grey(Value::from(0.5)), &tolerance);
+        assert_eq!(result.map_err(|f| f.check), Err("value"));
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! Per thread counts of the basic operations (conversions between RGB and
+//! HCV, hue calculations and `Prop` multiplications) for finding redundant
+//! work e.g. in widget update paths.  Only available with the `diagnostics`
+//! feature: without it the counting compiles to nothing.
+use std::{cell::Cell, fmt, ops::Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counter {
+    RgbToHcv,
+    HcvToRgb,
+    HueCalculation,
+    PropMultiplication,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Counts {
+    pub rgb_to_hcv: u64,
+    pub hcv_to_rgb: u64,
+    pub hue_calculations: u64,
+    pub prop_multiplications: u64,
+}
+
+impl Counts {
+    pub fn get(&self, counter: Counter) -> u64 {
+        match counter {
+            Counter::RgbToHcv => self.rgb_to_hcv,
+            Counter::HcvToRgb => self.hcv_to_rgb,
+            Counter::HueCalculation => self.hue_calculations,
+            Counter::PropMultiplication => self.prop_multiplications,
+        }
+    }
+
+    fn get_mut(&mut self, counter: Counter) -> &mut u64 {
+        match counter {
+            Counter::RgbToHcv => &mut self.rgb_to_hcv,
+            Counter::HcvToRgb => &mut self.hcv_to_rgb,
+            Counter::HueCalculation => &mut self.hue_calculations,
+            Counter::PropMultiplication => &mut self.prop_multiplications,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Sub for Counts {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            rgb_to_hcv: self.rgb_to_hcv.saturating_sub(rhs.rgb_to_hcv),
+            hcv_to_rgb: self.hcv_to_rgb.saturating_sub(rhs.hcv_to_rgb),
+            hue_calculations: self.hue_calculations.saturating_sub(rhs.hue_calculations),
+            prop_multiplications: self
+                .prop_multiplications
+                .saturating_sub(rhs.prop_multiplications),
+        }
+    }
+}
+
+impl fmt::Display for Counts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "RGB -> HCV conversions: {}", self.rgb_to_hcv)?;
+        writeln!(f, "HCV -> RGB conversions: {}", self.hcv_to_rgb)?;
+        writeln!(f, "Hue calculations:       {}", self.hue_calculations)?;
+        write!(f, "Prop multiplications:   {}", self.prop_multiplications)
+    }
+}
+
+thread_local! {
+    static COUNTS: Cell<Counts> = Cell::new(Counts::default());
+}
+
+pub(crate) fn increment(counter: Counter) {
+    COUNTS.with(|counts| {
+        let mut incremented = counts.get();
+        *incremented.get_mut(counter) += 1;
+        counts.set(incremented);
+    })
+}
+
+/// The counts (for this thread) since it started or `reset()` was last
+/// called.
+pub fn counts() -> Counts {
+    COUNTS.with(|counts| counts.get())
+}
+
+pub fn reset() {
+    COUNTS.with(|counts| counts.set(Counts::default()))
+}
+
+/// Perform `operation` returning its result and the counts it incurred.
+pub fn measure<R>(operation: impl FnOnce() -> R) -> (R, Counts) {
+    let start = counts();
+    let result = operation();
+    (result, counts() - start)
+}
+
+#[cfg(test)]
+mod diagnostics_tests {
+    use super::*;
+    use crate::{fdrn::Prop, hcv::HCV, rgb::RGB, ColourBasics, HueConstants, RGBConstants};
+
+    #[test]
+    fn conversion_counts() {
+        reset();
+        let (hcv, counts) = measure(|| HCV::from(RGB::<u8>::RED));
+        assert_eq!(hcv, HCV::RED);
+        assert_eq!(counts.get(Counter::RgbToHcv), 1);
+        assert_eq!(counts.get(Counter::HueCalculation), 1);
+        assert_eq!(counts.hcv_to_rgb, 0);
+        let (_, counts) = measure(|| HCV::RED.rgb::<f64>());
+        assert_eq!(counts.hcv_to_rgb, 1);
+        assert_eq!(counts.rgb_to_hcv, 0);
+        let (_, counts) = measure(|| Prop::ONE * Prop::ZERO);
+        assert_eq!(counts.prop_multiplications, 1);
+        assert!(measure(|| ()).1.is_zero());
+        assert!(!counts().is_zero());
+        reset();
+        assert!(counts().is_zero());
+        // greys have no hue but checking that counts
+        let (_, counts) = measure(|| HCV::from(RGB::<u8>::WHITE));
+        assert_eq!(counts.hue_calculations, 1);
+        assert!(format!("{counts}").contains("RGB -> HCV conversions: 1"));
+    }
+}
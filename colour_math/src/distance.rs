@@ -0,0 +1,231 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::fmt::Write;
+
+use crate::{
+    beigui::{Draw, Point},
+    fdrn::{FDRNumber, Prop},
+    lab::Lab,
+    rgb::RGB,
+    ColourBasics,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DistanceMetric {
+    /// Euclidean distance in sRGB normalised to the range 0 to 1.
+    #[default]
+    Rgb,
+    /// CIE 1976 ΔE*ab (a perceptual measure where about 2.3 is just
+    /// noticeable).
+    DeltaE76,
+}
+
+impl DistanceMetric {
+    pub fn distance(&self, lhs: &impl ColourBasics, rhs: &impl ColourBasics) -> f64 {
+        match self {
+            DistanceMetric::Rgb => {
+                let lhs = lhs.rgb::<f64>();
+                let rhs = rhs.rgb::<f64>();
+                let sum_sq: f64 = (0..3).map(|i| (lhs[i] - rhs[i]).powi(2)).sum();
+                (sum_sq / 3.0).sqrt()
+            }
+            DistanceMetric::DeltaE76 => Lab::from(&lhs.hcv()).delta_e(&Lab::from(&rhs.hcv())),
+        }
+    }
+}
+
+/// The (symmetric) distances between every pair of a list of colours.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DistanceMatrix {
+    metric: DistanceMetric,
+    labels: Vec<String>,
+    distances: Vec<Vec<f64>>,
+}
+
+pub fn distance_matrix(colours: &[impl ColourBasics], metric: DistanceMetric) -> DistanceMatrix {
+    let distances = colours
+        .iter()
+        .map(|lhs| {
+            colours
+                .iter()
+                .map(|rhs| metric.distance(lhs, rhs))
+                .collect()
+        })
+        .collect();
+    DistanceMatrix {
+        metric,
+        labels: (0..colours.len()).map(|i| i.to_string()).collect(),
+        distances,
+    }
+}
+
+impl DistanceMatrix {
+    pub fn metric(&self) -> DistanceMetric {
+        self.metric
+    }
+
+    pub fn len(&self) -> usize {
+        self.distances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.distances.is_empty()
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Replace the default labels (the colours' indices) e.g. with names.
+    /// Surplus labels are ignored and missing ones left unchanged.
+    pub fn set_labels(&mut self, labels: &[&str]) {
+        for (label, new_label) in self.labels.iter_mut().zip(labels.iter()) {
+            *label = new_label.to_string();
+        }
+    }
+
+    pub fn distance(&self, i: usize, j: usize) -> f64 {
+        self.distances[i][j]
+    }
+
+    /// The indices and distance of the two most similar colours (which is
+    /// what limits how well separated the colours are).
+    pub fn closest_pair(&self) -> Option<(usize, usize, f64)> {
+        let mut closest: Option<(usize, usize, f64)> = None;
+        for (i, row) in self.distances.iter().enumerate() {
+            for (j, distance) in row.iter().enumerate().skip(i + 1) {
+                match closest {
+                    Some((_, _, min)) if min <= *distance => (),
+                    _ => closest = Some((i, j, *distance)),
+                }
+            }
+        }
+        closest
+    }
+
+    pub fn max_distance(&self) -> f64 {
+        self.distances
+            .iter()
+            .flat_map(|row| row.iter())
+            .fold(0.0, |max, distance| distance.max(max))
+    }
+
+    /// A header row of labels followed by a labelled row per colour.
+    pub fn to_csv(&self) -> String {
+        let quoted = |label: &String| format!("\"{}\"", label.replace('"', "\"\""));
+        let mut csv = String::new();
+        for label in self.labels.iter() {
+            csv.push(',');
+            csv.push_str(&quoted(label));
+        }
+        csv.push('\n');
+        for (label, row) in self.labels.iter().zip(self.distances.iter()) {
+            csv.push_str(&quoted(label));
+            for distance in row.iter() {
+                write!(csv, ",{distance:.6}").expect("writing to a String can't fail");
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Fails if the JSON doesn't have a label and a full row of distances
+    /// for each colour.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let matrix: Self = serde_json::from_str(json)?;
+        let size = matrix.labels.len();
+        if matrix.distances.len() != size || matrix.distances.iter().any(|row| row.len() != size) {
+            return Err(serde::de::Error::custom(format!(
+                "distances are not a {size} by {size} matrix"
+            )));
+        }
+        Ok(matrix)
+    }
+
+    /// Draw the matrix as a grid of cells coloured from hot (red) for
+    /// identical colours to cool (cyan) for the most distant pair.
+    pub fn draw_heat_map(&self, drawer: &impl Draw) {
+        if self.is_empty() {
+            return;
+        }
+        let size = drawer.size();
+        let cell_width = f64::from(size.width) / self.len() as f64;
+        let cell_height = f64::from(size.height) / self.len() as f64;
+        let max_distance = self.max_distance();
+        for (i, row) in self.distances.iter().enumerate() {
+            for (j, distance) in row.iter().enumerate() {
+                let coolness = if max_distance > 0.0 {
+                    distance / max_distance
+                } else {
+                    0.0
+                };
+                let colour = RGB::<f64>::new_warmth_rgb(Prop::from(1.0 - coolness).into());
+                let x = j as f64 * cell_width;
+                let y = i as f64 * cell_height;
+                let corners = [
+                    (x, y),
+                    (x + cell_width, y),
+                    (x + cell_width, y + cell_height),
+                    (x, y + cell_height),
+                ];
+                let polygon: Vec<Point> = corners
+                    .iter()
+                    .map(|(x, y)| [FDRNumber::from(*x), FDRNumber::from(*y)].into())
+                    .collect();
+                drawer.set_fill_colour(&colour);
+                drawer.draw_polygon(&polygon, true);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod distance_tests {
+    use super::*;
+    use crate::{hcv::HCV, HueConstants, RGBConstants};
+
+    #[test]
+    fn matrix() {
+        let colours = [HCV::RED, HCV::BLACK, HCV::WHITE, HCV::RED_YELLOW];
+        let matrix = distance_matrix(&colours, DistanceMetric::Rgb);
+        assert_eq!(matrix.len(), 4);
+        for i in 0..4 {
+            assert_eq!(matrix.distance(i, i), 0.0);
+            for j in 0..4 {
+                assert_eq!(matrix.distance(i, j), matrix.distance(j, i));
+            }
+        }
+        assert!((matrix.distance(1, 2) - 1.0).abs() < 1e-9);
+        assert_eq!(matrix.max_distance(), matrix.distance(1, 2));
+        let (i, j, _) = matrix.closest_pair().unwrap();
+        assert_eq!((i, j), (0, 3));
+        let matrix = distance_matrix(&colours, DistanceMetric::DeltaE76);
+        assert!((matrix.distance(1, 2) - 100.0).abs() < 0.01);
+        assert!(distance_matrix(&[HCV::RED], DistanceMetric::Rgb)
+            .closest_pair()
+            .is_none());
+    }
+
+    #[test]
+    fn export() {
+        let mut matrix = distance_matrix(&[HCV::BLACK, HCV::WHITE], DistanceMetric::Rgb);
+        matrix.set_labels(&["Black", "Titanium \"White\""]);
+        let csv = matrix.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], ",\"Black\",\"Titanium \"\"White\"\"\"");
+        assert!(lines[1].starts_with("\"Black\",0.000000,1.000000"));
+        let json = matrix.to_json().unwrap();
+        let read = DistanceMatrix::from_json(&json).unwrap();
+        assert_eq!(read.labels(), matrix.labels());
+        assert_eq!(read.metric(), DistanceMetric::Rgb);
+        assert!((read.distance(0, 1) - matrix.distance(0, 1)).abs() < 1e-9);
+        let short_row = r#"{"metric":"Rgb","labels":["a","b"],"distances":[[0.0,1.0],[1.0]]}"#;
+        assert!(DistanceMatrix::from_json(short_row).is_err());
+        let extra_label =
+            r#"{"metric":"Rgb","labels":["a","b","c"],"distances":[[0.0,1.0],[1.0,0.0]]}"#;
+        assert!(DistanceMatrix::from_json(extra_label).is_err());
+    }
+}
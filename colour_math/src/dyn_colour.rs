@@ -0,0 +1,99 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    attributes::{Chroma, Value},
+    fdrn::Prop,
+    hcv::HCV,
+    hue::Hue,
+    rgb::RGB,
+    ColourBasics, LightLevel,
+};
+
+// NB: not re-exported at the crate root as having both this and
+// `ColourBasics` in scope makes their shared method names ambiguous.
+/// An object safe subset of `ColourBasics` for use where colours of
+/// different types need to be mixed e.g. `Vec<Box<dyn DynColour>>`.
+pub trait DynColour {
+    fn hue(&self) -> Option<Hue>;
+    fn chroma(&self) -> Chroma;
+    fn chroma_prop(&self) -> Prop;
+    fn value(&self) -> Value;
+    fn hcv(&self) -> HCV;
+    fn rgb_u8(&self) -> RGB<u8>;
+}
+
+impl<C: ColourBasics> DynColour for C {
+    fn hue(&self) -> Option<Hue> {
+        ColourBasics::hue(self)
+    }
+
+    fn chroma(&self) -> Chroma {
+        ColourBasics::chroma(self)
+    }
+
+    fn chroma_prop(&self) -> Prop {
+        ColourBasics::chroma_prop(self)
+    }
+
+    fn value(&self) -> Value {
+        ColourBasics::value(self)
+    }
+
+    fn hcv(&self) -> HCV {
+        ColourBasics::hcv(self)
+    }
+
+    fn rgb_u8(&self) -> RGB<u8> {
+        ColourBasics::rgb::<u8>(self)
+    }
+}
+
+// So that trait objects can be passed wherever `impl ColourBasics` is accepted.
+impl ColourBasics for dyn DynColour + '_ {
+    fn hue(&self) -> Option<Hue> {
+        DynColour::hue(self)
+    }
+
+    fn chroma(&self) -> Chroma {
+        DynColour::chroma(self)
+    }
+
+    fn chroma_prop(&self) -> Prop {
+        DynColour::chroma_prop(self)
+    }
+
+    fn value(&self) -> Value {
+        DynColour::value(self)
+    }
+
+    fn hcv(&self) -> HCV {
+        DynColour::hcv(self)
+    }
+
+    fn rgb<L: LightLevel>(&self) -> RGB<L> {
+        DynColour::hcv(self).rgb::<L>()
+    }
+}
+
+#[cfg(test)]
+mod dyn_colour_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    #[test]
+    fn heterogeneous_collection() {
+        let colours: Vec<Box<dyn DynColour>> = vec![
+            Box::new(HCV::RED),
+            Box::new(RGB::<u8>::GREEN),
+            Box::new(RGB::<f64>::WHITE),
+        ];
+        let bytes: Vec<RGB<u8>> = colours.iter().map(|c| c.rgb_u8()).collect();
+        assert_eq!(
+            bytes,
+            vec![RGB::<u8>::RED, RGB::<u8>::GREEN, RGB::<u8>::WHITE]
+        );
+        assert_eq!(DynColour::value(colours[2].as_ref()), Value::ONE);
+        let colour: &dyn DynColour = colours[0].as_ref();
+        assert_eq!(ColourBasics::hue_angle(colour), HCV::RED.hue_angle());
+        assert!(ColourBasics::is_grey(colours[2].as_ref()));
+    }
+}
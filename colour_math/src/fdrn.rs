@@ -435,6 +435,37 @@ impl Prop {
     //pub(crate) const ALMOST_ZERO: Self = Self(1);
     pub(crate) const ALMOST_ONE: Self = Self(u64::MAX - 1);
     pub(crate) const HALF: Self = Self(u64::MAX / 2);
+
+    // Rounds to the nearest multiple of 1/divisor with halves rounded up.
+    // As conversions to Prop truncate, Prop(n) stands for the values from
+    // n/ONE up to (n + 1)/ONE and the mid point of that range is what is
+    // rounded (which makes e.g. HALF exactly a half).
+    fn rounded_fraction(self, divisor: u16) -> u64 {
+        let twice_one = 2 * u64::MAX as u128;
+        (((2 * self.0 as u128 + 1) * divisor as u128 + u64::MAX as u128) / twice_one) as u64
+    }
+
+    /// Exact i.e. `Prop::from_percent(n).to_percent() == n`.  Values greater
+    /// than 100 are treated as 100.
+    pub fn from_percent(percent: u8) -> Self {
+        Self::from([percent.min(100) as u64, 100])
+    }
+
+    /// Rounded to the nearest whole percent (halves are rounded up).
+    pub fn to_percent(self) -> u8 {
+        self.rounded_fraction(100) as u8
+    }
+
+    /// Exact i.e. `Prop::from_byte(n).to_byte() == n`.
+    pub fn from_byte(byte: u8) -> Self {
+        Self::from(byte)
+    }
+
+    /// Rounded to the nearest 1/255 (halves are rounded up).  NB: this
+    /// differs from `u8::from(prop)` which truncates.
+    pub fn to_byte(self) -> u8 {
+        self.rounded_fraction(255) as u8
+    }
 }
 
 impl AbsDiff for Prop {}
@@ -467,10 +498,17 @@ pub trait IntoProp: Sized + Copy + Into<Prop> {
     }
 }
 
+impl Prop {
+    /// As `Prop::from([numerator, denominator])` but usable in constants.
+    pub const fn from_fraction(numerator: u64, denominator: u64) -> Self {
+        debug_assert!(denominator >= numerator);
+        Self(((numerator as u128 * u64::MAX as u128) / denominator as u128) as u64)
+    }
+}
+
 impl From<[u64; 2]> for Prop {
     fn from(fraction: [u64; 2]) -> Self {
-        debug_assert!(fraction[1] >= fraction[0]);
-        Self(((fraction[0] as u128 * u64::MAX as u128) / fraction[1] as u128) as u64)
+        Self::from_fraction(fraction[0], fraction[1])
     }
 }
 
@@ -571,6 +609,7 @@ impl Mul for Prop {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self {
+        count!(PropMultiplication);
         Self(((self.0 as u128 * rhs.0 as u128) / u64::MAX as u128) as u64)
     }
 }
@@ -181,3 +181,22 @@ fn from_unsigned() {
     assert_eq!(Prop::from(u32::MAX), Prop::ONE);
     assert_eq!(Prop::from(u8::MAX / 2), Prop::from([127, 255]));
 }
+
+#[test]
+fn percent_and_byte() {
+    for n in 0..=100 {
+        assert_eq!(Prop::from_percent(n).to_percent(), n);
+    }
+    assert_eq!(Prop::from_percent(150), Prop::ONE);
+    assert_eq!(Prop::HALF.to_percent(), 50);
+    assert_eq!(Prop::from([1, 200]).to_percent(), 1);
+    assert_eq!(Prop::from([1, 201]).to_percent(), 0);
+    for n in 0..=255 {
+        let prop = Prop::from_byte(n);
+        assert_eq!(prop.to_byte(), n);
+        // small errors (e.g. from calculations) in either direction are absorbed
+        assert_eq!(Prop(prop.0.saturating_sub(0x1000)).to_byte(), n);
+        assert_eq!(Prop(prop.0.saturating_add(0x1000)).to_byte(), n);
+    }
+    assert_eq!(Prop::HALF.to_byte(), 128);
+}
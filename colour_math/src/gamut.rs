@@ -0,0 +1,132 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! Explicit handling of hue, sum and chroma combinations that don't fit in
+//! the RGB cube (e.g. a light colour with high chroma) so that callers can
+//! choose the behaviour rather than hand rolling fallbacks.
+use crate::{
+    fdrn::{Prop, UFDRNumber},
+    hcv::HCV,
+    hue::{ColourModificationHelpers, Hue, HueIfce, OrderedTriplets, SumChromaCompatibility},
+    rgb::RGB,
+    ColourBasics, LightLevel,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GamutStrategy {
+    /// Calculate the channels ignoring the limits and then clip each to the
+    /// range zero to one (which may change all three attributes).
+    ClipChannel,
+    /// Keep the hue and sum (i.e. value) and reduce the chroma.
+    #[default]
+    PreserveHueReduceChroma,
+    /// Keep the hue and chroma and move the sum to the nearest in range.
+    PreserveChromaAdjustSum,
+}
+
+/// Whether `hue` with `sum` and `c_prop` fits in the RGB cube.
+pub fn in_gamut(hue: Option<Hue>, sum: UFDRNumber, c_prop: Prop) -> bool {
+    match hue {
+        Some(hue) => sum.is_valid_sum() && hue.sum_and_chroma_prop_are_compatible(sum, c_prop),
+        None => c_prop == Prop::ZERO && sum.is_valid_sum(),
+    }
+}
+
+fn grey<L: LightLevel>(sum: UFDRNumber) -> RGB<L> {
+    let value: Prop = (sum.min(UFDRNumber::THREE) / 3).into();
+    RGB::from([value, value, value])
+}
+
+fn clipped<L: LightLevel>(hue: Hue, sum: UFDRNumber, c_prop: Prop) -> RGB<L> {
+    let hue_rgb = hue.max_chroma_hcv().rgb::<f64>();
+    let c_prop = f64::from(c_prop);
+    let hue_sum = hue_rgb[0] + hue_rgb[1] + hue_rgb[2];
+    let grey = (f64::from(sum) - c_prop * hue_sum) / 3.0;
+    let rgb = [0, 1, 2].map(|i| (c_prop * hue_rgb[i] + grey).clamp(0.0, 1.0));
+    RGB::<f64>::from(rgb).rgb()
+}
+
+/// The RGB for `hue` with `sum` and `c_prop` brought into gamut (if
+/// necessary) using `strategy`.
+pub fn map_attributes_to_gamut<L: LightLevel>(
+    hue: Option<Hue>,
+    sum: UFDRNumber,
+    c_prop: Prop,
+    strategy: GamutStrategy,
+) -> RGB<L> {
+    let hue = match hue {
+        Some(hue) if c_prop > Prop::ZERO => hue,
+        _ => return grey(sum),
+    };
+    let sum = sum.min(UFDRNumber::THREE);
+    if hue.sum_and_chroma_prop_are_compatible(sum, c_prop) {
+        if let Some(triplet) = hue.rgb_ordered_triplet(sum, c_prop) {
+            return RGB::from(triplet);
+        }
+    }
+    let adjusted = match strategy {
+        GamutStrategy::ClipChannel => return clipped(hue, sum, c_prop),
+        GamutStrategy::PreserveHueReduceChroma => hue.adjusted_favouring_sum(sum, c_prop),
+        GamutStrategy::PreserveChromaAdjustSum => hue.adjusted_favouring_chroma(sum, c_prop),
+    };
+    match adjusted.and_then(|(c_prop, sum)| hue.rgb_ordered_triplet(sum, c_prop)) {
+        Some(triplet) => RGB::from(triplet),
+        None => grey(sum),
+    }
+}
+
+/// As `map_attributes_to_gamut()` for an `HCV` from an untrusted source
+/// (e.g. a file) that may not be valid.
+pub fn map_to_gamut<L: LightLevel>(hcv: &HCV, strategy: GamutStrategy) -> RGB<L> {
+    map_attributes_to_gamut(hcv.hue, hcv.sum, hcv.c_prop, strategy)
+}
+
+#[cfg(test)]
+mod gamut_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    const STRATEGIES: [GamutStrategy; 3] = [
+        GamutStrategy::ClipChannel,
+        GamutStrategy::PreserveHueReduceChroma,
+        GamutStrategy::PreserveChromaAdjustSum,
+    ];
+
+    fn near(rgb: RGB<f64>, expected: [f64; 3]) -> bool {
+        (0..3).all(|i| (rgb[i] - expected[i]).abs() < 0.001)
+    }
+
+    #[test]
+    fn in_gamut_unchanged() {
+        for hcv in [HCV::RED, HCV::CYAN, HCV::WHITE, HCV::BLACK] {
+            for strategy in STRATEGIES {
+                assert_eq!(map_to_gamut::<u16>(&hcv, strategy), hcv.rgb::<u16>());
+            }
+        }
+        assert!(in_gamut(Some(Hue::RED), UFDRNumber::ONE, Prop::ONE));
+        assert!(in_gamut(None, UFDRNumber::THREE, Prop::ZERO));
+        assert!(!in_gamut(Some(Hue::RED), UFDRNumber::from(2.5), Prop::ONE));
+        assert!(!in_gamut(None, UFDRNumber::ONE, Prop::ONE));
+    }
+
+    #[test]
+    fn out_of_gamut() {
+        // a very light colour can't have full chroma
+        let (hue, sum, c_prop) = (Some(Hue::RED), UFDRNumber::from(2.5), Prop::ONE);
+        let clipped: RGB<f64> =
+            map_attributes_to_gamut(hue, sum, c_prop, GamutStrategy::ClipChannel);
+        assert!(near(clipped, [1.0, 0.5, 0.5]));
+        let reduced: RGB<f64> =
+            map_attributes_to_gamut(hue, sum, c_prop, GamutStrategy::PreserveHueReduceChroma);
+        assert!((f64::from(reduced.sum()) - 2.5).abs() < 0.001);
+        assert!(reduced.chroma_prop() < Prop::ONE);
+        assert_eq!(reduced.hue(), Some(Hue::RED));
+        let adjusted: RGB<f64> =
+            map_attributes_to_gamut(hue, sum, c_prop, GamutStrategy::PreserveChromaAdjustSum);
+        assert!(near(adjusted, [1.0, 0.0, 0.0]));
+        // and greys can't have chroma
+        for strategy in STRATEGIES {
+            let rgb: RGB<f64> =
+                map_attributes_to_gamut(None, UFDRNumber::from(1.5), c_prop, strategy);
+            assert!(near(rgb, [0.5, 0.5, 0.5]));
+        }
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    fdrn::{IntoProp, Prop},
+    hcv::HCV,
+    hue::{angle::Angle, Hue, HueIfce},
+    rgb::RGB,
+    ColourBasics,
+};
+
+/// How colours between two stops are calculated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Straight line through RGB space (as cairo does).
+    #[default]
+    Rgb,
+    /// Around the hue circle (interpolating chroma and value separately) by
+    /// the shortest arc.
+    ShortestHue,
+    /// Around the hue circle by the longest arc.
+    LongestHue,
+}
+
+// The signed angle (degrees) to travel from `from` to `to`
+fn arc(from: Hue, to: Hue, shortest: bool) -> f64 {
+    let diff = f64::from(to.angle() - from.angle());
+    if shortest || diff == 0.0 {
+        diff
+    } else if diff > 0.0 {
+        diff - 360.0
+    } else {
+        diff + 360.0
+    }
+}
+
+fn interpolate(lhs: &HCV, rhs: &HCV, prop: Prop, interpolation: Interpolation) -> HCV {
+    let shortest = match interpolation {
+        Interpolation::Rgb => {
+            let lhs = lhs.rgb::<f64>();
+            let rhs = rhs.rgb::<f64>();
+            let f = f64::from(prop);
+            let rgb = [0, 1, 2].map(|i| (lhs[i] + (rhs[i] - lhs[i]) * f).clamp(0.0, 1.0));
+            return HCV::from(RGB::<f64>::from(rgb));
+        }
+        Interpolation::ShortestHue => true,
+        Interpolation::LongestHue => false,
+    };
+    let complement = Prop::ONE - prop;
+    let sum = lhs.sum * complement + rhs.sum * prop;
+    let c_prop = (lhs.c_prop * complement + rhs.c_prop * prop).into_prop();
+    let hue = match (lhs.hue, rhs.hue) {
+        (Some(lhs_hue), Some(rhs_hue)) => {
+            let mut degrees = arc(lhs_hue, rhs_hue, shortest) * f64::from(prop);
+            if degrees > 180.0 {
+                degrees -= 360.0;
+            } else if degrees < -180.0 {
+                degrees += 360.0;
+            }
+            Some(lhs_hue + Angle::from(degrees))
+        }
+        // greys have no hue to contribute
+        (Some(hue), None) | (None, Some(hue)) => Some(hue),
+        (None, None) => None,
+    };
+    HCV::new_approx(hue, sum, c_prop)
+}
+
+/// A sequence of colours at positions (from zero to one) along a line that
+/// can be sampled at any position independently of any drawing backend.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Gradient {
+    stops: Vec<(HCV, Prop)>,
+    interpolation: Interpolation,
+}
+
+impl Gradient {
+    /// The stops are sorted by position (stops with equal positions keep
+    /// their order and make a sharp transition).
+    pub fn new(stops: &[(HCV, Prop)], interpolation: Interpolation) -> Self {
+        let mut stops = stops.to_vec();
+        stops.sort_by_key(|stop| stop.1);
+        Self {
+            stops,
+            interpolation,
+        }
+    }
+
+    /// Evenly spaced stops from the first colour to the last.
+    pub fn evenly_spaced(colours: &[HCV], interpolation: Interpolation) -> Self {
+        let last = colours.len().saturating_sub(1).max(1) as f64;
+        let stops: Vec<(HCV, Prop)> = colours
+            .iter()
+            .enumerate()
+            .map(|(i, colour)| (*colour, Prop::from(i as f64 / last)))
+            .collect();
+        Self::new(&stops, interpolation)
+    }
+
+    pub fn stops(&self) -> &[(HCV, Prop)] {
+        &self.stops
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation
+    }
+
+    /// The colour at `posn`. Stop colours are returned exactly and positions
+    /// outside the stops get the nearest end colour.
+    pub fn sample(&self, posn: Prop) -> Option<HCV> {
+        let first = self.stops.first()?;
+        if posn <= first.1 {
+            return Some(first.0);
+        }
+        for pair in self.stops.windows(2) {
+            let (lhs, lhs_posn) = pair[0];
+            let (rhs, rhs_posn) = pair[1];
+            if posn > rhs_posn {
+                continue;
+            } else if posn == rhs_posn || lhs_posn == rhs_posn {
+                return Some(rhs);
+            }
+            let prop = (f64::from(posn) - f64::from(lhs_posn))
+                / (f64::from(rhs_posn) - f64::from(lhs_posn));
+            return Some(interpolate(
+                &lhs,
+                &rhs,
+                Prop::from(prop),
+                self.interpolation,
+            ));
+        }
+        self.stops.last().map(|stop| stop.0)
+    }
+
+    /// `count` colours evenly spaced from one end of the gradient to the
+    /// other.
+    pub fn samples(&self, count: usize) -> Vec<HCV> {
+        let last = count.saturating_sub(1).max(1) as f64;
+        (0..count)
+            .filter_map(|i| self.sample(Prop::from(i as f64 / last)))
+            .collect()
+    }
+
+    /// Stops for a backend that interpolates through RGB (e.g. cairo) that
+    /// approximate this gradient: hue interpolation is broken up into
+    /// `count` evenly spaced stops and RGB interpolation needs no more than
+    /// the original stops.
+    pub fn colour_stops(&self, count: usize) -> Vec<(HCV, Prop)> {
+        match self.interpolation {
+            Interpolation::Rgb => self.stops.clone(),
+            _ => {
+                let last = count.saturating_sub(1).max(1) as f64;
+                (0..count)
+                    .filter_map(|i| {
+                        let posn = Prop::from(i as f64 / last);
+                        Some((self.sample(posn)?, posn))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod gradient_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    fn near(colour: &HCV, angle: Angle) -> bool {
+        let hue_angle = colour.hue_angle().expect("not grey");
+        f64::from(hue_angle.abs_diff(&angle)) < 0.01
+    }
+
+    #[test]
+    fn rgb_interpolation() {
+        let gradient = Gradient::evenly_spaced(&[HCV::BLACK, HCV::WHITE], Interpolation::Rgb);
+        assert_eq!(gradient.sample(Prop::ZERO), Some(HCV::BLACK));
+        assert_eq!(gradient.sample(Prop::ONE), Some(HCV::WHITE));
+        let middle = gradient.sample(Prop::HALF).unwrap();
+        assert!(middle.is_grey());
+        assert!((f64::from(Prop::from(middle.value())) - 0.5).abs() < 0.001);
+        assert_eq!(gradient.colour_stops(10), gradient.stops());
+        assert_eq!(gradient.samples(5).len(), 5);
+        assert!(Gradient::default().sample(Prop::HALF).is_none());
+    }
+
+    #[test]
+    fn hue_interpolation() {
+        let stops = [(HCV::RED, Prop::ZERO), (HCV::BLUE, Prop::ONE)];
+        let gradient = Gradient::new(&stops, Interpolation::ShortestHue);
+        let middle = gradient.sample(Prop::HALF).unwrap();
+        assert!(near(&middle, Angle::MAGENTA));
+        assert_eq!(gradient.sample(Prop::ONE), Some(HCV::BLUE));
+        let gradient = Gradient::new(&stops, Interpolation::LongestHue);
+        let middle = gradient.sample(Prop::HALF).unwrap();
+        assert!(near(&middle, Angle::GREEN));
+        assert_eq!(gradient.colour_stops(7).len(), 7);
+        // a grey stop takes on the hue of its neighbour
+        let stops = [(HCV::RED, Prop::ZERO), (HCV::WHITE, Prop::ONE)];
+        let gradient = Gradient::new(&stops, Interpolation::ShortestHue);
+        assert!(near(&gradient.sample(Prop::HALF).unwrap(), Angle::RED));
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{hue::angle::Angle, ManipulatedColour};
+
+/// The colour with the opposite hue (greys are their own complement).
+pub fn complement<C: ManipulatedColour>(colour: &C) -> C {
+    colour.rotated(Angle::from(180))
+}
+
+/// The two colours either side of the complement separated from it by
+/// `spread`.
+pub fn split_complements<C: ManipulatedColour>(colour: &C, spread: Angle) -> [C; 2] {
+    let complement = complement(colour);
+    [complement.rotated(-spread), complement.rotated(spread)]
+}
+
+/// The two colours that make an equilateral triangle on the hue wheel with
+/// `colour`.
+pub fn triad<C: ManipulatedColour>(colour: &C) -> [C; 2] {
+    [
+        colour.rotated(Angle::from(-120)),
+        colour.rotated(Angle::from(120)),
+    ]
+}
+
+/// The three colours that make a rectangle on the hue wheel with `colour`
+/// where `spread` is the angle between `colour` and its adjacent corner
+/// (90 degrees makes a square).
+pub fn tetrad<C: ManipulatedColour>(colour: &C, spread: Angle) -> [C; 3] {
+    let complement = complement(colour);
+    [
+        colour.rotated(spread),
+        complement,
+        complement.rotated(spread),
+    ]
+}
+
+/// The two colours either side of `colour` separated from it by `spread`.
+pub fn analogues<C: ManipulatedColour>(colour: &C, spread: Angle) -> [C; 2] {
+    [colour.rotated(-spread), colour.rotated(spread)]
+}
+
+#[cfg(test)]
+mod harmony_tests {
+    use super::*;
+    use crate::{hcv::HCV, hue::Hue, ColourBasics, HueConstants, RGBConstants, RGB};
+
+    fn near(colour: &HCV, degrees: i16) -> bool {
+        let angle = colour.hue_angle().expect("not grey");
+        f64::from(angle.abs_diff(&Angle::from(degrees))) < 0.01
+    }
+
+    #[test]
+    fn complements() {
+        assert_eq!(complement(&HCV::RED).hue(), Some(Hue::CYAN));
+        assert_eq!(complement(&RGB::<f64>::YELLOW).hue(), Some(Hue::BLUE));
+        assert_eq!(complement(&HCV::WHITE), HCV::WHITE);
+        let [first, second] = split_complements(&HCV::RED, Angle::from(30));
+        assert!(near(&first, 150));
+        assert!(near(&second, -150));
+    }
+
+    #[test]
+    fn triads_and_analogues() {
+        let [first, second] = triad(&HCV::RED);
+        assert!(near(&first, -120));
+        assert!(near(&second, 120));
+        let [first, second, third] = tetrad(&HCV::RED, Angle::from(60));
+        assert!(near(&first, 60));
+        assert!(near(&second, 180));
+        assert!(near(&third, -120));
+        let [first, second] = analogues(&HCV::GREEN, Angle::from(15));
+        assert!(near(&first, 105));
+        assert!(near(&second, 135));
+    }
+}
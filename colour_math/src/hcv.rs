@@ -56,8 +56,8 @@ impl HCV {
         }
     }
 
-    pub(crate) fn new_grey_sum(sum: UFDRNumber) -> Self {
-        debug_assert!(sum % 3 == UFDRNumber::ZERO);
+    pub(crate) const fn new_grey_sum(sum: UFDRNumber) -> Self {
+        debug_assert!(sum.0 % 3 == 0);
         Self {
             hue: None,
             c_prop: Prop::ZERO,
@@ -65,8 +65,21 @@ impl HCV {
         }
     }
 
-    pub fn new_grey(value: Value) -> Self {
-        Self::new_grey_sum(value * 3)
+    pub const fn new_grey(value: Value) -> Self {
+        Self::new_grey_sum(UFDRNumber(value.0 as u128 * 3))
+    }
+
+    // The closest valid colour to the given hue, sum and chroma favouring sum
+    pub(crate) fn new_approx(hue: Option<Hue>, sum: UFDRNumber, c_prop: Prop) -> Self {
+        if let Some(hue) = hue {
+            if let Some((c_prop, sum)) = hue.adjusted_favouring_sum(sum, c_prop) {
+                return match Self::try_new(Some((hue, c_prop)), sum) {
+                    Ok(hcv) => hcv,
+                    Err(hcv) => hcv,
+                };
+            }
+        }
+        Self::new_grey((sum / 3).into())
     }
 
     pub fn is_grey(&self) -> bool {
@@ -249,6 +262,7 @@ fn sum_and_chroma_prop((hue, [red, green, blue]): (Hue, [Prop; 3])) -> (UFDRNumb
 
 impl From<[Prop; 3]> for HCV {
     fn from(array: [Prop; 3]) -> Self {
+        count!(RgbToHcv);
         if let Ok(hue) = Hue::try_from(array) {
             let (sum, c_prop) = sum_and_chroma_prop((hue, array));
             Self {
@@ -274,14 +288,23 @@ impl From<&[Prop; 3]> for HCV {
 
 impl From<HCV> for [Prop; 3] {
     fn from(hcv: HCV) -> Self {
+        count!(HcvToRgb);
         debug_assert!(hcv.is_valid());
         if let Some(hue) = hcv.hue {
-            hue.rgb_ordered_triplet(hcv.sum, hcv.c_prop)
-                .expect("Invalid Hue")
-        } else {
-            let value: Prop = (hcv.sum / 3).into();
-            [value, value, value]
+            if let Some(triplet) = hue.rgb_ordered_triplet(hcv.sum, hcv.c_prop) {
+                return triplet;
+            }
+            // An invalid HCV (e.g. from corrupt data) gets the nearest valid
+            // colour rather than an abort in release builds.
+            if let Some(triplet) = hue
+                .adjusted_favouring_chroma(hcv.sum.min(UFDRNumber::THREE), hcv.c_prop)
+                .and_then(|(c_prop, sum)| hue.rgb_ordered_triplet(sum, c_prop))
+            {
+                return triplet;
+            }
         }
+        let value: Prop = (hcv.sum.min(UFDRNumber::THREE) / 3).into();
+        [value, value, value]
     }
 }
 
@@ -367,6 +390,24 @@ impl ColourBasics for HCV {
     }
 }
 
+impl HCV {
+    fn with_sum_favouring_chroma(&self, new_sum: UFDRNumber) -> Self {
+        if let Some(hue) = self.hue {
+            if let Some((c_prop, sum)) = hue.adjusted_favouring_chroma(new_sum, self.c_prop) {
+                // near enough is good enough
+                match HCV::try_new(Some((hue, c_prop)), sum) {
+                    Ok(hcv) => hcv,
+                    Err(hcv) => hcv,
+                }
+            } else {
+                HCV::new_grey((new_sum / 3).into())
+            }
+        } else {
+            HCV::new_grey((new_sum / 3).into())
+        }
+    }
+}
+
 impl ManipulatedColour for HCV {
     fn lightened(&self, prop: Prop) -> Self {
         let compl = Prop::ONE - prop;
@@ -386,6 +427,14 @@ impl ManipulatedColour for HCV {
         HCV::from(array)
     }
 
+    fn lightened_keeping_chroma(&self, prop: Prop) -> Self {
+        self.with_sum_favouring_chroma(self.sum + (UFDRNumber::THREE - self.sum) * prop)
+    }
+
+    fn darkened_keeping_chroma(&self, prop: Prop) -> Self {
+        self.with_sum_favouring_chroma(self.sum - self.sum * prop)
+    }
+
     fn saturated(&self, prop: Prop) -> Self {
         if let Some(hue) = self.hue {
             let new_c_prop = (self.c_prop - self.c_prop * prop + prop).into_prop();
@@ -4,8 +4,10 @@ use std::convert::From;
 use num_traits_plus::assert_approx_eq;
 
 use crate::{
-    attributes::Warmth, debug::ApproxEq, hcv::*, ColourBasics, HueConstants, Prop, RGBConstants,
-    RGB,
+    attributes::{Value, Warmth},
+    debug::ApproxEq,
+    hcv::*,
+    ColourBasics, HueConstants, ManipulatedColour, Prop, RGBConstants, RGB,
 };
 
 #[test]
@@ -180,3 +182,74 @@ fn hcv_add_sub_angle() {
         }
     }
 }
+
+#[test]
+fn rgb_ordering_is_total() {
+    let dark_red = RGB::<u8>::from([0x80, 0, 0]);
+    let mut rgbs = vec![
+        RGB::<u8>::WHITE,
+        RGB::<u8>::GREEN,
+        RGB::<u8>::BLACK,
+        RGB::<u8>::RED,
+        dark_red,
+        RGB::<u8>::BLUE,
+    ];
+    rgbs.sort();
+    assert_eq!(
+        rgbs,
+        vec![
+            RGB::<u8>::BLACK,
+            RGB::<u8>::WHITE,
+            RGB::<u8>::BLUE,
+            dark_red,
+            RGB::<u8>::RED,
+            RGB::<u8>::GREEN,
+        ]
+    );
+}
+
+#[test]
+fn const_construction() {
+    const PALETTE: [RGB<u8>; 2] = [RGB::new(0x12, 0x34, 0x56), RGB::new(0xFF, 0, 0)];
+    const GREYS: [HCV; 3] = [
+        HCV::new_grey(Value::ZERO),
+        HCV::new_grey(Value::from_prop(Prop::from_fraction(1, 2))),
+        HCV::new_grey(Value::ONE),
+    ];
+    assert_eq!(PALETTE[0], RGB::<u8>::from([0x12, 0x34, 0x56]));
+    assert_eq!(PALETTE[1], RGB::<u8>::RED);
+    assert_eq!(GREYS[0], HCV::BLACK);
+    assert_eq!(GREYS[1], HCV::new_grey(Value::from(Prop::from([1, 2]))));
+    assert_eq!(GREYS[2], HCV::WHITE);
+    assert!(GREYS.iter().all(|grey| grey.is_grey() && grey.is_valid()));
+}
+
+#[test]
+fn lighten_darken_keeping_chroma() {
+    let tolerance = Some(Prop::from(0.000_001));
+    let colour = HCV::from(RGB::<f64>::from([0.6, 0.3, 0.3]));
+    let lighter = colour.lightened_keeping_chroma(Prop::ONE / 2);
+    assert!(lighter
+        .rgb::<f64>()
+        .approx_eq(&RGB::from([0.9, 0.6, 0.6]), tolerance));
+    let darker = colour.darkened_keeping_chroma(Prop::ONE / 2);
+    assert!(darker
+        .rgb::<f64>()
+        .approx_eq(&RGB::from([0.4, 0.1, 0.1]), tolerance));
+    // stops at the limits for the chroma
+    let lightest = colour.lightened_keeping_chroma(Prop::ONE);
+    assert!(lightest
+        .rgb::<f64>()
+        .approx_eq(&RGB::from([1.0, 0.7, 0.7]), tolerance));
+    assert_eq!(HCV::RED.lightened_keeping_chroma(Prop::ONE / 2), HCV::RED);
+    assert_eq!(HCV::RED.darkened_keeping_chroma(Prop::ONE / 2), HCV::RED);
+    // greys behave like the plain versions
+    let grey = HCV::new_grey(Value::ONE / 2);
+    assert_eq!(grey.lightened_keeping_chroma(Prop::ONE), HCV::WHITE);
+    assert_eq!(grey.darkened_keeping_chroma(Prop::ONE), HCV::BLACK);
+    let rgb = RGB::<u8>::from([153, 76, 76]);
+    assert!(rgb.lightened_keeping_chroma(Prop::ONE / 2).value() > rgb.value());
+    let darker = rgb.darkened_keeping_chroma(Prop::ONE / 2);
+    assert!(darker.value() < rgb.value());
+    assert!((f64::from(darker.chroma_prop()) - f64::from(rgb.chroma_prop())).abs() < 0.01);
+}
@@ -116,8 +116,8 @@ pub(crate) trait OrderedTriplets: HueBasics + SumChromaCompatibility {
                     let triplet = [first.to_prop(), second.to_prop(), third.to_prop()];
                     debug_assert!(Self::has_valid_value_order(&triplet));
                     debug_assert_eq!(
-                        Hue::try_from(self.triplet_to_rgb_order(&triplet)).unwrap(),
-                        (*self).into()
+                        Hue::try_from(self.triplet_to_rgb_order(&triplet)).ok(),
+                        Some((*self).into())
                     );
                     Some(triplet)
                 } else {
@@ -308,7 +308,7 @@ pub(crate) trait ColourModificationHelpers: HueBasics + Debug + Sized {
                 match sum.cmp(&self.sum_for_max_chroma()) {
                     Ordering::Equal | Ordering::Less => Some((c_prop, sum)),
                     Ordering::Greater => {
-                        let max_sum = self.max_sum_for_chroma_prop(c_prop).expect("chroma > 0");
+                        let max_sum = self.max_sum_for_chroma_prop(c_prop)?;
                         if sum > max_sum {
                             Some(self.trim_overs(max_sum, c_prop)?)
                         } else {
@@ -327,7 +327,7 @@ pub(crate) trait ColourModificationHelpers: HueBasics + Debug + Sized {
             c_prop => match sum.cmp(&self.sum_for_max_chroma()) {
                 Ordering::Equal => Some(self.trim_overs(sum, c_prop)?),
                 Ordering::Less => {
-                    let min_sum = self.min_sum_for_chroma_prop(c_prop).expect("c_prop > 0");
+                    let min_sum = self.min_sum_for_chroma_prop(c_prop)?;
                     if sum < min_sum {
                         let max_chroma = self.max_chroma_prop_for_sum(sum)?;
                         Some(self.trim_overs(sum, max_chroma)?)
@@ -1210,6 +1210,7 @@ impl TryFrom<[Prop; 3]> for Hue {
     type Error = &'static str;
 
     fn try_from(arrayx: [Prop; 3]) -> Result<Self, Self::Error> {
+        count!(HueCalculation);
         use Sextant::*;
         let [red, green, blue] = arrayx;
         match red.cmp(&green) {
@@ -1321,6 +1322,42 @@ impl From<Angle> for Hue {
     }
 }
 
+impl Hue {
+    /// `steps` evenly spaced hues around the wheel starting at RED and
+    /// going anticlockwise.
+    pub fn wheel_iter(steps: usize) -> HueWheelIter {
+        HueWheelIter { next: 0, steps }
+    }
+}
+
+/// Iterator over evenly spaced hues (see `Hue::wheel_iter()`).
+#[derive(Debug, Clone)]
+pub struct HueWheelIter {
+    next: usize,
+    steps: usize,
+}
+
+impl Iterator for HueWheelIter {
+    type Item = Hue;
+
+    fn next(&mut self) -> Option<Hue> {
+        if self.next < self.steps {
+            let angle = Angle::wheel_step(self.next, self.steps);
+            self.next += 1;
+            Some(Hue::from(angle))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.steps - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for HueWheelIter {}
+
 impl From<Hue> for Angle {
     fn from(hue: Hue) -> Self {
         hue.angle()
@@ -1641,12 +1678,6 @@ impl PropDiff for Hue {
 
 impl ApproxEq for Hue {}
 
-impl Hue {
-    pub fn ord_index(&self) -> u8 {
-        0
-    }
-}
-
 impl Add<Angle> for Hue {
     type Output = Self;
 
@@ -1673,13 +1704,15 @@ impl Sub for Hue {
 
 impl PartialOrd for Hue {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.ord_index().partial_cmp(&other.ord_index())
+        Some(self.cmp(other))
     }
 }
 
+/// Hues are ordered by angle i.e. from CYAN (-180) via BLUE, RED and GREEN
+/// back to CYAN.
 impl Ord for Hue {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.angle().cmp(&other.angle())
     }
 }
 
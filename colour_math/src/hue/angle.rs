@@ -19,6 +19,56 @@ impl Angle {
     pub(crate) const MIN: Self = Self(Self::DEGREE.0 * -180);
     pub(crate) const MAX: Self = Self(Self::DEGREE.0 * 180);
 
+    /// The step between hues on a 12 step wheel (30 degrees).
+    pub const STEP_12: Self = Self(Self::DEGREE.0 * 30);
+    /// The step between hues on a 24 step wheel (15 degrees).
+    pub const STEP_24: Self = Self(Self::DEGREE.0 * 15);
+
+    /// The multiple of `step` closest to this angle.  `step` should divide
+    /// 360 degrees evenly.
+    pub fn nearest_step(self, step: Self) -> Self {
+        if step.0 == 0 {
+            return self;
+        }
+        let steps = (self.0 as f64 / step.0 as f64).round() as i128;
+        let ws = steps * step.0 as i128;
+        if ws >= Self::MAX.0 as i128 {
+            Self((ws - Self::MAX.0 as i128 * 2) as i64)
+        } else if ws < Self::MIN.0 as i128 {
+            Self((ws + Self::MAX.0 as i128 * 2) as i64)
+        } else {
+            Self(ws as i64)
+        }
+    }
+
+    /// The multiples of `step` around the wheel starting at RED (zero) and
+    /// going anticlockwise.  `step` should divide 360 degrees evenly.
+    pub fn steps(step: Self) -> impl Iterator<Item = Self> {
+        let step = step.0.abs().max(1) as i128;
+        let count = Self::MAX.0 as i128 * 2 / step;
+        (0..count).map(move |n| {
+            let ws = n * step;
+            if ws >= Self::MAX.0 as i128 {
+                Self((ws - Self::MAX.0 as i128 * 2) as i64)
+            } else {
+                Self(ws as i64)
+            }
+        })
+    }
+
+    /// The `n`th of `steps` evenly spaced angles around the wheel starting
+    /// at RED (zero) and going anticlockwise.  Each is calculated directly
+    /// (rather than by accumulating a step) so there is no drift.
+    pub fn wheel_step(n: usize, steps: usize) -> Self {
+        let steps = steps.max(1) as i128;
+        let ws = (n as i128 % steps) * Self::MAX.0 as i128 * 2 / steps;
+        if ws >= Self::MAX.0 as i128 {
+            Self((ws - Self::MAX.0 as i128 * 2) as i64)
+        } else {
+            Self(ws as i64)
+        }
+    }
+
     pub fn asin(arg: FDRNumber) -> Self {
         Self::from(f64::from(arg).asin().to_degrees())
     }
@@ -229,4 +279,34 @@ mod angle_tests {
             10000
         );
     }
+
+    #[test]
+    fn wheel_steps() {
+        assert_eq!(
+            Angle::from(44).nearest_step(Angle::STEP_12),
+            Angle::from(30)
+        );
+        assert_eq!(
+            Angle::from(46).nearest_step(Angle::STEP_12),
+            Angle::from(60)
+        );
+        assert_eq!(
+            Angle::from(-52).nearest_step(Angle::STEP_24),
+            Angle::from(-45)
+        );
+        assert_eq!(Angle::from(175).nearest_step(Angle::STEP_12), Angle::CYAN);
+        assert_eq!(Angle::from(-178).nearest_step(Angle::STEP_12), Angle::CYAN);
+        let steps: Vec<Angle> = Angle::steps(Angle::STEP_12).collect();
+        assert_eq!(steps.len(), 12);
+        assert_eq!(steps[0], Angle::RED);
+        assert_eq!(steps[4], Angle::GREEN);
+        assert_eq!(steps[6], Angle::CYAN);
+        assert_eq!(steps[11], Angle::RED_MAGENTA);
+        assert_eq!(Angle::steps(Angle::STEP_24).count(), 24);
+        for (n, step) in Angle::steps(Angle::STEP_24).enumerate() {
+            assert_eq!(Angle::wheel_step(n, 24), step);
+        }
+        assert_eq!(Angle::wheel_step(12, 12), Angle::RED);
+        assert_eq!(Angle::wheel_step(3, 6), Angle::CYAN);
+    }
 }
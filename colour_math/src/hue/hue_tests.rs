@@ -222,6 +222,24 @@ fn hue_to_from_angle() {
     }
 }
 
+#[test]
+fn hue_wheel_iter() {
+    let hues: Vec<Hue> = Hue::wheel_iter(12).collect();
+    assert_eq!(hues.len(), 12);
+    assert_eq!(hues[0], Hue::RED);
+    assert_eq!(hues[2], Hue::YELLOW);
+    assert_eq!(hues[4], Hue::GREEN);
+    assert_eq!(hues[6], Hue::CYAN);
+    assert_eq!(hues[8], Hue::BLUE);
+    assert_eq!(hues[10], Hue::MAGENTA);
+    assert_eq!(hues[11], Hue::RED_MAGENTA);
+    assert_eq!(Hue::wheel_iter(7).len(), 7);
+    assert_eq!(Hue::wheel_iter(0).count(), 0);
+    for (n, hue) in Hue::wheel_iter(7).enumerate() {
+        assert_approx_eq!(hue.angle(), Angle::wheel_step(n, 7), 0x1000);
+    }
+}
+
 #[test]
 fn hue_add_sub_angle() {
     for hue in Hue::PRIMARIES
@@ -1375,3 +1393,30 @@ fn general_try_rgb_for_sum_and_chroma() {
         }
     }
 }
+
+#[test]
+fn hue_ordering_is_total() {
+    let mut hues = vec![
+        Hue::GREEN,
+        Hue::CYAN,
+        Hue::RED,
+        Hue::BLUE,
+        Hue::from(Angle::from(30_i16)),
+    ];
+    hues.sort();
+    assert_eq!(
+        hues,
+        vec![
+            Hue::CYAN,
+            Hue::BLUE,
+            Hue::RED,
+            Hue::from(Angle::from(30_i16)),
+            Hue::GREEN
+        ]
+    );
+    for hue in hues.iter() {
+        for other in hues.iter() {
+            assert_eq!(hue.cmp(other) == Ordering::Equal, hue == other);
+        }
+    }
+}
@@ -0,0 +1,491 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{error::Error, fmt};
+
+use crate::{
+    attributes::{Chroma, Value},
+    fdrn::{Prop, UFDRNumber},
+    hcv::HCV,
+    hue::{angle::Angle, Hue},
+    progress::{self, Cancelled, NoProgress, Progress},
+    rgb::RGB,
+    transform::{ChromaDenoise, ChromaKey, ChromaMask, HueRangeMask},
+    ColourBasics, LightLevel,
+};
+
+pub mod tiles;
+
+use tiles::{Region, Tiles};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImageError {
+    BadBufferSize { expected: usize, actual: usize },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::BadBufferSize { expected, actual } => write!(
+                f,
+                "Image buffer has {actual} elements when {expected} were expected"
+            ),
+        }
+    }
+}
+
+impl Error for ImageError {}
+
+/// An image stored as separate hue, chroma and sum planes (row major) so
+/// that operations on a single attribute don't need to touch the others.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HcvImage {
+    hues: Vec<Option<Hue>>,
+    chromas: Vec<Chroma>,
+    sums: Vec<UFDRNumber>,
+    width: usize,
+    height: usize,
+}
+
+impl HcvImage {
+    /// A black image.
+    pub fn new(width: usize, height: usize) -> Self {
+        let size = width * height;
+        Self {
+            hues: vec![None; size],
+            chromas: vec![Chroma::ZERO; size],
+            sums: vec![UFDRNumber::ZERO; size],
+            width,
+            height,
+        }
+    }
+
+    pub fn from_pixels(pixels: &[HCV], width: usize, height: usize) -> Result<Self, ImageError> {
+        if pixels.len() != width * height {
+            return Err(ImageError::BadBufferSize {
+                expected: width * height,
+                actual: pixels.len(),
+            });
+        }
+        let mut image = Self::new(width, height);
+        for (index, pixel) in pixels.iter().enumerate() {
+            image.set_index(index, pixel);
+        }
+        Ok(image)
+    }
+
+    /// Construct from a buffer of interleaved red, green and blue components.
+    pub fn from_rgb_buffer<L: LightLevel>(
+        buffer: &[L],
+        width: usize,
+        height: usize,
+    ) -> Result<Self, ImageError> {
+        if buffer.len() != width * height * 3 {
+            return Err(ImageError::BadBufferSize {
+                expected: width * height * 3,
+                actual: buffer.len(),
+            });
+        }
+        let mut image = Self::new(width, height);
+        for (index, rgb) in buffer.chunks_exact(3).enumerate() {
+            image.set_index(index, &RGB::<L>::from([rgb[0], rgb[1], rgb[2]]));
+        }
+        Ok(image)
+    }
+
+    /// A buffer of interleaved red, green and blue components.
+    pub fn to_rgb_buffer<L: LightLevel>(&self) -> Vec<L> {
+        let mut buffer = Vec::with_capacity(self.len() * 3);
+        for index in 0..self.len() {
+            buffer.extend_from_slice(&<[L; 3]>::from(self.hcv_at_index(index).rgb::<L>()));
+        }
+        buffer
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.sums.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sums.is_empty()
+    }
+
+    pub fn hues(&self) -> &[Option<Hue>] {
+        &self.hues
+    }
+
+    pub fn chromas(&self) -> &[Chroma] {
+        &self.chromas
+    }
+
+    pub fn sums(&self) -> &[UFDRNumber] {
+        &self.sums
+    }
+
+    fn hcv_at_index(&self, index: usize) -> HCV {
+        HCV {
+            hue: self.hues[index],
+            c_prop: self.chromas[index].into(),
+            sum: self.sums[index],
+        }
+    }
+
+    fn set_index(&mut self, index: usize, colour: &impl ColourBasics) {
+        let hcv = colour.hcv();
+        self.hues[index] = hcv.hue;
+        self.chromas[index] = hcv.chroma();
+        self.sums[index] = hcv.sum;
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> HCV {
+        debug_assert!(x < self.width && y < self.height);
+        self.hcv_at_index(y * self.width + x)
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, colour: &impl ColourBasics) {
+        debug_assert!(x < self.width && y < self.height);
+        self.set_index(y * self.width + x, colour)
+    }
+
+    pub fn pixels(&self) -> Vec<HCV> {
+        (0..self.len()).map(|i| self.hcv_at_index(i)).collect()
+    }
+
+    /// Apply `curve` to the value of every pixel keeping hue and chroma
+    /// (where possible).
+    pub fn map_values(&mut self, curve: impl Fn(Value) -> Value) {
+        for index in 0..self.len() {
+            let sum = curve((self.sums[index] / 3).into()) * 3;
+            let hcv = HCV::new_approx(self.hues[index], sum, Prop::from(self.chromas[index]));
+            self.set_index(index, &hcv);
+        }
+    }
+
+    pub fn chroma_denoised(&self, denoise: &ChromaDenoise) -> Self {
+        let pixels = denoise.apply(&self.pixels(), self.width);
+        Self::from_pixels(&pixels, self.width, self.height).expect("same size")
+    }
+
+    /// A greyscale image of the pixels' chromas (see `ChromaMask`).
+    pub fn chroma_mask(&self, mask: &ChromaMask) -> Self {
+        let mut image = Self::new(self.width, self.height);
+        for (sum, chroma) in image.sums.iter_mut().zip(self.chromas.iter()) {
+            *sum = mask.mask_value(Prop::from(*chroma)) * 3;
+        }
+        image
+    }
+
+    /// A greyscale image of the degree to which each pixel's hue is
+    /// selected by `mask`.
+    pub fn hue_range_mask(&self, mask: &HueRangeMask) -> Self {
+        let mut image = Self::new(self.width, self.height);
+        for (sum, hue) in image.sums.iter_mut().zip(self.hues.iter()) {
+            *sum = mask.mask_value(*hue) * 3;
+        }
+        image
+    }
+
+    /// A copy of the part of the image within `region`.
+    pub fn sub_image(&self, region: &Region) -> Self {
+        debug_assert!(region.x + region.width <= self.width);
+        debug_assert!(region.y + region.height <= self.height);
+        let mut sub_image = Self::new(region.width, region.height);
+        for y in 0..region.height {
+            let start = (region.y + y) * self.width + region.x;
+            let sub_start = y * region.width;
+            let range = start..start + region.width;
+            let sub_range = sub_start..sub_start + region.width;
+            sub_image.hues[sub_range.clone()].copy_from_slice(&self.hues[range.clone()]);
+            sub_image.chromas[sub_range.clone()].copy_from_slice(&self.chromas[range.clone()]);
+            sub_image.sums[sub_range].copy_from_slice(&self.sums[range]);
+        }
+        sub_image
+    }
+
+    /// Copy the `from` part of `source` into this image with its top left
+    /// corner at (`x`, `y`).
+    pub fn paste(&mut self, x: usize, y: usize, source: &Self, from: &Region) {
+        debug_assert!(x + from.width <= self.width && y + from.height <= self.height);
+        for row in 0..from.height {
+            let start = (y + row) * self.width + x;
+            let source_start = (from.y + row) * source.width + from.x;
+            let range = start..start + from.width;
+            let source_range = source_start..source_start + from.width;
+            self.hues[range.clone()].copy_from_slice(&source.hues[source_range.clone()]);
+            self.chromas[range.clone()].copy_from_slice(&source.chromas[source_range.clone()]);
+            self.sums[range].copy_from_slice(&source.sums[source_range]);
+        }
+    }
+
+    /// Copy all of `source` into this image with its top left corner at
+    /// (`x`, `y`).
+    pub fn paste_all(&mut self, x: usize, y: usize, source: &Self) {
+        let from = Region {
+            width: source.width,
+            height: source.height,
+            ..Region::default()
+        };
+        self.paste(x, y, source, &from)
+    }
+
+    /// Apply `operation` to the image a tile at a time.  Each tile is given
+    /// `overlap` pixels of context on each side (where available) which is
+    /// discarded from the result so that neighbourhood operations don't
+    /// produce seams.
+    pub fn tiled_map(
+        &self,
+        tile_size: usize,
+        overlap: usize,
+        operation: impl FnMut(&Self) -> Self,
+    ) -> Self {
+        self.tiled_map_with_progress(tile_size, overlap, operation, &NoProgress)
+            .expect("NoProgress never cancels")
+    }
+
+    pub fn tiled_map_with_progress(
+        &self,
+        tile_size: usize,
+        overlap: usize,
+        operation: impl FnMut(&Self) -> Self,
+        progress: &impl Progress,
+    ) -> Result<Self, Cancelled> {
+        let mut output = Self::new(self.width, self.height);
+        map_tiles::<Cancelled>(
+            self.width,
+            self.height,
+            tile_size,
+            overlap,
+            |region| Ok(self.sub_image(region)),
+            operation,
+            |region, result| {
+                output.paste_all(region.x, region.y, result);
+                Ok(())
+            },
+            progress,
+        )?;
+        Ok(output)
+    }
+}
+
+/// Apply `operation` a tile at a time to a `width` by `height` image that
+/// need never be held in memory as a whole.  `source` is asked for the
+/// pixels in each tile's padded region (e.g. by reading them from a file)
+/// and `sink` is given each tile's core region and the corresponding part
+/// of the result (e.g. to write it to a file).  Tiles are visited in row
+/// major order and the first error from `source` or `sink` (or
+/// cancellation) stops the processing.
+#[allow(clippy::too_many_arguments)]
+pub fn map_tiles<E: From<Cancelled>>(
+    width: usize,
+    height: usize,
+    tile_size: usize,
+    overlap: usize,
+    mut source: impl FnMut(&Region) -> Result<HcvImage, E>,
+    mut operation: impl FnMut(&HcvImage) -> HcvImage,
+    mut sink: impl FnMut(&Region, &HcvImage) -> Result<(), E>,
+    progress: &impl Progress,
+) -> Result<(), E> {
+    let tiles = Tiles::with_overlap(width, height, tile_size, overlap);
+    let total = tiles.len();
+    for (index, tile) in tiles.enumerate() {
+        progress.checkpoint(progress::proportion(index, total))?;
+        let result = operation(&source(&tile.padded)?);
+        debug_assert_eq!(
+            (result.width, result.height),
+            (tile.padded.width, tile.padded.height)
+        );
+        let core = Region {
+            x: tile.core.x - tile.padded.x,
+            y: tile.core.y - tile.padded.y,
+            ..tile.core
+        };
+        sink(&tile.core, &result.sub_image(&core))?;
+    }
+    progress.report(Prop::ONE);
+    Ok(())
+}
+
+/// A soft mask of the pixels in `image` whose hues are in the range `from`
+/// anticlockwise to `to` (see `HueRangeMask`).
+pub fn mask_by_hue_range(image: &HcvImage, from: Angle, to: Angle, feather: Angle) -> HcvImage {
+    image.hue_range_mask(&HueRangeMask::new(from, to, feather))
+}
+
+/// The result of chroma keying an image: the (spill corrected) colours and
+/// a row major alpha plane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyedImage {
+    pub colours: HcvImage,
+    pub alpha: Vec<Prop>,
+}
+
+pub fn chroma_key(
+    image: &HcvImage,
+    key: Hue,
+    tolerance: Angle,
+    spill_suppression: Prop,
+) -> KeyedImage {
+    let chroma_key = ChromaKey::new(key, tolerance, spill_suppression);
+    let mut colours = HcvImage::new(image.width, image.height);
+    let mut alpha = Vec::with_capacity(image.len());
+    for index in 0..image.len() {
+        let hcv = image.hcv_at_index(index);
+        alpha.push(chroma_key.alpha(&hcv));
+        colours.set_index(index, &chroma_key.despilled(&hcv));
+    }
+    KeyedImage { colours, alpha }
+}
+
+#[cfg(test)]
+mod image_tests {
+    use super::*;
+    use crate::progress::CancelToken;
+    use crate::{HueConstants, RGBConstants};
+
+    #[test]
+    fn rgb_buffer_round_trip() {
+        let buffer: Vec<u8> = vec![
+            255, 0, 0, 0, 255, 0, 0, 0, 255, 128, 128, 128, 0, 255, 255, 0, 0, 0,
+        ];
+        let image = HcvImage::from_rgb_buffer(&buffer, 3, 2).unwrap();
+        assert_eq!(image.len(), 6);
+        assert_eq!(image.get(0, 0), HCV::RED);
+        assert_eq!(image.hues()[1], Some(Hue::GREEN));
+        assert_eq!(image.hues()[3], None);
+        assert_eq!(image.to_rgb_buffer::<u8>(), buffer);
+        assert_eq!(
+            HcvImage::from_rgb_buffer(&buffer, 3, 3),
+            Err(ImageError::BadBufferSize {
+                expected: 27,
+                actual: 18
+            })
+        );
+    }
+
+    #[test]
+    fn chroma_mask() {
+        let pixels = [
+            HCV::RED,
+            HCV::WHITE,
+            HCV::from(RGB::<f64>::from([0.6, 0.5, 0.5])),
+        ];
+        let image = HcvImage::from_pixels(&pixels, 3, 1).unwrap();
+        let mask = image.chroma_mask(&ChromaMask::default());
+        assert_eq!(mask.hues(), &[None, None, None]);
+        assert_eq!(mask.get(0, 0), HCV::WHITE);
+        assert_eq!(mask.get(1, 0), HCV::BLACK);
+        assert!(mask.get(2, 0).value() < Value::from(0.2));
+        let mask = image.chroma_mask(&ChromaMask::new(Some(Prop::from(0.05))));
+        assert_eq!(mask.get(2, 0), HCV::WHITE);
+    }
+
+    #[test]
+    fn hue_range_mask() {
+        let pixels = [HCV::GREEN, HCV::RED, HCV::WHITE];
+        let image = HcvImage::from_pixels(&pixels, 3, 1).unwrap();
+        let mask = mask_by_hue_range(
+            &image,
+            Angle::from(90_i16),
+            Angle::from(150_i16),
+            Angle::from(10_i16),
+        );
+        assert_eq!(mask.get(0, 0), HCV::WHITE);
+        assert_eq!(mask.get(1, 0), HCV::BLACK);
+        assert_eq!(mask.get(2, 0), HCV::BLACK);
+    }
+
+    #[test]
+    fn chroma_key() {
+        let pixels = [HCV::GREEN, HCV::RED, HCV::WHITE];
+        let image = HcvImage::from_pixels(&pixels, 3, 1).unwrap();
+        let keyed = super::chroma_key(&image, Hue::GREEN, Angle::from(15_i16), Prop::ONE);
+        assert_eq!(keyed.alpha, vec![Prop::ZERO, Prop::ONE, Prop::ONE]);
+        assert_eq!(keyed.colours.get(1, 0), HCV::RED);
+        assert_eq!(keyed.colours.get(2, 0), HCV::WHITE);
+    }
+
+    #[test]
+    fn value_curve() {
+        let pixels = [HCV::WHITE, HCV::BLACK, HCV::new_grey(Value::from(0.5))];
+        let mut image = HcvImage::from_pixels(&pixels, 3, 1).unwrap();
+        image.map_values(|value| Value::from(Prop::ONE - Prop::from(value)));
+        assert_eq!(image.get(0, 0), HCV::BLACK);
+        assert_eq!(image.get(1, 0), HCV::WHITE);
+        let mut image = HcvImage::new(1, 1);
+        image.set(0, 0, &RGB::<f64>::from([0.4, 0.2, 0.2]));
+        image.map_values(|value| Value::from(f64::from(value) * 1.5));
+        assert_eq!(image.hues()[0], Some(Hue::RED));
+        assert!(image.get(0, 0).value() > Value::from(0.26));
+    }
+
+    #[test]
+    fn tiled_denoise_matches_whole() {
+        let mut image = HcvImage::new(9, 7);
+        for y in 0..7 {
+            for x in 0..9 {
+                let rgb = [(x * 25) as f64 / 255.0, (y * 30) as f64 / 255.0, 0.5];
+                image.set(x, y, &RGB::<f64>::from(rgb));
+            }
+        }
+        let denoise = ChromaDenoise::new(1);
+        let whole = image.chroma_denoised(&denoise);
+        let tiled = image.tiled_map(4, 1, |tile| tile.chroma_denoised(&denoise));
+        assert_eq!(tiled, whole);
+        assert_eq!(image.tiled_map(3, 0, |tile| tile.clone()), image);
+        let token = CancelToken::new();
+        token.cancel();
+        let result = image.tiled_map_with_progress(3, 0, |tile| tile.clone(), &token);
+        assert_eq!(result, Err(Cancelled));
+    }
+
+    #[test]
+    fn map_tiles_streams() {
+        let mut image = HcvImage::new(7, 5);
+        for y in 0..5 {
+            for x in 0..7 {
+                let rgb = [x as f64 / 7.0, y as f64 / 5.0, 0.25];
+                image.set(x, y, &RGB::<f64>::from(rgb));
+            }
+        }
+        let denoise = ChromaDenoise::new(1);
+        let mut output = HcvImage::new(7, 5);
+        let mut largest = 0;
+        let result: Result<(), Cancelled> = map_tiles(
+            7,
+            5,
+            3,
+            1,
+            |region| {
+                largest = largest.max(region.len());
+                Ok(image.sub_image(region))
+            },
+            |tile| tile.chroma_denoised(&denoise),
+            |region, tile| {
+                assert_eq!((tile.width(), tile.height()), (region.width, region.height));
+                output.paste_all(region.x, region.y, tile);
+                Ok(())
+            },
+            &NoProgress,
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(output, image.chroma_denoised(&denoise));
+        assert_eq!(largest, 20);
+        let result = map_tiles(
+            7,
+            5,
+            3,
+            1,
+            |region| Ok(image.sub_image(region)),
+            |tile| tile.clone(),
+            |_, _| Err(Cancelled),
+            &NoProgress,
+        );
+        assert_eq!(result, Err(Cancelled));
+    }
+}
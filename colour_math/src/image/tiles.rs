@@ -0,0 +1,154 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Region {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Region {
+    pub fn len(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// A tile's `core` is its share of the image and `padded` is the `core`
+/// expanded by the overlap (clipped to the image) for use by operations
+/// that need to see neighbouring pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub core: Region,
+    pub padded: Region,
+}
+
+/// Iterate (row major) over the tiles covering a `width` by `height` image.
+#[derive(Debug, Clone)]
+pub struct Tiles {
+    width: usize,
+    height: usize,
+    tile_size: usize,
+    overlap: usize,
+    next: usize,
+}
+
+impl Tiles {
+    pub fn new(width: usize, height: usize, tile_size: usize) -> Self {
+        Self::with_overlap(width, height, tile_size, 0)
+    }
+
+    /// A `tile_size` of zero is treated as one.
+    pub fn with_overlap(width: usize, height: usize, tile_size: usize, overlap: usize) -> Self {
+        Self {
+            width,
+            height,
+            tile_size: tile_size.max(1),
+            overlap,
+            next: 0,
+        }
+    }
+
+    fn columns(&self) -> usize {
+        self.width.div_ceil(self.tile_size)
+    }
+
+    fn rows(&self) -> usize {
+        self.height.div_ceil(self.tile_size)
+    }
+}
+
+impl Iterator for Tiles {
+    type Item = Tile;
+
+    fn next(&mut self) -> Option<Tile> {
+        if self.next >= self.columns() * self.rows() {
+            return None;
+        }
+        let x = (self.next % self.columns()) * self.tile_size;
+        let y = (self.next / self.columns()) * self.tile_size;
+        self.next += 1;
+        let core = Region {
+            x,
+            y,
+            width: self.tile_size.min(self.width - x),
+            height: self.tile_size.min(self.height - y),
+        };
+        let padded_x = x.saturating_sub(self.overlap);
+        let padded_y = y.saturating_sub(self.overlap);
+        let padded = Region {
+            x: padded_x,
+            y: padded_y,
+            width: (x + core.width + self.overlap).min(self.width) - padded_x,
+            height: (y + core.height + self.overlap).min(self.height) - padded_y,
+        };
+        Some(Tile { core, padded })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.columns() * self.rows()).saturating_sub(self.next);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Tiles {}
+
+#[cfg(test)]
+mod tiles_tests {
+    use super::*;
+
+    #[test]
+    fn tiles_cover_image_once() {
+        let tiles: Vec<Tile> = Tiles::new(10, 7, 4).collect();
+        assert_eq!(tiles.len(), 6);
+        for y in 0..7 {
+            for x in 0..10 {
+                let count = tiles.iter().filter(|t| t.core.contains(x, y)).count();
+                assert_eq!(count, 1);
+            }
+        }
+        assert_eq!(tiles[5].core, tiles[5].padded);
+        assert_eq!(
+            tiles[5].core,
+            Region {
+                x: 8,
+                y: 4,
+                width: 2,
+                height: 3
+            }
+        );
+    }
+
+    #[test]
+    fn tiles_overlap() {
+        let tiles: Vec<Tile> = Tiles::with_overlap(10, 7, 4, 2).collect();
+        assert_eq!(
+            tiles[0].padded,
+            Region {
+                x: 0,
+                y: 0,
+                width: 6,
+                height: 6
+            }
+        );
+        assert_eq!(
+            tiles[4].padded,
+            Region {
+                x: 2,
+                y: 2,
+                width: 8,
+                height: 5
+            }
+        );
+        assert!(Tiles::new(0, 0, 4).next().is_none());
+        assert_eq!(Tiles::new(3, 2, 0).len(), 6);
+    }
+}
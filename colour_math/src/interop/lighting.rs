@@ -0,0 +1,78 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{ColourBasics, RGB};
+
+/// The components of `colour` as OSC style floats in the range 0.0 to 1.0.
+pub fn osc_floats(colour: &impl ColourBasics) -> [f32; 3] {
+    let rgb: RGB<f64> = colour.rgb();
+    [rgb[0] as f32, rgb[1] as f32, rgb[2] as f32]
+}
+
+fn push_osc_string(packet: &mut Vec<u8>, string: &str) {
+    packet.extend_from_slice(string.as_bytes());
+    // null terminated and padded to a multiple of 4 bytes
+    let padding = 4 - string.len() % 4;
+    packet.resize(packet.len() + padding, 0);
+}
+
+/// A complete OSC message (e.g. for sending in a UDP packet) with three
+/// float arguments: red, green and blue.
+pub fn osc_message(address: &str, colour: &impl ColourBasics) -> Vec<u8> {
+    let mut packet = vec![];
+    push_osc_string(&mut packet, address);
+    push_osc_string(&mut packet, ",fff");
+    for float in osc_floats(colour).iter() {
+        packet.extend_from_slice(&float.to_be_bytes());
+    }
+    packet
+}
+
+/// The components of `colour` as MIDI data bytes (0 to 127).
+pub fn midi_values(colour: &impl ColourBasics) -> [u8; 3] {
+    let rgb: RGB<f64> = colour.rgb();
+    let value = |component: f64| (component.clamp(0.0, 1.0) * 127.0).round() as u8;
+    [value(rgb[0]), value(rgb[1]), value(rgb[2])]
+}
+
+/// Three control change messages setting the controllers `controllers` on
+/// `channel` (0 to 15) to the red, green and blue components of `colour`.
+pub fn midi_control_changes(
+    channel: u8,
+    controllers: [u8; 3],
+    colour: &impl ColourBasics,
+) -> [[u8; 3]; 3] {
+    let status = 0xB0 | (channel & 0x0F);
+    let values = midi_values(colour);
+    [
+        [status, controllers[0] & 0x7F, values[0]],
+        [status, controllers[1] & 0x7F, values[1]],
+        [status, controllers[2] & 0x7F, values[2]],
+    ]
+}
+
+#[cfg(test)]
+mod lighting_tests {
+    use super::*;
+    use crate::{hcv::HCV, HueConstants, RGBConstants};
+
+    #[test]
+    fn osc() {
+        assert_eq!(osc_floats(&HCV::YELLOW), [1.0, 1.0, 0.0]);
+        let message = osc_message("/rgb", &HCV::RED);
+        assert_eq!(message.len(), 8 + 8 + 12);
+        assert_eq!(&message[..8], b"/rgb\0\0\0\0");
+        assert_eq!(&message[8..16], b",fff\0\0\0\0");
+        assert_eq!(&message[16..20], &1.0_f32.to_be_bytes());
+        assert_eq!(&message[20..], &[0u8; 8]);
+        assert_eq!(osc_message("/a", &HCV::RED).len(), 4 + 8 + 12);
+    }
+
+    #[test]
+    fn midi() {
+        assert_eq!(midi_values(&HCV::WHITE), [127, 127, 127]);
+        assert_eq!(midi_values(&HCV::CYAN), [0, 127, 127]);
+        assert_eq!(
+            midi_control_changes(2, [20, 21, 22], &HCV::RED),
+            [[0xB2, 20, 127], [0xB2, 21, 0], [0xB2, 22, 0]]
+        );
+    }
+}
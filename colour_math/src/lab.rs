@@ -0,0 +1,318 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    hcv::HCV,
+    rgb::RGB,
+    standards::WhitePoint,
+    xyz::{Adaptation, XYZ},
+    ColourBasics, LightLevel,
+};
+
+const EPSILON: f64 = 216.0 / 24389.0;
+const KAPPA: f64 = 24389.0 / 27.0;
+
+// sRGB (whose native white is D65) to XYZ relative to `white_point`
+fn xyz_from_colour(colour: &impl ColourBasics, white_point: &WhitePoint) -> [f64; 3] {
+    XYZ::from_colour(colour, *white_point).xyz()
+}
+
+// Colours outside the sRGB gamut are clipped
+fn rgb_from_xyz<L: LightLevel>(xyz: [f64; 3], white_point: &WhitePoint) -> RGB<L> {
+    XYZ::from((xyz, *white_point)).rgb()
+}
+
+fn lab_f(t: f64) -> f64 {
+    if t > EPSILON {
+        t.cbrt()
+    } else {
+        (KAPPA * t + 16.0) / 116.0
+    }
+}
+
+fn lab_f_inverse(f: f64) -> f64 {
+    let cube = f * f * f;
+    if cube > EPSILON {
+        cube
+    } else {
+        (116.0 * f - 16.0) / KAPPA
+    }
+}
+
+fn l_star(y: f64) -> f64 {
+    116.0 * lab_f(y) - 16.0
+}
+
+fn y_from_l_star(l: f64) -> f64 {
+    if l > KAPPA * EPSILON {
+        ((l + 16.0) / 116.0).powi(3)
+    } else {
+        l / KAPPA
+    }
+}
+
+/// CIE 1976 L*a*b* relative to a reference white.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Lab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+    pub white_point: WhitePoint,
+}
+
+impl Lab {
+    pub fn new(l: f64, a: f64, b: f64, white_point: WhitePoint) -> Self {
+        Self {
+            l,
+            a,
+            b,
+            white_point,
+        }
+    }
+
+    pub fn from_colour(colour: &impl ColourBasics, white_point: WhitePoint) -> Self {
+        let xyz = xyz_from_colour(colour, &white_point);
+        let white = white_point.xyz();
+        let [fx, fy, fz] = [0, 1, 2].map(|i| lab_f(xyz[i] / white[i]));
+        Self {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+            white_point,
+        }
+    }
+
+    pub fn xyz(&self) -> [f64; 3] {
+        let fy = (self.l + 16.0) / 116.0;
+        let fx = fy + self.a / 500.0;
+        let fz = fy - self.b / 200.0;
+        let white = self.white_point.xyz();
+        [
+            lab_f_inverse(fx) * white[0],
+            y_from_l_star(self.l) * white[1],
+            lab_f_inverse(fz) * white[2],
+        ]
+    }
+
+    /// The same colour relative to a different reference white.
+    pub fn adapted(&self, white_point: WhitePoint) -> Self {
+        if white_point == self.white_point {
+            return *self;
+        }
+        let xyz = Adaptation::Bradford.adapt(self.xyz(), &self.white_point, &white_point);
+        let white = white_point.xyz();
+        let [fx, fy, fz] = [0, 1, 2].map(|i| lab_f(xyz[i] / white[i]));
+        Self::new(
+            116.0 * fy - 16.0,
+            500.0 * (fx - fy),
+            200.0 * (fy - fz),
+            white_point,
+        )
+    }
+
+    /// C*ab
+    pub fn chroma(&self) -> f64 {
+        self.a.hypot(self.b)
+    }
+
+    /// h_ab in degrees (0 to 360)
+    pub fn hue_degrees(&self) -> f64 {
+        self.b.atan2(self.a).to_degrees().rem_euclid(360.0)
+    }
+
+    /// CIE 1976 colour difference (ΔE*ab).
+    pub fn delta_e(&self, other: &Self) -> f64 {
+        let other = other.adapted(self.white_point);
+        let dl = self.l - other.l;
+        let da = self.a - other.a;
+        let db = self.b - other.b;
+        (dl * dl + da * da + db * db).sqrt()
+    }
+
+    /// Out of (sRGB) gamut colours are clipped.
+    pub fn rgb<L: LightLevel>(&self) -> RGB<L> {
+        rgb_from_xyz(self.xyz(), &self.white_point)
+    }
+
+    pub fn hcv(&self) -> HCV {
+        HCV::from(self.rgb::<f64>())
+    }
+}
+
+impl<L: LightLevel> From<&RGB<L>> for Lab {
+    fn from(rgb: &RGB<L>) -> Self {
+        Lab::from_colour(rgb, WhitePoint::D65)
+    }
+}
+
+impl From<&HCV> for Lab {
+    fn from(hcv: &HCV) -> Self {
+        Lab::from_colour(hcv, WhitePoint::D65)
+    }
+}
+
+impl<L: LightLevel> From<Lab> for RGB<L> {
+    fn from(lab: Lab) -> Self {
+        lab.rgb::<L>()
+    }
+}
+
+impl From<Lab> for HCV {
+    fn from(lab: Lab) -> Self {
+        lab.hcv()
+    }
+}
+
+/// CIE 1976 L*u*v* relative to a reference white.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Luv {
+    pub l: f64,
+    pub u: f64,
+    pub v: f64,
+    pub white_point: WhitePoint,
+}
+
+// u' and v' chromaticity coordinates
+fn uv_prime(xyz: [f64; 3]) -> (f64, f64) {
+    let denominator = xyz[0] + 15.0 * xyz[1] + 3.0 * xyz[2];
+    if denominator == 0.0 {
+        (0.0, 0.0)
+    } else {
+        (4.0 * xyz[0] / denominator, 9.0 * xyz[1] / denominator)
+    }
+}
+
+impl Luv {
+    pub fn new(l: f64, u: f64, v: f64, white_point: WhitePoint) -> Self {
+        Self {
+            l,
+            u,
+            v,
+            white_point,
+        }
+    }
+
+    pub fn from_colour(colour: &impl ColourBasics, white_point: WhitePoint) -> Self {
+        let xyz = xyz_from_colour(colour, &white_point);
+        let white = white_point.xyz();
+        let l = l_star(xyz[1] / white[1]);
+        let (u_prime, v_prime) = uv_prime(xyz);
+        let (un_prime, vn_prime) = uv_prime(white);
+        Self {
+            l,
+            u: 13.0 * l * (u_prime - un_prime),
+            v: 13.0 * l * (v_prime - vn_prime),
+            white_point,
+        }
+    }
+
+    pub fn xyz(&self) -> [f64; 3] {
+        if self.l <= 0.0 {
+            return [0.0; 3];
+        }
+        let white = self.white_point.xyz();
+        let (un_prime, vn_prime) = uv_prime(white);
+        let u_prime = self.u / (13.0 * self.l) + un_prime;
+        let v_prime = self.v / (13.0 * self.l) + vn_prime;
+        let y = y_from_l_star(self.l) * white[1];
+        [
+            y * 9.0 * u_prime / (4.0 * v_prime),
+            y,
+            y * (12.0 - 3.0 * u_prime - 20.0 * v_prime) / (4.0 * v_prime),
+        ]
+    }
+
+    /// C*uv
+    pub fn chroma(&self) -> f64 {
+        self.u.hypot(self.v)
+    }
+
+    /// h_uv in degrees (0 to 360)
+    pub fn hue_degrees(&self) -> f64 {
+        self.v.atan2(self.u).to_degrees().rem_euclid(360.0)
+    }
+
+    /// Out of (sRGB) gamut colours are clipped.
+    pub fn rgb<L: LightLevel>(&self) -> RGB<L> {
+        rgb_from_xyz(self.xyz(), &self.white_point)
+    }
+
+    pub fn hcv(&self) -> HCV {
+        HCV::from(self.rgb::<f64>())
+    }
+}
+
+impl<L: LightLevel> From<&RGB<L>> for Luv {
+    fn from(rgb: &RGB<L>) -> Self {
+        Luv::from_colour(rgb, WhitePoint::D65)
+    }
+}
+
+impl From<&HCV> for Luv {
+    fn from(hcv: &HCV) -> Self {
+        Luv::from_colour(hcv, WhitePoint::D65)
+    }
+}
+
+impl<L: LightLevel> From<Luv> for RGB<L> {
+    fn from(luv: Luv) -> Self {
+        luv.rgb::<L>()
+    }
+}
+
+impl From<Luv> for HCV {
+    fn from(luv: Luv) -> Self {
+        luv.hcv()
+    }
+}
+
+#[cfg(test)]
+mod lab_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    fn assert_close(lhs: f64, rhs: f64, tolerance: f64) {
+        assert!((lhs - rhs).abs() < tolerance, "{lhs} != {rhs}");
+    }
+
+    #[test]
+    fn lab() {
+        let white = Lab::from(&HCV::WHITE);
+        assert_close(white.l, 100.0, 1e-3);
+        assert_close(white.chroma(), 0.0, 0.05);
+        let red = Lab::from(&RGB::<f64>::RED);
+        assert_close(red.l, 53.24, 0.05);
+        assert_close(red.a, 80.09, 0.05);
+        assert_close(red.b, 67.20, 0.05);
+        let d50_white = Lab::from_colour(&HCV::WHITE, WhitePoint::D50);
+        assert_close(d50_white.l, 100.0, 1e-3);
+        assert_close(d50_white.chroma(), 0.0, 0.05);
+        let d50_red = red.adapted(WhitePoint::D50);
+        assert_close(d50_red.delta_e(&red), 0.0, 1e-6);
+        assert_close(red.delta_e(&white), 114.53, 0.1);
+        for colour in HCV::PRIMARIES
+            .iter()
+            .chain(HCV::SECONDARIES.iter())
+            .chain(HCV::GREYS.iter())
+        {
+            let rgb = colour.rgb::<u8>();
+            let lab = Lab::from(colour);
+            assert_eq!(RGB::<u8>::rounded_from(&lab.rgb::<f64>()), rgb);
+            let lab = Lab::from_colour(colour, WhitePoint::D50);
+            assert_eq!(RGB::<u8>::rounded_from(&lab.hcv()), rgb);
+        }
+    }
+
+    #[test]
+    fn luv() {
+        let red = Luv::from(&HCV::RED);
+        assert_close(red.l, 53.24, 0.05);
+        assert_close(red.u, 175.01, 0.1);
+        assert_close(red.v, 37.76, 0.1);
+        let white = Luv::from(&HCV::WHITE);
+        assert_close(white.chroma(), 0.0, 0.05);
+        assert_eq!(Luv::from(&HCV::BLACK).rgb::<u8>(), RGB::<u8>::BLACK);
+        for colour in HCV::PRIMARIES.iter().chain(HCV::SECONDARIES.iter()) {
+            let rgb = colour.rgb::<u8>();
+            assert_eq!(RGB::<u8>::rounded_from(&Luv::from(colour).hcv()), rgb);
+        }
+    }
+}
@@ -1,32 +1,96 @@
 // Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+// Policy: nothing outside of tests may panic in a release build.  Invariants
+// are checked with `debug_assert!()` and, where a value can't be produced,
+// either an `Option`/`Result` is returned or the nearest valid value is used.
+// An `expect()` is only acceptable where the invariant is local and obvious.
+#![cfg_attr(not(test), deny(clippy::unwrap_used))]
 #[macro_use]
 extern crate serde_derive;
 
-use std::fmt::{Debug, LowerExp, LowerHex, UpperExp, UpperHex};
+use std::{
+    fmt::{Debug, LowerExp, LowerHex, UpperExp, UpperHex},
+    fs, io,
+    path::{Path, PathBuf},
+};
 
 use num_traits::{Signed, Unsigned};
 use num_traits_plus::float_plus::*;
 
 use hue::HueIfce;
 
+// Count an operation for diagnostics (see the `diagnostics` module)
+#[cfg(feature = "diagnostics")]
+macro_rules! count {
+    ($counter:ident) => {
+        crate::diagnostics::increment(crate::diagnostics::Counter::$counter)
+    };
+}
+
+#[cfg(not(feature = "diagnostics"))]
+macro_rules! count {
+    ($counter:ident) => {};
+}
+
 pub use crate::{
     attributes::{Chroma, Greyness, Value, Warmth},
     beigui::{attr_display, hue_wheel},
     fdrn::{IntoProp, Prop, UFDRNumber},
     hcv::HCV,
     hue::{angle::Angle, Hue},
+    palette::{Palette, PaletteEntry},
     rgb::RGB,
 };
 
+pub mod alpha;
+pub mod analysis;
+pub mod ansi;
+pub mod api;
 pub mod attributes;
 pub mod beigui;
+pub mod bulk;
+pub mod cmyk;
+pub mod code;
+pub mod colour_vision;
+pub mod conformance;
 pub mod debug;
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
+pub mod distance;
+pub mod dyn_colour;
 pub mod fdrn;
+pub mod gamut;
+pub mod gradient;
+pub mod harmony;
 pub mod hcv;
 pub mod hue;
+pub mod image;
+pub mod interop;
+pub mod lab;
 pub mod manipulator;
+pub mod matching;
 pub mod mixing;
+pub mod names;
+pub mod palette;
+pub mod palette_io;
+#[cfg(feature = "pigments")]
+pub mod pigments;
+pub mod preferences;
+pub mod prelude;
+pub mod progress;
+pub mod project;
+pub mod proof;
+pub mod quantise;
+pub mod ramps;
+pub mod recording;
 pub mod rgb;
+pub mod rgb_space;
+pub mod schemes;
+#[cfg(feature = "serde-human")]
+pub mod serde_human;
+pub mod standards;
+pub mod transfer;
+pub mod transform;
+pub mod xyz;
 
 pub trait Float: FloatPlus + std::iter::Sum + FloatApproxEq<Self> {}
 
@@ -212,14 +276,56 @@ pub trait ColourBasics {
     }
 }
 
+// NB: the serialised names are explicit so that they can't be changed by
+// accident (config files etc. depend on them)
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
 pub enum ScalarAttribute {
+    #[serde(rename = "Chroma", alias = "chroma")]
     Chroma,
+    #[serde(rename = "Greyness", alias = "greyness")]
     Greyness,
+    #[serde(rename = "Value", alias = "value")]
     Value,
+    #[serde(rename = "Warmth", alias = "warmth")]
     Warmth,
 }
 
+impl ScalarAttribute {
+    pub const ALL: [Self; 4] = [
+        ScalarAttribute::Chroma,
+        ScalarAttribute::Greyness,
+        ScalarAttribute::Value,
+        ScalarAttribute::Warmth,
+    ];
+
+    pub fn iter() -> impl Iterator<Item = Self> {
+        Self::ALL.into_iter()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ScalarAttributeParseError(String);
+
+impl std::fmt::Display for ScalarAttributeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown scalar attribute: {}", self.0)
+    }
+}
+
+impl std::error::Error for ScalarAttributeParseError {}
+
+impl std::str::FromStr for ScalarAttribute {
+    type Err = ScalarAttributeParseError;
+
+    /// The (case insensitive) names as displayed.
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let trimmed = string.trim();
+        Self::iter()
+            .find(|attribute| attribute.to_string().eq_ignore_ascii_case(trimmed))
+            .ok_or_else(|| ScalarAttributeParseError(string.to_string()))
+    }
+}
+
 impl std::fmt::Display for ScalarAttribute {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
@@ -261,9 +367,78 @@ impl<L: LightLevel> ColourIfce for RGB<L> {}
 
 pub trait ManipulatedColour: ColourBasics {
     // TODO: modify Manipulated colour to make it more widely applicable
+    /// Mixed with `prop` of white (which reduces chroma).
     fn lightened(&self, prop: Prop) -> Self;
+    /// Mixed with `prop` of black (which reduces chroma).
     fn darkened(&self, prop: Prop) -> Self;
+    /// Value moved `prop` of the way to white keeping the hue and chroma
+    /// (so it stops at the lightest possible value for the chroma).
+    fn lightened_keeping_chroma(&self, prop: Prop) -> Self
+    where
+        Self: From<HCV>,
+    {
+        Self::from(self.hcv().lightened_keeping_chroma(prop))
+    }
+    /// Value moved `prop` of the way to black keeping the hue and chroma
+    /// (so it stops at the darkest possible value for the chroma).
+    fn darkened_keeping_chroma(&self, prop: Prop) -> Self
+    where
+        Self: From<HCV>,
+    {
+        Self::from(self.hcv().darkened_keeping_chroma(prop))
+    }
     fn saturated(&self, prop: Prop) -> Self;
     fn greyed(&self, prop: Prop) -> Self;
     fn rotated(&self, angle: Angle) -> Self;
 }
+
+/// Write `contents` to a temporary file (`path` with ".tmp" appended) and
+/// then rename it to `path` so that a failure part way through can't
+/// destroy the previous copy.
+pub(crate) fn write_atomically(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut temp_path = path.as_os_str().to_os_string();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path)
+}
+
+#[cfg(test)]
+mod scalar_attribute_tests {
+    use super::*;
+
+    #[test]
+    fn all_parse_and_serde() {
+        assert_eq!(ScalarAttribute::iter().count(), 4);
+        for attribute in ScalarAttribute::iter() {
+            let name = attribute.to_string();
+            assert_eq!(name.parse::<ScalarAttribute>(), Ok(attribute));
+            assert_eq!(
+                name.to_uppercase().parse::<ScalarAttribute>(),
+                Ok(attribute)
+            );
+            let json = serde_json::to_string(&attribute).unwrap();
+            assert_eq!(json, format!("\"{name}\""));
+            assert_eq!(
+                serde_json::from_str::<ScalarAttribute>(&json).unwrap(),
+                attribute
+            );
+            let json = json.to_lowercase();
+            assert_eq!(
+                serde_json::from_str::<ScalarAttribute>(&json).unwrap(),
+                attribute
+            );
+        }
+        assert_eq!(
+            " warmth ".parse::<ScalarAttribute>(),
+            Ok(ScalarAttribute::Warmth)
+        );
+        assert!("Hue".parse::<ScalarAttribute>().is_err());
+        let attributes: Vec<ScalarAttribute> =
+            serde_json::from_str(r#"["Value", "chroma"]"#).unwrap();
+        assert_eq!(
+            attributes,
+            vec![ScalarAttribute::Value, ScalarAttribute::Chroma]
+        );
+    }
+}
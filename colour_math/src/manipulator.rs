@@ -8,14 +8,14 @@ use crate::{
     ColourBasics, HueConstants, LightLevel,
 };
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SetScalar {
     Clamp,
     Accommodate,
     Reject,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum SetHue {
     #[default]
     FavourChroma,
@@ -392,13 +392,15 @@ impl ColourManipulator {
             Prop::ONE => match policy {
                 SetHue::FavourChroma => self.hcv = new_hue.max_chroma_hcv(),
                 SetHue::FavourValue => {
-                    let c_prop = new_hue.max_chroma_prop_for_sum(self.hcv.sum).unwrap();
-                    let (c_prop, sum) = new_hue
-                        .adjusted_favouring_sum(self.hcv.sum, c_prop)
-                        .unwrap();
-                    self.hcv = match HCV::try_new(Some((new_hue, c_prop)), sum) {
-                        Ok(hcv) => hcv,
-                        Err(hcv) => hcv,
+                    self.hcv = match new_hue
+                        .max_chroma_prop_for_sum(self.hcv.sum)
+                        .and_then(|c_prop| new_hue.adjusted_favouring_sum(self.hcv.sum, c_prop))
+                    {
+                        Some((c_prop, sum)) => match HCV::try_new(Some((new_hue, c_prop)), sum) {
+                            Ok(hcv) => hcv,
+                            Err(hcv) => hcv,
+                        },
+                        None => new_hue.max_chroma_hcv(),
                     };
                 }
             },
@@ -434,20 +436,22 @@ impl ColourManipulator {
                             }
                         }
                         SetHue::FavourValue => {
-                            let max_c_prop = new_hue
+                            if let Some((c_prop, sum)) = new_hue
                                 .max_chroma_prop_for_sum(self.hcv.sum)
-                                .expect("0.0 < sum < 3.0");
-                            let c_prop = if self.hcv.c_prop < max_c_prop {
-                                self.hcv.c_prop
+                                .and_then(|max_c_prop| {
+                                    new_hue.adjusted_favouring_sum(
+                                        self.hcv.sum,
+                                        self.hcv.c_prop.min(max_c_prop),
+                                    )
+                                })
+                            {
+                                match HCV::try_new(Some((new_hue, c_prop)), sum) {
+                                    Ok(hcv) => hcv,
+                                    Err(hcv) => hcv,
+                                }
                             } else {
-                                max_c_prop
-                            };
-                            let (c_prop, sum) = new_hue
-                                .adjusted_favouring_sum(self.hcv.sum, c_prop)
-                                .unwrap();
-                            match HCV::try_new(Some((new_hue, c_prop)), sum) {
-                                Ok(hcv) => hcv,
-                                Err(hcv) => hcv,
+                                self.saved_hue = new_hue;
+                                HCV::new_grey((self.hcv.sum / 3).into())
                             }
                         }
                     }
@@ -0,0 +1,192 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::{
+    distance::DistanceMetric,
+    fdrn::Prop,
+    hcv::HCV,
+    lab::Lab,
+    progress::{self, Cancelled, NoProgress, Progress},
+    project::{Paint, PaintSeries},
+    ColourBasics,
+};
+
+// How many colours are ranked between progress checkpoints
+const RANKING_CHECKPOINT_INTERVAL: usize = 1024;
+
+/// Colours with their CIE Lab equivalents precalculated for repeated
+/// nearest colour searches.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ColourIndex {
+    colours: Vec<HCV>,
+    labs: Vec<Lab>,
+}
+
+impl ColourIndex {
+    pub fn new(colours: &[HCV]) -> Self {
+        Self {
+            colours: colours.to_vec(),
+            labs: colours.iter().map(Lab::from).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.colours.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colours.is_empty()
+    }
+
+    /// The indices of all the colours with their distances from `target`
+    /// ordered nearest first.
+    pub fn ranked(&self, target: &impl ColourBasics, metric: DistanceMetric) -> Vec<(usize, f64)> {
+        self.ranked_with_progress(target, metric, &NoProgress)
+            .expect("NoProgress never cancels")
+    }
+
+    pub fn ranked_with_progress(
+        &self,
+        target: &impl ColourBasics,
+        metric: DistanceMetric,
+        progress: &impl Progress,
+    ) -> Result<Vec<(usize, f64)>, Cancelled> {
+        let lab_target = Lab::from(&target.hcv());
+        let mut ranking = Vec::with_capacity(self.len());
+        for (index, (colour, lab)) in self.colours.iter().zip(self.labs.iter()).enumerate() {
+            if index % RANKING_CHECKPOINT_INTERVAL == 0 {
+                progress.checkpoint(progress::proportion(index, self.len()))?;
+            }
+            let distance = match metric {
+                DistanceMetric::Rgb => metric.distance(colour, target),
+                DistanceMetric::DeltaE76 => lab.delta_e(&lab_target),
+            };
+            ranking.push((index, distance));
+        }
+        ranking.sort_by(|lhs, rhs| lhs.1.total_cmp(&rhs.1));
+        progress.report(Prop::ONE);
+        Ok(ranking)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaintMatch {
+    pub paint: Paint,
+    pub distance: f64,
+}
+
+/// Answers "which paints are nearest to this colour" queries for a paint
+/// series.  Rankings are cached (per colour) as the same few colours tend
+/// to be asked about repeatedly e.g. while a user hovers over a target.
+#[derive(Debug)]
+pub struct PaintMatcher {
+    series: PaintSeries,
+    index: ColourIndex,
+    metric: DistanceMetric,
+    cache: RefCell<HashMap<[Prop; 3], Vec<(usize, f64)>>>,
+}
+
+impl PaintMatcher {
+    /// Maximum number of colours whose rankings are kept.
+    pub const CACHE_CAPACITY: usize = 256;
+
+    pub fn new(series: PaintSeries, metric: DistanceMetric) -> Self {
+        let colours: Vec<HCV> = series.paints().iter().map(|paint| paint.colour()).collect();
+        Self {
+            index: ColourIndex::new(&colours),
+            series,
+            metric,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn series(&self) -> &PaintSeries {
+        &self.series
+    }
+
+    pub fn metric(&self) -> DistanceMetric {
+        self.metric
+    }
+
+    pub fn set_metric(&mut self, metric: DistanceMetric) {
+        if metric != self.metric {
+            self.metric = metric;
+            self.cache.borrow_mut().clear();
+        }
+    }
+
+    /// The number of colours whose rankings are currently cached.
+    pub fn cached(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
+    /// The (at most) `count` paints nearest to `colour` ordered nearest
+    /// first.
+    pub fn nearest(&self, colour: &impl ColourBasics, count: usize) -> Vec<PaintMatch> {
+        let hcv = colour.hcv();
+        let key: [Prop; 3] = hcv.into();
+        let mut cache = self.cache.borrow_mut();
+        if cache.len() >= Self::CACHE_CAPACITY && !cache.contains_key(&key) {
+            cache.clear();
+        }
+        cache
+            .entry(key)
+            .or_insert_with(|| self.index.ranked(&hcv, self.metric))
+            .iter()
+            .take(count)
+            .map(|(index, distance)| PaintMatch {
+                paint: self.series.paints()[*index].clone(),
+                distance: *distance,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod matching_tests {
+    use super::*;
+    use crate::{progress::CancelToken, HueConstants, RGBConstants};
+
+    #[test]
+    fn colour_index() {
+        let index = ColourIndex::new(&[HCV::BLACK, HCV::RED, HCV::WHITE]);
+        for metric in [DistanceMetric::Rgb, DistanceMetric::DeltaE76] {
+            let ranking = index.ranked(&HCV::YELLOW, metric);
+            assert_eq!(ranking.len(), 3);
+            assert!(ranking.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+            assert_eq!(index.ranked(&HCV::RED, metric)[0], (1, 0.0));
+            let token = CancelToken::new();
+            token.cancel();
+            assert_eq!(
+                index.ranked_with_progress(&HCV::RED, metric, &token),
+                Err(Cancelled)
+            );
+        }
+    }
+
+    #[cfg(feature = "pigments")]
+    #[test]
+    fn paint_matcher() {
+        use crate::pigments;
+        let mut matcher =
+            PaintMatcher::new(pigments::artist_pigments_series(), DistanceMetric::DeltaE76);
+        let matches = matcher.nearest(&pigments::ULTRAMARINE_BLUE.hcv(), 3);
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[0].paint.name(), "Ultramarine Blue");
+        assert_eq!(matches[0].distance, 0.0);
+        assert_eq!(matcher.cached(), 1);
+        assert_eq!(
+            matcher.nearest(&pigments::ULTRAMARINE_BLUE.hcv(), 3),
+            matches
+        );
+        assert_eq!(matcher.cached(), 1);
+        assert_eq!(
+            matcher.nearest(&HCV::WHITE, 1000).len(),
+            matcher.series().paints().len()
+        );
+        matcher.set_metric(DistanceMetric::Rgb);
+        assert_eq!(matcher.cached(), 0);
+        let matches = matcher.nearest(&HCV::WHITE, 1);
+        assert_eq!(matches[0].paint.name(), "Titanium White");
+    }
+}
@@ -3,16 +3,51 @@
 use crate::{
     fdrn::{Prop, UFDRNumber},
     hcv::HCV,
+    progress::{self, Cancelled, NoProgress, Progress},
     rgb::RGB,
     ColourBasics, LightLevel,
 };
 
+/// How the colours of the components of a mixture combine.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MixingModel {
+    /// Weighted average of the RGB components (like mixing light or
+    /// dithering rather than pigments).
+    #[default]
+    WeightedAverage,
+    /// Weighted geometric mean of the RGB components (reflectances) which
+    /// models the light being filtered by each of the components e.g.
+    /// transparent glazes.
+    Multiplicative,
+    /// Weighted average of the single constant Kubelka-Munk absorption to
+    /// scattering ratios (K/S) of the RGB components which behaves more like
+    /// opaque paints (e.g. a little black goes a long way).
+    KubelkaMunk,
+}
+
+// Avoid infinities (from logs and K/S) for zero reflectance
+const MIN_REFLECTANCE: f64 = 1.0e-4;
+
+// Kubelka-Munk K/S for a reflectance
+fn k_over_s(reflectance: f64) -> f64 {
+    let reflectance = reflectance.max(MIN_REFLECTANCE);
+    (1.0 - reflectance).powi(2) / (2.0 * reflectance)
+}
+
+// The reflectance for a Kubelka-Munk K/S
+fn reflectance(k_over_s: f64) -> f64 {
+    1.0 + k_over_s - (k_over_s.powi(2) + 2.0 * k_over_s).sqrt()
+}
+
 #[derive(Default, Debug)]
 pub struct SubtractiveMixer {
     red: UFDRNumber,
     green: UFDRNumber,
     blue: UFDRNumber,
+    log_sums: [f64; 3],
+    k_over_s_sums: [f64; 3],
     total_parts: u64,
+    model: MixingModel,
 }
 
 impl SubtractiveMixer {
@@ -20,19 +55,52 @@ impl SubtractiveMixer {
         Self::default()
     }
 
+    pub fn with_model(model: MixingModel) -> Self {
+        Self {
+            model,
+            ..Self::default()
+        }
+    }
+
+    pub fn model(&self) -> MixingModel {
+        self.model
+    }
+
+    /// All models are kept up to date so the model can be changed at any
+    /// time (e.g. to compare the results).
+    pub fn set_model(&mut self, model: MixingModel) {
+        self.model = model
+    }
+
     pub fn add(&mut self, colour: &impl ColourBasics, parts: u64) {
         let [red, green, blue] = <[Prop; 3]>::from(colour.hcv());
         self.red = self.red + UFDRNumber(red.0 as u128 * parts as u128);
         self.green = self.green + UFDRNumber(green.0 as u128 * parts as u128);
         self.blue = self.blue + UFDRNumber(blue.0 as u128 * parts as u128);
+        for (i, component) in [red, green, blue].iter().enumerate() {
+            let component = f64::from(*component);
+            self.log_sums[i] += component.max(MIN_REFLECTANCE).ln() * parts as f64;
+            self.k_over_s_sums[i] += k_over_s(component) * parts as f64;
+        }
         self.total_parts += parts;
     }
 
     pub fn mixed_colour(&self) -> Option<HCV> {
         if self.total_parts > 0 {
-            let red = Prop((self.red.0 / self.total_parts as u128) as u64);
-            let green = Prop((self.green.0 / self.total_parts as u128) as u64);
-            let blue = Prop((self.blue.0 / self.total_parts as u128) as u64);
+            let total_parts = self.total_parts as f64;
+            let [red, green, blue] = match self.model {
+                MixingModel::WeightedAverage => [
+                    Prop((self.red.0 / self.total_parts as u128) as u64),
+                    Prop((self.green.0 / self.total_parts as u128) as u64),
+                    Prop((self.blue.0 / self.total_parts as u128) as u64),
+                ],
+                MixingModel::Multiplicative => self
+                    .log_sums
+                    .map(|log_sum| Prop::from((log_sum / total_parts).exp().clamp(0.0, 1.0))),
+                MixingModel::KubelkaMunk => self.k_over_s_sums.map(|k_over_s_sum| {
+                    Prop::from(reflectance(k_over_s_sum / total_parts).clamp(0.0, 1.0))
+                }),
+            };
             Some(HCV::from(&[red, green, blue]))
         } else {
             None
@@ -43,18 +111,299 @@ impl SubtractiveMixer {
         Some(self.mixed_colour()?.into())
     }
 
+    /// Empty the mixer (keeping the model).
     pub fn reset(&mut self) {
         self.red = UFDRNumber::ZERO;
         self.green = UFDRNumber::ZERO;
         self.blue = UFDRNumber::ZERO;
+        self.log_sums = [0.0; 3];
+        self.k_over_s_sums = [0.0; 3];
         self.total_parts = 0;
     }
 }
 
+// The mixture of the (RGB, weight) contributions according to `model`
+fn mix(contributions: &[([f64; 3], f64)], model: MixingModel) -> Option<HCV> {
+    let total: f64 = contributions.iter().map(|(_, weight)| weight).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let rgb = [0, 1, 2].map(|i| {
+        let component = |rgb: &[f64; 3]| rgb[i].max(MIN_REFLECTANCE);
+        let mixed = match model {
+            MixingModel::WeightedAverage => {
+                contributions
+                    .iter()
+                    .map(|(rgb, weight)| rgb[i] * weight)
+                    .sum::<f64>()
+                    / total
+            }
+            MixingModel::Multiplicative => (contributions
+                .iter()
+                .map(|(rgb, weight)| component(rgb).ln() * weight)
+                .sum::<f64>()
+                / total)
+                .exp(),
+            MixingModel::KubelkaMunk => reflectance(
+                contributions
+                    .iter()
+                    .map(|(rgb, weight)| k_over_s(rgb[i]) * weight)
+                    .sum::<f64>()
+                    / total,
+            ),
+        };
+        mixed.clamp(0.0, 1.0)
+    });
+    Some(HCV::from(RGB::<f64>::from(rgb)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjustment {
+    More,
+    Less,
+}
+
+/// A change to one contributor of a `Mixer` that brings the mixture closer
+/// to a target and the errors that would result.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Suggestion {
+    /// Index of the contributor in `Mixer::contributions()`
+    pub contributor: usize,
+    pub adjustment: Adjustment,
+    pub parts: UFDRNumber,
+    pub errors: TargetErrors,
+}
+
+/// A mixture of colours in (not necessarily whole) parts which can suggest
+/// how to change the recipe to get closer to a target colour.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Mixer {
+    contributions: Vec<(HCV, UFDRNumber)>,
+    model: MixingModel,
+}
+
+impl Mixer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_model(model: MixingModel) -> Self {
+        Self {
+            contributions: vec![],
+            model,
+        }
+    }
+
+    pub fn model(&self) -> MixingModel {
+        self.model
+    }
+
+    pub fn set_model(&mut self, model: MixingModel) {
+        self.model = model
+    }
+
+    pub fn contributions(&self) -> &[(HCV, UFDRNumber)] {
+        &self.contributions
+    }
+
+    pub fn total_parts(&self) -> UFDRNumber {
+        self.contributions
+            .iter()
+            .fold(UFDRNumber::ZERO, |total, (_, parts)| total + *parts)
+    }
+
+    /// Add `parts` of `colour` (to the existing contribution if `colour` is
+    /// already in the mixture) returning the index of its contribution.
+    pub fn add(&mut self, colour: &impl ColourBasics, parts: UFDRNumber) -> usize {
+        let colour = colour.hcv();
+        match self.contributions.iter().position(|(c, _)| *c == colour) {
+            Some(index) => {
+                self.contributions[index].1 = self.contributions[index].1 + parts;
+                index
+            }
+            None => {
+                self.contributions.push((colour, parts));
+                self.contributions.len() - 1
+            }
+        }
+    }
+
+    /// Out of range indices are ignored.
+    pub fn set_parts(&mut self, index: usize, parts: UFDRNumber) {
+        if let Some(contribution) = self.contributions.get_mut(index) {
+            contribution.1 = parts;
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<(HCV, UFDRNumber)> {
+        if index < self.contributions.len() {
+            Some(self.contributions.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.contributions.clear()
+    }
+
+    fn mixed_colour_for(&self, contributions: &[(HCV, UFDRNumber)]) -> Option<HCV> {
+        let contributions: Vec<([f64; 3], f64)> = contributions
+            .iter()
+            .map(|(colour, parts)| {
+                let rgb = colour.rgb::<f64>();
+                ([rgb[0], rgb[1], rgb[2]], f64::from(*parts))
+            })
+            .collect();
+        mix(&contributions, self.model)
+    }
+
+    /// The running mixture (`None` if there are no parts).
+    pub fn mixed_colour(&self) -> Option<HCV> {
+        self.mixed_colour_for(&self.contributions)
+    }
+
+    /// Adjustments of `step` (as a proportion of the total parts) to each
+    /// contributor that would bring the mixture closer to `target` ordered
+    /// most effective first.
+    pub fn suggestions(&self, target: &impl ColourBasics, step: Prop) -> Vec<Suggestion> {
+        self.suggestions_with_progress(target, step, &NoProgress)
+            .expect("NoProgress never cancels")
+    }
+
+    pub fn suggestions_with_progress(
+        &self,
+        target: &impl ColourBasics,
+        step: Prop,
+        progress: &impl Progress,
+    ) -> Result<Vec<Suggestion>, Cancelled> {
+        let current = match self.mixed_colour() {
+            Some(mixed_colour) => TargetErrors::new(&mixed_colour, target).magnitude(),
+            None => return Ok(vec![]),
+        };
+        let delta = self.total_parts() * step;
+        let mut suggestions = vec![];
+        for (contributor, (_, parts)) in self.contributions.iter().enumerate() {
+            progress.checkpoint(progress::proportion(contributor, self.contributions.len()))?;
+            for adjustment in [Adjustment::More, Adjustment::Less] {
+                let mut contributions = self.contributions.clone();
+                let change = match adjustment {
+                    Adjustment::More => delta,
+                    Adjustment::Less => delta.min(*parts),
+                };
+                if change == UFDRNumber::ZERO {
+                    continue;
+                }
+                contributions[contributor].1 = match adjustment {
+                    Adjustment::More => *parts + change,
+                    Adjustment::Less => *parts - change,
+                };
+                if let Some(mixed_colour) = self.mixed_colour_for(&contributions) {
+                    let errors = TargetErrors::new(&mixed_colour, target);
+                    if errors.magnitude() < current {
+                        suggestions.push(Suggestion {
+                            contributor,
+                            adjustment,
+                            parts: change,
+                            errors,
+                        });
+                    }
+                }
+            }
+        }
+        suggestions.sort_by(|lhs, rhs| lhs.errors.magnitude().total_cmp(&rhs.errors.magnitude()));
+        progress.report(Prop::ONE);
+        Ok(suggestions)
+    }
+}
+
+/// The differences (mixed minus target) between a mixed colour and the
+/// colour it is meant to match.  Value and chroma are proportions and hue
+/// is in degrees (`None` if either colour is grey).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TargetErrors {
+    pub value: f64,
+    pub chroma: f64,
+    pub hue: Option<f64>,
+}
+
+impl TargetErrors {
+    pub fn new(mixed: &impl ColourBasics, target: &impl ColourBasics) -> Self {
+        let value = f64::from(Prop::from(mixed.value())) - f64::from(Prop::from(target.value()));
+        let chroma = f64::from(mixed.chroma_prop()) - f64::from(target.chroma_prop());
+        let hue = match (mixed.hue_angle(), target.hue_angle()) {
+            (Some(mixed_angle), Some(target_angle)) => Some(f64::from(mixed_angle - target_angle)),
+            _ => None,
+        };
+        Self { value, chroma, hue }
+    }
+
+    /// The errors combined into a single figure (with a hue error of 180
+    /// degrees counting the same as a value error of one).
+    pub fn magnitude(&self) -> f64 {
+        let hue = self.hue.unwrap_or(0.0) / 180.0;
+        (self.value.powi(2) + self.chroma.powi(2) + hue.powi(2)).sqrt()
+    }
+}
+
+/// A mixture being made to match a target colour with a record of how far
+/// off the target it was after each addition.
+#[derive(Debug)]
+pub struct MixingSession {
+    mixer: SubtractiveMixer,
+    target: HCV,
+    history: Vec<TargetErrors>,
+}
+
+impl MixingSession {
+    pub fn new(target: &impl ColourBasics) -> Self {
+        Self {
+            mixer: SubtractiveMixer::new(),
+            target: target.hcv(),
+            history: vec![],
+        }
+    }
+
+    pub fn target(&self) -> HCV {
+        self.target
+    }
+
+    pub fn add(&mut self, colour: &impl ColourBasics, parts: u64) {
+        self.mixer.add(colour, parts);
+        if let Some(mixed_colour) = self.mixer.mixed_colour() {
+            self.history
+                .push(TargetErrors::new(&mixed_colour, &self.target));
+        }
+    }
+
+    pub fn mixed_colour(&self) -> Option<HCV> {
+        self.mixer.mixed_colour()
+    }
+
+    /// Oldest first.
+    pub fn history(&self) -> &[TargetErrors] {
+        &self.history
+    }
+
+    /// Whether the most recent addition brought the mixture closer to the
+    /// target (`None` until there have been two additions).
+    pub fn is_converging(&self) -> Option<bool> {
+        match self.history.as_slice() {
+            [.., previous, latest] => Some(latest.magnitude() < previous.magnitude()),
+            _ => None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.mixer.reset();
+        self.history.clear();
+    }
+}
+
 #[cfg(test)]
 mod mixing_tests {
     use super::*;
-    use crate::HueConstants;
+    use crate::{progress::CancelToken, HueConstants};
 
     #[test]
     fn subtractive_mixing() {
@@ -69,4 +418,94 @@ mod mixing_tests {
         assert_eq!(subtractve_mixer.mixed_colour(), Some(expected.into()));
         assert_eq!(subtractve_mixer.mixed_rgb::<u16>(), Some(expected));
     }
+
+    #[test]
+    fn mixing_models() {
+        let mut mixer = SubtractiveMixer::with_model(MixingModel::Multiplicative);
+        mixer.add(&HCV::YELLOW, 1);
+        mixer.add(&HCV::CYAN, 1);
+        // yellow and cyan filters only let green through
+        let mixed = RGB::<u8>::rounded_from(&mixer.mixed_rgb::<f64>().unwrap());
+        assert!(mixed[0] < 5 && mixed[2] < 5);
+        assert_eq!(mixed[1], 255);
+        mixer.set_model(MixingModel::WeightedAverage);
+        assert_eq!(
+            mixer.mixed_colour(),
+            Some(HCV::from(&[Prop::HALF, Prop::ONE, Prop::HALF]))
+        );
+        for model in [MixingModel::Multiplicative, MixingModel::KubelkaMunk] {
+            let mut mixer = SubtractiveMixer::with_model(model);
+            mixer.add(&HCV::RED, 3);
+            let mixed = mixer.mixed_rgb::<f64>().unwrap();
+            assert!(mixed.approx_eq(&RGB::<f64>::RED, Some(Prop::from(0.001))));
+            // a little black darkens much more than it would by averaging
+            mixer.add(&HCV::BLACK, 1);
+            let value = f64::from(Prop::from(mixer.mixed_colour().unwrap().value()));
+            assert!(value < 0.25 / 2.0);
+            mixer.reset();
+            assert_eq!(mixer.mixed_colour(), None);
+            assert_eq!(mixer.model(), model);
+        }
+    }
+
+    #[test]
+    fn mixer() {
+        let mut mixer = Mixer::new();
+        assert_eq!(mixer.mixed_colour(), None);
+        let red = mixer.add(&HCV::RED, UFDRNumber::ONE);
+        let yellow = mixer.add(&HCV::YELLOW, UFDRNumber::ONE);
+        assert_eq!(mixer.add(&HCV::RED, UFDRNumber::ONE), red);
+        assert_eq!(mixer.total_parts(), UFDRNumber::ONE * 3);
+        mixer.set_parts(red, UFDRNumber::ONE);
+        let orange = RGB::<f64>::from([1.0, 0.5, 0.0]);
+        let mixed = mixer.mixed_colour().unwrap().rgb::<f64>();
+        assert!(mixed.approx_eq(&orange, Some(Prop::from(0.001))));
+        let target = HCV::from(&[Prop::ONE, Prop::from(0.25), Prop::ZERO]);
+        let suggestions = mixer.suggestions(&target, Prop::from(0.1));
+        assert!(!suggestions.is_empty());
+        for suggestion in suggestions.iter() {
+            match suggestion.adjustment {
+                Adjustment::More => assert_eq!(suggestion.contributor, red),
+                Adjustment::Less => assert_eq!(suggestion.contributor, yellow),
+            }
+        }
+        assert!(mixer
+            .suggestions(&mixer.mixed_colour().unwrap(), Prop::from(0.1))
+            .is_empty());
+        let token = CancelToken::new();
+        token.cancel();
+        assert_eq!(
+            mixer.suggestions_with_progress(&target, Prop::from(0.1), &token),
+            Err(Cancelled)
+        );
+        assert_eq!(mixer.remove(yellow), Some((HCV::YELLOW, UFDRNumber::ONE)));
+        // stale indices
+        assert_eq!(mixer.remove(yellow), None);
+        mixer.set_parts(yellow, UFDRNumber::ONE);
+        assert_eq!(mixer.contributions().len(), 1);
+        mixer.set_model(MixingModel::KubelkaMunk);
+        let mixed = mixer.mixed_colour().unwrap().rgb::<f64>();
+        assert!(mixed.approx_eq(&RGB::<f64>::RED, Some(Prop::from(0.001))));
+    }
+
+    #[test]
+    fn mixing_session() {
+        let target = HCV::from(&[Prop::ONE, Prop::HALF, Prop::ZERO]);
+        let mut session = MixingSession::new(&target);
+        assert!(session.history().is_empty());
+        session.add(&HCV::RED, 1);
+        assert_eq!(session.is_converging(), None);
+        let errors = session.history()[0];
+        assert!(errors.value < 0.0);
+        assert!(errors.hue.unwrap() < 0.0);
+        session.add(&HCV::YELLOW, 1);
+        assert_eq!(session.is_converging(), Some(true));
+        assert!(session.history()[1].magnitude() < 0.01);
+        session.add(&HCV::BLUE, 2);
+        assert_eq!(session.is_converging(), Some(false));
+        assert_eq!(session.history().len(), 3);
+        session.reset();
+        assert!(session.history().is_empty());
+        assert_eq!(session.mixed_colour(), None);
+    }
 }
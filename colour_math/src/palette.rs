@@ -0,0 +1,347 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    slice::Iter,
+};
+
+use regex::Regex;
+
+use crate::{
+    beigui::hue_wheel::{ColouredShape, MakeColouredShape, Shape},
+    hcv::HCV,
+    hue::angle::Angle,
+    rgb::RGB,
+    ColourBasics, LightLevel,
+};
+
+pub mod manager;
+pub mod small;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    name: String,
+    colour: HCV,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl PaletteEntry {
+    pub fn new(name: &str, colour: &impl ColourBasics) -> Self {
+        Self {
+            name: name.to_string(),
+            colour: colour.hcv(),
+            tags: vec![],
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string()
+    }
+
+    pub fn colour(&self) -> HCV {
+        self.colour
+    }
+
+    pub fn set_colour(&mut self, colour: &impl ColourBasics) {
+        self.colour = colour.hcv()
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Returns false if the entry already had the tag.
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        if self.has_tag(tag) {
+            false
+        } else {
+            self.tags.push(tag.to_string());
+            true
+        }
+    }
+
+    /// Returns false if the entry didn't have the tag.
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let len = self.tags.len();
+        self.tags.retain(|t| t != tag);
+        self.tags.len() != len
+    }
+}
+
+impl MakeColouredShape for PaletteEntry {
+    fn coloured_shape(&self) -> ColouredShape {
+        let tooltip_text = format!("{}: {}", self.name, self.colour.pango_string());
+        ColouredShape::new(&self.colour, &self.name, &tooltip_text, Shape::Circle)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Palette {
+    name: String,
+    entries: Vec<PaletteEntry>,
+}
+
+impl Palette {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            entries: vec![],
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, PaletteEntry> {
+        self.entries.iter()
+    }
+
+    pub fn entries(&self) -> &[PaletteEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PaletteEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    pub fn push(&mut self, entry: PaletteEntry) {
+        self.entries.push(entry)
+    }
+
+    pub fn add_colour(&mut self, name: &str, colour: &impl ColourBasics) {
+        self.entries.push(PaletteEntry::new(name, colour))
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<PaletteEntry> {
+        let index = self.entries.iter().position(|e| e.name == name)?;
+        Some(self.entries.remove(index))
+    }
+
+    pub fn colours(&self) -> Vec<HCV> {
+        self.entries.iter().map(|e| e.colour).collect()
+    }
+
+    pub fn rgbs<L: LightLevel>(&self) -> Vec<RGB<L>> {
+        self.entries.iter().map(|e| e.colour.rgb::<L>()).collect()
+    }
+
+    /// The indices of the entries with `tag`.
+    pub fn tagged(&self, tag: &str) -> Vec<usize> {
+        (0..self.entries.len())
+            .filter(|index| self.entries[*index].has_tag(tag))
+            .collect()
+    }
+
+    // The selected entries (ignoring indices that are out of range).
+    fn selected_mut<'a>(
+        &'a mut self,
+        selection: &'a [usize],
+    ) -> impl Iterator<Item = &'a mut PaletteEntry> + 'a {
+        self.entries
+            .iter_mut()
+            .enumerate()
+            .filter(move |(index, _)| selection.contains(index))
+            .map(|(_, entry)| entry)
+    }
+
+    /// Replace all matches of `regex` in the names of the selected entries
+    /// with `replacement` (which may refer to capture groups e.g. "$1")
+    /// returning the number of names that changed.
+    pub fn rename_matching(
+        &mut self,
+        selection: &[usize],
+        regex: &Regex,
+        replacement: &str,
+    ) -> usize {
+        let mut count = 0;
+        for entry in self.selected_mut(selection) {
+            let name = regex.replace_all(&entry.name, replacement).to_string();
+            if name != entry.name {
+                entry.name = name;
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Prefix the names of the selected entries with `prefix` (unless
+    /// they already start with it) returning the number changed.
+    pub fn prefix_names(&mut self, selection: &[usize], prefix: &str) -> usize {
+        let mut count = 0;
+        for entry in self.selected_mut(selection) {
+            if !entry.name.starts_with(prefix) {
+                entry.name.insert_str(0, prefix);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Returns the number of selected entries that didn't already have `tag`.
+    pub fn add_tag(&mut self, selection: &[usize], tag: &str) -> usize {
+        self.selected_mut(selection)
+            .map(|entry| entry.add_tag(tag))
+            .filter(|changed| *changed)
+            .count()
+    }
+
+    /// Returns the number of selected entries that had `tag`.
+    pub fn remove_tag(&mut self, selection: &[usize], tag: &str) -> usize {
+        self.selected_mut(selection)
+            .map(|entry| entry.remove_tag(tag))
+            .filter(|changed| *changed)
+            .count()
+    }
+
+    pub fn coloured_shapes(&self) -> Vec<ColouredShape> {
+        self.entries.iter().map(|e| e.coloured_shape()).collect()
+    }
+
+    /// A hash of the palette's colours (ignoring names and order) that is
+    /// unchanged if all of the colours are rotated by the same angle.  Hue
+    /// differences are quantised to whole degrees and chroma and value to
+    /// hundredths so that rounding during rotation doesn't matter.
+    pub fn rotation_invariant_hash(&self) -> u64 {
+        let quantised = |prop: f64| (prop * 100.0).round() as i64;
+        let angles: Vec<Angle> = self
+            .entries
+            .iter()
+            .filter_map(|e| e.colour.hue_angle())
+            .collect();
+        let mut hue_diffs: Vec<i64> = vec![];
+        for (i, angle) in angles.iter().enumerate() {
+            for other in angles[i + 1..].iter() {
+                hue_diffs.push(f64::from(*angle - *other).abs().round() as i64);
+            }
+        }
+        hue_diffs.sort_unstable();
+        let mut chroma_values: Vec<(bool, i64, i64)> = self
+            .entries
+            .iter()
+            .map(|e| {
+                (
+                    e.colour.is_grey(),
+                    quantised(f64::from(e.colour.chroma_prop())),
+                    quantised(f64::from(e.colour.value())),
+                )
+            })
+            .collect();
+        chroma_values.sort_unstable();
+        let mut hasher = DefaultHasher::new();
+        hue_diffs.hash(&mut hasher);
+        chroma_values.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a Palette {
+    type Item = &'a PaletteEntry;
+    type IntoIter = Iter<'a, PaletteEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+impl FromIterator<PaletteEntry> for Palette {
+    fn from_iter<I: IntoIterator<Item = PaletteEntry>>(iter: I) -> Self {
+        Self {
+            name: String::new(),
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod palette_tests {
+    use super::*;
+    use crate::{fdrn::UFDRNumber, hue::Hue, HueConstants, ManipulatedColour, Prop};
+
+    #[test]
+    fn batch_edits() {
+        let mut palette: Palette = ["Colour 1", "Colour 2", "Colour 3", "Other"]
+            .iter()
+            .map(|name| PaletteEntry::new(name, &HCV::RED))
+            .collect();
+        let regex = Regex::new(r"^Colour (\d+)$").unwrap();
+        assert_eq!(palette.rename_matching(&[0, 1, 3, 99], &regex, "Red $1"), 2);
+        let names: Vec<&str> = palette.iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["Red 1", "Red 2", "Colour 3", "Other"]);
+        assert_eq!(palette.prefix_names(&[0, 3], "Red "), 1);
+        assert_eq!(palette.entries()[3].name(), "Red Other");
+        assert_eq!(palette.add_tag(&[0, 2], "warm"), 2);
+        assert_eq!(palette.add_tag(&[0, 1], "warm"), 1);
+        assert_eq!(palette.tagged("warm"), vec![0, 1, 2]);
+        assert_eq!(palette.remove_tag(&[1, 3], "warm"), 1);
+        assert_eq!(palette.tagged("warm"), vec![0, 2]);
+        assert_eq!(palette.entries()[0].tags(), &["warm".to_string()]);
+        // tags are optional in saved palettes
+        let entry: PaletteEntry = serde_json::from_str(&format!(
+            r#"{{"name":"old","colour":{}}}"#,
+            serde_json::to_string(&HCV::RED).unwrap()
+        ))
+        .unwrap();
+        assert!(entry.tags().is_empty());
+    }
+
+    #[test]
+    fn rotation_invariant_hash() {
+        let colour = |degrees: i16| {
+            HCV::new_approx(
+                Some(Hue::from(Angle::from(degrees))),
+                UFDRNumber::from(1.5),
+                Prop::from(0.3),
+            )
+        };
+        let grey = PaletteEntry::new("grey", &HCV::new_grey(crate::Value::ONE / 2));
+        let palette: Palette = [0, 50, 130]
+            .iter()
+            .map(|degrees| PaletteEntry::new("", &colour(*degrees)))
+            .chain(std::iter::once(grey.clone()))
+            .collect();
+        let rotated: Palette = palette
+            .iter()
+            .rev()
+            .map(|e| PaletteEntry::new("renamed", &e.colour().rotated(Angle::from(40_i16))))
+            .collect();
+        assert_eq!(
+            palette.rotation_invariant_hash(),
+            rotated.rotation_invariant_hash()
+        );
+        let different: Palette = [0, 60, 130]
+            .iter()
+            .map(|degrees| PaletteEntry::new("", &colour(*degrees)))
+            .chain(std::iter::once(grey))
+            .collect();
+        assert_ne!(
+            palette.rotation_invariant_hash(),
+            different.rotation_invariant_hash()
+        );
+        assert_ne!(
+            Palette::from_iter(vec![PaletteEntry::new("", &HCV::RED)]).rotation_invariant_hash(),
+            palette.rotation_invariant_hash()
+        );
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::path::{Path, PathBuf};
+
+use crate::{
+    palette::Palette,
+    palette_io::{read_palette_and_format, write_palette, PaletteFormat, PaletteIoError},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PaletteId(u64);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteEvent {
+    Opened(PaletteId),
+    Changed(PaletteId),
+    DirtyChanged(PaletteId, bool),
+    PathChanged(PaletteId),
+    Saved(PaletteId),
+    Closed(PaletteId),
+}
+
+type EventCallback = Box<dyn Fn(&PaletteEvent)>;
+
+struct Document {
+    id: PaletteId,
+    palette: Palette,
+    path: Option<PathBuf>,
+    format: PaletteFormat,
+    dirty: bool,
+}
+
+/// Keeps track of the palettes open in an application, which file (if
+/// any) each is associated with and whether they have unsaved changes.
+#[derive(Default)]
+pub struct PaletteManager {
+    documents: Vec<Document>,
+    next_id: u64,
+    callbacks: Vec<EventCallback>,
+}
+
+impl PaletteManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// In the order that they were opened.
+    pub fn ids(&self) -> Vec<PaletteId> {
+        self.documents.iter().map(|document| document.id).collect()
+    }
+
+    fn document(&self, id: PaletteId) -> Option<&Document> {
+        self.documents.iter().find(|document| document.id == id)
+    }
+
+    fn document_mut(&mut self, id: PaletteId) -> Option<&mut Document> {
+        self.documents.iter_mut().find(|document| document.id == id)
+    }
+
+    fn notify(&self, event: PaletteEvent) {
+        for callback in self.callbacks.iter() {
+            callback(&event)
+        }
+    }
+
+    pub fn connect_event<F: Fn(&PaletteEvent) + 'static>(&mut self, callback: F) {
+        self.callbacks.push(Box::new(callback))
+    }
+
+    /// Start managing `palette` (e.g. a new empty palette or one obtained
+    /// from somewhere other than a file).  It will be saved in the format
+    /// implied by `path`'s extension.
+    pub fn add(&mut self, palette: Palette, path: Option<&Path>) -> PaletteId {
+        let format = path.map_or(PaletteFormat::Session, PaletteFormat::for_path);
+        self.add_with_format(palette, path, format)
+    }
+
+    fn add_with_format(
+        &mut self,
+        palette: Palette,
+        path: Option<&Path>,
+        format: PaletteFormat,
+    ) -> PaletteId {
+        let id = PaletteId(self.next_id);
+        self.next_id += 1;
+        self.documents.push(Document {
+            id,
+            palette,
+            path: path.map(|path| path.to_path_buf()),
+            format,
+            dirty: false,
+        });
+        self.notify(PaletteEvent::Opened(id));
+        id
+    }
+
+    /// Open the palette in the file at `path` unless it is already open.
+    pub fn open(&mut self, path: &Path) -> Result<PaletteId, PaletteIoError> {
+        if let Some(id) = self.find_by_path(path) {
+            Ok(id)
+        } else {
+            let (palette, format) = read_palette_and_format(path)?;
+            Ok(self.add_with_format(palette, Some(path), format))
+        }
+    }
+
+    pub fn find_by_path(&self, path: &Path) -> Option<PaletteId> {
+        self.documents
+            .iter()
+            .find(|document| document.path.as_deref() == Some(path))
+            .map(|document| document.id)
+    }
+
+    pub fn palette(&self, id: PaletteId) -> Option<&Palette> {
+        self.document(id).map(|document| &document.palette)
+    }
+
+    /// Apply `modify` to the palette and mark it as having unsaved changes.
+    pub fn modify<R, F: FnOnce(&mut Palette) -> R>(
+        &mut self,
+        id: PaletteId,
+        modify: F,
+    ) -> Option<R> {
+        let document = self.document_mut(id)?;
+        let result = modify(&mut document.palette);
+        let was_dirty = document.dirty;
+        document.dirty = true;
+        self.notify(PaletteEvent::Changed(id));
+        if !was_dirty {
+            self.notify(PaletteEvent::DirtyChanged(id, true));
+        }
+        Some(result)
+    }
+
+    pub fn path(&self, id: PaletteId) -> Option<&Path> {
+        self.document(id)?.path.as_deref()
+    }
+
+    pub fn format(&self, id: PaletteId) -> Option<PaletteFormat> {
+        self.document(id).map(|document| document.format)
+    }
+
+    /// Associate the palette with `path` (which also determines the format
+    /// that it will be saved in).
+    pub fn set_path(&mut self, id: PaletteId, path: &Path) {
+        if let Some(document) = self.document_mut(id) {
+            document.path = Some(path.to_path_buf());
+            document.format = PaletteFormat::for_path(path);
+            self.notify(PaletteEvent::PathChanged(id));
+        }
+    }
+
+    pub fn is_dirty(&self, id: PaletteId) -> bool {
+        self.document(id).map_or(false, |document| document.dirty)
+    }
+
+    pub fn dirty_ids(&self) -> Vec<PaletteId> {
+        self.documents
+            .iter()
+            .filter(|document| document.dirty)
+            .map(|document| document.id)
+            .collect()
+    }
+
+    pub fn any_dirty(&self) -> bool {
+        self.documents.iter().any(|document| document.dirty)
+    }
+
+    /// Save the palette to its associated file in the format that it was
+    /// read in.  Palettes read from image files (which can't be written)
+    /// have to be saved elsewhere with `save_as()`.
+    pub fn save(&mut self, id: PaletteId) -> Result<(), PaletteIoError> {
+        let document = self.document(id).ok_or(PaletteIoError::NoPalette)?;
+        let path = document
+            .path
+            .as_ref()
+            .filter(|_| document.format.is_writable())
+            .ok_or(PaletteIoError::NoFilePath)?;
+        write_palette(path, &document.palette, document.format)?;
+        let was_dirty = document.dirty;
+        if let Some(document) = self.document_mut(id) {
+            document.dirty = false;
+        }
+        self.notify(PaletteEvent::Saved(id));
+        if was_dirty {
+            self.notify(PaletteEvent::DirtyChanged(id, false));
+        }
+        Ok(())
+    }
+
+    pub fn save_as(&mut self, id: PaletteId, path: &Path) -> Result<(), PaletteIoError> {
+        if self.document(id).is_none() {
+            return Err(PaletteIoError::NoPalette);
+        }
+        let format = PaletteFormat::for_path(path);
+        if !format.is_writable() {
+            return Err(PaletteIoError::UnknownFormat(format!(
+                "{format:?} palettes can't be written"
+            )));
+        }
+        self.set_path(id, path);
+        self.save(id)
+    }
+
+    /// Stop managing the palette (regardless of unsaved changes) and return
+    /// it.
+    pub fn close(&mut self, id: PaletteId) -> Option<Palette> {
+        let index = self
+            .documents
+            .iter()
+            .position(|document| document.id == id)?;
+        let document = self.documents.remove(index);
+        self.notify(PaletteEvent::Closed(id));
+        Some(document.palette)
+    }
+}
+
+#[cfg(test)]
+mod manager_tests {
+    use super::*;
+    use crate::{hcv::HCV, palette_io::ase, HueConstants, RGBConstants};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn dirty_save_and_close() {
+        let events = Rc::new(RefCell::new(vec![]));
+        let mut manager = PaletteManager::new();
+        let events_c = Rc::clone(&events);
+        manager.connect_event(move |event| events_c.borrow_mut().push(event.clone()));
+        let id = manager.add(Palette::new("greys"), None);
+        assert!(!manager.is_dirty(id));
+        manager.modify(id, |palette| palette.add_colour("black", &HCV::BLACK));
+        manager.modify(id, |palette| palette.add_colour("white", &HCV::WHITE));
+        assert!(manager.any_dirty());
+        assert_eq!(manager.dirty_ids(), vec![id]);
+        assert!(matches!(manager.save(id), Err(PaletteIoError::NoFilePath)));
+        let path = std::env::temp_dir().join("colour_math_manager_test.txt");
+        manager.save_as(id, &path).unwrap();
+        assert!(!manager.is_dirty(id));
+        assert_eq!(manager.find_by_path(&path), Some(id));
+        assert_eq!(manager.open(&path).unwrap(), id);
+        let palette = manager.close(id).unwrap();
+        assert!(manager.is_empty());
+        let id = manager.open(&path).unwrap();
+        assert_eq!(manager.palette(id), Some(&palette));
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                PaletteEvent::Opened(PaletteId(0)),
+                PaletteEvent::Changed(PaletteId(0)),
+                PaletteEvent::DirtyChanged(PaletteId(0), true),
+                PaletteEvent::Changed(PaletteId(0)),
+                PaletteEvent::PathChanged(PaletteId(0)),
+                PaletteEvent::Saved(PaletteId(0)),
+                PaletteEvent::DirtyChanged(PaletteId(0), false),
+                PaletteEvent::Closed(PaletteId(0)),
+                PaletteEvent::Opened(PaletteId(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn save_in_format_read() {
+        let mut palette = Palette::new("primaries");
+        palette.add_colour("Red", &HCV::RED);
+        palette.add_colour("Blue", &HCV::BLUE);
+        let dir = std::env::temp_dir();
+        let mut manager = PaletteManager::new();
+        let ase_path = dir.join("colour_math_manager_test.ase");
+        ase::write_palette(&ase_path, &palette).unwrap();
+        let id = manager.open(&ase_path).unwrap();
+        assert_eq!(manager.format(id), Some(PaletteFormat::Ase));
+        manager.modify(id, |palette| palette.add_colour("Green", &HCV::GREEN));
+        manager.save(id).unwrap();
+        let read = ase::read_palette(&ase_path).unwrap();
+        assert_eq!(read.colours(), manager.palette(id).unwrap().colours());
+        std::fs::remove_file(&ase_path).ok();
+        // image files can't be written
+        let gif_path = dir.join("colour_math_manager_test.gif");
+        let id = manager.add(palette, Some(gif_path.as_path()));
+        assert!(matches!(manager.save(id), Err(PaletteIoError::NoFilePath)));
+        assert!(matches!(
+            manager.save_as(id, &dir.join("colour_math_manager_test.png")),
+            Err(PaletteIoError::UnknownFormat(_))
+        ));
+        assert_eq!(manager.path(id), Some(gif_path.as_path()));
+        assert!(!gif_path.exists());
+    }
+}
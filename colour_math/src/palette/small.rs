@@ -0,0 +1,198 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    hcv::HCV,
+    palette::{Palette, PaletteEntry},
+    rgb::RGB,
+    ColourBasics, LightLevel,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SmallPaletteEntry {
+    name: &'static str,
+    colour: HCV,
+}
+
+impl SmallPaletteEntry {
+    pub fn new(name: &'static str, colour: &impl ColourBasics) -> Self {
+        Self {
+            name,
+            colour: colour.hcv(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn colour(&self) -> HCV {
+        self.colour
+    }
+
+    pub fn set_colour(&mut self, colour: &impl ColourBasics) {
+        self.colour = colour.hcv()
+    }
+}
+
+/// A palette of at most `N` colours stored in an array (i.e. no heap
+/// allocation) for use where there is no allocator e.g. driving LED strips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmallPalette<const N: usize> {
+    entries: [SmallPaletteEntry; N],
+    len: usize,
+}
+
+impl<const N: usize> Default for SmallPalette<N> {
+    fn default() -> Self {
+        Self {
+            entries: [SmallPaletteEntry::default(); N],
+            len: 0,
+        }
+    }
+}
+
+impl<const N: usize> SmallPalette<N> {
+    pub const CAPACITY: usize = N;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, SmallPaletteEntry> {
+        self.entries().iter()
+    }
+
+    pub fn entries(&self) -> &[SmallPaletteEntry] {
+        &self.entries[..self.len]
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SmallPaletteEntry> {
+        self.iter().find(|e| e.name == name)
+    }
+
+    pub fn colour(&self, index: usize) -> Option<HCV> {
+        self.entries().get(index).map(|e| e.colour)
+    }
+
+    pub fn rgb<L: LightLevel>(&self, index: usize) -> Option<RGB<L>> {
+        self.entries().get(index).map(|e| e.colour.rgb::<L>())
+    }
+
+    /// Returns the entry if the palette is full.
+    pub fn push(&mut self, entry: SmallPaletteEntry) -> Result<(), SmallPaletteEntry> {
+        if self.is_full() {
+            Err(entry)
+        } else {
+            self.entries[self.len] = entry;
+            self.len += 1;
+            Ok(())
+        }
+    }
+
+    pub fn add_colour(
+        &mut self,
+        name: &'static str,
+        colour: &impl ColourBasics,
+    ) -> Result<(), SmallPaletteEntry> {
+        self.push(SmallPaletteEntry::new(name, colour))
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<SmallPaletteEntry> {
+        let index = self.iter().position(|e| e.name == name)?;
+        let entry = self.entries[index];
+        self.entries.copy_within(index + 1..self.len, index);
+        self.len -= 1;
+        self.entries[self.len] = SmallPaletteEntry::default();
+        Some(entry)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default()
+    }
+
+    /// The entry whose colour is closest to `colour` in RGB space.
+    pub fn nearest(&self, colour: &impl ColourBasics) -> Option<&SmallPaletteEntry> {
+        let target = colour.rgb::<f64>();
+        let distance = |entry: &SmallPaletteEntry| {
+            let rgb = entry.colour.rgb::<f64>();
+            (0..3)
+                .map(|i| (rgb[i] - target[i]) * (rgb[i] - target[i]))
+                .sum::<f64>()
+        };
+        self.iter()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+    }
+
+    /// Unstable i.e. the order of entries with equal keys is not preserved.
+    pub fn sort_by_key<K: Ord, F: FnMut(&HCV) -> K>(&mut self, mut f: F) {
+        self.entries[..self.len].sort_unstable_by_key(|e| f(&e.colour))
+    }
+
+    pub fn sort_by_value(&mut self) {
+        self.sort_by_key(|colour| colour.value())
+    }
+
+    /// Greys come first.
+    pub fn sort_by_hue(&mut self) {
+        self.sort_by_key(|colour| colour.hue())
+    }
+
+    pub fn to_palette(&self, name: &str) -> Palette {
+        let mut palette = Palette::new(name);
+        for entry in self.iter() {
+            palette.push(PaletteEntry::new(entry.name, &entry.colour));
+        }
+        palette
+    }
+}
+
+impl<'a, const N: usize> IntoIterator for &'a SmallPalette<N> {
+    type Item = &'a SmallPaletteEntry;
+    type IntoIter = core::slice::Iter<'a, SmallPaletteEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod small_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    #[test]
+    fn capacity_query_and_sort() {
+        let mut palette = SmallPalette::<3>::new();
+        assert!(palette.is_empty());
+        palette.add_colour("white", &HCV::WHITE).unwrap();
+        palette.add_colour("green", &HCV::GREEN).unwrap();
+        palette.add_colour("red", &HCV::RED).unwrap();
+        assert!(palette.is_full());
+        assert_eq!(
+            palette.add_colour("blue", &HCV::BLUE),
+            Err(SmallPaletteEntry::new("blue", &HCV::BLUE))
+        );
+        assert_eq!(palette.get("red").unwrap().colour(), HCV::RED);
+        assert_eq!(palette.nearest(&HCV::YELLOW).unwrap().name(), "white");
+        palette.sort_by_hue();
+        let names: Vec<&str> = palette.iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["white", "red", "green"]);
+        palette.sort_by_value();
+        assert_eq!(palette.entries()[2].name(), "white");
+        assert_eq!(palette.remove("red").unwrap().colour(), HCV::RED);
+        assert_eq!(palette.len(), 2);
+        assert_eq!(palette.rgb::<u8>(1), Some(RGB::<u8>::WHITE));
+        assert_eq!(palette.to_palette("small").len(), 2);
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{fs, io, path::Path};
+
+use crate::{hcv::HCV, palette::Palette, rgb::RGB};
+
+pub mod aco;
+pub mod ase;
+pub mod gif;
+pub mod png;
+pub mod session;
+
+#[derive(Debug)]
+pub enum PaletteIoError {
+    IOError(io::Error),
+    UnknownFormat(String),
+    Malformed(String),
+    Truncated,
+    NoPalette,
+    NoFilePath,
+}
+
+impl std::fmt::Display for PaletteIoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaletteIoError::IOError(error) => write!(f, "I/O Error: {error}"),
+            PaletteIoError::UnknownFormat(string) => write!(f, "Unknown format: {string}"),
+            PaletteIoError::Malformed(string) => write!(f, "Malformed data: {string}"),
+            PaletteIoError::Truncated => write!(f, "Data is truncated"),
+            PaletteIoError::NoPalette => write!(f, "No palette found"),
+            PaletteIoError::NoFilePath => write!(f, "No file path"),
+        }
+    }
+}
+
+impl std::error::Error for PaletteIoError {}
+
+impl From<io::Error> for PaletteIoError {
+    fn from(error: io::Error) -> Self {
+        PaletteIoError::IOError(error)
+    }
+}
+
+pub(crate) fn colour_from_bytes(bytes: &[u8]) -> HCV {
+    debug_assert_eq!(bytes.len(), 3);
+    HCV::from(RGB::<u8>::from([bytes[0], bytes[1], bytes[2]]))
+}
+
+// For Adobe's (big endian) binary formats
+pub(crate) struct BigEndianReader<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl<'a> BigEndianReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, index: 0 }
+    }
+
+    pub(crate) fn is_at_end(&self) -> bool {
+        self.index >= self.bytes.len()
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], PaletteIoError> {
+        if n > self.bytes.len() - self.index {
+            Err(PaletteIoError::Truncated)
+        } else {
+            let slice = &self.bytes[self.index..self.index + n];
+            self.index += n;
+            Ok(slice)
+        }
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, PaletteIoError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, PaletteIoError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub(crate) fn f32(&mut self) -> Result<f32, PaletteIoError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    /// `length` UTF-16 code units (including any terminating null).
+    pub(crate) fn utf16(&mut self, length: usize) -> Result<String, PaletteIoError> {
+        let units = (0..length)
+            .map(|_| self.u16())
+            .collect::<Result<Vec<u16>, PaletteIoError>>()?;
+        let string = String::from_utf16_lossy(&units);
+        Ok(string.trim_end_matches('\0').to_string())
+    }
+}
+
+/// `string` as null terminated UTF-16 big endian code units.
+pub(crate) fn utf16_bytes(string: &str) -> Vec<u8> {
+    string
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(u16::to_be_bytes)
+        .collect()
+}
+
+pub(crate) fn palette_name_for_path(path: &Path) -> String {
+    match path.file_stem() {
+        Some(stem) => stem.to_string_lossy().to_string(),
+        None => String::new(),
+    }
+}
+
+/// The formats that palette files may be in.  Palettes can be read from
+/// all of them but only written to those that aren't image files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteFormat {
+    Session,
+    Png,
+    Gif,
+    Ase,
+    Aco,
+}
+
+impl PaletteFormat {
+    /// The format implied by `path`'s extension (defaulting to `Session`).
+    pub fn for_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("png") => PaletteFormat::Png,
+            Some("gif") => PaletteFormat::Gif,
+            Some("ase") => PaletteFormat::Ase,
+            Some("aco") => PaletteFormat::Aco,
+            _ => PaletteFormat::Session,
+        }
+    }
+
+    pub fn is_writable(&self) -> bool {
+        !matches!(self, PaletteFormat::Png | PaletteFormat::Gif)
+    }
+}
+
+pub fn read_palette(path: &Path) -> Result<Palette, PaletteIoError> {
+    Ok(read_palette_and_format(path)?.0)
+}
+
+/// Read the palette in the file at `path` and report the format that it
+/// was in (so that it may be saved in the same format).
+pub fn read_palette_and_format(path: &Path) -> Result<(Palette, PaletteFormat), PaletteIoError> {
+    let bytes = fs::read(path)?;
+    if bytes.starts_with(session::HEADER.as_bytes()) {
+        // session files hold the palette's name
+        let string = String::from_utf8_lossy(&bytes);
+        let palette = session::session_from_str(&string)?
+            .into_iter()
+            .next()
+            .ok_or(PaletteIoError::NoPalette)?;
+        return Ok((palette, PaletteFormat::Session));
+    }
+    let (mut palette, format) = if bytes.starts_with(&png::SIGNATURE) {
+        (png::palette_from_bytes(&bytes)?, PaletteFormat::Png)
+    } else if bytes.starts_with(gif::SIGNATURE_87A) || bytes.starts_with(gif::SIGNATURE_89A) {
+        (gif::palette_from_bytes(&bytes)?, PaletteFormat::Gif)
+    } else if bytes.starts_with(ase::SIGNATURE) {
+        (ase::palette_from_bytes(&bytes)?, PaletteFormat::Ase)
+    } else if PaletteFormat::for_path(path) == PaletteFormat::Aco {
+        // colour swatch files have no signature
+        (aco::palette_from_bytes(&bytes)?, PaletteFormat::Aco)
+    } else {
+        return Err(PaletteIoError::UnknownFormat(
+            path.to_string_lossy().to_string(),
+        ));
+    };
+    palette.set_name(&palette_name_for_path(path));
+    Ok((palette, format))
+}
+
+/// Write `palette` to `path` in `format` (which must be writable).
+pub fn write_palette(
+    path: &Path,
+    palette: &Palette,
+    format: PaletteFormat,
+) -> Result<(), PaletteIoError> {
+    match format {
+        PaletteFormat::Session => session::write_session(&[palette.clone()], path),
+        PaletteFormat::Ase => ase::write_palette(path, palette),
+        PaletteFormat::Aco => aco::write_palette(path, palette),
+        PaletteFormat::Png | PaletteFormat::Gif => Err(PaletteIoError::UnknownFormat(format!(
+            "{format:?} palettes can't be written"
+        ))),
+    }
+}
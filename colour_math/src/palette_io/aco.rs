@@ -0,0 +1,227 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! Photoshop colour swatch (.aco) files.  RGB, HSB, CMYK, Lab (D50) and
+//! greyscale colours are read (with colours outside the sRGB gamut
+//! clipped) and names are taken from the version 2 section if present.
+//! Palettes are written as RGB colours in both version 1 and version 2
+//! sections (as Photoshop does).
+use std::{fs, path::Path};
+
+use crate::{
+    cmyk::CMYK,
+    hcv::HCV,
+    lab::Lab,
+    palette::{Palette, PaletteEntry},
+    palette_io::{palette_name_for_path, utf16_bytes, BigEndianReader, PaletteIoError},
+    rgb::RGB,
+    standards::WhitePoint,
+    ColourBasics,
+};
+
+const RGB_SPACE: u16 = 0;
+const HSB_SPACE: u16 = 1;
+const CMYK_SPACE: u16 = 2;
+const LAB_SPACE: u16 = 7;
+const GREYSCALE_SPACE: u16 = 8;
+
+fn hsb_to_rgb(hue: f64, saturation: f64, brightness: f64) -> [f64; 3] {
+    let chroma = brightness * saturation;
+    let sector = (hue / 60.0).rem_euclid(6.0);
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let [red, green, blue] = match sector as u8 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    let grey = brightness - chroma;
+    [red + grey, green + grey, blue + grey]
+}
+
+fn colour(space: u16, data: [u16; 4]) -> Result<HCV, PaletteIoError> {
+    let prop = |value: u16| value as f64 / u16::MAX as f64;
+    match space {
+        RGB_SPACE => Ok(HCV::from(RGB::<u16>::from([data[0], data[1], data[2]]))),
+        HSB_SPACE => {
+            let rgb = hsb_to_rgb(prop(data[0]) * 360.0, prop(data[1]), prop(data[2]));
+            Ok(HCV::from(RGB::<f64>::from(rgb.map(|c| c.clamp(0.0, 1.0)))))
+        }
+        // zero is full coverage
+        CMYK_SPACE => {
+            let [cyan, magenta, yellow, black] = data.map(|value| 1.0 - prop(value));
+            Ok(CMYK::<f64>::new(cyan, magenta, yellow, black).hcv())
+        }
+        // L* in hundredths and a* and b* in signed hundredths
+        LAB_SPACE => {
+            let l = (data[0] as f64 / 100.0).clamp(0.0, 100.0);
+            let a = data[1] as i16 as f64 / 100.0;
+            let b = data[2] as i16 as f64 / 100.0;
+            Ok(Lab::new(l, a, b, WhitePoint::D50).hcv())
+        }
+        // black ink coverage in hundredths of a percent
+        GREYSCALE_SPACE => {
+            let grey = 1.0 - (data[0] as f64 / 10_000.0).clamp(0.0, 1.0);
+            Ok(HCV::from(RGB::<f64>::from([grey, grey, grey])))
+        }
+        _ => Err(PaletteIoError::Malformed(format!(
+            "unsupported colour space: {space}"
+        ))),
+    }
+}
+
+fn colour_record(reader: &mut BigEndianReader) -> Result<HCV, PaletteIoError> {
+    let space = reader.u16()?;
+    let data = [reader.u16()?, reader.u16()?, reader.u16()?, reader.u16()?];
+    colour(space, data)
+}
+
+fn section(reader: &mut BigEndianReader, version: u16) -> Result<Palette, PaletteIoError> {
+    let count = reader.u16()?;
+    let mut palette = Palette::default();
+    for index in 0..count {
+        let colour = colour_record(reader)?;
+        let name = if version == 2 {
+            let length = reader.u32()? as usize;
+            reader.utf16(length)?
+        } else {
+            String::new()
+        };
+        let name = if name.is_empty() {
+            format!("Index {index:03}")
+        } else {
+            name
+        };
+        palette.push(PaletteEntry::new(&name, &colour));
+    }
+    Ok(palette)
+}
+
+pub fn palette_from_bytes(bytes: &[u8]) -> Result<Palette, PaletteIoError> {
+    let mut reader = BigEndianReader::new(bytes);
+    let mut palette = match reader.u16()? {
+        1 => section(&mut reader, 1)?,
+        2 => section(&mut reader, 2)?,
+        version => {
+            return Err(PaletteIoError::UnknownFormat(format!(
+                "ACO version: {version}"
+            )))
+        }
+    };
+    // the version 2 section (which has names) usually follows version 1's
+    if !reader.is_at_end() && reader.u16()? == 2 {
+        palette = section(&mut reader, 2)?;
+    }
+    if palette.is_empty() {
+        Err(PaletteIoError::NoPalette)
+    } else {
+        Ok(palette)
+    }
+}
+
+pub fn palette_to_bytes(palette: &Palette) -> Vec<u8> {
+    // the count is only 16 bits
+    let count = palette.len().min(u16::MAX as usize);
+    let mut bytes = vec![];
+    for version in [1_u16, 2] {
+        bytes.extend(version.to_be_bytes());
+        bytes.extend((count as u16).to_be_bytes());
+        for entry in palette.iter().take(count) {
+            bytes.extend(RGB_SPACE.to_be_bytes());
+            let rgb = entry.colour().rgb::<u16>();
+            for component in [rgb[0], rgb[1], rgb[2], 0] {
+                bytes.extend(component.to_be_bytes());
+            }
+            if version == 2 {
+                let name = utf16_bytes(entry.name());
+                bytes.extend(((name.len() / 2) as u32).to_be_bytes());
+                bytes.extend(name);
+            }
+        }
+    }
+    bytes
+}
+
+pub fn read_palette(path: &Path) -> Result<Palette, PaletteIoError> {
+    let bytes = fs::read(path)?;
+    let mut palette = palette_from_bytes(&bytes)?;
+    palette.set_name(&palette_name_for_path(path));
+    Ok(palette)
+}
+
+pub fn write_palette(path: &Path, palette: &Palette) -> Result<(), PaletteIoError> {
+    fs::write(path, palette_to_bytes(palette))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod aco_tests {
+    use super::*;
+    use crate::{fdrn::Prop, HueConstants, RGBConstants};
+
+    fn record(space: u16, data: [u16; 4]) -> Vec<u8> {
+        let mut bytes = space.to_be_bytes().to_vec();
+        for value in data {
+            bytes.extend(value.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut palette = Palette::default();
+        palette.add_colour("Red", &HCV::RED);
+        palette.add_colour("Cyan", &HCV::CYAN);
+        palette.add_colour("", &HCV::WHITE);
+        let bytes = palette_to_bytes(&palette);
+        let read = palette_from_bytes(&bytes).unwrap();
+        assert_eq!(read.colours(), palette.colours());
+        let names: Vec<&str> = read.iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["Red", "Cyan", "Index 002"]);
+        // version 1 only
+        let v1_length = 4 + 3 * 10;
+        let read = palette_from_bytes(&bytes[..v1_length]).unwrap();
+        assert_eq!(read.colours(), palette.colours());
+        assert_eq!(read.entries()[0].name(), "Index 000");
+    }
+
+    #[test]
+    fn colour_spaces() {
+        let mut bytes = vec![0, 1, 0, 5];
+        bytes.extend(record(HSB_SPACE, [u16::MAX / 3, u16::MAX, u16::MAX, 0]));
+        bytes.extend(record(CMYK_SPACE, [u16::MAX, 0, u16::MAX, u16::MAX]));
+        bytes.extend(record(LAB_SPACE, [5_000, 0, 0, 0]));
+        bytes.extend(record(GREYSCALE_SPACE, [2_500, 0, 0, 0]));
+        bytes.extend(record(RGB_SPACE, [0, 0, u16::MAX, 0]));
+        let palette = palette_from_bytes(&bytes).unwrap();
+        let tolerance = Some(Prop::from(0.01));
+        let rgbs = palette.rgbs::<f64>();
+        assert!(rgbs[0].approx_eq(&RGB::<f64>::GREEN, tolerance));
+        assert!(rgbs[1].approx_eq(&RGB::<f64>::MAGENTA, tolerance));
+        assert!(rgbs[2].approx_eq(&Lab::new(50.0, 0.0, 0.0, WhitePoint::D50).rgb(), tolerance));
+        assert!(rgbs[3].approx_eq(&RGB::from([0.75, 0.75, 0.75]), tolerance));
+        assert_eq!(palette.entries()[4].colour(), HCV::BLUE);
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(matches!(
+            palette_from_bytes(&[0, 1, 0, 1, 0, 0]),
+            Err(PaletteIoError::Truncated)
+        ));
+        let mut bytes = vec![0, 1, 0, 1];
+        bytes.extend(record(3, [0; 4]));
+        assert!(matches!(
+            palette_from_bytes(&bytes),
+            Err(PaletteIoError::Malformed(_))
+        ));
+        assert!(matches!(
+            palette_from_bytes(&[0, 9]),
+            Err(PaletteIoError::UnknownFormat(_))
+        ));
+        assert!(matches!(
+            palette_from_bytes(&[0, 1, 0, 0]),
+            Err(PaletteIoError::NoPalette)
+        ));
+    }
+}
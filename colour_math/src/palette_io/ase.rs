@@ -0,0 +1,234 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! Adobe Swatch Exchange (.ase) files.  RGB, Lab (D50), CMYK and grey
+//! colours are read (with colours outside the sRGB gamut clipped) and the
+//! names of any groups that they are in become their tags.  Palettes are
+//! written as ungrouped RGB colours.
+use std::{fs, path::Path};
+
+use crate::{
+    cmyk::CMYK,
+    hcv::HCV,
+    lab::Lab,
+    palette::{Palette, PaletteEntry},
+    palette_io::{palette_name_for_path, utf16_bytes, BigEndianReader, PaletteIoError},
+    rgb::RGB,
+    standards::WhitePoint,
+    ColourBasics,
+};
+
+pub const SIGNATURE: &[u8] = b"ASEF";
+
+const VERSION: [u16; 2] = [1, 0];
+const GROUP_START: u16 = 0xC001;
+const GROUP_END: u16 = 0xC002;
+const COLOUR_ENTRY: u16 = 0x0001;
+const NORMAL_COLOUR: u16 = 2;
+
+fn colour(model: &[u8], reader: &mut BigEndianReader) -> Result<HCV, PaletteIoError> {
+    let mut component = || -> Result<f64, PaletteIoError> {
+        let component = reader.f32()? as f64;
+        if component.is_finite() {
+            Ok(component)
+        } else {
+            Err(PaletteIoError::Malformed(
+                "non-finite colour component".to_string(),
+            ))
+        }
+    };
+    match model {
+        b"RGB " => {
+            let rgb = [component()?, component()?, component()?];
+            Ok(HCV::from(RGB::<f64>::from(rgb.map(|c| c.clamp(0.0, 1.0)))))
+        }
+        b"LAB " => {
+            let (l, a, b) = (component()? * 100.0, component()?, component()?);
+            Ok(Lab::new(l, a, b, WhitePoint::D50).hcv())
+        }
+        b"CMYK" => {
+            let mut ink = || -> Result<f64, PaletteIoError> { Ok(component()?.clamp(0.0, 1.0)) };
+            Ok(CMYK::<f64>::new(ink()?, ink()?, ink()?, ink()?).hcv())
+        }
+        b"Gray" => {
+            let grey = component()?.clamp(0.0, 1.0);
+            Ok(HCV::from(RGB::<f64>::from([grey, grey, grey])))
+        }
+        _ => Err(PaletteIoError::Malformed(format!(
+            "unsupported colour model: {}",
+            String::from_utf8_lossy(model)
+        ))),
+    }
+}
+
+pub fn palette_from_bytes(bytes: &[u8]) -> Result<Palette, PaletteIoError> {
+    if !bytes.starts_with(SIGNATURE) {
+        return Err(PaletteIoError::UnknownFormat("not an ASE file".to_string()));
+    }
+    let mut reader = BigEndianReader::new(&bytes[SIGNATURE.len()..]);
+    // version (2 + 2) then the number of blocks
+    reader.take(4)?;
+    let n_blocks = reader.u32()?;
+    let mut palette = Palette::default();
+    let mut group: Option<String> = None;
+    for _ in 0..n_blocks {
+        let block_type = reader.u16()?;
+        let length = reader.u32()? as usize;
+        let mut block = BigEndianReader::new(reader.take(length)?);
+        match block_type {
+            GROUP_START => {
+                let name_length = block.u16()? as usize;
+                group = Some(block.utf16(name_length)?);
+            }
+            GROUP_END => group = None,
+            COLOUR_ENTRY => {
+                let name_length = block.u16()? as usize;
+                let mut name = block.utf16(name_length)?;
+                if name.is_empty() {
+                    name = format!("Index {:03}", palette.len());
+                }
+                let model = block.take(4)?;
+                let mut entry = PaletteEntry::new(&name, &colour(model, &mut block)?);
+                if let Some(group) = &group {
+                    entry.add_tag(group);
+                }
+                palette.push(entry);
+            }
+            // unknown blocks are skipped
+            _ => (),
+        }
+    }
+    if palette.is_empty() {
+        Err(PaletteIoError::NoPalette)
+    } else {
+        Ok(palette)
+    }
+}
+
+pub fn palette_to_bytes(palette: &Palette) -> Vec<u8> {
+    let mut bytes = SIGNATURE.to_vec();
+    for number in VERSION {
+        bytes.extend(number.to_be_bytes());
+    }
+    bytes.extend((palette.len() as u32).to_be_bytes());
+    for entry in palette.iter() {
+        let name = utf16_bytes(entry.name());
+        let mut block = ((name.len() / 2) as u16).to_be_bytes().to_vec();
+        block.extend(name);
+        block.extend(b"RGB ");
+        let rgb = entry.colour().rgb::<f64>();
+        for component in [rgb[0], rgb[1], rgb[2]] {
+            block.extend((component as f32).to_be_bytes());
+        }
+        block.extend(NORMAL_COLOUR.to_be_bytes());
+        bytes.extend(COLOUR_ENTRY.to_be_bytes());
+        bytes.extend((block.len() as u32).to_be_bytes());
+        bytes.extend(block);
+    }
+    bytes
+}
+
+pub fn read_palette(path: &Path) -> Result<Palette, PaletteIoError> {
+    let bytes = fs::read(path)?;
+    let mut palette = palette_from_bytes(&bytes)?;
+    palette.set_name(&palette_name_for_path(path));
+    Ok(palette)
+}
+
+pub fn write_palette(path: &Path, palette: &Palette) -> Result<(), PaletteIoError> {
+    fs::write(path, palette_to_bytes(palette))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod ase_tests {
+    use super::*;
+    use crate::{fdrn::Prop, HueConstants, RGBConstants};
+
+    fn block(block_type: u16, data: &[u8]) -> Vec<u8> {
+        let mut bytes = block_type.to_be_bytes().to_vec();
+        bytes.extend((data.len() as u32).to_be_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    fn colour_entry(name: &str, model: &[u8], components: &[f32]) -> Vec<u8> {
+        let utf16 = utf16_bytes(name);
+        let mut data = ((utf16.len() / 2) as u16).to_be_bytes().to_vec();
+        data.extend(utf16);
+        data.extend(model);
+        for component in components {
+            data.extend(component.to_be_bytes());
+        }
+        data.extend(NORMAL_COLOUR.to_be_bytes());
+        block(COLOUR_ENTRY, &data)
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut palette = Palette::default();
+        palette.add_colour("Red", &HCV::RED);
+        palette.add_colour("Bleu clair \u{e9}t\u{e9}", &HCV::CYAN);
+        palette.add_colour("Black", &HCV::BLACK);
+        let read = palette_from_bytes(&palette_to_bytes(&palette)).unwrap();
+        assert_eq!(read, palette);
+    }
+
+    #[test]
+    fn models_and_groups() {
+        let mut bytes = SIGNATURE.to_vec();
+        bytes.extend([0, 1, 0, 0, 0, 0, 0, 6]);
+        let group_name = utf16_bytes("Brand");
+        let mut group = ((group_name.len() / 2) as u16).to_be_bytes().to_vec();
+        group.extend(group_name);
+        bytes.extend(block(GROUP_START, &group));
+        bytes.extend(colour_entry("Lab", b"LAB ", &[0.5, 0.0, 0.0]));
+        bytes.extend(colour_entry("CMYK", b"CMYK", &[1.0, 0.0, 0.0, 0.0]));
+        bytes.extend(block(GROUP_END, &[]));
+        bytes.extend(colour_entry("", b"Gray", &[0.25]));
+        bytes.extend(block(0x1234, &[1, 2, 3]));
+        let palette = palette_from_bytes(&bytes).unwrap();
+        assert_eq!(palette.len(), 3);
+        let tolerance = Some(Prop::from(0.01));
+        let lab = palette.entries()[0].colour();
+        assert!(lab
+            .rgb::<f64>()
+            .approx_eq(&Lab::new(50.0, 0.0, 0.0, WhitePoint::D50).rgb(), tolerance));
+        assert_eq!(palette.entries()[1].colour(), HCV::CYAN);
+        assert_eq!(palette.tagged("Brand"), vec![0, 1]);
+        assert_eq!(palette.entries()[2].name(), "Index 002");
+        assert!(palette.entries()[2]
+            .colour()
+            .rgb::<f64>()
+            .approx_eq(&RGB::from([0.25, 0.25, 0.25]), tolerance));
+    }
+
+    #[test]
+    fn malformed() {
+        let mut bytes = SIGNATURE.to_vec();
+        bytes.extend([0, 1, 0, 0, 0, 0, 0, 1]);
+        assert!(matches!(
+            palette_from_bytes(&bytes),
+            Err(PaletteIoError::Truncated)
+        ));
+        bytes.extend(colour_entry("XYZ", b"XYZ ", &[0.0, 0.0, 0.0]));
+        assert!(matches!(
+            palette_from_bytes(&bytes),
+            Err(PaletteIoError::Malformed(_))
+        ));
+        for (model, components) in [
+            (b"RGB ", [0.5, f32::NAN, 0.5]),
+            (b"LAB ", [0.5, f32::INFINITY, 0.0]),
+        ] {
+            let mut bytes = SIGNATURE.to_vec();
+            bytes.extend([0, 1, 0, 0, 0, 0, 0, 1]);
+            bytes.extend(colour_entry("Bad", model, &components));
+            assert!(matches!(
+                palette_from_bytes(&bytes),
+                Err(PaletteIoError::Malformed(_))
+            ));
+        }
+        assert!(matches!(
+            palette_from_bytes(b"GIF89a"),
+            Err(PaletteIoError::UnknownFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{fs, path::Path};
+
+use crate::{
+    palette::{Palette, PaletteEntry},
+    palette_io::{colour_from_bytes, palette_name_for_path, PaletteIoError},
+};
+
+pub const SIGNATURE_87A: &[u8] = b"GIF87a";
+pub const SIGNATURE_89A: &[u8] = b"GIF89a";
+
+const EXTENSION_INTRODUCER: u8 = 0x21;
+const IMAGE_SEPARATOR: u8 = 0x2C;
+const TRAILER: u8 = 0x3B;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], PaletteIoError> {
+        if self.index + n > self.bytes.len() {
+            Err(PaletteIoError::Truncated)
+        } else {
+            let slice = &self.bytes[self.index..self.index + n];
+            self.index += n;
+            Ok(slice)
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, PaletteIoError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn skip_sub_blocks(&mut self) -> Result<(), PaletteIoError> {
+        loop {
+            match self.byte()? {
+                0 => return Ok(()),
+                size => {
+                    self.take(size as usize)?;
+                }
+            }
+        }
+    }
+
+    fn colour_table(&mut self, packed: u8, name: &str) -> Result<Palette, PaletteIoError> {
+        let size = 3 * (2_usize << (packed & 0x07));
+        let mut palette: Palette = self
+            .take(size)?
+            .chunks(3)
+            .enumerate()
+            .map(|(index, chunk)| {
+                PaletteEntry::new(&format!("Index {index:03}"), &colour_from_bytes(chunk))
+            })
+            .collect();
+        palette.set_name(name);
+        Ok(palette)
+    }
+}
+
+/// Return the global colour table (if present) followed by the local colour
+/// tables of any images that have one.
+pub fn palettes_from_bytes(bytes: &[u8]) -> Result<Vec<Palette>, PaletteIoError> {
+    if !(bytes.starts_with(SIGNATURE_87A) || bytes.starts_with(SIGNATURE_89A)) {
+        return Err(PaletteIoError::UnknownFormat("not a GIF".to_string()));
+    }
+    let mut reader = Reader { bytes, index: 6 };
+    let mut palettes = vec![];
+    // Logical Screen Descriptor: width (2), height (2), packed (1), bg index (1), aspect (1)
+    let packed = reader.take(7)?[4];
+    if packed & 0x80 != 0 {
+        palettes.push(reader.colour_table(packed, "Global")?);
+    }
+    let mut image_number = 0;
+    loop {
+        match reader.byte()? {
+            EXTENSION_INTRODUCER => {
+                reader.byte()?; // label
+                reader.skip_sub_blocks()?;
+            }
+            IMAGE_SEPARATOR => {
+                // Image Descriptor: left (2), top (2), width (2), height (2), packed (1)
+                let packed = reader.take(9)?[8];
+                if packed & 0x80 != 0 {
+                    let name = format!("Image {image_number}");
+                    palettes.push(reader.colour_table(packed, &name)?);
+                }
+                reader.byte()?; // LZW minimum code size
+                reader.skip_sub_blocks()?;
+                image_number += 1;
+            }
+            TRAILER => break,
+            byte => {
+                return Err(PaletteIoError::Malformed(format!(
+                    "unexpected block: {byte:#04X}"
+                )))
+            }
+        }
+    }
+    Ok(palettes)
+}
+
+/// Return the global colour table or, if there isn't one, the first local
+/// colour table.
+pub fn palette_from_bytes(bytes: &[u8]) -> Result<Palette, PaletteIoError> {
+    palettes_from_bytes(bytes)?
+        .into_iter()
+        .next()
+        .ok_or(PaletteIoError::NoPalette)
+}
+
+pub fn read_palette(path: &Path) -> Result<Palette, PaletteIoError> {
+    let bytes = fs::read(path)?;
+    let mut palette = palette_from_bytes(&bytes)?;
+    palette.set_name(&palette_name_for_path(path));
+    Ok(palette)
+}
+
+#[cfg(test)]
+mod gif_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants, HCV};
+
+    fn header(global_table: Option<&[u8]>) -> Vec<u8> {
+        let mut bytes = SIGNATURE_89A.to_vec();
+        bytes.extend_from_slice(&[1, 0, 1, 0]);
+        match global_table {
+            Some(table) => {
+                // two entries
+                bytes.extend_from_slice(&[0x80, 0, 0]);
+                bytes.extend_from_slice(table);
+            }
+            None => bytes.extend_from_slice(&[0, 0, 0]),
+        }
+        bytes
+    }
+
+    fn image(local_table: Option<&[u8]>) -> Vec<u8> {
+        let mut bytes = vec![IMAGE_SEPARATOR, 0, 0, 0, 0, 1, 0, 1, 0];
+        match local_table {
+            Some(table) => {
+                bytes.push(0x80);
+                bytes.extend_from_slice(table);
+            }
+            None => bytes.push(0),
+        }
+        bytes.extend_from_slice(&[2, 2, 0x4C, 0x01, 0]);
+        bytes
+    }
+
+    #[test]
+    fn read_global_and_local_tables() {
+        let mut bytes = header(Some(&[0xFF, 0, 0, 0, 0, 0xFF]));
+        // Graphic Control Extension
+        bytes.extend_from_slice(&[EXTENSION_INTRODUCER, 0xF9, 4, 0, 0, 0, 0, 0]);
+        bytes.extend(image(None));
+        bytes.extend(image(Some(&[0xFF, 0xFF, 0xFF, 0, 0, 0])));
+        bytes.push(TRAILER);
+        let palettes = palettes_from_bytes(&bytes).unwrap();
+        assert_eq!(palettes.len(), 2);
+        assert_eq!(palettes[0].name(), "Global");
+        assert_eq!(palettes[0].colours(), vec![HCV::RED, HCV::BLUE]);
+        assert_eq!(palettes[1].name(), "Image 1");
+        assert_eq!(palettes[1].colours(), vec![HCV::WHITE, HCV::BLACK]);
+        assert_eq!(palette_from_bytes(&bytes).unwrap(), palettes[0]);
+    }
+
+    #[test]
+    fn no_table() {
+        let mut bytes = header(None);
+        bytes.extend(image(None));
+        bytes.push(TRAILER);
+        assert!(matches!(
+            palette_from_bytes(&bytes),
+            Err(PaletteIoError::NoPalette)
+        ));
+        bytes.truncate(bytes.len() - 4);
+        assert!(matches!(
+            palette_from_bytes(&bytes),
+            Err(PaletteIoError::Truncated)
+        ));
+    }
+}
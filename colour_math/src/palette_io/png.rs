@@ -0,0 +1,124 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{fs, path::Path};
+
+use crate::{
+    palette::{Palette, PaletteEntry},
+    palette_io::{colour_from_bytes, palette_name_for_path, PaletteIoError},
+};
+
+pub const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn palette_from_plte(data: &[u8]) -> Result<Palette, PaletteIoError> {
+    if data.is_empty() || data.len() % 3 != 0 || data.len() > 256 * 3 {
+        return Err(PaletteIoError::Malformed(format!(
+            "PLTE chunk length: {}",
+            data.len()
+        )));
+    }
+    Ok(data
+        .chunks(3)
+        .enumerate()
+        .map(|(index, chunk)| {
+            PaletteEntry::new(&format!("Index {index:03}"), &colour_from_bytes(chunk))
+        })
+        .collect())
+}
+
+pub fn palette_from_bytes(bytes: &[u8]) -> Result<Palette, PaletteIoError> {
+    if !bytes.starts_with(&SIGNATURE) {
+        return Err(PaletteIoError::UnknownFormat("not a PNG".to_string()));
+    }
+    let mut index = SIGNATURE.len();
+    while index < bytes.len() {
+        // length (4) + type (4) + data (length) + CRC (4)
+        if index + 8 > bytes.len() {
+            return Err(PaletteIoError::Truncated);
+        }
+        let length = be_u32(&bytes[index..]) as usize;
+        let chunk_type = &bytes[index + 4..index + 8];
+        let data_start = index + 8;
+        let data_end = data_start + length;
+        if data_end + 4 > bytes.len() {
+            return Err(PaletteIoError::Truncated);
+        }
+        match chunk_type {
+            b"PLTE" => return palette_from_plte(&bytes[data_start..data_end]),
+            // PLTE must precede IDAT so there's no point looking any further
+            b"IDAT" | b"IEND" => break,
+            _ => (),
+        }
+        index = data_end + 4;
+    }
+    Err(PaletteIoError::NoPalette)
+}
+
+pub fn read_palette(path: &Path) -> Result<Palette, PaletteIoError> {
+    let bytes = fs::read(path)?;
+    let mut palette = palette_from_bytes(&bytes)?;
+    palette.set_name(&palette_name_for_path(path));
+    Ok(palette)
+}
+
+#[cfg(test)]
+mod png_tests {
+    use super::*;
+    use crate::{ColourBasics, HueConstants, RGBConstants, HCV, RGB};
+
+    fn chunk(chunk_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut bytes = (data.len() as u32).to_be_bytes().to_vec();
+        bytes.extend_from_slice(chunk_type);
+        bytes.extend_from_slice(data);
+        // The reader doesn't check CRCs
+        bytes.extend_from_slice(&[0, 0, 0, 0]);
+        bytes
+    }
+
+    #[test]
+    fn read_plte() {
+        let mut bytes = SIGNATURE.to_vec();
+        bytes.extend(chunk(b"IHDR", &[0; 13]));
+        bytes.extend(chunk(b"PLTE", &[0xFF, 0, 0, 0, 0xFF, 0, 0xFF, 0xFF, 0xFF]));
+        bytes.extend(chunk(b"IDAT", &[]));
+        bytes.extend(chunk(b"IEND", &[]));
+        let palette = palette_from_bytes(&bytes).unwrap();
+        assert_eq!(palette.len(), 3);
+        assert_eq!(palette.colours(), vec![HCV::RED, HCV::GREEN, HCV::WHITE]);
+        assert_eq!(palette.entries()[1].name(), "Index 001");
+        assert_eq!(palette.entries()[2].colour().rgb::<u8>(), RGB::<u8>::WHITE);
+    }
+
+    #[test]
+    fn no_plte() {
+        let mut bytes = SIGNATURE.to_vec();
+        bytes.extend(chunk(b"IHDR", &[0; 13]));
+        bytes.extend(chunk(b"IDAT", &[]));
+        bytes.extend(chunk(b"IEND", &[]));
+        assert!(matches!(
+            palette_from_bytes(&bytes),
+            Err(PaletteIoError::NoPalette)
+        ));
+    }
+
+    #[test]
+    fn bad_data() {
+        assert!(matches!(
+            palette_from_bytes(b"GIF89a"),
+            Err(PaletteIoError::UnknownFormat(_))
+        ));
+        let mut bytes = SIGNATURE.to_vec();
+        bytes.extend(chunk(b"PLTE", &[0xFF, 0, 0, 0]));
+        assert!(matches!(
+            palette_from_bytes(&bytes),
+            Err(PaletteIoError::Malformed(_))
+        ));
+        bytes.truncate(bytes.len() - 6);
+        assert!(matches!(
+            palette_from_bytes(&bytes),
+            Err(PaletteIoError::Truncated)
+        ));
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{fs, path::Path, str::FromStr};
+
+use crate::{
+    hcv::HCV,
+    palette::{Palette, PaletteEntry},
+    palette_io::PaletteIoError,
+    rgb::RGB,
+    write_atomically, ColourBasics,
+};
+
+pub const HEADER: &str = "colour_math session 1";
+
+const PALETTE_PREFIX: &str = "palette ";
+const TAGS_PREFIX: &str = "tags\t";
+
+/// A plain text representation of the palettes in a session: a header line
+/// followed by a "palette <name>" line for each palette and a
+/// "RGB16(0xRRRR, 0xGGGG, 0xBBBB)\t<name>" line for each of its entries.
+/// An entry with tags is followed by a "tags\t<tag>\t<tag>..." line (so
+/// files written before tags existed are still readable).
+pub fn session_to_string(palettes: &[Palette]) -> String {
+    let mut string = format!("{HEADER}\n");
+    for palette in palettes {
+        string += &format!("{PALETTE_PREFIX}{}\n", palette.name());
+        for entry in palette.iter() {
+            let rgb = entry.colour().rgb::<u16>();
+            string += &format!(
+                "RGB16(0x{:04X}, 0x{:04X}, 0x{:04X})\t{}\n",
+                rgb[0],
+                rgb[1],
+                rgb[2],
+                entry.name()
+            );
+            if !entry.tags().is_empty() {
+                string += &format!("{TAGS_PREFIX}{}\n", entry.tags().join("\t"));
+            }
+        }
+    }
+    string
+}
+
+pub fn session_from_str(string: &str) -> Result<Vec<Palette>, PaletteIoError> {
+    let mut lines = string.lines();
+    match lines.next() {
+        Some(HEADER) => (),
+        Some(line) => return Err(PaletteIoError::UnknownFormat(line.to_string())),
+        None => return Err(PaletteIoError::Truncated),
+    }
+    let mut palettes: Vec<Palette> = vec![];
+    for line in lines.filter(|line| !line.is_empty()) {
+        if let Some(name) = line.strip_prefix(PALETTE_PREFIX) {
+            palettes.push(Palette::new(name));
+        } else if let Some(tags) = line.strip_prefix(TAGS_PREFIX) {
+            // for the preceding entry
+            let palette = palettes
+                .last_mut()
+                .filter(|palette| !palette.is_empty())
+                .ok_or_else(|| PaletteIoError::Malformed(line.to_string()))?;
+            let index = palette.len() - 1;
+            for tag in tags.split('\t').filter(|tag| !tag.is_empty()) {
+                palette.add_tag(&[index], tag);
+            }
+        } else if let Some(palette) = palettes.last_mut() {
+            let (rgb, name) = line
+                .split_once('\t')
+                .ok_or_else(|| PaletteIoError::Malformed(line.to_string()))?;
+            let rgb = RGB::<u16>::from_str(rgb)
+                .map_err(|_| PaletteIoError::Malformed(line.to_string()))?;
+            palette.push(PaletteEntry::new(name, &HCV::from(rgb)));
+        } else {
+            return Err(PaletteIoError::NoPalette);
+        }
+    }
+    Ok(palettes)
+}
+
+pub fn write_session(palettes: &[Palette], path: &Path) -> Result<(), PaletteIoError> {
+    Ok(write_atomically(path, session_to_string(palettes))?)
+}
+
+pub fn read_session(path: &Path) -> Result<Vec<Palette>, PaletteIoError> {
+    session_from_str(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod session_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    #[test]
+    fn round_trip() {
+        let mut primaries = Palette::new("primaries");
+        primaries.add_colour("red", &HCV::RED);
+        primaries.add_colour("blue and green", &HCV::CYAN);
+        let mut greys = Palette::new("greys");
+        greys.add_colour("black", &HCV::BLACK);
+        let palettes = vec![primaries, greys, Palette::new("empty")];
+        let string = session_to_string(&palettes);
+        assert_eq!(session_from_str(&string).unwrap(), palettes);
+        assert!(matches!(
+            session_from_str("rubbish"),
+            Err(PaletteIoError::UnknownFormat(_))
+        ));
+        assert!(matches!(
+            session_from_str(&format!("{HEADER}\nRGB16(0x0000, 0x0000, 0x0000)\tblack")),
+            Err(PaletteIoError::NoPalette)
+        ));
+        assert!(matches!(
+            session_from_str(&format!("{HEADER}\npalette p\n#000000\tblack")),
+            Err(PaletteIoError::Malformed(_))
+        ));
+        assert!(matches!(
+            session_from_str(&format!("{HEADER}\npalette p\ntags\twarm")),
+            Err(PaletteIoError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn tags_round_trip() {
+        let mut palette = Palette::new("tagged");
+        palette.add_colour("red", &HCV::RED);
+        palette.add_colour("cyan", &HCV::CYAN);
+        palette.add_colour("black", &HCV::BLACK);
+        palette.add_tag(&[0], "warm");
+        palette.add_tag(&[0, 2], "brand colours");
+        palette.add_tag(&[1], "cool");
+        let string = session_to_string(&[palette.clone()]);
+        assert!(string.contains("tags\twarm\tbrand colours\n"));
+        let read = session_from_str(&string).unwrap();
+        assert_eq!(read, vec![palette]);
+        assert_eq!(read[0].tagged("brand colours"), vec![0, 2]);
+        // files without tags are still read
+        let untagged = format!("{HEADER}\npalette tagged\nRGB16(0xFFFF, 0x0000, 0x0000)\tred\n");
+        let read = session_from_str(&untagged).unwrap();
+        assert_eq!(read[0].entries()[0].colour(), HCV::RED);
+        assert!(read[0].entries()[0].tags().is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    hcv::HCV,
+    project::{Paint, PaintSeries},
+    rgb::RGB,
+};
+
+/// Approximate (masstone) appearance of a common artist pigment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pigment {
+    pub name: &'static str,
+    /// Colour Index generic name e.g. "PR108"
+    pub colour_index: &'static str,
+    pub opaque: bool,
+    pub rgb: RGB<u8>,
+}
+
+impl Pigment {
+    pub fn hcv(&self) -> HCV {
+        HCV::from(self.rgb)
+    }
+
+    pub fn paint(&self) -> Paint {
+        let mut paint = Paint::new(self.name, &self.rgb);
+        let opacity = if self.opaque { "opaque" } else { "transparent" };
+        paint.set_notes(&format!("{}, {}", self.colour_index, opacity));
+        paint
+    }
+}
+
+macro_rules! pigments {
+    ($($ident:ident: $name:expr, $ci:expr, $opaque:expr, [$r:expr, $g:expr, $b:expr];)*) => {
+        $(
+            pub const $ident: Pigment = Pigment {
+                name: $name,
+                colour_index: $ci,
+                opaque: $opaque,
+                rgb: RGB([$r, $g, $b]),
+            };
+        )*
+
+        pub const ARTIST_PIGMENTS: &[Pigment] = &[$($ident),*];
+    };
+}
+
+pigments! {
+    CADMIUM_RED: "Cadmium Red", "PR108", true, [0xE3, 0x00, 0x22];
+    ALIZARIN_CRIMSON: "Alizarin Crimson", "PR83", false, [0x93, 0x0D, 0x20];
+    QUINACRIDONE_MAGENTA: "Quinacridone Magenta", "PR122", false, [0x8E, 0x3A, 0x59];
+    CADMIUM_ORANGE: "Cadmium Orange", "PO20", true, [0xED, 0x87, 0x2D];
+    CADMIUM_YELLOW: "Cadmium Yellow", "PY35", true, [0xFF, 0xC4, 0x00];
+    HANSA_YELLOW: "Hansa Yellow", "PY3", false, [0xE9, 0xD6, 0x00];
+    YELLOW_OCHRE: "Yellow Ochre", "PY43", true, [0xCB, 0x9D, 0x06];
+    RAW_SIENNA: "Raw Sienna", "PBr7", false, [0xC7, 0x7D, 0x42];
+    BURNT_SIENNA: "Burnt Sienna", "PBr7", false, [0x8A, 0x36, 0x0F];
+    RAW_UMBER: "Raw Umber", "PBr7", true, [0x73, 0x4A, 0x12];
+    BURNT_UMBER: "Burnt Umber", "PBr7", true, [0x8A, 0x33, 0x24];
+    SAP_GREEN: "Sap Green", "PG7", false, [0x50, 0x7D, 0x2A];
+    VIRIDIAN: "Viridian", "PG18", false, [0x40, 0x82, 0x6D];
+    PHTHALO_GREEN: "Phthalo Green", "PG7", false, [0x12, 0x35, 0x24];
+    CERULEAN_BLUE: "Cerulean Blue", "PB35", true, [0x2A, 0x52, 0xBE];
+    COBALT_BLUE: "Cobalt Blue", "PB28", false, [0x00, 0x47, 0xAB];
+    ULTRAMARINE_BLUE: "Ultramarine Blue", "PB29", false, [0x12, 0x0A, 0x8F];
+    PHTHALO_BLUE: "Phthalo Blue", "PB15", false, [0x00, 0x0F, 0x89];
+    DIOXAZINE_VIOLET: "Dioxazine Violet", "PV23", false, [0x3C, 0x1E, 0x64];
+    TITANIUM_WHITE: "Titanium White", "PW6", true, [0xF4, 0xF4, 0xF0];
+    IVORY_BLACK: "Ivory Black", "PBk9", true, [0x29, 0x24, 0x21];
+}
+
+pub fn find(name: &str) -> Option<&'static Pigment> {
+    ARTIST_PIGMENTS
+        .iter()
+        .find(|pigment| pigment.name.eq_ignore_ascii_case(name))
+}
+
+/// All of the pigments as a paint series (e.g. as demo content or a
+/// starting point for paint mixing).
+pub fn artist_pigments_series() -> PaintSeries {
+    let mut series = PaintSeries::new("Generic", "Artist Pigments");
+    for pigment in ARTIST_PIGMENTS.iter() {
+        series.add_paint(pigment.paint());
+    }
+    series
+}
+
+#[cfg(test)]
+mod pigments_tests {
+    use super::*;
+    use crate::{hue::angle::Angle, ColourBasics, HueConstants};
+
+    #[test]
+    fn pigments() {
+        assert_eq!(find("ultramarine blue"), Some(&ULTRAMARINE_BLUE));
+        assert!(find("unobtainium").is_none());
+        let red_angle = CADMIUM_RED.hcv().hue_angle().unwrap();
+        assert!(red_angle.abs_diff(&Angle::RED) < Angle::from(15));
+        assert!(TITANIUM_WHITE.hcv().chroma_prop() < crate::Prop::from(0.05));
+        let series = artist_pigments_series();
+        assert_eq!(series.paints().len(), ARTIST_PIGMENTS.len());
+        let paint = series.paint("Yellow Ochre").unwrap();
+        assert_eq!(paint.colour(), YELLOW_OCHRE.hcv());
+        assert_eq!(paint.notes(), "PY43, opaque");
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! User interface preferences (which attributes to display and how the
+//! wheel and editor are set up) that can be saved to and loaded from a
+//! JSON file and handed to the toolkit builders (e.g. `from_config()` in
+//! `colour_math_gtk`).  Missing fields take their default values so files
+//! written by older versions (or by hand) remain usable.
+use std::{fs, io, path::Path};
+
+use crate::{beigui::hue_wheel::AngularLabels, write_atomically, ScalarAttribute};
+
+#[derive(Debug)]
+pub enum PreferencesError {
+    IOError(io::Error),
+    Malformed(serde_json::Error),
+}
+
+impl std::fmt::Display for PreferencesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreferencesError::IOError(error) => write!(f, "I/O Error: {error}"),
+            PreferencesError::Malformed(error) => write!(f, "Malformed data: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for PreferencesError {}
+
+impl From<io::Error> for PreferencesError {
+    fn from(error: io::Error) -> Self {
+        PreferencesError::IOError(error)
+    }
+}
+
+impl From<serde_json::Error> for PreferencesError {
+    fn from(error: serde_json::Error) -> Self {
+        PreferencesError::Malformed(error)
+    }
+}
+
+/// The attribute display stack.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct AttributePreferences {
+    /// In display order (top first).
+    pub attributes: Vec<ScalarAttribute>,
+    pub include_hue: bool,
+    pub warmth_gradient: bool,
+}
+
+impl Default for AttributePreferences {
+    fn default() -> Self {
+        Self {
+            attributes: ScalarAttribute::ALL.to_vec(),
+            include_hue: true,
+            warmth_gradient: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct WheelPreferences {
+    /// The choices for the radial attribute (the first is the initial
+    /// choice).
+    pub attributes: Vec<ScalarAttribute>,
+    pub angular_labels: AngularLabels,
+    pub legend: bool,
+    pub find_entry: bool,
+}
+
+impl Default for WheelPreferences {
+    fn default() -> Self {
+        Self {
+            attributes: vec![ScalarAttribute::Chroma],
+            angular_labels: AngularLabels::default(),
+            legend: false,
+            find_entry: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct EditorPreferences {
+    pub harmony_swatches: bool,
+    pub hue_dial: bool,
+    pub sv_box: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct UiPreferences {
+    pub attributes: AttributePreferences,
+    pub wheel: WheelPreferences,
+    pub editor: EditorPreferences,
+}
+
+impl UiPreferences {
+    pub fn to_json(&self) -> Result<String, PreferencesError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, PreferencesError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PreferencesError> {
+        Ok(write_atomically(path, self.to_json()?)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, PreferencesError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// As `load()` but the defaults are returned if `path` doesn't exist
+    /// (e.g. on first use).
+    pub fn load_or_default(path: &Path) -> Result<Self, PreferencesError> {
+        match fs::read_to_string(path) {
+            Ok(json) => Self::from_json(&json),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod preferences_tests {
+    use super::*;
+
+    #[test]
+    fn save_and_load() {
+        let mut preferences = UiPreferences::default();
+        preferences.attributes.attributes = vec![ScalarAttribute::Value, ScalarAttribute::Chroma];
+        preferences.wheel.angular_labels = AngularLabels::HueNames;
+        preferences.editor.sv_box = true;
+        let dir = std::env::temp_dir().join(format!("colour_math_prefs_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("preferences.json");
+        // not to be mistaken for the temporary file
+        let other_path = dir.join("preferences.tmp");
+        fs::write(&other_path, "other").unwrap();
+        assert_eq!(
+            UiPreferences::load_or_default(&path).unwrap(),
+            UiPreferences::default()
+        );
+        assert!(matches!(
+            UiPreferences::load(&path),
+            Err(PreferencesError::IOError(_))
+        ));
+        preferences.save(&path).unwrap();
+        assert_eq!(UiPreferences::load(&path).unwrap(), preferences);
+        assert_eq!(UiPreferences::load_or_default(&path).unwrap(), preferences);
+        assert_eq!(fs::read_to_string(&other_path).unwrap(), "other");
+        assert!(!dir.join("preferences.json.tmp").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn partial_and_malformed() {
+        let preferences =
+            UiPreferences::from_json(r#"{"attributes": {"attributes": ["value", "Warmth"]}}"#)
+                .unwrap();
+        assert_eq!(
+            preferences.attributes.attributes,
+            vec![ScalarAttribute::Value, ScalarAttribute::Warmth]
+        );
+        assert!(preferences.attributes.include_hue);
+        assert_eq!(preferences.wheel, WheelPreferences::default());
+        assert!(matches!(
+            UiPreferences::from_json(r#"{"attributes": {"attributes": ["Hue"]}}"#),
+            Err(PreferencesError::Malformed(_))
+        ));
+    }
+}
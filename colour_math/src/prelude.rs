@@ -0,0 +1,12 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+// Only items in the `Stable` tier (see `api`) belong here.
+pub use crate::{
+    attributes::{Chroma, Greyness, Value, Warmth},
+    fdrn::{IntoProp, Prop},
+    hcv::HCV,
+    hue::{angle::Angle, Hue},
+    palette::{Palette, PaletteEntry},
+    rgb::RGB,
+    ColourAttributes, ColourBasics, ColourIfce, HueConstants, LightLevel, ManipulatedColour,
+    RGBConstants, ScalarAttribute,
+};
@@ -0,0 +1,140 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::fdrn::Prop;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Operation cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+/// Passed to long running operations so that they can report how far they
+/// have got and be asked to stop early.
+pub trait Progress {
+    fn report(&self, _done: Prop) {}
+
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+
+    /// Report `done` and return `Err(Cancelled)` if cancellation has been
+    /// requested (for use with `?`).
+    fn checkpoint(&self, done: Prop) -> Result<(), Cancelled> {
+        self.report(done);
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// For callers that don't care.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {}
+
+/// A cheaply cloned flag that may be set from another thread (or a GUI
+/// callback) to cancel an operation.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed)
+    }
+
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed)
+    }
+}
+
+impl Progress for CancelToken {
+    fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Combines a progress callback with an (optional) cancel token.
+pub struct ProgressReporter<F: Fn(Prop)> {
+    callback: F,
+    cancel_token: Option<CancelToken>,
+}
+
+impl<F: Fn(Prop)> ProgressReporter<F> {
+    pub fn new(callback: F) -> Self {
+        Self {
+            callback,
+            cancel_token: None,
+        }
+    }
+
+    pub fn with_cancel_token(callback: F, cancel_token: &CancelToken) -> Self {
+        Self {
+            callback,
+            cancel_token: Some(cancel_token.clone()),
+        }
+    }
+}
+
+impl<F: Fn(Prop)> Progress for ProgressReporter<F> {
+    fn report(&self, done: Prop) {
+        (self.callback)(done)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        match self.cancel_token {
+            Some(ref cancel_token) => cancel_token.is_cancelled(),
+            None => false,
+        }
+    }
+}
+
+/// The proportion of `total` represented by `count`.
+pub fn proportion(count: usize, total: usize) -> Prop {
+    if total == 0 {
+        Prop::ONE
+    } else {
+        Prop::from(count.min(total) as f64 / total as f64)
+    }
+}
+
+#[cfg(test)]
+mod progress_tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn report_and_cancel() {
+        let reports = RefCell::new(vec![]);
+        let token = CancelToken::new();
+        let reporter =
+            ProgressReporter::with_cancel_token(|done| reports.borrow_mut().push(done), &token);
+        assert_eq!(reporter.checkpoint(proportion(1, 4)), Ok(()));
+        token.clone().cancel();
+        assert_eq!(reporter.checkpoint(proportion(2, 4)), Err(Cancelled));
+        assert_eq!(*reports.borrow(), vec![Prop::from(0.25), Prop::from(0.5)]);
+        token.reset();
+        assert!(!reporter.is_cancelled());
+        assert_eq!(NoProgress.checkpoint(Prop::ONE), Ok(()));
+        assert_eq!(proportion(0, 0), Prop::ONE);
+    }
+}
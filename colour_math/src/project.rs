@@ -0,0 +1,438 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{fs, io, path::Path};
+
+use crate::{hcv::HCV, mixing::SubtractiveMixer, write_atomically, ColourBasics};
+
+/// The version written by this version of the crate.  Files with a higher
+/// version are rejected; files with a lower one are read with any missing
+/// fields defaulted.
+pub const PROJECT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum ProjectError {
+    IOError(io::Error),
+    Malformed(serde_json::Error),
+    UnsupportedVersion(u32),
+    UnknownPaint(PaintId),
+    InvalidColour(String),
+}
+
+impl std::fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectError::IOError(error) => write!(f, "I/O Error: {error}"),
+            ProjectError::Malformed(error) => write!(f, "Malformed data: {error}"),
+            ProjectError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported project format version: {version}")
+            }
+            ProjectError::UnknownPaint(id) => {
+                write!(f, "Unknown paint: {}: {}", id.series, id.name)
+            }
+            ProjectError::InvalidColour(name) => write!(f, "Invalid colour: {name}"),
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+impl From<io::Error> for ProjectError {
+    fn from(error: io::Error) -> Self {
+        ProjectError::IOError(error)
+    }
+}
+
+impl From<serde_json::Error> for ProjectError {
+    fn from(error: serde_json::Error) -> Self {
+        ProjectError::Malformed(error)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PaintId {
+    pub series: String,
+    pub name: String,
+}
+
+impl PaintId {
+    pub fn new(series: &str, name: &str) -> Self {
+        Self {
+            series: series.to_string(),
+            name: name.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Paint {
+    name: String,
+    colour: HCV,
+    #[serde(default)]
+    notes: String,
+}
+
+impl Paint {
+    pub fn new(name: &str, colour: &impl ColourBasics) -> Self {
+        Self {
+            name: name.to_string(),
+            colour: colour.hcv(),
+            notes: String::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn colour(&self) -> HCV {
+        self.colour
+    }
+
+    pub fn notes(&self) -> &str {
+        &self.notes
+    }
+
+    pub fn set_notes(&mut self, notes: &str) {
+        self.notes = notes.to_string()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct PaintSeries {
+    manufacturer: String,
+    name: String,
+    paints: Vec<Paint>,
+}
+
+impl PaintSeries {
+    pub fn new(manufacturer: &str, name: &str) -> Self {
+        Self {
+            manufacturer: manufacturer.to_string(),
+            name: name.to_string(),
+            paints: vec![],
+        }
+    }
+
+    pub fn manufacturer(&self) -> &str {
+        &self.manufacturer
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn paints(&self) -> &[Paint] {
+        &self.paints
+    }
+
+    pub fn paint(&self, name: &str) -> Option<&Paint> {
+        self.paints.iter().find(|paint| paint.name == name)
+    }
+
+    pub fn add_paint(&mut self, paint: Paint) {
+        self.paints.push(paint)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub paint: PaintId,
+    pub parts: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Mixture {
+    name: String,
+    components: Vec<Component>,
+    #[serde(default)]
+    target: Option<String>,
+    #[serde(default)]
+    notes: String,
+}
+
+impl Mixture {
+    pub fn new(name: &str, components: &[Component]) -> Self {
+        Self {
+            name: name.to_string(),
+            components: components.to_vec(),
+            target: None,
+            notes: String::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn components(&self) -> &[Component] {
+        &self.components
+    }
+
+    /// The name of the target that this mixture is intended to match.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    pub fn set_target(&mut self, target: Option<&str>) {
+        self.target = target.map(|target| target.to_string())
+    }
+
+    pub fn notes(&self) -> &str {
+        &self.notes
+    }
+
+    pub fn set_notes(&mut self, notes: &str) {
+        self.notes = notes.to_string()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    name: String,
+    colour: HCV,
+    #[serde(default)]
+    notes: String,
+}
+
+impl Target {
+    pub fn new(name: &str, colour: &impl ColourBasics) -> Self {
+        Self {
+            name: name.to_string(),
+            colour: colour.hcv(),
+            notes: String::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn colour(&self) -> HCV {
+        self.colour
+    }
+
+    pub fn notes(&self) -> &str {
+        &self.notes
+    }
+
+    pub fn set_notes(&mut self, notes: &str) {
+        self.notes = notes.to_string()
+    }
+}
+
+#[derive(Deserialize)]
+struct VersionOnly {
+    version: u32,
+}
+
+/// Paint series, the mixtures made from them and the colours that those
+/// mixtures are intended to match.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Project {
+    version: u32,
+    #[serde(default)]
+    notes: String,
+    #[serde(default)]
+    series: Vec<PaintSeries>,
+    #[serde(default)]
+    mixtures: Vec<Mixture>,
+    #[serde(default)]
+    targets: Vec<Target>,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self {
+            version: PROJECT_FORMAT_VERSION,
+            notes: String::new(),
+            series: vec![],
+            mixtures: vec![],
+            targets: vec![],
+        }
+    }
+}
+
+impl Project {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn notes(&self) -> &str {
+        &self.notes
+    }
+
+    pub fn set_notes(&mut self, notes: &str) {
+        self.notes = notes.to_string()
+    }
+
+    pub fn series(&self) -> &[PaintSeries] {
+        &self.series
+    }
+
+    pub fn add_series(&mut self, series: PaintSeries) {
+        self.series.push(series)
+    }
+
+    pub fn paint(&self, id: &PaintId) -> Option<&Paint> {
+        self.series
+            .iter()
+            .find(|series| series.name == id.series)?
+            .paint(&id.name)
+    }
+
+    pub fn mixtures(&self) -> &[Mixture] {
+        &self.mixtures
+    }
+
+    /// Fails if the mixture uses a paint that isn't in the project.
+    pub fn add_mixture(&mut self, mixture: Mixture) -> Result<(), ProjectError> {
+        if let Some(component) = mixture
+            .components
+            .iter()
+            .find(|component| self.paint(&component.paint).is_none())
+        {
+            return Err(ProjectError::UnknownPaint(component.paint.clone()));
+        }
+        self.mixtures.push(mixture);
+        Ok(())
+    }
+
+    pub fn targets(&self) -> &[Target] {
+        &self.targets
+    }
+
+    pub fn target(&self, name: &str) -> Option<&Target> {
+        self.targets.iter().find(|target| target.name == name)
+    }
+
+    pub fn add_target(&mut self, target: Target) {
+        self.targets.push(target)
+    }
+
+    /// The colour resulting from mixing the mixture's components.
+    pub fn mixed_colour(&self, mixture: &Mixture) -> Result<Option<HCV>, ProjectError> {
+        let mut mixer = SubtractiveMixer::new();
+        for component in mixture.components.iter() {
+            let paint = self
+                .paint(&component.paint)
+                .ok_or_else(|| ProjectError::UnknownPaint(component.paint.clone()))?;
+            mixer.add(&paint.colour, component.parts);
+        }
+        Ok(mixer.mixed_colour())
+    }
+
+    pub fn to_json(&self) -> Result<String, ProjectError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ProjectError> {
+        let version_only: VersionOnly = serde_json::from_str(json)?;
+        if version_only.version > PROJECT_FORMAT_VERSION {
+            return Err(ProjectError::UnsupportedVersion(version_only.version));
+        }
+        let mut project: Self = serde_json::from_str(json)?;
+        project.version = PROJECT_FORMAT_VERSION;
+        // the file may have been edited (or corrupted) so colours can't be
+        // trusted to be consistent
+        for series in project.series.iter() {
+            for paint in series.paints.iter() {
+                if !paint.colour.is_valid() {
+                    return Err(ProjectError::InvalidColour(paint.name.clone()));
+                }
+            }
+        }
+        for target in project.targets.iter() {
+            if !target.colour.is_valid() {
+                return Err(ProjectError::InvalidColour(target.name.clone()));
+            }
+        }
+        Ok(project)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ProjectError> {
+        Ok(write_atomically(path, self.to_json()?)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ProjectError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod project_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants, Value};
+
+    #[test]
+    fn save_and_load() {
+        let mut series = PaintSeries::new("Acme", "Basics");
+        series.add_paint(Paint::new("Red", &HCV::RED));
+        series.add_paint(Paint::new("Yellow", &HCV::YELLOW));
+        series.add_paint(Paint::new("White", &HCV::WHITE));
+        let mut project = Project::new();
+        project.add_series(series);
+        project.add_target(Target::new("Sunset", &HCV::new_grey(Value::ONE / 2)));
+        let mut mixture = Mixture::new(
+            "Orange",
+            &[
+                Component {
+                    paint: PaintId::new("Basics", "Red"),
+                    parts: 1,
+                },
+                Component {
+                    paint: PaintId::new("Basics", "Yellow"),
+                    parts: 1,
+                },
+            ],
+        );
+        mixture.set_target(Some("Sunset"));
+        project.add_mixture(mixture).unwrap();
+        assert!(matches!(
+            project.add_mixture(Mixture::new(
+                "Bad",
+                &[Component {
+                    paint: PaintId::new("Basics", "Blue"),
+                    parts: 1
+                }]
+            )),
+            Err(ProjectError::UnknownPaint(_))
+        ));
+        let mixed = project.mixed_colour(&project.mixtures()[0]).unwrap();
+        let mixed = mixed.unwrap();
+        assert!(mixed.hue().is_some() && mixed.hue() != HCV::RED.hue());
+        let json = project.to_json().unwrap();
+        assert_eq!(Project::from_json(&json).unwrap(), project);
+        let path = std::env::temp_dir().join("colour_math_project_test.json");
+        project.save(&path).unwrap();
+        assert_eq!(Project::load(&path).unwrap(), project);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn versioning() {
+        let project = Project::from_json(r#"{"version": 0}"#).unwrap();
+        assert_eq!(project, Project::new());
+        assert!(matches!(
+            Project::from_json(r#"{"version": 99}"#),
+            Err(ProjectError::UnsupportedVersion(99))
+        ));
+        assert!(matches!(
+            Project::from_json("rubbish"),
+            Err(ProjectError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_colour() {
+        let mut series = PaintSeries::new("Acme", "Basics");
+        series.add_paint(Paint::new("Red", &HCV::RED));
+        let mut project = Project::new();
+        project.add_series(series);
+        let mut json = serde_json::to_value(&project).unwrap();
+        // full chroma red with a sum of zero is impossible
+        json["series"][0]["paints"][0]["colour"]["sum"] = serde_json::json!(0);
+        assert!(matches!(
+            Project::from_json(&json.to_string()),
+            Err(ProjectError::InvalidColour(_))
+        ));
+    }
+}
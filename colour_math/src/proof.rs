@@ -0,0 +1,92 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! Soft proofing i.e. showing on the (sRGB) display how colours will come
+//! out on a target device (e.g. a printer) with a smaller gamut.  The
+//! target is described by an `RGBSpace` and colours outside its gamut are
+//! clipped (relative colorimetric with no paper simulation).
+use crate::{fdrn::Prop, rgb::RGB, rgb_space::RGBSpace, ColourBasics, LightLevel};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoftProof {
+    target: RGBSpace,
+    tolerance: f64,
+}
+
+impl SoftProof {
+    pub fn new(target: RGBSpace) -> Self {
+        Self {
+            target,
+            tolerance: 0.000_1,
+        }
+    }
+
+    /// How far (in linear light) outside the target's range a component
+    /// may be and still be considered in gamut.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance.abs();
+        self
+    }
+
+    pub fn target(&self) -> RGBSpace {
+        self.target
+    }
+
+    pub fn is_in_gamut(&self, colour: &impl ColourBasics) -> bool {
+        let linear = RGBSpace::SRGB.decode(&colour.rgb::<f64>());
+        RGBSpace::SRGB
+            .convert_linear(linear, &self.target)
+            .iter()
+            .all(|component| (-self.tolerance..=1.0 + self.tolerance).contains(component))
+    }
+
+    /// How `rgb` would look if reproduced by the target.
+    pub fn proofed<L: LightLevel>(&self, rgb: &RGB<L>) -> RGB<L> {
+        let on_target = RGBSpace::SRGB.convert(&rgb.rgb::<f64>(), &self.target);
+        let proofed = self.target.convert(&on_target, &RGBSpace::SRGB);
+        RGB::from(<[Prop; 3]>::from(proofed))
+    }
+
+    /// The proofed colour and whether the original was in gamut.
+    pub fn proof<L: LightLevel>(&self, rgb: &RGB<L>) -> (RGB<L>, bool) {
+        (self.proofed(rgb), self.is_in_gamut(rgb))
+    }
+}
+
+#[cfg(test)]
+mod proof_tests {
+    use super::*;
+    use crate::{standards::WhitePoint, transfer::TransferFunction, RGBConstants};
+
+    fn narrow() -> SoftProof {
+        SoftProof::new(
+            RGBSpace::new(
+                "narrow",
+                [(0.50, 0.33), (0.32, 0.50), (0.20, 0.15)],
+                WhitePoint::D65,
+                TransferFunction::Srgb,
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn proofing() {
+        let proof = narrow();
+        let tolerance = Some(Prop::from(0.000_1));
+        for rgb in [
+            RGB::<f64>::WHITE,
+            RGB::<f64>::BLACK,
+            RGB::from([0.5, 0.5, 0.5]),
+        ] {
+            let (proofed, in_gamut) = proof.proof(&rgb);
+            assert!(in_gamut);
+            assert!(proofed.approx_eq(&rgb, tolerance));
+        }
+        let (proofed, in_gamut) = proof.proof(&RGB::<f64>::RED);
+        assert!(!in_gamut);
+        assert!(!proofed.approx_eq(&RGB::<f64>::RED, tolerance));
+        assert!(proof.is_in_gamut(&proofed));
+        let wide = SoftProof::new(RGBSpace::REC_2020);
+        assert!(wide.is_in_gamut(&RGB::<u8>::GREEN));
+        assert_eq!(wide.proofed(&RGB::<u8>::CYAN), RGB::<u8>::CYAN);
+    }
+}
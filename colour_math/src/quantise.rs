@@ -0,0 +1,340 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! Reduction of an image's pixels to a palette of (at most) N colours
+//! each with the number of pixels that it represents.
+use std::str::FromStr;
+
+use crate::{
+    fdrn::Prop,
+    hcv::HCV,
+    progress::{self, Cancelled, NoProgress, Progress},
+    rgb::RGB,
+    ColourBasics, LightLevel,
+};
+
+/// Colours (most popular first) and the number of pixels each represents.
+pub type ColourCounts = Vec<(HCV, usize)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quantisation {
+    #[default]
+    MedianCut,
+    Octree,
+}
+
+impl Quantisation {
+    pub fn palette<L: LightLevel>(&self, pixels: &[RGB<L>], count: usize) -> ColourCounts {
+        self.palette_with_progress(pixels, count, &NoProgress)
+            .expect("NoProgress never cancels")
+    }
+
+    pub fn palette_with_progress<L: LightLevel>(
+        &self,
+        pixels: &[RGB<L>],
+        count: usize,
+        progress: &impl Progress,
+    ) -> Result<ColourCounts, Cancelled> {
+        match self {
+            Quantisation::MedianCut => median_cut_with_progress(pixels, count, progress),
+            Quantisation::Octree => octree_with_progress(pixels, count, progress),
+        }
+    }
+}
+
+impl FromStr for Quantisation {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string.to_lowercase().as_str() {
+            "median_cut" | "median-cut" | "mediancut" => Ok(Quantisation::MedianCut),
+            "octree" => Ok(Quantisation::Octree),
+            _ => Err(format!("{string}: unknown quantisation method")),
+        }
+    }
+}
+
+fn by_population(mut colour_counts: ColourCounts) -> ColourCounts {
+    colour_counts.sort_by(|lhs, rhs| rhs.1.cmp(&lhs.1));
+    colour_counts
+}
+
+fn mean(sums: [u64; 3], count: usize) -> HCV {
+    let divisor = (count.max(1) * 255) as f64;
+    HCV::from(RGB::<f64>::from(sums.map(|sum| sum as f64 / divisor)))
+}
+
+fn components<L: LightLevel>(pixel: &RGB<L>) -> [u8; 3] {
+    let rgb = pixel.rgb::<u8>();
+    [rgb[0], rgb[1], rgb[2]]
+}
+
+struct PixelBox(Vec<[u8; 3]>);
+
+impl PixelBox {
+    // The channel with the widest range and that range
+    fn widest_channel(&self) -> (usize, u8) {
+        (0..3)
+            .map(|channel| {
+                let (min, max) = self.0.iter().fold((u8::MAX, 0), |(min, max), rgb| {
+                    (min.min(rgb[channel]), max.max(rgb[channel]))
+                });
+                (channel, max.saturating_sub(min))
+            })
+            .fold((0, 0), |widest, candidate| {
+                if candidate.1 > widest.1 {
+                    candidate
+                } else {
+                    widest
+                }
+            })
+    }
+
+    // At the median of the widest channel (whose range must be non zero)
+    // adjusted so that equal components end up in the same box.
+    fn split(mut self) -> (Self, Self) {
+        let (channel, _) = self.widest_channel();
+        self.0.sort_by_key(|rgb| rgb[channel]);
+        let median = self.0[self.0.len() / 2][channel];
+        let mut index = self.0.partition_point(|rgb| rgb[channel] < median);
+        if index == 0 {
+            index = self.0.partition_point(|rgb| rgb[channel] <= median);
+        }
+        let upper = self.0.split_off(index);
+        (self, Self(upper))
+    }
+
+    fn colour(&self) -> (HCV, usize) {
+        let mut sums = [0_u64; 3];
+        for rgb in self.0.iter() {
+            for (sum, component) in sums.iter_mut().zip(rgb.iter()) {
+                *sum += *component as u64;
+            }
+        }
+        (mean(sums, self.0.len()), self.0.len())
+    }
+}
+
+/// Up to `count` colours found by repeatedly splitting the box (in RGB
+/// space) with the widest range at the median of that range.
+pub fn median_cut<L: LightLevel>(pixels: &[RGB<L>], count: usize) -> ColourCounts {
+    median_cut_with_progress(pixels, count, &NoProgress).expect("NoProgress never cancels")
+}
+
+pub fn median_cut_with_progress<L: LightLevel>(
+    pixels: &[RGB<L>],
+    count: usize,
+    progress: &impl Progress,
+) -> Result<ColourCounts, Cancelled> {
+    if pixels.is_empty() || count == 0 {
+        return Ok(vec![]);
+    }
+    let mut boxes = vec![PixelBox(pixels.iter().map(components).collect())];
+    while boxes.len() < count {
+        progress.checkpoint(progress::proportion(boxes.len(), count))?;
+        let candidate = boxes
+            .iter()
+            .enumerate()
+            .map(|(index, pixel_box)| (index, pixel_box.widest_channel().1))
+            .filter(|(_, range)| *range > 0)
+            .max_by_key(|(_, range)| *range);
+        match candidate {
+            Some((index, _)) => {
+                let (lower, upper) = boxes.swap_remove(index).split();
+                boxes.push(lower);
+                boxes.push(upper);
+            }
+            None => break,
+        }
+    }
+    progress.report(Prop::ONE);
+    Ok(by_population(boxes.iter().map(PixelBox::colour).collect()))
+}
+
+const OCTREE_DEPTH: usize = 8;
+
+#[derive(Default)]
+struct OctreeNode {
+    level: usize,
+    sums: [u64; 3],
+    count: usize,
+    children: [Option<usize>; 8],
+}
+
+impl OctreeNode {
+    fn is_leaf(&self) -> bool {
+        self.children.iter().all(Option::is_none)
+    }
+}
+
+/// Up to `count` colours found by building an octree (eight levels deep)
+/// of the pixels' components and merging the least popular leaves of the
+/// deepest level into their parents until few enough remain.
+pub fn octree<L: LightLevel>(pixels: &[RGB<L>], count: usize) -> ColourCounts {
+    octree_with_progress(pixels, count, &NoProgress).expect("NoProgress never cancels")
+}
+
+// How many pixels are added to the octree between progress checkpoints
+const OCTREE_CHECKPOINT_INTERVAL: usize = 4096;
+
+pub fn octree_with_progress<L: LightLevel>(
+    pixels: &[RGB<L>],
+    count: usize,
+    progress: &impl Progress,
+) -> Result<ColourCounts, Cancelled> {
+    if pixels.is_empty() || count == 0 {
+        return Ok(vec![]);
+    }
+    let mut nodes = vec![OctreeNode::default()];
+    let mut leaves = 0;
+    for (pixel_index, pixel) in pixels.iter().enumerate() {
+        if pixel_index % OCTREE_CHECKPOINT_INTERVAL == 0 {
+            progress.checkpoint(progress::proportion(pixel_index, pixels.len()))?;
+        }
+        let rgb = components(pixel);
+        let mut index = 0;
+        for level in 0..OCTREE_DEPTH {
+            let shift = 7 - level;
+            let octant = ((((rgb[0] >> shift) & 1) << 2)
+                | (((rgb[1] >> shift) & 1) << 1)
+                | ((rgb[2] >> shift) & 1)) as usize;
+            index = match nodes[index].children[octant] {
+                Some(child) => child,
+                None => {
+                    let child = nodes.len();
+                    nodes.push(OctreeNode {
+                        level: level + 1,
+                        ..OctreeNode::default()
+                    });
+                    nodes[index].children[octant] = Some(child);
+                    if level + 1 == OCTREE_DEPTH {
+                        leaves += 1;
+                    }
+                    child
+                }
+            };
+        }
+        let leaf = &mut nodes[index];
+        for (sum, component) in leaf.sums.iter_mut().zip(rgb.iter()) {
+            *sum += *component as u64;
+        }
+        leaf.count += 1;
+    }
+    // Once a level has been completely reduced all the children of the
+    // nodes at the level above are leaves.
+    for level in (0..OCTREE_DEPTH).rev() {
+        if leaves <= count {
+            break;
+        }
+        let mut reducible: Vec<usize> = (0..nodes.len())
+            .filter(|index| nodes[*index].level == level && !nodes[*index].is_leaf())
+            .collect();
+        for index in reducible.iter() {
+            let population = nodes[*index]
+                .children
+                .iter()
+                .flatten()
+                .map(|child| nodes[*child].count)
+                .sum::<usize>();
+            nodes[*index].count = population;
+        }
+        reducible.sort_by_key(|index| nodes[*index].count);
+        for index in reducible {
+            if leaves <= count {
+                break;
+            }
+            let children = std::mem::take(&mut nodes[index].children);
+            let mut sums = [0_u64; 3];
+            for child in children.iter().flatten() {
+                for (sum, component) in sums.iter_mut().zip(nodes[*child].sums.iter()) {
+                    *sum += component;
+                }
+                nodes[*child].count = 0;
+                leaves -= 1;
+            }
+            nodes[index].sums = sums;
+            leaves += 1;
+        }
+    }
+    progress.report(Prop::ONE);
+    Ok(by_population(
+        nodes
+            .iter()
+            .filter(|node| node.is_leaf() && node.count > 0)
+            .map(|node| (mean(node.sums, node.count), node.count))
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod quantise_tests {
+    use super::*;
+    use crate::{progress::CancelToken, RGBConstants};
+
+    fn pixels() -> Vec<RGB<u8>> {
+        let mut pixels = vec![RGB::<u8>::RED; 7];
+        pixels.extend([RGB::<u8>::BLUE; 3]);
+        pixels.extend([RGB::<u8>::YELLOW; 2]);
+        pixels
+    }
+
+    #[test]
+    fn palettes() {
+        for method in [Quantisation::MedianCut, Quantisation::Octree] {
+            let palette = method.palette(&pixels(), 3);
+            assert_eq!(
+                palette,
+                vec![(HCV::RED, 7), (HCV::BLUE, 3), (HCV::YELLOW, 2)],
+                "{method:?}"
+            );
+            // an octree may have to merge more than one pair of colours
+            let palette = method.palette(&pixels(), 2);
+            assert!(!palette.is_empty() && palette.len() <= 2, "{method:?}");
+            assert_eq!(palette.iter().map(|(_, n)| n).sum::<usize>(), 12);
+            // can't have more colours than there are
+            assert_eq!(method.palette(&pixels(), 4).len(), 3, "{method:?}");
+            assert_eq!(
+                method.palette(&[RGB::<u8>::CYAN; 5], 3),
+                vec![(HCV::CYAN, 5)],
+                "{method:?}"
+            );
+            assert!(method.palette::<u8>(&[], 3).is_empty());
+            assert!(method.palette(&pixels(), 0).is_empty());
+        }
+    }
+
+    #[test]
+    fn octree_reduces_to_count() {
+        let pixels: Vec<RGB<u8>> = (0..=255_u8)
+            .map(|i| RGB::from([i, 255 - i, i / 2]))
+            .collect();
+        for count in [1, 2, 5, 16, 100] {
+            let palette = octree(&pixels, count);
+            assert!(!palette.is_empty() && palette.len() <= count);
+            assert_eq!(palette.iter().map(|(_, n)| n).sum::<usize>(), 256);
+        }
+    }
+
+    #[test]
+    fn cancellation() {
+        let token = CancelToken::new();
+        token.cancel();
+        for method in [Quantisation::MedianCut, Quantisation::Octree] {
+            let result = method.palette_with_progress(&pixels(), 3, &token);
+            assert_eq!(result, Err(Cancelled), "{method:?}");
+        }
+        token.reset();
+        assert_eq!(
+            median_cut_with_progress(&pixels(), 3, &token),
+            Ok(median_cut(&pixels(), 3))
+        );
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!(
+            "median-cut".parse::<Quantisation>(),
+            Ok(Quantisation::MedianCut)
+        );
+        assert_eq!("Octree".parse::<Quantisation>(), Ok(Quantisation::Octree));
+        assert!("nonesuch".parse::<Quantisation>().is_err());
+    }
+}
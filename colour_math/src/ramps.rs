@@ -0,0 +1,377 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    fdrn::{FDRNumber, Prop, UFDRNumber},
+    hcv::HCV,
+    hue::{angle::Angle, Hue, HueIfce},
+    lab::Lab,
+    palette::{Palette, PaletteEntry},
+    ColourBasics,
+};
+
+/// How the steps of a generated ramp are spaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Spacing {
+    /// Equal steps in the sum of the RGB components (bunches up visually at
+    /// the dark end).
+    #[default]
+    Sum,
+    /// Equal steps in CIE L* (perceived lightness).
+    Lightness,
+}
+
+// CIE L* (0.0 to 100.0) of an sRGB colour.
+fn lightness(hcv: &HCV) -> f64 {
+    Lab::from(hcv).l
+}
+
+/// A sequence of shades of (approximately) the same hue ordered from
+/// darkest to lightest.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Ramp {
+    entries: Vec<PaletteEntry>,
+}
+
+impl Ramp {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[PaletteEntry] {
+        &self.entries
+    }
+
+    pub fn colours(&self) -> Vec<HCV> {
+        self.entries.iter().map(|e| e.colour()).collect()
+    }
+
+    pub fn is_grey(&self) -> bool {
+        self.entries.iter().all(|e| e.colour().is_grey())
+    }
+
+    /// The hue of the most chromatic member of the ramp.
+    pub fn hue(&self) -> Option<Hue> {
+        self.entries
+            .iter()
+            .filter(|e| !e.colour().is_grey())
+            .max_by_key(|e| e.colour().chroma_prop())?
+            .colour()
+            .hue()
+    }
+
+    pub fn hue_angle(&self) -> Option<Angle> {
+        Some(self.hue()?.angle())
+    }
+
+    pub fn to_palette(&self, name: &str) -> Palette {
+        let mut palette: Palette = self.entries.iter().cloned().collect();
+        palette.set_name(name);
+        palette
+    }
+
+    fn angle_of(&self, hcv: &HCV) -> Option<Angle> {
+        match hcv.hue_angle() {
+            Some(angle) => Some(angle),
+            None => self.hue_angle(),
+        }
+    }
+
+    fn interpolated(&self, from: &HCV, to: &HCV, fraction: f64) -> HCV {
+        let fraction = FDRNumber::from(fraction);
+        let sum = FDRNumber::from(from.sum)
+            + (FDRNumber::from(to.sum) - FDRNumber::from(from.sum)) * fraction;
+        let c_prop = FDRNumber::from(from.c_prop)
+            + (FDRNumber::from(to.c_prop) - FDRNumber::from(from.c_prop)) * fraction;
+        let hue = match (self.angle_of(from), self.angle_of(to)) {
+            (Some(first), Some(second)) => Some(Hue::from(
+                first + Angle::from(f64::from(second - first) * f64::from(fraction)),
+            )),
+            _ => None,
+        };
+        HCV::new_approx(hue, sum.into(), c_prop.into())
+    }
+
+    /// Suggest a colour midway (in hue, chroma and sum) between each pair
+    /// of adjacent members of the ramp.
+    pub fn intermediates(&self) -> Vec<HCV> {
+        self.colours()
+            .windows(2)
+            .map(|pair| self.interpolated(&pair[0], &pair[1], 0.5))
+            .collect()
+    }
+
+    /// Generate `count` colours running from the darkest to the lightest
+    /// member of the ramp and following its chroma/hue progression.
+    pub fn resampled(&self, count: usize, spacing: Spacing) -> Vec<HCV> {
+        let colours = self.colours();
+        match (colours.len(), count) {
+            (0, _) | (_, 0) => return vec![],
+            (1, _) | (_, 1) => return vec![colours[0]],
+            _ => (),
+        }
+        let metric = |hcv: &HCV| match spacing {
+            Spacing::Sum => f64::from(hcv.sum),
+            Spacing::Lightness => lightness(hcv),
+        };
+        let positions: Vec<f64> = colours.iter().map(metric).collect();
+        let first = positions[0];
+        let last = positions[positions.len() - 1];
+        let mut resampled = vec![];
+        for i in 0..count {
+            let target = first + (last - first) * i as f64 / (count - 1) as f64;
+            let segment = (1..positions.len())
+                .find(|j| positions[*j] >= target)
+                .unwrap_or(positions.len() - 1);
+            let (from, to) = (&colours[segment - 1], &colours[segment]);
+            let span = positions[segment] - positions[segment - 1];
+            let mut fraction = if span > 0.0 {
+                ((target - positions[segment - 1]) / span).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            if spacing == Spacing::Lightness {
+                // L* isn't linear in sum so home in on the target
+                let (mut lo, mut hi) = (0.0, 1.0);
+                for _ in 0..24 {
+                    fraction = (lo + hi) / 2.0;
+                    if metric(&self.interpolated(from, to, fraction)) < target {
+                        lo = fraction;
+                    } else {
+                        hi = fraction;
+                    }
+                }
+            }
+            resampled.push(self.interpolated(from, to, fraction));
+        }
+        resampled
+    }
+
+    fn extrapolated(&self, from: &HCV, to: &HCV, count: usize) -> Vec<HCV> {
+        let sum_step = FDRNumber::from(to.sum) - FDRNumber::from(from.sum);
+        let c_prop_step = FDRNumber::from(to.c_prop) - FDRNumber::from(from.c_prop);
+        let angle_step = match (self.angle_of(from), self.angle_of(to)) {
+            (Some(first), Some(second)) => Some((second, second - first)),
+            _ => None,
+        };
+        let mut suggestions = vec![];
+        for k in 1..=count {
+            let sum = FDRNumber::from(to.sum) + sum_step * k as i32;
+            if sum <= FDRNumber::ZERO || sum >= FDRNumber::ONE * 3 {
+                break;
+            }
+            let c_prop = (FDRNumber::from(to.c_prop) + c_prop_step * k as i32)
+                .max(FDRNumber::ZERO)
+                .min(FDRNumber::ONE);
+            let hue = angle_step.map(|(angle, step)| {
+                let mut angle = angle;
+                for _ in 0..k {
+                    angle = angle + step;
+                }
+                Hue::from(angle)
+            });
+            suggestions.push(HCV::new_approx(hue, sum.into(), c_prop.into()));
+        }
+        suggestions
+    }
+
+    /// Suggest up to `darker` colours extending the dark end of the ramp and
+    /// up to `lighter` colours extending its light end by continuing the
+    /// progression of its two end members.  Fewer suggestions are returned
+    /// if the progression runs into black or white.
+    pub fn extensions(&self, darker: usize, lighter: usize) -> (Vec<HCV>, Vec<HCV>) {
+        let colours = self.colours();
+        match colours.len() {
+            0 | 1 => (vec![], vec![]),
+            len => (
+                self.extrapolated(&colours[1], &colours[0], darker),
+                self.extrapolated(&colours[len - 2], &colours[len - 1], lighter),
+            ),
+        }
+    }
+
+    fn sort(&mut self) {
+        self.entries.sort_by_key(|e| e.colour().value());
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RampDetector {
+    hue_tolerance: Angle,
+    grey_threshold: Prop,
+}
+
+impl Default for RampDetector {
+    fn default() -> Self {
+        Self {
+            hue_tolerance: Angle::from(15),
+            grey_threshold: Prop::from(0.05),
+        }
+    }
+}
+
+impl RampDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The largest hue difference between adjacent members of a ramp.
+    pub fn hue_tolerance(&mut self, hue_tolerance: Angle) -> &mut Self {
+        self.hue_tolerance = hue_tolerance;
+        self
+    }
+
+    /// Colours whose chroma is below this threshold are treated as greys.
+    pub fn grey_threshold(&mut self, grey_threshold: Prop) -> &mut Self {
+        self.grey_threshold = grey_threshold;
+        self
+    }
+
+    /// Group the palette's colours into ramps.  Hued ramps are returned in
+    /// hue order (from CYAN to CYAN via BLUE, RED and GREEN) followed by
+    /// the grey ramp (if any).
+    pub fn detect(&self, palette: &Palette) -> Vec<Ramp> {
+        let mut greys = Ramp::default();
+        let mut hued: Vec<(f64, &PaletteEntry)> = vec![];
+        for entry in palette.iter() {
+            let colour = entry.colour();
+            match colour.hue_angle() {
+                Some(angle) if colour.chroma_prop() >= self.grey_threshold => {
+                    hued.push((f64::from(angle), entry))
+                }
+                _ => greys.entries.push(entry.clone()),
+            }
+        }
+        hued.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut ramps = vec![];
+        if !hued.is_empty() {
+            // Start the sweep after the biggest gap so that a ramp straddling
+            // the -180/180 boundary isn't split in two.
+            let gap = |i: usize| {
+                let next = hued[(i + 1) % hued.len()].0;
+                let this = hued[i].0;
+                if next > this {
+                    next - this
+                } else {
+                    next - this + 360.0
+                }
+            };
+            let biggest = (0..hued.len())
+                .max_by(|a, b| gap(*a).total_cmp(&gap(*b)))
+                .expect("hued is not empty");
+            let tolerance = f64::from(self.hue_tolerance).abs();
+            let mut ramp = Ramp::default();
+            for step in 1..=hued.len() {
+                let i = (biggest + step) % hued.len();
+                ramp.entries.push(hued[i].1.clone());
+                if gap(i) > tolerance || step == hued.len() {
+                    ramp.sort();
+                    ramps.push(std::mem::take(&mut ramp));
+                }
+            }
+            ramps.sort_by_key(|ramp| ramp.hue_angle());
+        }
+        if !greys.is_empty() {
+            greys.sort();
+            ramps.push(greys);
+        }
+        ramps
+    }
+}
+
+#[cfg(test)]
+mod ramps_tests {
+    use super::*;
+    use crate::RGB;
+
+    fn palette(rgbs: &[[f64; 3]]) -> Palette {
+        rgbs.iter()
+            .enumerate()
+            .map(|(i, rgb)| PaletteEntry::new(&format!("{i}"), &RGB::<f64>::from(*rgb)))
+            .collect()
+    }
+
+    fn names(ramp: &Ramp) -> Vec<&str> {
+        ramp.entries().iter().map(|e| e.name()).collect()
+    }
+
+    #[test]
+    fn detect_ramps() {
+        let palette = palette(&[
+            [0.5, 0.5, 0.5],
+            [0.9, 0.1, 0.1],
+            [0.0, 0.0, 0.5],
+            [0.3, 0.0, 0.0],
+            [0.2, 0.2, 0.2],
+            [0.1, 0.1, 0.9],
+            [0.6, 0.05, 0.05],
+        ]);
+        let ramps = RampDetector::new().detect(&palette);
+        assert_eq!(ramps.len(), 3);
+        assert_eq!(names(&ramps[0]), vec!["2", "5"]);
+        assert_eq!(names(&ramps[1]), vec!["3", "6", "1"]);
+        assert_eq!(names(&ramps[2]), vec!["4", "0"]);
+        assert!(!ramps[0].is_grey());
+        assert!(ramps[2].is_grey());
+        assert_eq!(ramps[2].hue(), None);
+    }
+
+    #[test]
+    fn detect_ramps_across_boundary() {
+        let palette = palette(&[[0.0, 0.8, 0.75], [0.0, 0.5, 0.55], [0.0, 0.9, 0.9]]);
+        let ramps = RampDetector::new().detect(&palette);
+        assert_eq!(ramps.len(), 1);
+        assert_eq!(names(&ramps[0]), vec!["1", "0", "2"]);
+        let ramps = RampDetector::new()
+            .hue_tolerance(Angle::from(1))
+            .detect(&palette);
+        assert_eq!(ramps.len(), 3);
+    }
+
+    #[test]
+    fn complete_ramp() {
+        let palette = palette(&[[0.3, 0.0, 0.0], [0.6, 0.05, 0.05], [0.9, 0.1, 0.1]]);
+        let ramp = &RampDetector::new().detect(&palette)[0];
+        let colours = ramp.colours();
+        let intermediates = ramp.intermediates();
+        assert_eq!(intermediates.len(), 2);
+        for (i, hcv) in intermediates.iter().enumerate() {
+            assert_eq!(hcv.hue(), Some(Hue::RED));
+            assert!(hcv.value() > colours[i].value());
+            assert!(hcv.value() < colours[i + 1].value());
+        }
+        let (darker, lighter) = ramp.extensions(2, 2);
+        assert!(darker.is_empty());
+        assert_eq!(lighter.len(), 2);
+        assert_eq!(lighter[0].hue(), Some(Hue::RED));
+        assert!(lighter[0].value() > colours[2].value());
+        assert!(lighter[1].value() > lighter[0].value());
+        let (darker, lighter) = ramp.extensions(0, 100);
+        assert!(darker.is_empty());
+        assert!(lighter.len() < 100);
+    }
+
+    #[test]
+    fn resample_ramp() {
+        let palette = palette(&[[0.1, 0.0, 0.0], [0.6, 0.05, 0.05], [0.9, 0.3, 0.3]]);
+        let ramp = &RampDetector::new().detect(&palette)[0];
+        assert!(ramp.resampled(0, Spacing::Sum).is_empty());
+        let by_sum = ramp.resampled(5, Spacing::Sum);
+        assert_eq!(by_sum.len(), 5);
+        assert_eq!(by_sum[0], ramp.colours()[0]);
+        let by_lightness = ramp.resampled(5, Spacing::Lightness);
+        assert_eq!(by_lightness.len(), 5);
+        let steps: Vec<f64> = by_lightness
+            .windows(2)
+            .map(|pair| lightness(&pair[1]) - lightness(&pair[0]))
+            .collect();
+        for step in &steps {
+            assert!((step - steps[0]).abs() < 1.0, "{steps:?}");
+        }
+        // linear in sum gives bigger lightness jumps at the dark end
+        assert!(lightness(&by_sum[1]) - lightness(&by_sum[0]) > steps[0]);
+    }
+}
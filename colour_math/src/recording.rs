@@ -0,0 +1,204 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::cell::RefCell;
+
+use crate::{
+    fdrn::{Prop, UFDRNumber},
+    hcv::HCV,
+    hue::{angle::Angle, Hue},
+    manipulator::{ColourManipulator, Outcome, SetHue, SetScalar},
+};
+
+/// A colour operation (with its parameters) as performed on a
+/// `ColourManipulator`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    SetColour(HCV),
+    IncrValue(Prop),
+    DecrValue(Prop),
+    IncrChroma(Prop),
+    DecrChroma(Prop),
+    Rotate(Angle),
+    SetChroma(Prop, SetScalar),
+    SetSum(UFDRNumber, SetScalar),
+    SetHue(Hue, SetHue),
+}
+
+impl Operation {
+    /// Perform the operation on `manipulator` returning whether it
+    /// succeeded.
+    pub fn apply(&self, manipulator: &mut ColourManipulator) -> bool {
+        let ok = |outcome: Outcome| !matches!(outcome, Outcome::Rejected);
+        match *self {
+            Operation::SetColour(colour) => {
+                manipulator.set_colour(&colour);
+                true
+            }
+            Operation::IncrValue(delta) => manipulator.incr_value(delta),
+            Operation::DecrValue(delta) => manipulator.decr_value(delta),
+            Operation::IncrChroma(delta) => manipulator.incr_chroma(delta),
+            Operation::DecrChroma(delta) => manipulator.decr_chroma(delta),
+            Operation::Rotate(angle) => manipulator.rotate(angle),
+            Operation::SetChroma(c_prop, policy) => ok(manipulator.set_chroma(c_prop, policy)),
+            Operation::SetSum(sum, policy) => ok(manipulator.set_sum(sum, policy)),
+            Operation::SetHue(hue, policy) => {
+                manipulator.set_hue(hue, policy);
+                true
+            }
+        }
+    }
+}
+
+/// An operation with the colours before and after it was performed.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Event {
+    pub operation: Operation,
+    pub before: HCV,
+    pub after: HCV,
+}
+
+/// Receiver of the events emitted by the GUI layers (e.g. to make a
+/// tutorial or a reproducible bug report).
+pub trait Recorder {
+    fn record(&self, event: Event);
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    /// Replaying the event at `index` produced `actual` instead of the
+    /// recorded colour.
+    Diverged {
+        index: usize,
+        expected: HCV,
+        actual: HCV,
+    },
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Diverged {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Replay diverged at event {index}: expected {expected:?} got {actual:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// A serialisable `Recorder` that keeps every event.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct EventLog {
+    events: RefCell<Vec<Event>>,
+}
+
+impl Recorder for EventLog {
+    fn record(&self, event: Event) {
+        self.events.borrow_mut().push(event)
+    }
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.borrow().is_empty()
+    }
+
+    /// Oldest first.
+    pub fn events(&self) -> Vec<Event> {
+        self.events.borrow().clone()
+    }
+
+    pub fn clear(&self) {
+        self.events.borrow_mut().clear()
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Perform the recorded operations on `manipulator` checking that each
+    /// produces the recorded colour.  The manipulator is set to each event's
+    /// `before` colour first so that changes made between events (which
+    /// weren't recorded) don't matter.  Returns the final colour.
+    pub fn replay(&self, manipulator: &mut ColourManipulator) -> Result<HCV, ReplayError> {
+        for (index, event) in self.events.borrow().iter().enumerate() {
+            manipulator.set_colour(&event.before);
+            event.operation.apply(manipulator);
+            let actual = manipulator.hcv();
+            if actual != event.after {
+                return Err(ReplayError::Diverged {
+                    index,
+                    expected: event.after,
+                    actual,
+                });
+            }
+        }
+        Ok(manipulator.hcv())
+    }
+}
+
+#[cfg(test)]
+mod recording_tests {
+    use super::*;
+    use crate::{manipulator::ColourManipulatorBuilder, HueConstants};
+
+    fn perform(log: &EventLog, manipulator: &mut ColourManipulator, operation: Operation) {
+        let before = manipulator.hcv();
+        operation.apply(manipulator);
+        log.record(Event {
+            operation,
+            before,
+            after: manipulator.hcv(),
+        });
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let log = EventLog::new();
+        let mut manipulator = ColourManipulatorBuilder::new().build();
+        for operation in [
+            Operation::SetColour(HCV::RED),
+            Operation::DecrValue(Prop::from(0.1)),
+            Operation::Rotate(Angle::from(30)),
+            Operation::DecrChroma(Prop::from(0.2)),
+        ] {
+            perform(&log, &mut manipulator, operation);
+        }
+        assert_eq!(log.len(), 4);
+        let last = manipulator.hcv();
+
+        let json = log.to_json().unwrap();
+        let read = EventLog::from_json(&json).unwrap();
+        assert_eq!(read.events(), log.events());
+        let mut manipulator = ColourManipulatorBuilder::new().build();
+        assert_eq!(read.replay(&mut manipulator).unwrap(), last);
+
+        read.record(Event {
+            operation: Operation::IncrValue(Prop::from(0.1)),
+            before: HCV::BLUE,
+            after: HCV::BLUE,
+        });
+        match read.replay(&mut manipulator) {
+            Err(ReplayError::Diverged { index, .. }) => assert_eq!(index, 4),
+            Ok(_) => panic!("replay should diverge"),
+        }
+        read.clear();
+        assert!(read.is_empty());
+    }
+}
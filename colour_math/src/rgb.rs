@@ -1,5 +1,6 @@
 // Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
 use std::{
+    cell::Cell,
     cmp::Ordering,
     convert::TryInto,
     convert::{From, TryFrom},
@@ -17,6 +18,7 @@ use crate::{
     fdrn::{Prop, UFDRNumber},
     hcv::HCV,
     hue::{angle::Angle, CMYHue, Hue, HueIfce, RGBHue, Sextant},
+    transfer::TransferFunction,
     ColourBasics, HueConstants, LightLevel, ManipulatedColour, RGBConstants,
 };
 
@@ -50,6 +52,13 @@ impl<T: LightLevel> RGBConstants for RGB<T> {
     const BLACK: Self = Self([T::ZERO, T::ZERO, T::ZERO]);
 }
 
+impl<T: LightLevel> RGB<T> {
+    /// Usable in constants e.g. to define a palette as a `const` array.
+    pub const fn new(red: T, green: T, blue: T) -> Self {
+        Self([red, green, blue])
+    }
+}
+
 impl<T: LightLevel + Copy + From<Prop>> RGB<T> {
     pub fn new_grey(value: Value) -> Self {
         let value: Prop = value.into();
@@ -201,42 +210,48 @@ impl<T: LightLevel> Index<usize> for RGB<T> {
 }
 
 // Comparisons
-impl<T: LightLevel> PartialOrd for RGB<T>
-where
-    T: PartialOrd,
-{
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+impl<T: LightLevel> RGB<T> {
+    // Total so that sorting can never panic
+    fn hue_order(&self, other: &Self) -> Ordering {
         if self.0 == other.0 {
-            Some(Ordering::Equal)
+            Ordering::Equal
         } else if let Ok(hue) = Hue::try_from(self) {
             if let Ok(other_hue) = Hue::try_from(other) {
-                // This orders via hue from CYAN to CYAN via GREEN, RED, BLUE in that order
+                // This orders via hue from CYAN to CYAN via BLUE, RED, GREEN in that order
                 match hue.cmp(&other_hue) {
                     Ordering::Equal => match self.chroma().cmp(&other.chroma()) {
-                        Ordering::Equal => Some(self.sum().cmp(&self.sum())),
-                        order => Some(order),
+                        Ordering::Equal => self.sum().cmp(&other.sum()),
+                        order => order,
                     },
-                    order => Some(order),
+                    order => order,
                 }
             } else {
-                Some(Ordering::Greater)
+                Ordering::Greater
             }
         } else if Hue::try_from(other).is_ok() {
-            Some(Ordering::Less)
+            Ordering::Less
         } else {
             // No need to look a chroma as it will be zero for both
-            self.sum().partial_cmp(&other.sum())
+            self.sum().cmp(&other.sum())
         }
     }
 }
 
+impl<T: LightLevel> PartialOrd for RGB<T>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.hue_order(other))
+    }
+}
+
 impl<T: LightLevel> Ord for RGB<T>
 where
     T: PartialOrd + Eq,
 {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other)
-            .expect("restricted range of values means this is OK")
+        self.hue_order(other)
     }
 }
 
@@ -351,22 +366,30 @@ impl From<std::num::ParseIntError> for RGBError {
     }
 }
 
+// Only called for groups that are present in every match of the regex
+fn named_group<'t>(captures: &regex::Captures<'t>, name: &str) -> &'t str {
+    captures.name(name).map_or("", |group| group.as_str())
+}
+
 lazy_static! {
     pub static ref RGB16_RE: Regex = Regex::new(
         r"RGB(16)?\((red=)?0x(?P<red>[a-fA-F0-9]{4}), (green=)?0x(?P<green>[a-fA-F0-9]{4}), (blue=)?0x(?P<blue>[a-fA-F0-9]{4})\)"
-    ).unwrap();
+    ).expect("valid regex");
     pub static ref RGB16_BASE_10_RE: Regex = Regex::new(
         r"RGB(16)?\((red=)?(?P<red>\d{1,5}), (green=)?(?P<green>\d{1,5}), (blue=)?(?P<blue>\d{1,5})\)"
-    ).unwrap();
+    ).expect("valid regex");
     pub static ref RGB8_RE: Regex = Regex::new(
         r"RGB(8)?\((red=)?0x(?P<red>[a-fA-F0-9]{2}), (green=)?0x(?P<green>[a-fA-F0-9]{2}), (blue=)?0x(?P<blue>[a-fA-F0-9]{2})\)"
-    ).unwrap();
+    ).expect("valid regex");
     pub static ref RGB8_BASE_10_RE: Regex = Regex::new(
         r"RGB(8)?\((red=)?(?P<red>\d{1,3}), (green=)?(?P<green>\d{1,3}), (blue=)?(?P<blue>\d{1,3})\)"
-    ).unwrap();
+    ).expect("valid regex");
     pub static ref RGB_PANGO_RE: Regex = Regex::new(
         r#"#(?P<red>[a-fA-F0-9][a-fA-F0-9])(?P<green>[a-fA-F0-9][a-fA-F0-9])(?P<blue>[a-fA-F0-9][a-fA-F0-9])"#
-    ).unwrap();
+    ).expect("valid regex");
+    pub static ref RGB8_HEX_RE: Regex = Regex::new(
+        r#"^#(?P<red>[a-fA-F0-9]{2})(?P<green>[a-fA-F0-9]{2})(?P<blue>[a-fA-F0-9]{2})$"#
+    ).expect("valid regex");
 }
 
 impl FromStr for RGB<u16> {
@@ -374,17 +397,303 @@ impl FromStr for RGB<u16> {
 
     fn from_str(string: &str) -> Result<Self, Self::Err> {
         if let Some(captures) = RGB16_RE.captures(string) {
-            let red = u16::from_str_radix(captures.name("red").unwrap().as_str(), 16)?;
-            let green = u16::from_str_radix(captures.name("green").unwrap().as_str(), 16)?;
-            let blue = u16::from_str_radix(captures.name("blue").unwrap().as_str(), 16)?;
+            let red = u16::from_str_radix(named_group(&captures, "red"), 16)?;
+            let green = u16::from_str_radix(named_group(&captures, "green"), 16)?;
+            let blue = u16::from_str_radix(named_group(&captures, "blue"), 16)?;
             Ok([red, green, blue].into())
         } else if let Some(captures) = RGB16_BASE_10_RE.captures(string) {
-            let red = captures.name("red").unwrap().as_str().parse::<u16>()?;
-            let green = captures.name("green").unwrap().as_str().parse::<u16>()?;
-            let blue = captures.name("blue").unwrap().as_str().parse::<u16>()?;
+            let red = named_group(&captures, "red").parse::<u16>()?;
+            let green = named_group(&captures, "green").parse::<u16>()?;
+            let blue = named_group(&captures, "blue").parse::<u16>()?;
             Ok([red, green, blue].into())
         } else {
             Err(RGBError::MalformedText(string.to_string()))
         }
     }
 }
+
+impl FromStr for RGB<u8> {
+    type Err = RGBError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if let Some(captures) = RGB8_HEX_RE.captures(string) {
+            let red = u8::from_str_radix(named_group(&captures, "red"), 16)?;
+            let green = u8::from_str_radix(named_group(&captures, "green"), 16)?;
+            let blue = u8::from_str_radix(named_group(&captures, "blue"), 16)?;
+            Ok([red, green, blue].into())
+        } else if let Some(captures) = RGB8_RE.captures(string) {
+            let red = u8::from_str_radix(named_group(&captures, "red"), 16)?;
+            let green = u8::from_str_radix(named_group(&captures, "green"), 16)?;
+            let blue = u8::from_str_radix(named_group(&captures, "blue"), 16)?;
+            Ok([red, green, blue].into())
+        } else if let Some(captures) = RGB8_BASE_10_RE.captures(string) {
+            let red = named_group(&captures, "red").parse::<u8>()?;
+            let green = named_group(&captures, "green").parse::<u8>()?;
+            let blue = named_group(&captures, "blue").parse::<u8>()?;
+            Ok([red, green, blue].into())
+        } else {
+            Err(RGBError::MalformedText(string.to_string()))
+        }
+    }
+}
+
+impl RGB<u8> {
+    /// Components rounded to the nearest byte (see `Prop::to_byte()`) rather
+    /// than truncated so that byte -> colour -> byte round trips are stable.
+    pub fn rounded_from(colour: &impl ColourBasics) -> Self {
+        let [red, green, blue] = <[Prop; 3]>::from(colour.hcv());
+        Self([red.to_byte(), green.to_byte(), blue.to_byte()])
+    }
+}
+
+/// An `RGB` that calculates its `HCV` (from which hue, chroma etc. are
+/// taken) at most once for hot paths that repeatedly query the attributes
+/// of the same colour.  Changing the colour invalidates the cache.
+#[derive(Debug, Clone)]
+pub struct CachedRGB<L: LightLevel> {
+    rgb: RGB<L>,
+    hcv: Cell<Option<HCV>>,
+}
+
+impl<L: LightLevel> CachedRGB<L> {
+    pub fn new(rgb: RGB<L>) -> Self {
+        Self {
+            rgb,
+            hcv: Cell::new(None),
+        }
+    }
+
+    pub fn inner(&self) -> RGB<L> {
+        self.rgb
+    }
+
+    pub fn set_rgb(&mut self, rgb: RGB<L>) {
+        if rgb != self.rgb {
+            self.rgb = rgb;
+            self.hcv.set(None);
+        }
+    }
+
+    /// Change the red (0), green (1) or blue (2) component.
+    pub fn set_component(&mut self, index: usize, component: L) {
+        let mut rgb = self.rgb;
+        if let Some(target) = rgb.0.get_mut(index) {
+            *target = component;
+            self.set_rgb(rgb);
+        }
+    }
+
+    pub fn is_cached(&self) -> bool {
+        self.hcv.get().is_some()
+    }
+
+    fn cached_hcv(&self) -> HCV {
+        match self.hcv.get() {
+            Some(hcv) => hcv,
+            None => {
+                let hcv = HCV::from(self.rgb);
+                self.hcv.set(Some(hcv));
+                hcv
+            }
+        }
+    }
+}
+
+impl<L: LightLevel> PartialEq for CachedRGB<L> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rgb == other.rgb
+    }
+}
+
+impl<L: LightLevel> From<RGB<L>> for CachedRGB<L> {
+    fn from(rgb: RGB<L>) -> Self {
+        Self::new(rgb)
+    }
+}
+
+impl<L: LightLevel> ColourBasics for CachedRGB<L> {
+    fn hue(&self) -> Option<Hue> {
+        self.cached_hcv().hue()
+    }
+
+    fn is_grey(&self) -> bool {
+        self.rgb.is_grey()
+    }
+
+    fn chroma(&self) -> Chroma {
+        self.cached_hcv().chroma()
+    }
+
+    fn chroma_prop(&self) -> Prop {
+        self.cached_hcv().chroma_prop()
+    }
+
+    fn value(&self) -> Value {
+        self.cached_hcv().value()
+    }
+
+    fn hcv(&self) -> HCV {
+        self.cached_hcv()
+    }
+
+    fn rgb<M: LightLevel>(&self) -> RGB<M> {
+        self.rgb.rgb()
+    }
+}
+
+impl<L: LightLevel> RGB<L> {
+    /// Treating `self` as sRGB encoded, the equivalent linear light.
+    pub fn to_linear(&self) -> Self {
+        TransferFunction::Srgb.rgb_to_linear(self)
+    }
+
+    /// The sRGB encoded equivalent of `linear` light.
+    pub fn from_linear(linear: &Self) -> Self {
+        TransferFunction::Srgb.rgb_to_encoded(linear)
+    }
+}
+
+// LED/DMX output
+fn led_byte(component: f64, gamma: f64, scale: f64) -> u8 {
+    let level = component.clamp(0.0, 1.0).powf(gamma) * scale.clamp(0.0, 1.0);
+    (level * 255.0).round() as u8
+}
+
+impl<T: LightLevel + Into<Prop>> RGB<T> {
+    /// Bytes for driving LEDs.  LED brightness is linear in drive level so
+    /// the components are raised to the power `gamma` (typically 2.2 to 2.8)
+    /// to make them look right.
+    pub fn to_led_bytes(&self, gamma: f64) -> [u8; 3] {
+        LedCalibration::new(gamma).led_bytes(self)
+    }
+}
+
+/// Gamma and per channel scaling (to balance LEDs of differing brightness)
+/// for a particular lighting fixture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LedCalibration {
+    pub gamma: f64,
+    pub scale: [f64; 3],
+}
+
+impl Default for LedCalibration {
+    fn default() -> Self {
+        Self::new(2.2)
+    }
+}
+
+impl LedCalibration {
+    pub fn new(gamma: f64) -> Self {
+        Self {
+            gamma,
+            scale: [1.0, 1.0, 1.0],
+        }
+    }
+
+    pub fn with_scale(gamma: f64, scale: [f64; 3]) -> Self {
+        Self { gamma, scale }
+    }
+
+    pub fn led_bytes<T: LightLevel + Into<Prop>>(&self, rgb: &RGB<T>) -> [u8; 3] {
+        let rgb: RGB<f64> = rgb.rgb();
+        [
+            led_byte(rgb[0], self.gamma, self.scale[0]),
+            led_byte(rgb[1], self.gamma, self.scale[1]),
+            led_byte(rgb[2], self.gamma, self.scale[2]),
+        ]
+    }
+
+    /// Packed RGB triples e.g. for a DMX universe or an LED strip.
+    pub fn led_buffer<T: LightLevel + Into<Prop>>(&self, rgbs: &[RGB<T>]) -> Vec<u8> {
+        rgbs.iter().flat_map(|rgb| self.led_bytes(rgb)).collect()
+    }
+
+    /// As `led_buffer()` but without allocating.  Returns the number of
+    /// colours written which will be less than `rgbs.len()` if `buffer`
+    /// is too small.
+    pub fn fill_led_buffer<T: LightLevel + Into<Prop>>(
+        &self,
+        rgbs: &[RGB<T>],
+        buffer: &mut [u8],
+    ) -> usize {
+        let mut count = 0;
+        for (rgb, bytes) in rgbs.iter().zip(buffer.chunks_exact_mut(3)) {
+            bytes.copy_from_slice(&self.led_bytes(rgb));
+            count += 1;
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod rgb_tests {
+    use super::*;
+
+    #[test]
+    fn rgb8_from_str() {
+        let rgb = RGB::<u8>::from([0x12, 0xAB, 0xFF]);
+        assert_eq!("#12ABFF".parse::<RGB<u8>>(), Ok(rgb));
+        assert_eq!("#12abff".parse::<RGB<u8>>(), Ok(rgb));
+        assert_eq!("RGB8(0x12, 0xAB, 0xFF)".parse::<RGB<u8>>(), Ok(rgb));
+        assert_eq!("RGB(18, 171, 255)".parse::<RGB<u8>>(), Ok(rgb));
+        for bad in ["#12ABFF00", "#12AB12AB12AB", "x#12ABFF", "#12ABF", "12ABFF"] {
+            assert!(bad.parse::<RGB<u8>>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn led_bytes() {
+        assert_eq!(RGB::<u8>::WHITE.to_led_bytes(2.2), [255, 255, 255]);
+        assert_eq!(RGB::<u8>::BLACK.to_led_bytes(2.2), [0, 0, 0]);
+        let grey = RGB::<f64>::from([0.6, 0.6, 0.6]);
+        assert_eq!(grey.to_led_bytes(1.0), [153, 153, 153]);
+        assert_eq!(grey.to_led_bytes(2.0), [92, 92, 92]);
+        let calibration = LedCalibration::with_scale(1.0, [1.0, 0.5, 0.0]);
+        assert_eq!(calibration.led_bytes(&RGB::<u8>::WHITE), [255, 128, 0]);
+        let rgbs = [RGB::<u16>::RED, RGB::<u16>::BLUE];
+        let buffer = calibration.led_buffer(&rgbs);
+        assert_eq!(buffer, vec![255, 0, 0, 0, 0, 0]);
+        let mut short = [0u8; 4];
+        assert_eq!(
+            LedCalibration::new(1.0).fill_led_buffer(&rgbs, &mut short),
+            1
+        );
+        assert_eq!(short, [255, 0, 0, 0]);
+    }
+
+    #[test]
+    fn cached_rgb() {
+        let rgb = RGB::<u8>::from([200, 100, 50]);
+        let mut cached = CachedRGB::from(rgb);
+        assert!(!cached.is_cached());
+        assert_eq!(cached.rgb::<u8>(), rgb);
+        assert!(!cached.is_cached());
+        let hcv = rgb.hcv();
+        assert_eq!(cached.hue(), hcv.hue());
+        assert!(cached.is_cached());
+        assert_eq!(cached.chroma(), hcv.chroma());
+        assert_eq!(cached.chroma_prop(), hcv.chroma_prop());
+        assert_eq!(cached.value(), hcv.value());
+        assert_eq!(cached.hcv(), hcv);
+        cached.set_rgb(rgb);
+        assert!(cached.is_cached());
+        cached.set_component(2, 200);
+        assert!(!cached.is_cached());
+        assert_eq!(cached.inner(), RGB::<u8>::from([200, 100, 200]));
+        assert_eq!(cached.hue(), cached.inner().hcv().hue());
+        cached.set_component(3, 0);
+        assert!(cached.is_cached());
+        cached.set_rgb(RGB::<u8>::WHITE);
+        assert!(cached.is_grey());
+        assert_eq!(cached.hue(), None);
+        assert_eq!(cached, CachedRGB::new(RGB::<u8>::WHITE));
+    }
+
+    #[test]
+    fn rounded_bytes() {
+        for n in 0..=255 {
+            let rgb = RGB::<u8>::from([n, 255 - n, n / 2]);
+            assert_eq!(RGB::<u8>::rounded_from(&HCV::from(rgb)), rgb);
+        }
+    }
+}
@@ -0,0 +1,304 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! RGB colour spaces other than sRGB (which is what `RGB` is everywhere
+//! else in this crate) defined by their primaries, white point and transfer
+//! function.  Conversions are done with matrices in linear light.
+use crate::{
+    fdrn::Prop,
+    rgb::RGB,
+    standards::WhitePoint,
+    transfer::TransferFunction,
+    xyz::{apply, multiply, Adaptation, Matrix},
+    LightLevel,
+};
+
+const IDENTITY: Matrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+fn determinant(m: &Matrix) -> f64 {
+    m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+}
+
+fn invert(m: &Matrix) -> Option<Matrix> {
+    let det = determinant(m);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| {
+        (m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]) / det
+    };
+    Some([
+        [
+            cofactor(1, 2, 1, 2),
+            -cofactor(0, 2, 1, 2),
+            cofactor(0, 1, 1, 2),
+        ],
+        [
+            -cofactor(1, 2, 0, 2),
+            cofactor(0, 2, 0, 2),
+            -cofactor(0, 1, 0, 2),
+        ],
+        [
+            cofactor(1, 2, 0, 1),
+            -cofactor(0, 2, 0, 1),
+            cofactor(0, 1, 0, 1),
+        ],
+    ])
+}
+
+// The XYZ (with Y one) of a chromaticity
+fn xyz(x: f64, y: f64) -> [f64; 3] {
+    [x / y, 1.0, (1.0 - x - y) / y]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RGBSpace {
+    name: &'static str,
+    primaries: [(f64, f64); 3],
+    white_point: WhitePoint,
+    transfer: TransferFunction,
+}
+
+impl RGBSpace {
+    pub const SRGB: Self = Self {
+        name: "sRGB",
+        primaries: [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06)],
+        white_point: WhitePoint::D65,
+        transfer: TransferFunction::Srgb,
+    };
+    pub const ADOBE_RGB: Self = Self {
+        name: "Adobe RGB (1998)",
+        primaries: [(0.64, 0.33), (0.21, 0.71), (0.15, 0.06)],
+        white_point: WhitePoint::D65,
+        transfer: TransferFunction::Gamma(563.0 / 256.0),
+    };
+    pub const DISPLAY_P3: Self = Self {
+        name: "Display P3",
+        primaries: [(0.680, 0.320), (0.265, 0.690), (0.150, 0.060)],
+        white_point: WhitePoint::D65,
+        transfer: TransferFunction::Srgb,
+    };
+    /// With the BT.1886 (gamma 2.4) display transfer function.
+    pub const REC_2020: Self = Self {
+        name: "Rec. 2020",
+        primaries: [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046)],
+        white_point: WhitePoint::D65,
+        transfer: TransferFunction::Gamma(2.4),
+    };
+
+    /// The `primaries` are the (x, y) chromaticities of red, green and
+    /// blue.  Returns `None` if they don't span a space e.g. if two are
+    /// the same.
+    pub fn new(
+        name: &'static str,
+        primaries: [(f64, f64); 3],
+        white_point: WhitePoint,
+        transfer: TransferFunction,
+    ) -> Option<Self> {
+        if primaries.iter().any(|(_, y)| *y <= 0.0) {
+            return None;
+        }
+        let space = Self {
+            name,
+            primaries,
+            white_point,
+            transfer,
+        };
+        invert(&space.primaries_matrix()).map(|_| space)
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn primaries(&self) -> [(f64, f64); 3] {
+        self.primaries
+    }
+
+    pub fn white_point(&self) -> WhitePoint {
+        self.white_point
+    }
+
+    pub fn transfer(&self) -> TransferFunction {
+        self.transfer
+    }
+
+    // Columns are the primaries' XYZ
+    fn primaries_matrix(&self) -> Matrix {
+        let [red, green, blue] = self.primaries.map(|(x, y)| xyz(x, y));
+        [
+            [red[0], green[0], blue[0]],
+            [red[1], green[1], blue[1]],
+            [red[2], green[2], blue[2]],
+        ]
+    }
+
+    /// The matrix taking linear RGB to XYZ (relative to the space's white
+    /// point).
+    pub fn linear_to_xyz_matrix(&self) -> Matrix {
+        let primaries = self.primaries_matrix();
+        // `new()` guarantees that the inverse exists
+        let inverse = invert(&primaries).unwrap_or(IDENTITY);
+        let scale = apply(&inverse, self.white_point.xyz());
+        let mut matrix = primaries;
+        for row in matrix.iter_mut() {
+            for (element, scale) in row.iter_mut().zip(scale.iter()) {
+                *element *= scale;
+            }
+        }
+        matrix
+    }
+
+    pub fn xyz_to_linear_matrix(&self) -> Matrix {
+        invert(&self.linear_to_xyz_matrix()).unwrap_or(IDENTITY)
+    }
+
+    /// The matrix taking linear RGB in this space to linear RGB in `other`
+    /// (with chromatic adaptation if the white points differ).
+    pub fn conversion_matrix(&self, other: &Self) -> Matrix {
+        let adapted = multiply(
+            &Adaptation::Bradford.matrix(&self.white_point, &other.white_point),
+            &self.linear_to_xyz_matrix(),
+        );
+        multiply(&other.xyz_to_linear_matrix(), &adapted)
+    }
+
+    pub fn linear_to_xyz(&self, linear: [f64; 3]) -> [f64; 3] {
+        apply(&self.linear_to_xyz_matrix(), linear)
+    }
+
+    /// Components outside the range zero to one indicate that the colour
+    /// is out of the space's gamut.
+    pub fn linear_from_xyz(&self, xyz: [f64; 3]) -> [f64; 3] {
+        apply(&self.xyz_to_linear_matrix(), xyz)
+    }
+
+    /// Linear light in this space to linear light in `other` without
+    /// clipping (so that out of gamut and HDR values survive).
+    pub fn convert_linear(&self, linear: [f64; 3], other: &Self) -> [f64; 3] {
+        apply(&self.conversion_matrix(other), linear)
+    }
+
+    /// Decode an (encoded) `rgb` in this space to linear light.
+    pub fn decode<L: LightLevel>(&self, rgb: &RGB<L>) -> [f64; 3] {
+        <[Prop; 3]>::from(*rgb).map(|component| self.transfer.to_linear(f64::from(component)))
+    }
+
+    /// Encode `linear` light clipping any out of gamut components.
+    pub fn encode<L: LightLevel>(&self, linear: [f64; 3]) -> RGB<L> {
+        RGB::from(linear.map(|component| {
+            Prop::from(
+                self.transfer
+                    .to_encoded(component.clamp(0.0, 1.0))
+                    .clamp(0.0, 1.0),
+            )
+        }))
+    }
+
+    /// Convert an (encoded) `rgb` in this space to `other` (clipping out
+    /// of gamut colours).  Conversion to the same space returns `rgb`
+    /// untouched so there's no loss of precision.
+    pub fn convert<L: LightLevel>(&self, rgb: &RGB<L>, other: &Self) -> RGB<L> {
+        if self == other {
+            *rgb
+        } else {
+            other.encode(self.convert_linear(self.decode(rgb), other))
+        }
+    }
+
+    pub fn xyz<L: LightLevel>(&self, rgb: &RGB<L>) -> [f64; 3] {
+        self.linear_to_xyz(self.decode(rgb))
+    }
+
+    pub fn rgb_from_xyz<L: LightLevel>(&self, xyz: [f64; 3]) -> RGB<L> {
+        self.encode(self.linear_from_xyz(xyz))
+    }
+}
+
+impl Default for RGBSpace {
+    fn default() -> Self {
+        Self::SRGB
+    }
+}
+
+#[cfg(test)]
+mod rgb_space_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    fn near(lhs: [f64; 3], rhs: [f64; 3], tolerance: f64) -> bool {
+        (0..3).all(|i| (lhs[i] - rhs[i]).abs() < tolerance)
+    }
+
+    #[test]
+    fn srgb_matrix() {
+        let matrix = RGBSpace::SRGB.linear_to_xyz_matrix();
+        assert!(near(
+            matrix[0],
+            [0.412_456_4, 0.357_576_1, 0.180_437_5],
+            1e-3
+        ));
+        assert!(near(
+            matrix[1],
+            [0.212_672_9, 0.715_152_2, 0.072_175_0],
+            1e-3
+        ));
+        let white = RGBSpace::SRGB.xyz(&RGB::<f64>::WHITE);
+        assert!(near(white, WhitePoint::D65.xyz(), 1e-9));
+        let product = multiply(&matrix, &RGBSpace::SRGB.xyz_to_linear_matrix());
+        for (i, row) in product.iter().enumerate() {
+            assert!(near(*row, IDENTITY[i], 1e-9));
+        }
+    }
+
+    #[test]
+    fn conversions() {
+        for space in [
+            RGBSpace::ADOBE_RGB,
+            RGBSpace::DISPLAY_P3,
+            RGBSpace::REC_2020,
+        ] {
+            // sRGB is inside the wide gamuts so round trips are lossless
+            let rgb = RGB::<f64>::from([0.8, 0.3, 0.1]);
+            let wide = RGBSpace::SRGB.convert(&rgb, &space);
+            assert_ne!(wide, rgb);
+            let back = space.convert(&wide, &RGBSpace::SRGB);
+            assert!(back.approx_eq(&rgb, Some(Prop::from(0.000_1))));
+            // but their primaries are outside sRGB
+            let green = space.decode(&RGB::<f64>::GREEN);
+            let linear = space.convert_linear(green, &RGBSpace::SRGB);
+            assert!(linear[0] < 0.0 || linear[2] < 0.0);
+            assert_eq!(space.convert(&RGB::<u8>::WHITE, &space), RGB::<u8>::WHITE);
+        }
+        assert_eq!(
+            RGBSpace::SRGB.convert(&RGB::<u16>::CYAN, &RGBSpace::SRGB),
+            RGB::<u16>::CYAN
+        );
+    }
+
+    #[test]
+    fn custom_spaces() {
+        assert!(RGBSpace::new(
+            "degenerate",
+            [(0.64, 0.33), (0.64, 0.33), (0.15, 0.06)],
+            WhitePoint::D65,
+            TransferFunction::Linear,
+        )
+        .is_none());
+        let d50 = RGBSpace::new(
+            "sRGB (D50)",
+            RGBSpace::SRGB.primaries(),
+            WhitePoint::D50,
+            TransferFunction::Linear,
+        )
+        .unwrap();
+        assert_eq!(d50.name(), "sRGB (D50)");
+        // white maps to white under chromatic adaptation
+        let white = RGBSpace::SRGB.convert_linear([1.0, 1.0, 1.0], &d50);
+        assert!(near(white, [1.0, 1.0, 1.0], 1e-3));
+        let colour = [0.2, 0.5, 0.7];
+        let round_trip =
+            d50.convert_linear(RGBSpace::SRGB.convert_linear(colour, &d50), &RGBSpace::SRGB);
+        assert!(near(round_trip, colour, 1e-3));
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{harmony, hcv::HCV, hue::angle::Angle, ManipulatedColour};
+
+/// Palettes built from a base colour by rotating its hue (with the base
+/// colour first).  Rotation keeps the base's chroma where the new hue
+/// permits so the members are as alike as possible apart from hue.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    Complementary,
+    SplitComplementary {
+        spread: Angle,
+    },
+    Triadic,
+    /// A rectangle on the hue wheel (a square if `spread` is 90 degrees).
+    Tetradic {
+        spread: Angle,
+    },
+    /// `count` colours (including the base) centred on the base colour and
+    /// `spread` apart.
+    Analogous {
+        spread: Angle,
+        count: u8,
+    },
+}
+
+impl Scheme {
+    pub fn generate(&self, base: &HCV) -> Vec<HCV> {
+        match *self {
+            Scheme::Complementary => complementary(base),
+            Scheme::SplitComplementary { spread } => split_complementary(base, spread),
+            Scheme::Triadic => triadic(base),
+            Scheme::Tetradic { spread } => tetradic(base, spread),
+            Scheme::Analogous { spread, count } => analogous(base, spread, count),
+        }
+    }
+}
+
+pub fn complementary(base: &HCV) -> Vec<HCV> {
+    vec![*base, harmony::complement(base)]
+}
+
+pub fn split_complementary(base: &HCV, spread: Angle) -> Vec<HCV> {
+    let [first, second] = harmony::split_complements(base, spread);
+    vec![*base, first, second]
+}
+
+pub fn triadic(base: &HCV) -> Vec<HCV> {
+    let [first, second] = harmony::triad(base);
+    vec![*base, first, second]
+}
+
+pub fn tetradic(base: &HCV, spread: Angle) -> Vec<HCV> {
+    let [first, second, third] = harmony::tetrad(base, spread);
+    vec![*base, first, second, third]
+}
+
+/// Members are in hue order with those either side of the base alternating
+/// (nearest first) after it.  An even `count` has its extra member on the
+/// positive side.
+pub fn analogous(base: &HCV, spread: Angle, count: u8) -> Vec<HCV> {
+    let mut colours = vec![];
+    if count > 0 {
+        colours.push(*base);
+    }
+    let mut angle = Angle::from(0);
+    for i in 1..count {
+        if i % 2 == 1 {
+            angle = angle + spread;
+            colours.push(base.rotated(angle));
+        } else {
+            colours.push(base.rotated(-angle));
+        }
+    }
+    colours
+}
+
+#[cfg(test)]
+mod schemes_tests {
+    use super::*;
+    use crate::{ColourBasics, HueConstants, RGBConstants};
+
+    fn hues_near(colours: &[HCV], degrees: &[i16]) -> bool {
+        colours.len() == degrees.len()
+            && colours.iter().zip(degrees.iter()).all(|(colour, degrees)| {
+                let angle = colour.hue_angle().expect("not grey");
+                f64::from(angle.abs_diff(&Angle::from(*degrees))) < 0.01
+            })
+    }
+
+    #[test]
+    fn schemes() {
+        let red = HCV::RED;
+        assert!(hues_near(&complementary(&red), &[0, 180]));
+        let scheme = Scheme::SplitComplementary {
+            spread: Angle::from(30),
+        };
+        assert!(hues_near(&scheme.generate(&red), &[0, 150, -150]));
+        assert!(hues_near(&triadic(&red), &[0, -120, 120]));
+        assert!(hues_near(
+            &tetradic(&red, Angle::from(90)),
+            &[0, 90, 180, -90]
+        ));
+        assert!(hues_near(
+            &analogous(&red, Angle::from(20), 4),
+            &[0, 20, -20, 40]
+        ));
+        assert!(analogous(&red, Angle::from(20), 0).is_empty());
+        assert_eq!(triadic(&HCV::WHITE), vec![HCV::WHITE; 3]);
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! Human friendly serde representations (for hand edited or third party
+//! JSON etc.) selected per field with `#[serde(with = "...")]` e.g.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Swatch {
+//!     #[serde(with = "colour_math::serde_human::rgb_hex")]
+//!     colour: RGB<u8>,
+//! }
+//! ```
+//!
+//! The derived representations (which expose the fixed point internals) are
+//! unchanged so existing files remain readable.  Only available with the
+//! `serde-human` feature.
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    attributes::Value,
+    fdrn::{Prop, UFDRNumber},
+    hcv::HCV,
+    hue::{angle::Angle, Hue, HueIfce},
+    rgb::RGB,
+    ColourBasics, LightLevel,
+};
+
+fn is_proportion(float: f64) -> bool {
+    (0.0..=1.0).contains(&float)
+}
+
+/// `RGB<u8>` as a "#RRGGBB" string.
+pub mod rgb_hex {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(rgb: &RGB<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&rgb.pango_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<RGB<u8>, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        string
+            .parse::<RGB<u8>>()
+            .map_err(|_| D::Error::custom(format!("{string:?}: malformed RGB hex string")))
+    }
+}
+
+/// `RGB<L>` as a triple of floats in the range zero to one.
+pub mod rgb_floats {
+    use super::*;
+
+    pub fn serialize<L: LightLevel, S: Serializer>(
+        rgb: &RGB<L>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        <[Prop; 3]>::from(*rgb).map(f64::from).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, L: LightLevel, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<RGB<L>, D::Error> {
+        let floats = <[f64; 3]>::deserialize(deserializer)?;
+        if floats.iter().all(|float| is_proportion(*float)) {
+            Ok(RGB::from(floats.map(Prop::from)))
+        } else {
+            Err(D::Error::custom(format!(
+                "{floats:?}: RGB components must be in the range 0.0 to 1.0"
+            )))
+        }
+    }
+}
+
+/// `HCV` as its hue angle (in degrees or null for greys), chroma (as a
+/// proportion of the maximum possible for the hue) and value.  Colours are
+/// reconstructed as closely as possible (favouring value) on the way in.
+pub mod hcv {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Attributes {
+        hue: Option<f64>,
+        chroma: f64,
+        value: f64,
+    }
+
+    pub fn serialize<S: Serializer>(hcv: &HCV, serializer: S) -> Result<S::Ok, S::Error> {
+        Attributes {
+            hue: hcv.hue.map(|hue| f64::from(hue.angle())),
+            chroma: f64::from(hcv.c_prop),
+            value: f64::from(hcv.sum) / 3.0,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HCV, D::Error> {
+        let attributes = Attributes::deserialize(deserializer)?;
+        if !is_proportion(attributes.chroma) || !is_proportion(attributes.value) {
+            return Err(D::Error::custom(
+                "HCV chroma and value must be in the range 0.0 to 1.0",
+            ));
+        }
+        match attributes.hue {
+            Some(degrees) if attributes.chroma > 0.0 => {
+                if !(-180.0..=180.0).contains(&degrees) {
+                    return Err(D::Error::custom(format!(
+                        "{degrees}: HCV hue must be in the range -180.0 to 180.0"
+                    )));
+                }
+                let hue = Hue::from(Angle::from(degrees));
+                let sum = UFDRNumber::from(attributes.value * 3.0);
+                Ok(HCV::new_approx(
+                    Some(hue),
+                    sum,
+                    Prop::from(attributes.chroma),
+                ))
+            }
+            _ => Ok(HCV::new_grey(Value::from(Prop::from(attributes.value)))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod serde_human_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Swatch {
+        #[serde(with = "rgb_hex")]
+        hex: RGB<u8>,
+        #[serde(with = "rgb_floats")]
+        floats: RGB<f64>,
+        #[serde(with = "rgb_floats")]
+        wide: RGB<u16>,
+        #[serde(with = "hcv")]
+        hcv: HCV,
+    }
+
+    #[test]
+    fn rgb_round_trips() {
+        let swatch = Swatch {
+            hex: RGB::from([0x12, 0xAB, 0xFF]),
+            floats: RGB::from([0.25, 0.5, 1.0]),
+            wide: RGB::<u16>::CYAN,
+            hcv: HCV::BLACK,
+        };
+        let json = serde_json::to_string(&swatch).unwrap();
+        assert!(json.contains(r##""hex":"#12ABFF""##));
+        assert!(json.contains(r#""floats":[0.25,0.5,1.0]"#));
+        assert!(json.contains(r#""wide":[0.0,1.0,1.0]"#));
+        assert_eq!(serde_json::from_str::<Swatch>(&json).unwrap(), swatch);
+        for hex in ["12ABFF", "#12ABFF00", "#12AB12AB12AB"] {
+            let bad = json.replace("#12ABFF", hex);
+            assert!(serde_json::from_str::<Swatch>(&bad).is_err());
+        }
+        let bad = json.replace("[0.25,0.5,1.0]", "[0.25,0.5,1.5]");
+        assert!(serde_json::from_str::<Swatch>(&bad).is_err());
+    }
+
+    #[test]
+    fn hcv_round_trips() {
+        let json = serde_json::to_string(&Swatch {
+            hex: RGB::<u8>::BLACK,
+            floats: RGB::<f64>::BLACK,
+            wide: RGB::<u16>::BLACK,
+            hcv: HCV::new_grey(Value::ONE / 2),
+        })
+        .unwrap();
+        assert!(json.contains(r#""hcv":{"hue":null,"chroma":0.0,"value":0.5"#));
+        for colour in [
+            HCV::RED,
+            HCV::CYAN,
+            HCV::WHITE,
+            HCV::from(RGB::<f64>::from([0.2, 0.6, 0.3])),
+            HCV::from(RGB::<u8>::from([200, 30, 100])),
+        ] {
+            let value = serde_json::to_value(Swatch {
+                hex: colour.rgb(),
+                floats: colour.rgb(),
+                wide: colour.rgb(),
+                hcv: colour,
+            })
+            .unwrap();
+            let swatch: Swatch = serde_json::from_value(value).unwrap();
+            assert_eq!(swatch.hex, colour.rgb::<u8>());
+            assert!(swatch
+                .hcv
+                .rgb::<f64>()
+                .approx_eq(&colour.rgb::<f64>(), Some(Prop::from(0.000_001))));
+        }
+        let json = r##"{"hex":"#000000","floats":[0,0,0],"wide":[0,0,0],
+            "hcv":{"hue":240.0,"chroma":1.0,"value":0.5}}"##;
+        assert!(serde_json::from_str::<Swatch>(json).is_err());
+    }
+}
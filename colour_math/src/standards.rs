@@ -0,0 +1,171 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::fmt;
+
+/// CIE standard colorimetric observers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Observer {
+    /// CIE 1931 2° observer
+    #[default]
+    Two,
+    /// CIE 1964 10° observer
+    Ten,
+}
+
+impl Observer {
+    pub const ALL: [Observer; 2] = [Observer::Two, Observer::Ten];
+
+    pub fn field_of_view_degrees(self) -> u8 {
+        match self {
+            Observer::Two => 2,
+            Observer::Ten => 10,
+        }
+    }
+
+    pub fn year(self) -> u16 {
+        match self {
+            Observer::Two => 1931,
+            Observer::Ten => 1964,
+        }
+    }
+}
+
+impl fmt::Display for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CIE {} {}°", self.year(), self.field_of_view_degrees())
+    }
+}
+
+/// CIE standard illuminants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Illuminant {
+    /// Incandescent/tungsten
+    A,
+    /// Horizon daylight (e.g. graphic arts and ICC profile connection space)
+    D50,
+    /// Noon daylight (e.g. sRGB)
+    #[default]
+    D65,
+    /// Equal energy
+    E,
+}
+
+impl Illuminant {
+    pub const ALL: [Illuminant; 4] = [
+        Illuminant::A,
+        Illuminant::D50,
+        Illuminant::D65,
+        Illuminant::E,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Illuminant::A => "A",
+            Illuminant::D50 => "D50",
+            Illuminant::D65 => "D65",
+            Illuminant::E => "E",
+        }
+    }
+
+    /// Nominal correlated colour temperature in kelvin.
+    pub fn cct(self) -> f64 {
+        match self {
+            Illuminant::A => 2856.0,
+            Illuminant::D50 => 5003.0,
+            Illuminant::D65 => 6504.0,
+            Illuminant::E => 5455.0,
+        }
+    }
+
+    pub fn white_point(self, observer: Observer) -> WhitePoint {
+        use Illuminant::*;
+        use Observer::*;
+        let (x, y) = match (self, observer) {
+            (A, Two) => (0.44757, 0.40745),
+            (A, Ten) => (0.45117, 0.40594),
+            (D50, Two) => (0.34567, 0.35850),
+            (D50, Ten) => (0.34773, 0.35952),
+            (D65, Two) => (0.31271, 0.32902),
+            (D65, Ten) => (0.31382, 0.33100),
+            (E, _) => (1.0 / 3.0, 1.0 / 3.0),
+        };
+        WhitePoint {
+            illuminant: self,
+            observer,
+            x,
+            y,
+        }
+    }
+}
+
+impl fmt::Display for Illuminant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The chromaticity of an illuminant as seen by an observer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WhitePoint {
+    pub illuminant: Illuminant,
+    pub observer: Observer,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl WhitePoint {
+    pub const D50: WhitePoint = WhitePoint {
+        illuminant: Illuminant::D50,
+        observer: Observer::Two,
+        x: 0.34567,
+        y: 0.35850,
+    };
+    pub const D65: WhitePoint = WhitePoint {
+        illuminant: Illuminant::D65,
+        observer: Observer::Two,
+        x: 0.31271,
+        y: 0.32902,
+    };
+    pub const A: WhitePoint = WhitePoint {
+        illuminant: Illuminant::A,
+        observer: Observer::Two,
+        x: 0.44757,
+        y: 0.40745,
+    };
+    pub const E: WhitePoint = WhitePoint {
+        illuminant: Illuminant::E,
+        observer: Observer::Two,
+        x: 1.0 / 3.0,
+        y: 1.0 / 3.0,
+    };
+
+    /// Tristimulus values normalised so that Y is one.
+    pub fn xyz(&self) -> [f64; 3] {
+        [self.x / self.y, 1.0, (1.0 - self.x - self.y) / self.y]
+    }
+}
+
+impl Default for WhitePoint {
+    fn default() -> Self {
+        WhitePoint::D65
+    }
+}
+
+#[cfg(test)]
+mod standards_tests {
+    use super::*;
+
+    #[test]
+    fn white_points() {
+        for illuminant in Illuminant::ALL.iter() {
+            let white_point = illuminant.white_point(Observer::Two);
+            assert_eq!(white_point.illuminant, *illuminant);
+        }
+        assert_eq!(Illuminant::D65.white_point(Observer::Two), WhitePoint::D65);
+        assert_eq!(Illuminant::D50.white_point(Observer::Two), WhitePoint::D50);
+        let xyz = WhitePoint::D65.xyz();
+        assert!((xyz[0] - 0.95047).abs() < 1e-3);
+        assert!((xyz[2] - 1.08883).abs() < 1e-3);
+        assert!(WhitePoint::E.xyz().iter().all(|v| (v - 1.0).abs() < 1e-9));
+        assert_eq!(Observer::Ten.to_string(), "CIE 1964 10°");
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! Transfer functions relating display encoded component values to linear
+//! light.  Arithmetic that models physical light (e.g. mixing or blurring)
+//! should be done in linear light whereas `RGB` values as read from files
+//! and widgets are (almost always) sRGB encoded.
+use crate::{fdrn::Prop, rgb::RGB, LightLevel};
+
+/// The sRGB electro-optical transfer function (EOTF) i.e. decode an sRGB
+/// encoded component to linear light.
+pub fn srgb_eotf(encoded: f64) -> f64 {
+    if encoded <= 0.04045 {
+        encoded / 12.92
+    } else {
+        ((encoded + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// The sRGB opto-electronic transfer function (OETF) i.e. encode a linear
+/// light component as sRGB.
+pub fn srgb_oetf(linear: f64) -> f64 {
+    if linear <= 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum TransferFunction {
+    #[default]
+    Srgb,
+    /// The values are linear light.
+    Linear,
+    /// A pure power law: linear light is the encoded value to this power.
+    Gamma(f64),
+}
+
+impl TransferFunction {
+    /// Decode `encoded` to linear light.
+    pub fn to_linear(&self, encoded: f64) -> f64 {
+        match *self {
+            TransferFunction::Srgb => srgb_eotf(encoded),
+            TransferFunction::Linear => encoded,
+            TransferFunction::Gamma(gamma) => encoded.max(0.0).powf(gamma),
+        }
+    }
+
+    /// Encode `linear` light.
+    pub fn to_encoded(&self, linear: f64) -> f64 {
+        match *self {
+            TransferFunction::Srgb => srgb_oetf(linear),
+            TransferFunction::Linear => linear,
+            TransferFunction::Gamma(gamma) => linear.max(0.0).powf(1.0 / gamma),
+        }
+    }
+
+    pub fn rgb_to_linear<L: LightLevel>(&self, rgb: &RGB<L>) -> RGB<L> {
+        map_rgb(rgb, |component| self.to_linear(component))
+    }
+
+    pub fn rgb_to_encoded<L: LightLevel>(&self, rgb: &RGB<L>) -> RGB<L> {
+        map_rgb(rgb, |component| self.to_encoded(component))
+    }
+}
+
+// Results are clamped to the valid range
+fn map_rgb<L: LightLevel>(rgb: &RGB<L>, function: impl Fn(f64) -> f64) -> RGB<L> {
+    let components = <[Prop; 3]>::from(*rgb)
+        .map(|component| Prop::from(function(f64::from(component)).clamp(0.0, 1.0)));
+    RGB::from(components)
+}
+
+/// An `RGB` that is explicitly display encoded (with a known transfer
+/// function) as opposed to being linear light.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct EncodedRGB<L: LightLevel> {
+    rgb: RGB<L>,
+    transfer: TransferFunction,
+}
+
+impl<L: LightLevel> EncodedRGB<L> {
+    pub fn new(rgb: RGB<L>, transfer: TransferFunction) -> Self {
+        Self { rgb, transfer }
+    }
+
+    pub fn srgb(rgb: RGB<L>) -> Self {
+        Self::new(rgb, TransferFunction::Srgb)
+    }
+
+    /// Encode `linear` light with `transfer`.
+    pub fn from_linear(linear: &RGB<L>, transfer: TransferFunction) -> Self {
+        Self::new(transfer.rgb_to_encoded(linear), transfer)
+    }
+
+    pub fn encoded(&self) -> RGB<L> {
+        self.rgb
+    }
+
+    pub fn transfer(&self) -> TransferFunction {
+        self.transfer
+    }
+
+    pub fn to_linear(&self) -> RGB<L> {
+        self.transfer.rgb_to_linear(&self.rgb)
+    }
+
+    /// The same light encoded with a different transfer function.
+    pub fn reencoded(&self, transfer: TransferFunction) -> Self {
+        Self::from_linear(&self.to_linear(), transfer)
+    }
+}
+
+impl<L: LightLevel> From<RGB<L>> for EncodedRGB<L> {
+    /// Colours are sRGB encoded unless stated otherwise.
+    fn from(rgb: RGB<L>) -> Self {
+        Self::srgb(rgb)
+    }
+}
+
+#[cfg(test)]
+mod transfer_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    #[test]
+    fn transfer_functions() {
+        for transfer in [
+            TransferFunction::Srgb,
+            TransferFunction::Linear,
+            TransferFunction::Gamma(2.2),
+        ] {
+            assert_eq!(transfer.to_linear(0.0), 0.0);
+            assert!((transfer.to_linear(1.0) - 1.0).abs() < 1e-12);
+            for i in 0..=10 {
+                let encoded = i as f64 / 10.0;
+                let linear = transfer.to_linear(encoded);
+                assert!(linear <= encoded + 1e-12);
+                assert!((transfer.to_encoded(linear) - encoded).abs() < 1e-9);
+            }
+        }
+        assert!((srgb_eotf(0.5) - 0.214_041).abs() < 1e-6);
+        assert!((TransferFunction::Gamma(2.0).to_linear(0.5) - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn encoded_rgb() {
+        let grey = RGB::<f64>::from([0.5, 0.5, 0.5]);
+        assert!(grey.to_linear()[0] < 0.22);
+        assert!(RGB::from_linear(&grey.to_linear()).approx_eq(&grey, Some(Prop::from(0.000_001))));
+        assert_eq!(RGB::<u8>::RED.to_linear(), RGB::<u8>::RED);
+        let encoded = EncodedRGB::from(grey);
+        assert_eq!(encoded.transfer(), TransferFunction::Srgb);
+        assert_eq!(encoded.encoded(), grey);
+        assert_eq!(encoded.to_linear(), grey.to_linear());
+        let linear = encoded.reencoded(TransferFunction::Linear);
+        assert!(linear
+            .encoded()
+            .approx_eq(&grey.to_linear(), Some(Prop::from(0.000_001))));
+        assert!(linear
+            .reencoded(TransferFunction::Srgb)
+            .encoded()
+            .approx_eq(&grey, Some(Prop::from(0.000_001))));
+    }
+}
@@ -0,0 +1,736 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    attributes::Value,
+    fdrn::{Prop, UFDRNumber},
+    hcv::HCV,
+    hue::{angle::Angle, Hue, HueIfce},
+    image::HcvImage,
+    progress::{self, Cancelled, NoProgress, Progress},
+    rgb::RGB,
+    ColourBasics, LightLevel, ManipulatedColour,
+};
+
+pub use crate::transfer::{srgb_eotf as linear_from_srgb, srgb_oetf as srgb_from_linear};
+
+/// A per pixel transformation carried out in linear light.  Results are
+/// clamped back into the valid range.
+pub trait PixelTransform {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3];
+
+    fn transform(&self, rgb: &RGB<f64>) -> RGB<f64> {
+        let linear = [
+            linear_from_srgb(rgb[0]),
+            linear_from_srgb(rgb[1]),
+            linear_from_srgb(rgb[2]),
+        ];
+        let [red, green, blue] = self.transform_linear(linear);
+        let encode = |component: f64| srgb_from_linear(component.clamp(0.0, 1.0)).min(1.0);
+        RGB::from([encode(red), encode(green), encode(blue)])
+    }
+
+    fn transform_pixels<L: LightLevel>(&self, pixels: &mut [RGB<L>])
+    where
+        Self: Sized,
+    {
+        for pixel in pixels.iter_mut() {
+            let rgb: RGB<f64> = RGB::from(<[Prop; 3]>::from(*pixel));
+            *pixel = RGB::from(<[Prop; 3]>::from(self.transform(&rgb)));
+        }
+    }
+}
+
+/// Scale linear light by 2 to the power of the given number of stops.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exposure(pub f64);
+
+impl PixelTransform for Exposure {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3] {
+        let scale = 2.0_f64.powf(self.0);
+        [linear[0] * scale, linear[1] * scale, linear[2] * scale]
+    }
+}
+
+/// Raise linear light to the power 1/gamma (gamma greater than one
+/// brightens the mid tones and less than one darkens them).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gamma(pub f64);
+
+impl PixelTransform for Gamma {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3] {
+        debug_assert!(self.0 > 0.0);
+        let power = 1.0 / self.0;
+        [
+            linear[0].max(0.0).powf(power),
+            linear[1].max(0.0).powf(power),
+            linear[2].max(0.0).powf(power),
+        ]
+    }
+}
+
+/// Replace each colour with the grey of the same value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ToMonochrome;
+
+impl PixelTransform for ToMonochrome {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3] {
+        let value = linear
+            .iter()
+            .map(|c| srgb_from_linear(c.max(0.0)))
+            .sum::<f64>()
+            / 3.0;
+        [linear_from_srgb(value); 3]
+    }
+}
+
+// Apply `transform` to the sRGB encoding of `linear`
+fn via_encoded(linear: [f64; 3], transform: impl Fn([f64; 3]) -> [f64; 3]) -> [f64; 3] {
+    let encoded = linear.map(|c| srgb_from_linear(c.clamp(0.0, 1.0)));
+    transform(encoded).map(|c| linear_from_srgb(c.clamp(0.0, 1.0)))
+}
+
+// Apply `transform` to the HCV of `linear`
+fn via_hcv(linear: [f64; 3], transform: impl Fn(HCV) -> HCV) -> [f64; 3] {
+    via_encoded(linear, |encoded| {
+        let rgb = transform(HCV::from(RGB::<f64>::from(encoded))).rgb::<f64>();
+        [rgb[0], rgb[1], rgb[2]]
+    })
+}
+
+/// The classic sepia tone matrix (applied to the encoded values).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ToSepia;
+
+impl PixelTransform for ToSepia {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3] {
+        const SEPIA: [[f64; 3]; 3] = [
+            [0.393, 0.769, 0.189],
+            [0.349, 0.686, 0.168],
+            [0.272, 0.534, 0.131],
+        ];
+        via_encoded(linear, |encoded| {
+            SEPIA.map(|row| (0..3).map(|i| row[i] * encoded[i]).sum())
+        })
+    }
+}
+
+/// The negative (of the encoded values).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Invert;
+
+impl PixelTransform for Invert {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3] {
+        via_encoded(linear, |encoded| encoded.map(|c| 1.0 - c))
+    }
+}
+
+/// Reduce each (encoded) component to `levels` evenly spaced levels (at
+/// least two).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Posterize {
+    pub levels: u8,
+}
+
+impl PixelTransform for Posterize {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3] {
+        let steps = f64::from(self.levels.max(2) - 1);
+        via_encoded(linear, |encoded| {
+            encoded.map(|c| (c * steps).round() / steps)
+        })
+    }
+}
+
+/// Rotate hues (greys are unaffected) keeping chroma and value where
+/// possible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotateHue {
+    pub angle: Angle,
+}
+
+impl PixelTransform for RotateHue {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3] {
+        via_hcv(linear, |hcv| hcv.rotated(self.angle))
+    }
+}
+
+/// Scale chroma by `factor` (keeping hue and value where possible).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdjustChroma {
+    pub factor: f64,
+}
+
+impl PixelTransform for AdjustChroma {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3] {
+        via_hcv(linear, |hcv| {
+            let c_prop = (f64::from(hcv.c_prop) * self.factor.max(0.0)).min(1.0);
+            HCV::new_approx(hcv.hue, hcv.sum, Prop::from(c_prop))
+        })
+    }
+}
+
+/// Shift the balance between red (warm) and cyan (cool) by scaling red by
+/// `1 + amount` and green and blue by `1 - amount` (in linear light).
+/// Positive amounts warm and negative ones cool with `amount` limited to
+/// the range -1 to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShiftWarmth {
+    pub amount: f64,
+}
+
+impl PixelTransform for ShiftWarmth {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3] {
+        let amount = self.amount.clamp(-1.0, 1.0);
+        [
+            linear[0] * (1.0 + amount),
+            linear[1] * (1.0 - amount),
+            linear[2] * (1.0 - amount),
+        ]
+    }
+}
+
+/// Apply `transforms` in order.
+#[derive(Default)]
+pub struct ChainedTransform {
+    transforms: Vec<Box<dyn PixelTransform + Send + Sync>>,
+}
+
+impl ChainedTransform {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn then(mut self, transform: impl PixelTransform + Send + Sync + 'static) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.transforms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+}
+
+impl PixelTransform for ChainedTransform {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3] {
+        self.transforms.iter().fold(linear, |linear, transform| {
+            transform.transform_linear(linear)
+        })
+    }
+}
+
+pub trait TransformedColour: ColourBasics + Sized {
+    fn transformed(&self, transform: &impl PixelTransform) -> Self;
+
+    fn exposed(&self, stops: f64) -> Self {
+        self.transformed(&Exposure(stops))
+    }
+
+    fn gamma_corrected(&self, gamma: f64) -> Self {
+        self.transformed(&Gamma(gamma))
+    }
+}
+
+impl TransformedColour for HCV {
+    fn transformed(&self, transform: &impl PixelTransform) -> Self {
+        HCV::from(transform.transform(&self.rgb::<f64>()))
+    }
+}
+
+impl<L: LightLevel> TransformedColour for RGB<L> {
+    fn transformed(&self, transform: &impl PixelTransform) -> Self {
+        RGB::from(<[Prop; 3]>::from(transform.transform(&self.rgb::<f64>())))
+    }
+}
+
+/// Reduce colour noise by replacing each pixel's hue and chroma with the
+/// (component wise) median of the chroma vectors in its neighbourhood while
+/// leaving its value untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChromaDenoise {
+    radius: usize,
+}
+
+impl Default for ChromaDenoise {
+    fn default() -> Self {
+        Self { radius: 1 }
+    }
+}
+
+impl ChromaDenoise {
+    pub fn new(radius: usize) -> Self {
+        Self { radius }
+    }
+
+    /// `pixels` is a row major plane `width` pixels wide.
+    pub fn apply(&self, pixels: &[HCV], width: usize) -> Vec<HCV> {
+        self.apply_with_progress(pixels, width, &NoProgress)
+            .expect("NoProgress never cancels")
+    }
+
+    pub fn apply_with_progress(
+        &self,
+        pixels: &[HCV],
+        width: usize,
+        progress: &impl Progress,
+    ) -> Result<Vec<HCV>, Cancelled> {
+        debug_assert!(width > 0 && pixels.len() % width == 0);
+        if width == 0 {
+            return Ok(pixels.to_vec());
+        }
+        let height = pixels.len() / width;
+        let vectors: Vec<(f64, f64)> = pixels
+            .iter()
+            .map(|pixel| match pixel.hue_angle() {
+                Some(angle) => {
+                    let radians = f64::from(angle).to_radians();
+                    let c_prop = f64::from(pixel.c_prop);
+                    (c_prop * radians.cos(), c_prop * radians.sin())
+                }
+                None => (0.0, 0.0),
+            })
+            .collect();
+        let median = |values: &mut Vec<f64>| {
+            values.sort_by(|a, b| a.total_cmp(b));
+            values[values.len() / 2]
+        };
+        let mut output = Vec::with_capacity(pixels.len());
+        let mut xs = vec![];
+        let mut ys = vec![];
+        for row in 0..height {
+            progress.checkpoint(progress::proportion(row, height))?;
+            for col in 0..width {
+                xs.clear();
+                ys.clear();
+                for r in row.saturating_sub(self.radius)..(row + self.radius + 1).min(height) {
+                    for c in col.saturating_sub(self.radius)..(col + self.radius + 1).min(width) {
+                        let (x, y) = vectors[r * width + c];
+                        xs.push(x);
+                        ys.push(y);
+                    }
+                }
+                let (x, y) = (median(&mut xs), median(&mut ys));
+                let c_prop = x.hypot(y).min(1.0);
+                let sum: UFDRNumber = pixels[row * width + col].sum;
+                output.push(if c_prop < 0.000_001 {
+                    HCV::new_approx(None, sum, Prop::ZERO)
+                } else {
+                    let hue = Hue::from(Angle::from(y.atan2(x).to_degrees()));
+                    HCV::new_approx(Some(hue), sum, Prop::from(c_prop))
+                });
+            }
+        }
+        progress.report(Prop::ONE);
+        Ok(output)
+    }
+}
+
+/// A grey whose value is the chroma of the input colour (or, if a
+/// threshold is given, white where the chroma reaches the threshold and
+/// black elsewhere) for selecting the colourful parts of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ChromaMask {
+    pub threshold: Option<Prop>,
+}
+
+impl ChromaMask {
+    pub fn new(threshold: Option<Prop>) -> Self {
+        Self { threshold }
+    }
+
+    pub fn mask_value(&self, c_prop: Prop) -> Value {
+        match self.threshold {
+            Some(threshold) if c_prop >= threshold => Value::ONE,
+            Some(_) => Value::ZERO,
+            None => c_prop.into(),
+        }
+    }
+
+    pub fn mask_colour(&self, colour: &impl ColourBasics) -> HCV {
+        HCV::new_grey(self.mask_value(colour.chroma_prop()))
+    }
+}
+
+impl PixelTransform for ChromaMask {
+    fn transform_linear(&self, linear: [f64; 3]) -> [f64; 3] {
+        let encoded = RGB::<f64>::from([
+            srgb_from_linear(linear[0].clamp(0.0, 1.0)),
+            srgb_from_linear(linear[1].clamp(0.0, 1.0)),
+            srgb_from_linear(linear[2].clamp(0.0, 1.0)),
+        ]);
+        let grey = linear_from_srgb(f64::from(self.mask_value(encoded.chroma_prop())));
+        [grey, grey, grey]
+    }
+
+    // the mask is defined in terms of encoded values so skip the round trip
+    fn transform(&self, rgb: &RGB<f64>) -> RGB<f64> {
+        self.mask_colour(rgb).rgb()
+    }
+}
+
+/// A soft mask selecting hues in the range from `from` anticlockwise to
+/// `to` (so that ranges may wrap around CYAN) fading to nothing over
+/// `feather` either side.  Greys are never selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HueRangeMask {
+    pub from: Angle,
+    pub to: Angle,
+    pub feather: Angle,
+}
+
+impl HueRangeMask {
+    pub fn new(from: Angle, to: Angle, feather: Angle) -> Self {
+        Self { from, to, feather }
+    }
+
+    /// The degree (between zero and one) to which `hue` is selected.
+    pub fn weight(&self, hue: Option<Hue>) -> f64 {
+        let hue = match hue {
+            Some(hue) => hue,
+            None => return 0.0,
+        };
+        let degrees = |angle: Angle| f64::from(angle).rem_euclid(360.0);
+        let offset = degrees(hue.angle() - self.from);
+        let span = degrees(self.to - self.from);
+        if offset <= span {
+            1.0
+        } else {
+            let distance = (offset - span).min(360.0 - offset);
+            let feather = f64::from(self.feather).abs();
+            if feather > 0.0 {
+                (1.0 - distance / feather).max(0.0)
+            } else {
+                0.0
+            }
+        }
+    }
+
+    pub fn mask_value(&self, hue: Option<Hue>) -> Value {
+        Value::from(self.weight(hue))
+    }
+}
+
+/// Make colours close to the `key` hue transparent.  Hues within
+/// `tolerance` of the key are fully keyed (in proportion to their chroma)
+/// fading to unkeyed at twice the tolerance.  The chroma of hues within 90
+/// degrees of the key is reduced by up to `spill_suppression` to remove
+/// reflected key colour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChromaKey {
+    pub key: Hue,
+    pub tolerance: Angle,
+    pub spill_suppression: Prop,
+}
+
+impl ChromaKey {
+    pub fn new(key: Hue, tolerance: Angle, spill_suppression: Prop) -> Self {
+        Self {
+            key,
+            tolerance,
+            spill_suppression,
+        }
+    }
+
+    fn hue_distance(&self, hue: Hue) -> f64 {
+        f64::from(hue.angle() - self.key.angle()).abs()
+    }
+
+    pub fn alpha(&self, colour: &impl ColourBasics) -> Prop {
+        let hue = match colour.hue() {
+            Some(hue) => hue,
+            None => return Prop::ONE,
+        };
+        let tolerance = f64::from(self.tolerance).abs();
+        let distance = self.hue_distance(hue);
+        let keyness = if distance <= tolerance {
+            1.0
+        } else if distance < 2.0 * tolerance {
+            2.0 - distance / tolerance
+        } else {
+            return Prop::ONE;
+        };
+        Prop::from(1.0 - keyness * f64::from(colour.chroma_prop()))
+    }
+
+    pub fn despilled(&self, colour: &impl ColourBasics) -> HCV {
+        let hcv = colour.hcv();
+        match hcv.hue {
+            Some(hue) => {
+                let distance = self.hue_distance(hue);
+                if distance < 90.0 {
+                    let reduction = f64::from(self.spill_suppression) * (1.0 - distance / 90.0);
+                    let c_prop = Prop::from(f64::from(hcv.c_prop) * (1.0 - reduction));
+                    HCV::new_approx(Some(hue), hcv.sum, c_prop)
+                } else {
+                    hcv
+                }
+            }
+            None => hcv,
+        }
+    }
+}
+
+/// Stretch values so that the `low_pct` percentile becomes black and the
+/// `high_pct` percentile becomes white (keeping hue and chroma where
+/// possible).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoContrast {
+    pub low_pct: f64,
+    pub high_pct: f64,
+}
+
+impl Default for AutoContrast {
+    fn default() -> Self {
+        Self {
+            low_pct: 0.5,
+            high_pct: 99.5,
+        }
+    }
+}
+
+impl AutoContrast {
+    pub fn new(low_pct: f64, high_pct: f64) -> Self {
+        Self { low_pct, high_pct }
+    }
+
+    /// The values at the low and high percentiles.
+    pub fn value_range(&self, image: &HcvImage) -> Option<(Value, Value)> {
+        if image.is_empty() {
+            return None;
+        }
+        let mut sums = image.sums().to_vec();
+        sums.sort_unstable();
+        let percentile = |pct: f64| -> Value {
+            let index = (pct.clamp(0.0, 100.0) / 100.0 * (sums.len() - 1) as f64).round();
+            (sums[index as usize] / 3).into()
+        };
+        Some((percentile(self.low_pct), percentile(self.high_pct)))
+    }
+
+    pub fn apply(&self, image: &mut HcvImage) {
+        if let Some((low, high)) = self.value_range(image) {
+            if high > low {
+                let low = f64::from(low);
+                let range = f64::from(high) - low;
+                image.map_values(|value| {
+                    Value::from(((f64::from(value) - low) / range).clamp(0.0, 1.0))
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod transform_tests {
+    use super::*;
+    use crate::{debug::ApproxEq, HueConstants, RGBConstants};
+
+    #[test]
+    fn srgb_round_trip() {
+        for i in 0..=100 {
+            let component = i as f64 / 100.0;
+            let round_trip = srgb_from_linear(linear_from_srgb(component));
+            assert!((component - round_trip).abs() < 0.000_001);
+        }
+    }
+
+    fn close(lhs: &RGB<f64>, rhs: &RGB<f64>) -> bool {
+        (0..3).all(|i| (lhs[i] - rhs[i]).abs() < 0.000_001)
+    }
+
+    #[test]
+    fn exposure() {
+        let grey = RGB::<f64>::from([0.5, 0.5, 0.5]);
+        let brighter = Exposure(1.0).transform(&grey);
+        assert!((linear_from_srgb(brighter[0]) - 2.0 * linear_from_srgb(0.5)).abs() < 0.000_001);
+        assert!(close(&Exposure(0.0).transform(&grey), &grey));
+        assert!(close(&Exposure(5.0).transform(&grey), &RGB::<f64>::WHITE));
+        assert!(HCV::RED.exposed(-1.0).value() < HCV::RED.value());
+        assert_eq!(HCV::RED.exposed(-1.0).hue(), HCV::RED.hue());
+        assert_eq!(RGB::<u8>::BLACK.exposed(3.0), RGB::<u8>::BLACK);
+    }
+
+    #[test]
+    fn gamma() {
+        let grey = RGB::<f64>::from([0.5, 0.5, 0.5]);
+        assert!(close(&Gamma(1.0).transform(&grey), &grey));
+        assert!(Gamma(2.2).transform(&grey)[0] > 0.5);
+        assert!(Gamma(0.5).transform(&grey)[0] < 0.5);
+        let mut pixels = vec![
+            RGB::<u16>::BLACK,
+            RGB::<u16>::from([0x8000, 0x8000, 0x8000]),
+        ];
+        Gamma(2.2).transform_pixels(&mut pixels);
+        assert_eq!(pixels[0], RGB::<u16>::BLACK);
+        assert!(pixels[1][0] > 0x8000);
+    }
+
+    #[test]
+    fn to_monochrome() {
+        let colour = RGB::<f64>::from([0.9, 0.3, 0.6]);
+        let grey = ToMonochrome.transform(&colour);
+        assert!(close(&grey, &RGB::<f64>::from([0.6, 0.6, 0.6])));
+        let grey = HCV::YELLOW.transformed(&ToMonochrome);
+        assert!(grey.is_grey());
+        assert!((grey.rgb::<f64>()[0] - 2.0 / 3.0).abs() < 0.000_001);
+    }
+
+    #[test]
+    fn more_transforms() {
+        let colour = RGB::<f64>::from([0.8, 0.4, 0.2]);
+        assert!(close(
+            &Invert.transform(&colour),
+            &RGB::<f64>::from([0.2, 0.6, 0.8])
+        ));
+        assert!(close(
+            &Posterize { levels: 3 }.transform(&colour),
+            &RGB::<f64>::from([1.0, 0.5, 0.0])
+        ));
+        assert!(close(
+            &Posterize { levels: 0 }.transform(&colour),
+            &RGB::<f64>::from([1.0, 0.0, 0.0])
+        ));
+        let sepia = ToSepia.transform(&RGB::<f64>::from([0.5, 0.5, 0.5]));
+        assert!(sepia[0] > sepia[1] && sepia[1] > sepia[2]);
+        let rotated = HCV::RED.transformed(&RotateHue {
+            angle: Angle::from(120),
+        });
+        assert!(rotated
+            .rgb::<f64>()
+            .approx_eq(&RGB::<f64>::GREEN, Some(Prop::from(0.000_01))));
+        let grey = RGB::<f64>::from([0.5, 0.5, 0.5]);
+        assert!(close(
+            &RotateHue {
+                angle: Angle::from(60)
+            }
+            .transform(&grey),
+            &grey
+        ));
+        let muted = AdjustChroma { factor: 0.5 }.transform(&colour);
+        assert!((f64::from(muted.chroma_prop()) - 0.3).abs() < 0.000_01);
+        assert!(AdjustChroma { factor: 0.0 }.transform(&colour).is_grey());
+        let warmer = ShiftWarmth { amount: 0.2 }.transform(&grey);
+        assert_eq!(warmer.hue(), Some(Hue::RED));
+        let cooler = ShiftWarmth { amount: -0.2 }.transform(&grey);
+        assert_eq!(cooler.hue(), Some(Hue::CYAN));
+        assert!(close(
+            &ShiftWarmth { amount: 0.0 }.transform(&colour),
+            &colour
+        ));
+        assert!(close(
+            &ShiftWarmth { amount: 5.0 }.transform(&colour),
+            &RGB::<f64>::RED
+        ));
+        let chain = ChainedTransform::new().then(Invert).then(Invert);
+        assert_eq!(chain.len(), 2);
+        assert!(close(&chain.transform(&colour), &colour));
+        let chain = ChainedTransform::new()
+            .then(AdjustChroma { factor: 0.0 })
+            .then(Exposure(1.0));
+        assert!(chain.transform(&colour).is_grey());
+        assert!(close(&ChainedTransform::new().transform(&colour), &colour));
+    }
+
+    #[test]
+    fn chroma_denoise() {
+        let grey = HCV::new_grey(Value::from(0.5));
+        let mut pixels = vec![grey; 25];
+        pixels[12] = HCV::from(RGB::<f64>::from([0.6, 0.45, 0.45]));
+        let denoised = ChromaDenoise::default().apply(&pixels, 5);
+        assert_eq!(denoised.len(), 25);
+        assert!(denoised[12].is_grey());
+        assert_eq!(denoised[12].value(), pixels[12].value());
+        assert_eq!(denoised[0], grey);
+
+        let red = HCV::from(RGB::<f64>::from([0.8, 0.2, 0.2]));
+        let mut pixels = vec![red; 12];
+        pixels[5] = HCV::from(RGB::<f64>::from([0.2, 0.5, 0.8]));
+        let denoised = ChromaDenoise::new(2).apply(&pixels, 4);
+        assert_eq!(denoised[5].hue(), red.hue());
+        assert!(denoised[5].sum.approx_eq(&pixels[5].sum, None));
+    }
+
+    #[test]
+    fn auto_contrast() {
+        let pixels: Vec<HCV> = [0.25, 0.5, 0.75]
+            .iter()
+            .map(|value| HCV::new_grey(Value::from(*value)))
+            .chain(std::iter::once(HCV::from(RGB::<f64>::from([
+                0.5, 0.4, 0.4,
+            ]))))
+            .collect();
+        let mut image = HcvImage::from_pixels(&pixels, 2, 2).unwrap();
+        let contrast = AutoContrast::new(0.0, 100.0);
+        let (low, high) = contrast.value_range(&image).unwrap();
+        assert!(low.approx_eq(&Value::from(0.25), None));
+        assert!(high.approx_eq(&Value::from(0.75), None));
+        contrast.apply(&mut image);
+        assert!(image.get(0, 0).value().approx_eq(&Value::ZERO, None));
+        assert!(image.get(1, 0).value().approx_eq(&Value::from(0.5), None));
+        assert!(image.get(0, 1).value().approx_eq(&Value::ONE, None));
+        assert_eq!(image.get(1, 1).hue(), pixels[3].hue());
+        assert!(image.get(1, 1).value() < pixels[3].value());
+        let mut flat = HcvImage::new(2, 2);
+        contrast.apply(&mut flat);
+        assert_eq!(flat, HcvImage::new(2, 2));
+    }
+
+    #[test]
+    fn chroma_mask() {
+        let pink = RGB::<f64>::from([0.8, 0.4, 0.4]);
+        let mask = ChromaMask::default();
+        assert!(mask.mask_colour(&pink).is_grey());
+        assert!(mask
+            .mask_colour(&pink)
+            .value()
+            .approx_eq(&Value::from(0.4), None));
+        assert_eq!(mask.mask_colour(&HCV::RED), HCV::WHITE);
+        assert_eq!(mask.mask_colour(&HCV::WHITE), HCV::BLACK);
+        let mask = ChromaMask::new(Some(Prop::from(0.5)));
+        assert_eq!(mask.transform(&pink), RGB::<f64>::BLACK);
+        assert_eq!(mask.transform(&RGB::<f64>::GREEN), RGB::<f64>::WHITE);
+    }
+
+    #[test]
+    fn hue_range_mask() {
+        let degrees = |degrees: i16| Some(Hue::from(Angle::from(degrees)));
+        let greens = HueRangeMask::new(
+            Angle::from(90_i16),
+            Angle::from(150_i16),
+            Angle::from(20_i16),
+        );
+        assert_eq!(greens.weight(Some(Hue::GREEN)), 1.0);
+        assert_eq!(greens.weight(None), 0.0);
+        assert_eq!(greens.weight(Some(Hue::RED)), 0.0);
+        assert!((greens.weight(degrees(160)) - 0.5).abs() < 0.001);
+        assert!((greens.weight(degrees(80)) - 0.5).abs() < 0.001);
+        // wrap around CYAN
+        let cyans = HueRangeMask::new(
+            Angle::from(160_i16),
+            Angle::from(-160_i16),
+            Angle::from(0_i16),
+        );
+        assert_eq!(cyans.weight(Some(Hue::CYAN)), 1.0);
+        assert_eq!(cyans.weight(degrees(170)), 1.0);
+        assert_eq!(cyans.weight(degrees(-170)), 1.0);
+        assert_eq!(cyans.weight(Some(Hue::GREEN)), 0.0);
+        assert_eq!(cyans.weight(Some(Hue::BLUE)), 0.0);
+    }
+
+    #[test]
+    fn chroma_key() {
+        let key = ChromaKey::new(Hue::GREEN, Angle::from(20_i16), Prop::from(0.5));
+        assert_eq!(key.alpha(&HCV::GREEN), Prop::ZERO);
+        assert_eq!(key.alpha(&HCV::WHITE), Prop::ONE);
+        assert_eq!(key.alpha(&HCV::RED), Prop::ONE);
+        let pale_green = HCV::from(RGB::<f64>::from([0.5, 0.75, 0.5]));
+        assert!(key.alpha(&pale_green) > Prop::from(0.7));
+        assert!(key.alpha(&pale_green) < Prop::ONE);
+        let yellow_green = HCV::from(RGB::<f64>::from([0.6, 0.8, 0.2]));
+        let despilled = key.despilled(&yellow_green);
+        assert_eq!(despilled.hue(), yellow_green.hue());
+        assert!(despilled.chroma_prop() < yellow_green.chroma_prop());
+        assert_eq!(key.despilled(&HCV::RED), HCV::RED);
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use crate::{
+    hcv::HCV,
+    rgb::RGB,
+    standards::WhitePoint,
+    transfer::{srgb_eotf, srgb_oetf},
+    ColourBasics, LightLevel,
+};
+
+pub(crate) type Matrix = [[f64; 3]; 3];
+
+const SRGB_TO_XYZ: Matrix = [
+    [0.412_456_4, 0.357_576_1, 0.180_437_5],
+    [0.212_672_9, 0.715_152_2, 0.072_175_0],
+    [0.019_333_9, 0.119_192_0, 0.950_304_1],
+];
+
+const XYZ_TO_SRGB: Matrix = [
+    [3.240_454_2, -1.537_138_5, -0.498_531_4],
+    [-0.969_266_0, 1.876_010_8, 0.041_556_0],
+    [0.055_643_4, -0.204_025_9, 1.057_225_2],
+];
+
+const BRADFORD: Matrix = [
+    [0.895_1, 0.266_4, -0.161_4],
+    [-0.750_2, 1.713_5, 0.036_7],
+    [0.038_9, -0.068_5, 1.029_6],
+];
+
+const BRADFORD_INVERSE: Matrix = [
+    [0.986_992_9, -0.147_054_3, 0.159_962_7],
+    [0.432_305_3, 0.518_360_3, 0.049_291_2],
+    [-0.008_528_7, 0.040_042_8, 0.968_486_7],
+];
+
+const CAT02: Matrix = [
+    [0.732_8, 0.429_6, -0.162_4],
+    [-0.703_6, 1.697_5, 0.006_1],
+    [0.003_0, 0.013_6, 0.983_4],
+];
+
+const CAT02_INVERSE: Matrix = [
+    [1.096_124, -0.278_869, 0.182_745],
+    [0.454_369, 0.473_533, 0.072_098],
+    [-0.009_628, -0.005_698, 1.015_326],
+];
+
+pub(crate) fn apply(matrix: &Matrix, vector: [f64; 3]) -> [f64; 3] {
+    let row = |i: usize| (0..3).map(|j| matrix[i][j] * vector[j]).sum();
+    [row(0), row(1), row(2)]
+}
+
+pub(crate) fn multiply(lhs: &Matrix, rhs: &Matrix) -> Matrix {
+    let mut product = [[0.0; 3]; 3];
+    for (i, row) in product.iter_mut().enumerate() {
+        for (j, element) in row.iter_mut().enumerate() {
+            *element = (0..3).map(|k| lhs[i][k] * rhs[k][j]).sum();
+        }
+    }
+    product
+}
+
+/// Chromatic adaptation transform i.e. how to find the colour that looks
+/// the same under a different illuminant.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Adaptation {
+    #[default]
+    Bradford,
+    /// As used in CIECAM02.
+    Cat02,
+}
+
+impl Adaptation {
+    fn cone_matrices(&self) -> (&'static Matrix, &'static Matrix) {
+        match self {
+            Adaptation::Bradford => (&BRADFORD, &BRADFORD_INVERSE),
+            Adaptation::Cat02 => (&CAT02, &CAT02_INVERSE),
+        }
+    }
+
+    fn cone_scale(&self, from: &WhitePoint, to: &WhitePoint) -> [f64; 3] {
+        let (cone, _) = self.cone_matrices();
+        let from_cone = apply(cone, from.xyz());
+        let to_cone = apply(cone, to.xyz());
+        [0, 1, 2].map(|i| to_cone[i] / from_cone[i])
+    }
+
+    pub fn adapt(&self, xyz: [f64; 3], from: &WhitePoint, to: &WhitePoint) -> [f64; 3] {
+        if (from.x, from.y) == (to.x, to.y) {
+            return xyz;
+        }
+        let (cone, inverse) = self.cone_matrices();
+        let scale = self.cone_scale(from, to);
+        let cone_response = apply(cone, xyz);
+        apply(inverse, [0, 1, 2].map(|i| cone_response[i] * scale[i]))
+    }
+
+    /// The matrix equivalent of `adapt()`.
+    pub fn matrix(&self, from: &WhitePoint, to: &WhitePoint) -> [[f64; 3]; 3] {
+        if (from.x, from.y) == (to.x, to.y) {
+            return [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        }
+        let (cone, inverse) = self.cone_matrices();
+        let [x, y, z] = self.cone_scale(from, to);
+        let scale = [[x, 0.0, 0.0], [0.0, y, 0.0], [0.0, 0.0, z]];
+        multiply(inverse, &multiply(&scale, cone))
+    }
+}
+
+/// CIE 1931 tristimulus values (scaled so that Y is one for the reference
+/// white) relative to a reference white.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct XYZ {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub white_point: WhitePoint,
+}
+
+impl XYZ {
+    pub fn new(x: f64, y: f64, z: f64, white_point: WhitePoint) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            white_point,
+        }
+    }
+
+    /// The (sRGB whose native white is D65) `colour` relative to
+    /// `white_point` using Bradford adaptation.
+    pub fn from_colour(colour: &impl ColourBasics, white_point: WhitePoint) -> Self {
+        let rgb = colour.rgb::<f64>();
+        let linear = [0, 1, 2].map(|i| srgb_eotf(rgb[i]));
+        let xyz =
+            Adaptation::Bradford.adapt(apply(&SRGB_TO_XYZ, linear), &WhitePoint::D65, &white_point);
+        Self::from((xyz, white_point))
+    }
+
+    pub fn xyz(&self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    /// The CIE xy chromaticity or `None` for black.
+    pub fn chromaticity(&self) -> Option<(f64, f64)> {
+        let sum = self.x + self.y + self.z;
+        if sum > 0.0 {
+            Some((self.x / sum, self.y / sum))
+        } else {
+            None
+        }
+    }
+
+    /// The corresponding colour under a different reference white.
+    pub fn adapted(&self, white_point: WhitePoint, adaptation: Adaptation) -> Self {
+        let xyz = adaptation.adapt(self.xyz(), &self.white_point, &white_point);
+        Self::from((xyz, white_point))
+    }
+
+    /// Out of (sRGB) gamut colours are clipped.
+    pub fn rgb<L: LightLevel>(&self) -> RGB<L> {
+        let xyz = Adaptation::Bradford.adapt(self.xyz(), &self.white_point, &WhitePoint::D65);
+        let linear = apply(&XYZ_TO_SRGB, xyz);
+        let rgb = RGB::<f64>::from(linear.map(|c| srgb_oetf(c.clamp(0.0, 1.0)).clamp(0.0, 1.0)));
+        rgb.rgb::<L>()
+    }
+
+    pub fn hcv(&self) -> HCV {
+        HCV::from(self.rgb::<f64>())
+    }
+}
+
+impl From<([f64; 3], WhitePoint)> for XYZ {
+    fn from((xyz, white_point): ([f64; 3], WhitePoint)) -> Self {
+        Self::new(xyz[0], xyz[1], xyz[2], white_point)
+    }
+}
+
+impl<L: LightLevel> From<&RGB<L>> for XYZ {
+    fn from(rgb: &RGB<L>) -> Self {
+        XYZ::from_colour(rgb, WhitePoint::D65)
+    }
+}
+
+impl From<&HCV> for XYZ {
+    fn from(hcv: &HCV) -> Self {
+        XYZ::from_colour(hcv, WhitePoint::D65)
+    }
+}
+
+impl<L: LightLevel> From<XYZ> for RGB<L> {
+    fn from(xyz: XYZ) -> Self {
+        xyz.rgb::<L>()
+    }
+}
+
+impl From<XYZ> for HCV {
+    fn from(xyz: XYZ) -> Self {
+        xyz.hcv()
+    }
+}
+
+#[cfg(test)]
+mod xyz_tests {
+    use super::*;
+    use crate::{HueConstants, RGBConstants};
+
+    fn near(lhs: [f64; 3], rhs: [f64; 3], tolerance: f64) -> bool {
+        (0..3).all(|i| (lhs[i] - rhs[i]).abs() < tolerance)
+    }
+
+    #[test]
+    fn rgb_round_trip() {
+        let white = XYZ::from(&RGB::<f64>::WHITE);
+        assert!(near(white.xyz(), WhitePoint::D65.xyz(), 1e-3));
+        let (x, y) = white.chromaticity().unwrap();
+        assert!((x - WhitePoint::D65.x).abs() < 1e-3 && (y - WhitePoint::D65.y).abs() < 1e-3);
+        assert_eq!(XYZ::from(&HCV::BLACK).chromaticity(), None);
+        let red = XYZ::from(&HCV::RED);
+        assert!(near(
+            red.xyz(),
+            [0.412_456_4, 0.212_672_9, 0.019_333_9],
+            1e-9
+        ));
+        let rgb = RGB::<f64>::from([0.2, 0.6, 0.9]);
+        assert!(
+            RGB::<f64>::from(XYZ::from(&rgb)).approx_eq(&rgb, Some(crate::Prop::from(0.000_01)))
+        );
+    }
+
+    #[test]
+    fn chromatic_adaptation() {
+        for adaptation in [Adaptation::Bradford, Adaptation::Cat02] {
+            // the reference white maps to the other reference white
+            let d65 = XYZ::from((WhitePoint::D65.xyz(), WhitePoint::D65));
+            let d50 = d65.adapted(WhitePoint::D50, adaptation);
+            assert!(near(d50.xyz(), WhitePoint::D50.xyz(), 1e-3));
+            let colour = XYZ::new(0.3, 0.4, 0.5, WhitePoint::D65);
+            let there = colour.adapted(WhitePoint::A, adaptation);
+            assert_ne!(there.xyz(), colour.xyz());
+            let back = there.adapted(WhitePoint::D65, adaptation);
+            assert!(near(back.xyz(), colour.xyz(), 1e-4));
+            let matrix = adaptation.matrix(&WhitePoint::D65, &WhitePoint::A);
+            assert!(near(apply(&matrix, colour.xyz()), there.xyz(), 1e-9));
+        }
+        let colour = XYZ::new(0.3, 0.4, 0.5, WhitePoint::D65);
+        assert_eq!(colour.adapted(WhitePoint::D65, Adaptation::Cat02), colour);
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! A hook for applications to correct the colours sent to a calibrated
+//! monitor (so that swatches better match print output).  Everything that
+//! sets a cairo source or GDK colour from a colour (including the
+//! `colour_math_gtk` widgets) passes it through the current calibration.
+//! There is none by default so colours are passed through unchanged.
+//!
+//! The calibration is per thread (i.e. per GUI).  Applications whose
+//! windows may be on monitors with different calibrations should install
+//! the appropriate one when a window moves (see `colour_math_gtk`'s
+//! `MonitorCalibrations`).
+//!
+//! Colours may also be soft proofed (before calibration) to show how they
+//! will come out on a printer etc. with `set_soft_proof()` or reduced to
+//! greys of the same value (for monochrome printers and value studies)
+//! with `set_monochrome()`.
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use colour_math::{
+    proof::SoftProof,
+    transfer::{srgb_eotf, srgb_oetf},
+    RGB,
+};
+
+/// Corrections applied (in order) to the sRGB values of colours before
+/// they are displayed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DisplayCalibration {
+    /// Applied to linear light (e.g. to map sRGB to the monitor's measured
+    /// primaries).
+    pub matrix: Option<[[f64; 3]; 3]>,
+    /// Per channel (red, green and blue) curves applied to the encoded
+    /// values.  Each maps evenly spaced inputs from zero to one to outputs
+    /// (with linear interpolation in between) and must have at least two
+    /// entries to have any effect.
+    pub curves: Option<[Vec<f64>; 3]>,
+}
+
+fn lookup(curve: &[f64], input: f64) -> f64 {
+    if curve.len() < 2 {
+        return input;
+    }
+    let position = input.clamp(0.0, 1.0) * (curve.len() - 1) as f64;
+    let index = (position.floor() as usize).min(curve.len() - 2);
+    let fraction = position - index as f64;
+    curve[index] + (curve[index + 1] - curve[index]) * fraction
+}
+
+impl DisplayCalibration {
+    pub fn from_matrix(matrix: [[f64; 3]; 3]) -> Self {
+        Self {
+            matrix: Some(matrix),
+            curves: None,
+        }
+    }
+
+    pub fn from_curves(curves: [Vec<f64>; 3]) -> Self {
+        Self {
+            matrix: None,
+            curves: Some(curves),
+        }
+    }
+
+    pub fn apply(&self, rgb: &RGB<f64>) -> RGB<f64> {
+        let mut channels = [rgb[0], rgb[1], rgb[2]];
+        if let Some(matrix) = self.matrix {
+            let linear = channels.map(srgb_eotf);
+            channels = [0, 1, 2].map(|i| {
+                let linear = (0..3).map(|j| matrix[i][j] * linear[j]).sum::<f64>();
+                srgb_oetf(linear.clamp(0.0, 1.0)).clamp(0.0, 1.0)
+            });
+        }
+        if let Some(curves) = &self.curves {
+            channels = [0, 1, 2].map(|i| lookup(&curves[i], channels[i]).clamp(0.0, 1.0));
+        }
+        RGB::from(channels)
+    }
+}
+
+thread_local! {
+    static CALIBRATION: RefCell<Option<Rc<DisplayCalibration>>> = RefCell::new(None);
+    static SOFT_PROOF: Cell<Option<SoftProof>> = Cell::new(None);
+    static MONOCHROME: Cell<bool> = Cell::new(false);
+}
+
+/// Install (or, with `None`, remove) the calibration used for subsequent
+/// drawing on this thread.  Widgets need to be redrawn to show the change.
+pub fn set_display_calibration(calibration: Option<DisplayCalibration>) {
+    CALIBRATION.with(|current| *current.borrow_mut() = calibration.map(Rc::new));
+}
+
+pub fn display_calibration() -> Option<Rc<DisplayCalibration>> {
+    CALIBRATION.with(|current| current.borrow().clone())
+}
+
+/// `rgb` corrected for the display (but not soft proofed).
+pub fn calibrated(rgb: &RGB<f64>) -> RGB<f64> {
+    CALIBRATION.with(|current| match &*current.borrow() {
+        Some(calibration) => calibration.apply(rgb),
+        None => *rgb,
+    })
+}
+
+/// Turn soft proofing (for subsequent drawing on this thread) on or off.
+pub fn set_soft_proof(soft_proof: Option<SoftProof>) {
+    SOFT_PROOF.with(|current| current.set(soft_proof));
+}
+
+pub fn soft_proof() -> Option<SoftProof> {
+    SOFT_PROOF.with(|current| current.get())
+}
+
+/// Whether `rgb` is outside the soft proof's gamut (always false when soft
+/// proofing is off).
+pub fn is_out_of_proof_gamut(rgb: &RGB<f64>) -> bool {
+    soft_proof().map_or(false, |soft_proof| !soft_proof.is_in_gamut(rgb))
+}
+
+/// Turn monochrome (value only) rendering on this thread on or off.
+pub fn set_monochrome(monochrome: bool) {
+    MONOCHROME.with(|current| current.set(monochrome));
+}
+
+pub fn is_monochrome() -> bool {
+    MONOCHROME.with(|current| current.get())
+}
+
+/// `rgb` as it should be sent to the display i.e. soft proofed (if on),
+/// reduced to grey (if monochrome) and calibrated.
+pub fn displayed(rgb: &RGB<f64>) -> RGB<f64> {
+    let rgb = match soft_proof() {
+        Some(soft_proof) => soft_proof.proofed(rgb),
+        None => *rgb,
+    };
+    if is_monochrome() {
+        let value = (rgb[0] + rgb[1] + rgb[2]) / 3.0;
+        calibrated(&RGB::from([value, value, value]))
+    } else {
+        calibrated(&rgb)
+    }
+}
+
+#[cfg(test)]
+mod calibration_tests {
+    use super::*;
+    use colour_math::{rgb_space::RGBSpace, standards::WhitePoint, transfer::TransferFunction};
+
+    fn near(lhs: &RGB<f64>, rhs: [f64; 3]) -> bool {
+        (0..3).all(|i| (lhs[i] - rhs[i]).abs() < 1e-9)
+    }
+
+    #[test]
+    fn calibrations() {
+        let rgb = RGB::<f64>::from([0.2, 0.5, 0.8]);
+        assert_eq!(calibrated(&rgb), rgb);
+        let identity =
+            DisplayCalibration::from_matrix([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+        assert!(near(&identity.apply(&rgb), [0.2, 0.5, 0.8]));
+        let curves =
+            DisplayCalibration::from_curves([vec![0.0, 0.5], vec![0.0, 0.5, 1.0], vec![1.0, 0.0]]);
+        assert!(near(&curves.apply(&rgb), [0.1, 0.5, 0.2]));
+        set_display_calibration(Some(curves.clone()));
+        assert!(near(&calibrated(&rgb), [0.1, 0.5, 0.2]));
+        assert_eq!(display_calibration().as_deref(), Some(&curves));
+        set_display_calibration(None);
+        assert_eq!(calibrated(&rgb), rgb);
+    }
+
+    #[test]
+    fn soft_proofing() {
+        let red = RGB::<f64>::from([1.0, 0.0, 0.0]);
+        assert!(!is_out_of_proof_gamut(&red));
+        assert_eq!(displayed(&red), red);
+        let proof = SoftProof::new(
+            RGBSpace::new(
+                "narrow",
+                [(0.50, 0.33), (0.32, 0.50), (0.20, 0.15)],
+                WhitePoint::D65,
+                TransferFunction::Srgb,
+            )
+            .unwrap(),
+        );
+        set_soft_proof(Some(proof));
+        assert!(is_out_of_proof_gamut(&red));
+        assert_eq!(displayed(&red), proof.proofed(&red));
+        set_display_calibration(Some(DisplayCalibration::from_curves([
+            vec![0.0, 0.5],
+            vec![0.0, 0.5],
+            vec![0.0, 0.5],
+        ])));
+        assert_eq!(displayed(&red), calibrated(&proof.proofed(&red)));
+        set_display_calibration(None);
+        set_soft_proof(None);
+        assert_eq!(displayed(&red), red);
+    }
+
+    #[test]
+    fn monochrome() {
+        let colour = RGB::<f64>::from([0.9, 0.3, 0.6]);
+        assert!(!is_monochrome());
+        set_monochrome(true);
+        assert!(near(&displayed(&colour), [0.6, 0.6, 0.6]));
+        set_monochrome(false);
+        assert_eq!(displayed(&colour), colour);
+    }
+}
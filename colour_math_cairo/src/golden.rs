@@ -0,0 +1,448 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! Visual regression testing of drawing code: render off-screen (via a
+//! cairo image surface) to PNG and compare with a stored "golden" image
+//! using a perceptual (CIE Lab delta E) difference rather than exact pixel
+//! equality so that small antialiasing differences between cairo versions
+//! don't cause failures.
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use pw_gtk_ext::cairo;
+
+use colour_math::{beigui::TextRendering, lab::Lab, HCV, RGB};
+
+use crate::{Drawer, Size};
+
+/// Set (to anything) to write the goldens (new or existing) from the
+/// current output.
+pub const UPDATE_ENV_VAR: &str = "COLOUR_MATH_UPDATE_GOLDENS";
+
+#[derive(Debug)]
+pub enum GoldenError {
+    Io(std::io::Error),
+    Cairo(String),
+    SizeMismatch {
+        expected: (usize, usize),
+        actual: (usize, usize),
+    },
+    /// There's no golden (and `UPDATE_ENV_VAR` isn't set).
+    Missing(PathBuf),
+    /// Too many pixels differ by more than the threshold.  The rendered
+    /// image has been written to the output directory for inspection.
+    Differs {
+        comparison: Comparison,
+        actual: PathBuf,
+    },
+}
+
+impl std::fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoldenError::Io(err) => write!(f, "I/O error: {err}"),
+            GoldenError::Cairo(err) => write!(f, "Cairo error: {err}"),
+            GoldenError::SizeMismatch { expected, actual } => write!(
+                f,
+                "Size mismatch: expected {}x{} got {}x{}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            GoldenError::Missing(path) => write!(
+                f,
+                "Golden {path:?} is missing: set {UPDATE_ENV_VAR} to create it"
+            ),
+            GoldenError::Differs { comparison, actual } => write!(
+                f,
+                "{} of {} pixels differ (max delta E {:.2}): see {:?}",
+                comparison.differing_pixels,
+                comparison.total_pixels,
+                comparison.max_delta_e,
+                actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+impl From<std::io::Error> for GoldenError {
+    fn from(err: std::io::Error) -> Self {
+        GoldenError::Io(err)
+    }
+}
+
+fn cairo_error(err: impl std::fmt::Debug) -> GoldenError {
+    GoldenError::Cairo(format!("{err:?}"))
+}
+
+/// The result of comparing two images of the same size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub total_pixels: usize,
+    /// The number of pixels whose delta E exceeds the threshold.
+    pub differing_pixels: usize,
+    pub max_delta_e: f64,
+}
+
+impl Comparison {
+    pub fn differing_proportion(&self) -> f64 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.differing_pixels as f64 / self.total_pixels as f64
+        }
+    }
+}
+
+/// The (opaque) pixels of an image in row order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pixels {
+    width: usize,
+    height: usize,
+    rgbs: Vec<RGB<u8>>,
+}
+
+impl Pixels {
+    pub fn from_surface(surface: &mut cairo::ImageSurface) -> Result<Self, GoldenError> {
+        surface.flush();
+        let width = surface.get_width() as usize;
+        let height = surface.get_height() as usize;
+        let stride = surface.get_stride() as usize;
+        let data = surface.get_data().map_err(cairo_error)?;
+        let mut rgbs = Vec::with_capacity(width * height);
+        for row in 0..height {
+            for pixel in data[row * stride..row * stride + width * 4].chunks_exact(4) {
+                // ARGB32 is native endian and premultiplied (which makes no
+                // difference as we render onto an opaque background)
+                let argb = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                rgbs.push(RGB::from([
+                    (argb >> 16) as u8,
+                    (argb >> 8) as u8,
+                    argb as u8,
+                ]));
+            }
+        }
+        Ok(Self {
+            width,
+            height,
+            rgbs,
+        })
+    }
+
+    pub fn read_png(path: &Path) -> Result<Self, GoldenError> {
+        let mut file = File::open(path)?;
+        let mut surface = cairo::ImageSurface::create_from_png(&mut file).map_err(cairo_error)?;
+        Self::from_surface(&mut surface)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Compare pixel by pixel counting those whose CIE Lab delta E exceeds
+    /// `threshold`.
+    pub fn compare(&self, other: &Self, threshold: f64) -> Result<Comparison, GoldenError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(GoldenError::SizeMismatch {
+                expected: (self.width, self.height),
+                actual: (other.width, other.height),
+            });
+        }
+        let mut comparison = Comparison {
+            total_pixels: self.rgbs.len(),
+            differing_pixels: 0,
+            max_delta_e: 0.0,
+        };
+        for (lhs, rhs) in self.rgbs.iter().zip(other.rgbs.iter()) {
+            if lhs == rhs {
+                continue;
+            }
+            let delta_e = Lab::from(&HCV::from(*lhs)).delta_e(&Lab::from(&HCV::from(*rhs)));
+            if delta_e > threshold {
+                comparison.differing_pixels += 1;
+            }
+            comparison.max_delta_e = comparison.max_delta_e.max(delta_e);
+        }
+        Ok(comparison)
+    }
+}
+
+/// Render with `draw` onto a `width` by `height` image surface with a white
+/// background.  Text is rendered as boxes (see `TextRendering::Boxes`) as
+/// glyphs vary with the fonts installed.
+pub fn render<F: Fn(&cairo::Context, &Drawer)>(
+    width: i32,
+    height: i32,
+    draw: F,
+) -> Result<cairo::ImageSurface, GoldenError> {
+    let surface =
+        cairo::ImageSurface::create(cairo::Format::ARgb32, width, height).map_err(cairo_error)?;
+    {
+        let cairo_context = cairo::Context::new(&surface);
+        cairo_context.set_source_rgb(1.0, 1.0, 1.0);
+        cairo_context.paint();
+        let size = Size {
+            width: width as f64,
+            height: height as f64,
+        };
+        let drawer = Drawer::new(&cairo_context, size);
+        drawer.set_text_rendering(TextRendering::Boxes);
+        draw(&cairo_context, &drawer);
+    }
+    Ok(surface)
+}
+
+/// Renders images and checks them against the goldens stored in a
+/// directory.  Goldens are only written when `UPDATE_ENV_VAR` is set (a
+/// missing golden is otherwise an error): commit new or changed goldens
+/// after checking them by eye.  Rendered images that fail the check are
+/// written to a separate output directory (by default in the system's
+/// temporary directory) so that checking never modifies the goldens'
+/// directory.
+#[derive(Debug, Clone)]
+pub struct GoldenTester {
+    dir: PathBuf,
+    output_dir: PathBuf,
+    threshold: f64,
+    tolerance: f64,
+}
+
+impl GoldenTester {
+    /// A delta E of about 2.3 is the "just noticeable difference".
+    pub const DEFAULT_THRESHOLD: f64 = 2.3;
+    pub const DEFAULT_TOLERANCE: f64 = 0.001;
+
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            output_dir: std::env::temp_dir().join("colour_math_goldens"),
+            threshold: Self::DEFAULT_THRESHOLD,
+            tolerance: Self::DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Where the rendered images that fail the check are written.
+    pub fn output_dir(mut self, output_dir: impl AsRef<Path>) -> Self {
+        self.output_dir = output_dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// The delta E above which a pixel counts as different.
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// The proportion of pixels that may differ before the check fails.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn golden_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.png"))
+    }
+
+    fn actual_path(&self, name: &str) -> PathBuf {
+        self.output_dir.join(format!("{name}.actual.png"))
+    }
+
+    fn write_png(surface: &cairo::ImageSurface, path: &Path) -> Result<(), GoldenError> {
+        let mut file = File::create(path)?;
+        surface.write_to_png(&mut file).map_err(cairo_error)
+    }
+
+    /// Render the image and compare it with the golden called `name`.
+    pub fn check<F: Fn(&cairo::Context, &Drawer)>(
+        &self,
+        name: &str,
+        width: i32,
+        height: i32,
+        draw: F,
+    ) -> Result<Comparison, GoldenError> {
+        let mut surface = render(width, height, draw)?;
+        let golden_path = self.golden_path(name);
+        if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+            std::fs::create_dir_all(&self.dir)?;
+            Self::write_png(&surface, &golden_path)?;
+        } else if !golden_path.exists() {
+            return Err(GoldenError::Missing(golden_path));
+        }
+        let golden = Pixels::read_png(&golden_path)?;
+        let actual = Pixels::from_surface(&mut surface)?;
+        let comparison = golden.compare(&actual, self.threshold)?;
+        let actual_path = self.actual_path(name);
+        if comparison.differing_proportion() > self.tolerance {
+            std::fs::create_dir_all(&self.output_dir)?;
+            Self::write_png(&surface, &actual_path)?;
+            Err(GoldenError::Differs {
+                comparison,
+                actual: actual_path,
+            })
+        } else {
+            if actual_path.exists() {
+                std::fs::remove_file(&actual_path)?;
+            }
+            Ok(comparison)
+        }
+    }
+}
+
+#[cfg(test)]
+mod golden_tests {
+    use super::*;
+
+    use colour_math::{
+        beigui::{
+            self,
+            attr_display::{
+                ChromaCAD, ColourAttributeDisplayIfce, GreynessCAD, HueCAD, ValueCAD, WarmthCAD,
+            },
+            hue_wheel::{ColouredShape, HueWheel, Shape},
+            Draw, DrawShapes,
+        },
+        gradient::{Gradient, Interpolation},
+        HueConstants, RGBConstants, ScalarAttribute, UFDRNumber, Value,
+    };
+
+    use crate::{CairoCartesian, Point};
+
+    const WIDTH: i32 = 240;
+    const HEIGHT: i32 = 40;
+
+    // The golden-backed tests are ignored until the PNGs have been
+    // generated (with `UPDATE_ENV_VAR` set), reviewed and committed.
+    fn tester() -> GoldenTester {
+        GoldenTester::new(Path::new(env!("CARGO_MANIFEST_DIR")).join("goldens"))
+    }
+
+    fn assert_matches_golden<F: Fn(&cairo::Context, &Drawer)>(
+        name: &str,
+        width: i32,
+        height: i32,
+        draw: F,
+    ) {
+        if let Err(err) = tester().check(name, width, height, draw) {
+            panic!("{name}: {err}");
+        }
+    }
+
+    fn point(x: f64, y: f64) -> beigui::Point {
+        Point { x, y }.into()
+    }
+
+    fn check_cad<A: ColourAttributeDisplayIfce>(name: &str) {
+        let mut cad = A::new();
+        cad.set_colour(Some(&HCV::RED_YELLOW));
+        cad.set_target_colour(Some(&HCV::BLUE));
+        assert_matches_golden(name, WIDTH, HEIGHT, |_, drawer| cad.draw_all(drawer));
+    }
+
+    #[test]
+    fn pixel_comparison() {
+        let mut white = render(4, 4, |_, _| ()).unwrap();
+        let mut grey = render(4, 4, |cairo_context, _| {
+            cairo_context.set_source_rgb(0.5, 0.5, 0.5);
+            cairo_context.rectangle(0.0, 0.0, 1.0, 1.0);
+            cairo_context.fill();
+        })
+        .unwrap();
+        let white = Pixels::from_surface(&mut white).unwrap();
+        let grey = Pixels::from_surface(&mut grey).unwrap();
+        assert_eq!((white.width(), white.height()), (4, 4));
+        let comparison = white.compare(&white, 1.0).unwrap();
+        assert_eq!(comparison.differing_pixels, 0);
+        let comparison = white.compare(&grey, 1.0).unwrap();
+        assert_eq!(comparison.differing_pixels, 1);
+        assert_eq!(comparison.differing_proportion(), 1.0 / 16.0);
+        assert!(comparison.max_delta_e > 40.0);
+        let small = Pixels::from_surface(&mut render(2, 2, |_, _| ()).unwrap()).unwrap();
+        assert!(white.compare(&small, 1.0).is_err());
+    }
+
+    #[test]
+    fn missing_golden() {
+        if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+            return;
+        }
+        let dir = std::env::temp_dir().join("colour_math_no_goldens");
+        let tester = GoldenTester::new(&dir).output_dir(dir.join("output"));
+        let result = tester.check("nonesuch", 4, 4, |_, _| ());
+        assert!(matches!(result, Err(GoldenError::Missing(_))));
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    #[ignore = "no goldens yet: generate with COLOUR_MATH_UPDATE_GOLDENS set"]
+    fn attribute_displays() {
+        check_cad::<HueCAD>("hue_cad");
+        check_cad::<ChromaCAD>("chroma_cad");
+        check_cad::<ValueCAD>("value_cad");
+        check_cad::<GreynessCAD>("greyness_cad");
+        check_cad::<WarmthCAD>("warmth_cad");
+    }
+
+    #[test]
+    #[ignore = "no goldens yet: generate with COLOUR_MATH_UPDATE_GOLDENS set"]
+    fn hue_wheel() {
+        let mut hue_wheel = HueWheel::new();
+        for (i, colour) in [HCV::RED, HCV::GREEN, HCV::BLUE, HCV::CYAN, HCV::WHITE]
+            .iter()
+            .enumerate()
+        {
+            let shape = [Shape::Circle, Shape::Diamond, Shape::Square][i % 3];
+            hue_wheel.add_item(ColouredShape::new(colour, &format!("{i}"), "", shape));
+        }
+        for attr in [ScalarAttribute::Value, ScalarAttribute::Chroma] {
+            assert_matches_golden(
+                &format!("hue_wheel_{attr}").to_lowercase(),
+                200,
+                200,
+                |cairo_context, drawer| {
+                    cairo_context
+                        .transform(CairoCartesian::cartesian_transform_matrix(200.0, 200.0));
+                    hue_wheel.draw(attr, drawer);
+                },
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "no goldens yet: generate with COLOUR_MATH_UPDATE_GOLDENS set"]
+    fn gradients() {
+        let colours = [HCV::RED, HCV::YELLOW, HCV::BLUE];
+        for (name, interpolation) in [
+            ("gradient_rgb", Interpolation::Rgb),
+            ("gradient_shortest_hue", Interpolation::ShortestHue),
+            ("gradient_longest_hue", Interpolation::LongestHue),
+        ] {
+            let gradient = Gradient::evenly_spaced(&colours, interpolation);
+            assert_matches_golden(name, WIDTH, HEIGHT, |_, drawer| {
+                drawer.paint_linear_gradient(
+                    point(0.0, 0.0),
+                    drawer.size(),
+                    &gradient.colour_stops(32),
+                )
+            });
+        }
+    }
+
+    #[test]
+    #[ignore = "no goldens yet: generate with COLOUR_MATH_UPDATE_GOLDENS set"]
+    fn shapes() {
+        assert_matches_golden("shapes", WIDTH, 80, |_, drawer| {
+            drawer.set_background_colour(&HCV::new_grey(Value::ONE / 2));
+            drawer.set_line_colour(&HCV::BLACK);
+            drawer.set_fill_colour(&HCV::CYAN);
+            drawer.draw_circle(point(40.0, 40.0), UFDRNumber::from(30.0), true);
+            drawer.draw_circle(point(40.0, 40.0), UFDRNumber::from(30.0), false);
+            drawer.set_fill_colour(&HCV::MAGENTA);
+            drawer.draw_diamond(point(120.0, 40.0), UFDRNumber::from(50.0), true);
+            drawer.draw_square(point(200.0, 40.0), UFDRNumber::from(50.0), false);
+        });
+    }
+}
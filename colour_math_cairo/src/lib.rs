@@ -5,10 +5,14 @@ use std::{cell::Cell, ops::Add, ops::Sub};
 use pw_gtk_ext::cairo;
 
 use colour_math::{
-    beigui::{self, Draw, DrawIsosceles, DrawShapes},
-    ColourBasics, Prop, RGBConstants, UFDRNumber, HCV, RGB,
+    beigui::{self, Draw, DrawIsosceles, DrawShapes, TextRendering},
+    fdrn::FDRNumber,
+    ColourBasics, Prop, RGBConstants, UFDRNumber, Value, HCV, RGB,
 };
 
+pub mod calibration;
+pub mod golden;
+
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Point {
     pub x: f64,
@@ -123,6 +127,7 @@ pub trait CairoSetColour {
 
 impl CairoSetColour for cairo::Context {
     fn set_source_colour_rgb(&self, rgb: &RGB<f64>) {
+        let rgb = calibration::displayed(rgb);
         self.set_source_rgb(rgb[0], rgb[1], rgb[2]);
     }
 }
@@ -133,6 +138,7 @@ pub struct Drawer<'a> {
     fill_colour: Cell<RGB<f64>>,
     line_colour: Cell<RGB<f64>>,
     text_colour: Cell<RGB<f64>>,
+    text_rendering: Cell<TextRendering>,
 }
 
 impl<'a> Drawer<'a> {
@@ -143,13 +149,80 @@ impl<'a> Drawer<'a> {
             fill_colour: Cell::new(RGB::<f64>::BLACK),
             line_colour: Cell::new(RGB::<f64>::BLACK),
             text_colour: Cell::new(RGB::<f64>::BLACK),
+            text_rendering: Cell::new(TextRendering::default()),
         }
     }
 
+    pub fn set_text_rendering(&self, text_rendering: TextRendering) {
+        self.text_rendering.set(text_rendering)
+    }
+
     fn fill(&self) {
-        self.cairo_context
-            .set_source_colour_rgb(&self.fill_colour.get());
-        self.cairo_context.fill();
+        let rgb = self.fill_colour.get();
+        self.cairo_context.set_source_colour_rgb(&rgb);
+        if calibration::is_out_of_proof_gamut(&rgb) {
+            self.cairo_context.fill_preserve();
+            self.hatch_path(&rgb);
+        } else {
+            self.cairo_context.fill();
+        }
+    }
+
+    // Describe the (monochrome) background colour in the bottom left corner
+    // (in device coordinates as the drawing may be transformed).
+    fn annotate_background(&self, rgb: &RGB<f64>) {
+        let text_colour = self.text_colour.get();
+        if rgb.value() > Value::ONE / 2 {
+            self.text_colour.set(RGB::<f64>::BLACK);
+        } else {
+            self.text_colour.set(RGB::<f64>::WHITE);
+        }
+        self.cairo_context.save();
+        self.cairo_context.identity_matrix();
+        self.draw_text_as_configured(
+            &beigui::monochrome_annotation(rgb),
+            beigui::TextPosn::BottomLeftCorner(beigui::Point {
+                x: FDRNumber::from(2.0),
+                y: FDRNumber::from(self.size.height - 2.0),
+            }),
+            UFDRNumber::from(10.0),
+        );
+        self.cairo_context.restore();
+        self.text_colour.set(text_colour);
+    }
+
+    // Mark the current path's area (as filled with `rgb`) as out of the soft
+    // proof's gamut with diagonal lines (and consume the path).  The lines
+    // are drawn in device coordinates so that their width and spacing are
+    // the same whatever the drawing's transform.
+    fn hatch_path(&self, rgb: &RGB<f64>) {
+        const SPACING: f64 = 6.0;
+        let cairo_context = self.cairo_context;
+        cairo_context.save();
+        cairo_context.clip();
+        let (ux0, uy0, ux1, uy1) = cairo_context.clip_extents();
+        let corners = [(ux0, uy0), (ux1, uy0), (ux0, uy1), (ux1, uy1)]
+            .map(|(x, y)| cairo_context.user_to_device(x, y));
+        let (x0, y0, x1, y1) = corners.iter().fold(
+            (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+            |(x0, y0, x1, y1), (x, y)| (x0.min(*x), y0.min(*y), x1.max(*x), y1.max(*y)),
+        );
+        cairo_context.identity_matrix();
+        if rgb.value() > Value::ONE / 2 {
+            cairo_context.set_source_colour_rgb(&RGB::<f64>::BLACK);
+        } else {
+            cairo_context.set_source_colour_rgb(&RGB::<f64>::WHITE);
+        }
+        cairo_context.set_line_width(1.0);
+        let height = y1 - y0;
+        let mut x = x0 - height;
+        while x < x1 {
+            cairo_context.move_to(x, y1);
+            cairo_context.line_to(x + height, y0);
+            x += SPACING;
+        }
+        cairo_context.stroke();
+        cairo_context.restore();
     }
 
     fn stroke(&self) {
@@ -211,33 +284,51 @@ impl<'a> Draw for Drawer<'a> {
         }
     }
 
+    fn text_rendering(&self) -> TextRendering {
+        self.text_rendering.get()
+    }
+
     fn draw_text(&self, text: &str, posn: beigui::TextPosn, font_size: UFDRNumber) {
         if text.is_empty() {
             return;
         }
+        let posn = TextPosn::from(posn);
+        let (anchor_x, anchor_y) = match posn {
+            TextPosn::Centre(x, y)
+            | TextPosn::TopLeftCorner(x, y)
+            | TextPosn::TopRightCorner(x, y)
+            | TextPosn::BottomLeftCorner(x, y)
+            | TextPosn::BottomRightCorner(x, y) => (x, y),
+        };
+        self.cairo_context.save();
+        self.cairo_context.translate(anchor_x, anchor_y);
+        // Keep text the right way up when drawing in cartesian coordinates
+        if self.cairo_context.get_matrix().yy < 0.0 {
+            self.cairo_context.scale(1.0, -1.0);
+        }
         self.cairo_context.set_font_size(font_size.into());
         let te = self.cairo_context.text_extents(text);
-        match TextPosn::from(posn) {
-            TextPosn::Centre(x, y) => {
-                self.cairo_context
-                    .move_to(x - te.width / 2.0, y + te.height / 2.0);
+        match posn {
+            TextPosn::Centre(_, _) => {
+                self.cairo_context.move_to(-te.width / 2.0, te.height / 2.0);
             }
-            TextPosn::TopLeftCorner(x, y) => {
-                self.cairo_context.move_to(x, y + te.height);
+            TextPosn::TopLeftCorner(_, _) => {
+                self.cairo_context.move_to(0.0, te.height);
             }
-            TextPosn::TopRightCorner(x, y) => {
-                self.cairo_context.move_to(x - te.width, y + te.height);
+            TextPosn::TopRightCorner(_, _) => {
+                self.cairo_context.move_to(-te.width, te.height);
             }
-            TextPosn::BottomLeftCorner(x, y) => {
-                self.cairo_context.move_to(x, y);
+            TextPosn::BottomLeftCorner(_, _) => {
+                self.cairo_context.move_to(0.0, 0.0);
             }
-            TextPosn::BottomRightCorner(x, y) => {
-                self.cairo_context.move_to(x - te.width, y);
+            TextPosn::BottomRightCorner(_, _) => {
+                self.cairo_context.move_to(-te.width, 0.0);
             }
         }
         self.cairo_context
             .set_source_colour_rgb(&self.text_colour.get());
         self.cairo_context.show_text(text);
+        self.cairo_context.restore();
     }
 
     fn paint_linear_gradient(
@@ -253,7 +344,7 @@ impl<'a> Draw for Drawer<'a> {
             0.5 * f64::from(size.height),
         );
         for colour_stop in colour_stops.iter() {
-            let rgb = colour_stop.0.rgb::<f64>();
+            let rgb = calibration::displayed(&colour_stop.0.rgb::<f64>());
             linear_gradient.add_color_stop_rgb(colour_stop.1.into(), rgb[0], rgb[1], rgb[2]);
         }
         self.cairo_context.rectangle(
@@ -265,15 +356,41 @@ impl<'a> Draw for Drawer<'a> {
         self.cairo_context.set_source(&linear_gradient);
         self.cairo_context.fill();
     }
+
+    // Straight to cairo without the round trip through beigui::Point
+    fn draw_time_series(&self, values: &[f64], range: (f64, f64)) {
+        let xy = beigui::time_series_xy(self.size.width, self.size.height, values, range);
+        if let Some((x, y)) = xy.first() {
+            self.cairo_context.move_to(*x, *y);
+            for (x, y) in xy[1..].iter() {
+                self.cairo_context.line_to(*x, *y);
+            }
+            if xy.len() > 1 {
+                self.stroke();
+            }
+        }
+    }
 }
 
 impl<'a> DrawIsosceles for Drawer<'a> {}
 
 impl<'a> DrawShapes for Drawer<'a> {
     fn set_background_colour(&self, colour: &impl ColourBasics) {
-        self.cairo_context
-            .set_source_colour_rgb(&colour.rgb::<f64>());
+        let rgb = colour.rgb::<f64>();
+        self.cairo_context.set_source_colour_rgb(&rgb);
         self.cairo_context.paint();
+        if calibration::is_out_of_proof_gamut(&rgb) {
+            // the whole surface (in device coordinates)
+            self.cairo_context.save();
+            self.cairo_context.identity_matrix();
+            self.cairo_context
+                .rectangle(0.0, 0.0, self.size.width, self.size.height);
+            self.hatch_path(&rgb);
+            self.cairo_context.restore();
+        }
+        if calibration::is_monochrome() {
+            self.annotate_background(&rgb);
+        }
     }
 
     fn draw_circle(&self, centre: beigui::Point, radius: UFDRNumber, fill: bool) {
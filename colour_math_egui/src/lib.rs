@@ -0,0 +1,224 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+
+use std::cell::Cell;
+
+use egui::{epaint::Mesh, Align2, Color32, FontId, Painter, Pos2, Rect, Shape, Stroke};
+
+use colour_math::{
+    beigui::{self, Draw, DrawIsosceles, DrawShapes},
+    ColourBasics, Prop, RGBConstants, UFDRNumber, HCV, RGB,
+};
+
+pub trait ToColor32 {
+    fn to_color32(&self) -> Color32;
+}
+
+impl<C: ColourBasics> ToColor32 for C {
+    fn to_color32(&self) -> Color32 {
+        let rgb = self.rgb::<u8>();
+        Color32::from_rgb(rgb[0], rgb[1], rgb[2])
+    }
+}
+
+/// Maps beigui coordinates to screen coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Transform {
+    origin: Pos2,
+    scale: f32,
+    y_sign: f32,
+}
+
+impl Transform {
+    fn point(&self, point: beigui::Point) -> Pos2 {
+        Pos2 {
+            x: self.origin.x + f64::from(point.x) as f32 * self.scale,
+            y: self.origin.y + f64::from(point.y) as f32 * self.scale * self.y_sign,
+        }
+    }
+
+    fn length(&self, length: UFDRNumber) -> f32 {
+        f64::from(length) as f32 * self.scale
+    }
+}
+
+pub struct Drawer<'a> {
+    pub painter: &'a Painter,
+    rect: Rect,
+    transform: Transform,
+    fill_colour: Cell<Color32>,
+    line_colour: Cell<Color32>,
+    text_colour: Cell<Color32>,
+    line_width: Cell<f32>,
+}
+
+impl<'a> Drawer<'a> {
+    fn with_transform(painter: &'a Painter, rect: Rect, transform: Transform) -> Self {
+        Self {
+            painter,
+            rect,
+            transform,
+            fill_colour: Cell::new(RGB::<u8>::BLACK.to_color32()),
+            line_colour: Cell::new(RGB::<u8>::BLACK.to_color32()),
+            text_colour: Cell::new(RGB::<u8>::BLACK.to_color32()),
+            line_width: Cell::new(1.0),
+        }
+    }
+
+    /// Drawing coordinates are in pixels relative to the top left
+    /// corner of `rect` (as for the GTK attribute displays).
+    pub fn new(painter: &'a Painter, rect: Rect) -> Self {
+        let transform = Transform {
+            origin: rect.left_top(),
+            scale: 1.0,
+            y_sign: 1.0,
+        };
+        Self::with_transform(painter, rect, transform)
+    }
+
+    /// Drawing coordinates are cartesian with the origin at the centre
+    /// of `rect` and a unit circle that fits (with a small margin) inside
+    /// it (as for the GTK hue wheel and hue dial).
+    pub fn cartesian(painter: &'a Painter, rect: Rect) -> Self {
+        let transform = Transform {
+            origin: rect.center(),
+            scale: rect.width().min(rect.height()) / 2.15,
+            y_sign: -1.0,
+        };
+        Self::with_transform(painter, rect, transform)
+    }
+
+    fn stroke(&self) -> Stroke {
+        Stroke::new(self.line_width.get(), self.line_colour.get())
+    }
+
+    fn points(&self, points: &[beigui::Point]) -> Vec<Pos2> {
+        points.iter().map(|p| self.transform.point(*p)).collect()
+    }
+}
+
+impl<'a> Draw for Drawer<'a> {
+    fn size(&self) -> beigui::Size {
+        let scale = f64::from(self.transform.scale);
+        beigui::Size {
+            width: (f64::from(self.rect.width()) / scale).into(),
+            height: (f64::from(self.rect.height()) / scale).into(),
+        }
+    }
+
+    /// NB: egui only fills convex polygons.
+    fn draw_polygon(&self, polygon: &[beigui::Point], fill: bool) {
+        if polygon.len() > 1 {
+            let points = self.points(polygon);
+            if fill {
+                self.painter.add(Shape::convex_polygon(
+                    points,
+                    self.fill_colour.get(),
+                    Stroke::NONE,
+                ));
+            } else {
+                self.painter.add(Shape::closed_line(points, self.stroke()));
+            }
+        }
+    }
+
+    fn set_fill_colour(&self, colour: &impl ColourBasics) {
+        self.fill_colour.set(colour.to_color32());
+    }
+
+    fn set_line_colour(&self, colour: &impl ColourBasics) {
+        self.line_colour.set(colour.to_color32());
+    }
+
+    fn set_text_colour(&self, colour: &impl ColourBasics) {
+        self.text_colour.set(colour.to_color32());
+    }
+
+    fn set_line_width(&self, width: UFDRNumber) {
+        self.line_width.set(self.transform.length(width));
+    }
+
+    fn draw_line(&self, line: &[beigui::Point]) {
+        if line.len() > 1 {
+            self.painter
+                .add(Shape::line(self.points(line), self.stroke()));
+        }
+    }
+
+    fn draw_text(&self, text: &str, posn: beigui::TextPosn, font_size: UFDRNumber) {
+        if text.is_empty() {
+            return;
+        }
+        // egui text is always the right way up so the anchor's vertical
+        // sense has to follow the direction of the y axis
+        let (top, bottom) = if self.transform.y_sign < 0.0 {
+            (Align2::LEFT_BOTTOM, Align2::LEFT_TOP)
+        } else {
+            (Align2::LEFT_TOP, Align2::LEFT_BOTTOM)
+        };
+        use beigui::TextPosn::*;
+        let (point, anchor) = match posn {
+            TopLeftCorner(point) => (point, top),
+            TopRightCorner(point) => (point, Align2([egui::Align::Max, top.y()])),
+            BottomLeftCorner(point) => (point, bottom),
+            BottomRightCorner(point) => (point, Align2([egui::Align::Max, bottom.y()])),
+            Centre(point) => (point, Align2::CENTER_CENTER),
+        };
+        self.painter.text(
+            self.transform.point(point),
+            anchor,
+            text,
+            FontId::proportional(self.transform.length(font_size)),
+            self.text_colour.get(),
+        );
+    }
+
+    fn paint_linear_gradient(
+        &self,
+        posn: beigui::Point,
+        size: beigui::Size,
+        colour_stops: &[(HCV, Prop)],
+    ) {
+        let left = self.transform.point(posn);
+        let width = self.transform.length(size.width);
+        let height = self.transform.length(size.height) * self.transform.y_sign;
+        let mut mesh = Mesh::default();
+        for (colour, offset) in colour_stops.iter() {
+            let x = left.x + width * f64::from(*offset) as f32;
+            let colour = colour.to_color32();
+            mesh.colored_vertex(Pos2 { x, y: left.y }, colour);
+            mesh.colored_vertex(
+                Pos2 {
+                    x,
+                    y: left.y + height,
+                },
+                colour,
+            );
+        }
+        for i in 1..colour_stops.len() as u32 {
+            let (a, b) = (2 * (i - 1), 2 * i);
+            mesh.add_triangle(a, a + 1, b);
+            mesh.add_triangle(a + 1, b + 1, b);
+        }
+        self.painter.add(Shape::mesh(mesh));
+    }
+}
+
+impl<'a> DrawIsosceles for Drawer<'a> {}
+
+impl<'a> DrawShapes for Drawer<'a> {
+    fn set_background_colour(&self, colour: &impl ColourBasics) {
+        self.painter
+            .rect_filled(self.rect, 0.0, colour.to_color32());
+    }
+
+    fn draw_circle(&self, centre: beigui::Point, radius: UFDRNumber, fill: bool) {
+        let centre = self.transform.point(centre);
+        let radius = self.transform.length(radius);
+        if fill {
+            self.painter
+                .circle_filled(centre, radius, self.fill_colour.get());
+        } else {
+            self.painter.circle_stroke(centre, radius, self.stroke());
+        }
+    }
+}
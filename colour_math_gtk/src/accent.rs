@@ -0,0 +1,117 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use pw_gtk_ext::{
+    gdk, glib,
+    gtk::{self, prelude::*},
+};
+
+use colour_math::{hcv::HCV, RGB};
+
+// In order of preference: the first is defined by most GTK3 themes and the
+// others by themes following libadwaita's naming
+const ACCENT_COLOUR_NAMES: [&str; 3] =
+    ["theme_selected_bg_color", "accent_bg_color", "accent_color"];
+
+type ChangeCallback = Box<dyn Fn(Option<HCV>)>;
+
+fn hcv_from_gdk_rgba(rgba: &gdk::RGBA) -> HCV {
+    RGB::<f64>::from([rgba.red, rgba.green, rgba.blue]).into()
+}
+
+/// The desktop theme's accent (selection) colour as seen by `widget`.
+pub fn system_accent_colour<W: IsA<gtk::Widget>>(widget: &W) -> Option<HCV> {
+    let style_context = widget.get_style_context();
+    ACCENT_COLOUR_NAMES
+        .iter()
+        .find_map(|name| style_context.lookup_color(name))
+        .map(|rgba| hcv_from_gdk_rgba(&rgba))
+}
+
+/// Tracks the system accent colour and notifies interested parties when the
+/// theme changes it.
+pub struct AccentColourWatcher {
+    widget: gtk::Widget,
+    colour: Cell<Option<HCV>>,
+    callbacks: RefCell<Vec<ChangeCallback>>,
+    style_handler: RefCell<Option<glib::SignalHandlerId>>,
+    // the (global) settings outlive the watcher so their handlers must go
+    settings_handlers: RefCell<Vec<(gtk::Settings, glib::SignalHandlerId)>>,
+}
+
+impl AccentColourWatcher {
+    pub fn new<W: IsA<gtk::Widget>>(widget: &W) -> Rc<Self> {
+        let widget = widget.clone().upcast::<gtk::Widget>();
+        let watcher = Rc::new(Self {
+            colour: Cell::new(system_accent_colour(&widget)),
+            widget,
+            callbacks: RefCell::new(vec![]),
+            style_handler: RefCell::new(None),
+            settings_handlers: RefCell::new(vec![]),
+        });
+
+        // weak references as the watcher owns the widget
+        let watcher_w = Rc::downgrade(&watcher);
+        let handler = watcher
+            .widget
+            .get_style_context()
+            .connect_changed(move |_| {
+                if let Some(watcher) = watcher_w.upgrade() {
+                    watcher.update()
+                }
+            });
+        *watcher.style_handler.borrow_mut() = Some(handler);
+
+        if let Some(settings) = gtk::Settings::get_default() {
+            let watcher_w = Rc::downgrade(&watcher);
+            let theme_handler = settings.connect_property_gtk_theme_name_notify(move |_| {
+                if let Some(watcher) = watcher_w.upgrade() {
+                    watcher.update()
+                }
+            });
+            let watcher_w = Rc::downgrade(&watcher);
+            let dark_handler =
+                settings.connect_property_gtk_application_prefer_dark_theme_notify(move |_| {
+                    if let Some(watcher) = watcher_w.upgrade() {
+                        watcher.update()
+                    }
+                });
+            *watcher.settings_handlers.borrow_mut() =
+                vec![(settings.clone(), theme_handler), (settings, dark_handler)];
+        }
+
+        watcher
+    }
+
+    pub fn colour(&self) -> Option<HCV> {
+        self.colour.get()
+    }
+
+    fn update(&self) {
+        let colour = system_accent_colour(&self.widget);
+        if colour != self.colour.get() {
+            self.colour.set(colour);
+            for callback in self.callbacks.borrow().iter() {
+                callback(colour)
+            }
+        }
+    }
+
+    pub fn connect_changed<F: Fn(Option<HCV>) + 'static>(&self, callback: F) {
+        self.callbacks.borrow_mut().push(Box::new(callback))
+    }
+}
+
+impl Drop for AccentColourWatcher {
+    fn drop(&mut self) {
+        if let Some(handler) = self.style_handler.borrow_mut().take() {
+            self.widget.get_style_context().disconnect(handler);
+        }
+        for (settings, handler) in self.settings_handlers.borrow_mut().drain(..) {
+            settings.disconnect(handler);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{
+    cell::{Cell, RefCell},
+    env, fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use pw_gtk_ext::glib;
+
+use colour_math::{
+    palette_io::{session, PaletteIoError},
+    Palette,
+};
+
+type SessionSource = Box<dyn Fn() -> Vec<Palette>>;
+type SaveFailedCallback = Box<dyn Fn(&PaletteIoError)>;
+
+// The per user data directory ("$XDG_DATA_HOME" or "~/.local/share") which,
+// unlike the temporary directory, survives a reboot.
+fn user_data_dir() -> PathBuf {
+    match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => match env::var_os("HOME") {
+            Some(home) if !home.is_empty() => Path::new(&home).join(".local").join("share"),
+            _ => env::temp_dir(),
+        },
+    }
+}
+
+/// Saves the session's palettes (obtained from the source function) to a
+/// recovery file a short while after the last reported change.
+pub struct SessionAutosaver {
+    path: PathBuf,
+    delay_ms: u32,
+    source: RefCell<Option<SessionSource>>,
+    generation: Cell<u64>,
+    pending: Cell<bool>,
+    last_error: RefCell<Option<String>>,
+    save_failed_callbacks: RefCell<Vec<SaveFailedCallback>>,
+}
+
+impl SessionAutosaver {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn set_source<F: Fn() -> Vec<Palette> + 'static>(&self, source: F) {
+        *self.source.borrow_mut() = Some(Box::new(source));
+    }
+
+    pub fn has_pending_save(&self) -> bool {
+        self.pending.get()
+    }
+
+    /// The reason that the most recent save failed (cleared by a successful
+    /// save) so that the user can be warned that their work isn't protected.
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.borrow().clone()
+    }
+
+    /// Called whenever a (debounced or explicit) save fails.
+    pub fn connect_save_failed<F: Fn(&PaletteIoError) + 'static>(&self, callback: F) {
+        self.save_failed_callbacks
+            .borrow_mut()
+            .push(Box::new(callback));
+    }
+
+    /// Request a save.  Saves are debounced so a burst of changes results in
+    /// a single write.
+    pub fn notify_changed(self: &Rc<Self>) {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        self.pending.set(true);
+        let self_c = Rc::clone(self);
+        glib::timeout_add_local(self.delay_ms, move || {
+            if self_c.generation.get() == generation {
+                // failures are reported by save_now() itself
+                let _ = self_c.save_now();
+            }
+            glib::Continue(false)
+        });
+    }
+
+    pub fn save_now(&self) -> Result<(), PaletteIoError> {
+        self.pending.set(false);
+        let result = match *self.source.borrow() {
+            Some(ref source) => self.write(&source()),
+            None => Ok(()),
+        };
+        match result {
+            Ok(()) => *self.last_error.borrow_mut() = None,
+            Err(ref error) => {
+                *self.last_error.borrow_mut() = Some(error.to_string());
+                for callback in self.save_failed_callbacks.borrow().iter() {
+                    callback(error);
+                }
+            }
+        }
+        result
+    }
+
+    fn write(&self, palettes: &[Palette]) -> Result<(), PaletteIoError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        session::write_session(palettes, &self.path)
+    }
+
+    /// The palettes saved by a session that didn't finish cleanly (if any).
+    pub fn recover_last_session(&self) -> Result<Option<Vec<Palette>>, PaletteIoError> {
+        if self.path.exists() {
+            Ok(Some(session::read_session(&self.path)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Call on clean exit so that the next session doesn't offer recovery.
+    pub fn discard(&self) -> Result<(), PaletteIoError> {
+        // invalidate any pending save
+        self.generation.set(self.generation.get() + 1);
+        self.pending.set(false);
+        match fs::remove_file(&self.path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct SessionAutosaverBuilder {
+    path: Option<PathBuf>,
+    app_id: String,
+    delay_ms: u32,
+}
+
+impl Default for SessionAutosaverBuilder {
+    fn default() -> Self {
+        Self {
+            path: None,
+            app_id: "colour_math".to_string(),
+            delay_ms: 2000,
+        }
+    }
+}
+
+impl SessionAutosaverBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn path(&mut self, path: &Path) -> &mut Self {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+
+    /// Names the directory (in the user's data directory) that holds the
+    /// recovery file when no `path()` is given.
+    pub fn app_id(&mut self, app_id: &str) -> &mut Self {
+        self.app_id = app_id.to_string();
+        self
+    }
+
+    pub fn delay_ms(&mut self, delay_ms: u32) -> &mut Self {
+        self.delay_ms = delay_ms;
+        self
+    }
+
+    pub fn build(&self) -> Rc<SessionAutosaver> {
+        let path = match self.path {
+            Some(ref path) => path.clone(),
+            None => user_data_dir()
+                .join(&self.app_id)
+                .join("recovery_session.txt"),
+        };
+        Rc::new(SessionAutosaver {
+            path,
+            delay_ms: self.delay_ms,
+            source: RefCell::new(None),
+            generation: Cell::new(0),
+            pending: Cell::new(false),
+            last_error: RefCell::new(None),
+            save_failed_callbacks: RefCell::new(Vec::new()),
+        })
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{cell::RefCell, rc::Rc};
+
+use pw_gtk_ext::gtk::{self, prelude::*};
+
+use colour_math::proof::SoftProof;
+
+pub use colour_math_cairo::calibration::{
+    calibrated, display_calibration, displayed, is_monochrome, is_out_of_proof_gamut,
+    set_display_calibration, soft_proof, DisplayCalibration,
+};
+
+/// Display calibrations for monitors (identified by their model name) that
+/// are installed (see `colour_math_cairo::calibration`) to match the
+/// monitor that a window is on.
+#[derive(Debug, Default)]
+pub struct MonitorCalibrations {
+    by_model: RefCell<Vec<(String, DisplayCalibration)>>,
+    default: RefCell<Option<DisplayCalibration>>,
+}
+
+impl MonitorCalibrations {
+    pub fn new() -> Rc<Self> {
+        Rc::new(Self::default())
+    }
+
+    pub fn insert(&self, model: &str, calibration: DisplayCalibration) {
+        let mut by_model = self.by_model.borrow_mut();
+        match by_model.iter_mut().find(|(m, _)| m == model) {
+            Some(entry) => entry.1 = calibration,
+            None => by_model.push((model.to_string(), calibration)),
+        }
+    }
+
+    pub fn remove(&self, model: &str) {
+        self.by_model.borrow_mut().retain(|(m, _)| m != model);
+    }
+
+    /// For monitors without a calibration of their own.
+    pub fn set_default(&self, calibration: Option<DisplayCalibration>) {
+        *self.default.borrow_mut() = calibration;
+    }
+
+    pub fn calibration_for(&self, model: Option<&str>) -> Option<DisplayCalibration> {
+        model
+            .and_then(|model| {
+                self.by_model
+                    .borrow()
+                    .iter()
+                    .find(|(m, _)| m == model)
+                    .map(|(_, calibration)| calibration.clone())
+            })
+            .or_else(|| self.default.borrow().clone())
+    }
+
+    /// Install the calibration for the monitor that `window` is (mostly)
+    /// on and redraw it.
+    pub fn install_for_window(&self, window: &gtk::Window) {
+        let model = window.get_window().and_then(|gdk_window| {
+            window
+                .get_display()
+                .get_monitor_at_window(&gdk_window)
+                .and_then(|monitor| monitor.get_model())
+        });
+        let calibration = self.calibration_for(model.as_deref());
+        if display_calibration().as_deref() != calibration.as_ref() {
+            set_display_calibration(calibration);
+            window.queue_draw();
+        }
+    }
+
+    /// Keep the installed calibration matched to the monitor `window` is
+    /// on as it moves.
+    pub fn track_window(self: &Rc<Self>, window: &gtk::Window) {
+        let calibrations = Rc::clone(self);
+        window.connect_configure_event(move |window, _| {
+            calibrations.install_for_window(window);
+            gtk::Inhibit(false)
+        });
+        let calibrations = Rc::clone(self);
+        window.connect_map(move |window| calibrations.install_for_window(window));
+    }
+}
+
+/// Turn soft proofing on or off (see `colour_math_cairo::calibration`) and
+/// redraw all windows.  Widgets drawn with cairo (e.g. the editor, hue
+/// wheel and attribute displays) show out of gamut colours hatched.
+pub fn set_soft_proof(soft_proof: Option<SoftProof>) {
+    colour_math_cairo::calibration::set_soft_proof(soft_proof);
+    for window in gtk::Window::list_toplevels() {
+        window.queue_draw();
+    }
+}
+
+/// Turn monochrome (value only) rendering on or off (see
+/// `colour_math_cairo::calibration`) and redraw all windows.  Backgrounds
+/// drawn with cairo are annotated with a description of their colour.
+pub fn set_monochrome(monochrome: bool) {
+    colour_math_cairo::calibration::set_monochrome(monochrome);
+    for window in gtk::Window::list_toplevels() {
+        window.queue_draw();
+    }
+}
+
+/// A check button that turns soft proofing with `soft_proof` on and off.
+pub fn soft_proof_toggle(soft_proof: SoftProof) -> gtk::CheckButton {
+    let button = gtk::CheckButton::with_label("Soft Proof");
+    button.set_tooltip_text(Some(&format!(
+        "Show colours as they would be reproduced in {}",
+        soft_proof.target().name()
+    )));
+    button.set_active(self::soft_proof() == Some(soft_proof));
+    button.connect_toggled(move |button| {
+        if button.get_active() {
+            set_soft_proof(Some(soft_proof));
+        } else {
+            set_soft_proof(None);
+        }
+    });
+    button
+}
@@ -0,0 +1,79 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::str::FromStr;
+
+use pw_gtk_ext::gtk::{self, prelude::*};
+
+use colour_math::{hcv::HCV, ColourBasics, RGB};
+
+use crate::colour::GdkColour;
+
+/// Renders a swatch of the colour held (as a "#RRGGBB" or RGB16/RGB8 text
+/// string) in `colour_column` of the model with the (optional) text from
+/// `text_column` drawn on top of it in the colour's best foreground colour.
+#[derive(Debug, Clone)]
+pub struct CellRendererColour {
+    renderer: gtk::CellRendererText,
+    colour_column: i32,
+    text_column: Option<i32>,
+}
+
+impl CellRendererColour {
+    pub fn new(colour_column: i32, text_column: Option<i32>) -> Self {
+        let renderer = gtk::CellRendererText::new();
+        if text_column.is_none() {
+            renderer.set_fixed_size(40, -1);
+        }
+        Self {
+            renderer,
+            colour_column,
+            text_column,
+        }
+    }
+
+    pub fn renderer(&self) -> &gtk::CellRendererText {
+        &self.renderer
+    }
+
+    pub fn pack_into(&self, column: &gtk::TreeViewColumn, expand: bool) {
+        column.pack_start(&self.renderer, expand);
+        let renderer = self.renderer.clone();
+        let colour_column = self.colour_column;
+        let text_column = self.text_column;
+        column.set_cell_data_func(
+            &self.renderer,
+            Some(Box::new(move |_, _, model, iter| {
+                let hcv = model
+                    .get_value(iter, colour_column)
+                    .get::<String>()
+                    .ok()
+                    .flatten()
+                    .and_then(|text| {
+                        RGB::<u8>::from_str(&text)
+                            .map(HCV::from)
+                            .or_else(|_| RGB::<u16>::from_str(&text).map(HCV::from))
+                            .ok()
+                    });
+                let text = match text_column {
+                    Some(text_column) => model
+                        .get_value(iter, text_column)
+                        .get::<String>()
+                        .ok()
+                        .flatten(),
+                    None => None,
+                };
+                match hcv {
+                    Some(hcv) => {
+                        renderer.set_property_background_rgba(Some(&hcv.gdk_rgba()));
+                        renderer
+                            .set_property_foreground_rgba(Some(&hcv.best_foreground().gdk_rgba()));
+                    }
+                    None => {
+                        renderer.set_property_background_rgba(None);
+                        renderer.set_property_foreground_rgba(None);
+                    }
+                }
+                renderer.set_property_text(text.as_deref());
+            })),
+        );
+    }
+}
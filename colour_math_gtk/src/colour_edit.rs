@@ -6,13 +6,22 @@ use pw_gtk_ext::{
     wrapper::*,
 };
 
-use colour_math::{hcv::HCV, LightLevel, RGB, ScalarAttribute, Value};
+use colour_math::{
+    harmony,
+    hcv::HCV,
+    preferences::UiPreferences,
+    recording::{Operation, Recorder},
+    Angle, ColourBasics, LightLevel, ManipulatedColour, ScalarAttribute, Value, RGB,
+};
 
 use crate::{
     attributes::{ColourAttributeDisplayStack, ColourAttributeDisplayStackBuilder},
     colour::{GdkColour, ManipGdkColour},
-    manipulator::{ChromaLabel, ColourManipulatorGUI, ColourManipulatorGUIBuilder},
+    coloured::Colourable,
+    hue_dial::{GtkHueDial, GtkHueDialBuilder},
+    manipulator::{ChromaLabel, ColourManipulatorGUI, ColourManipulatorGUIBuilder, NudgeSteps},
     rgb_entry::{Hexable, RGBHexEntry, RGBHexEntryBuilder},
+    sv_box::{GtkSVBox, GtkSVBoxBuilder},
 };
 
 type ChangeCallback = Box<dyn Fn(&HCV)>;
@@ -25,6 +34,10 @@ pub struct ColourEditor<U: Hexable> {
     rgb_entry: Rc<RGBHexEntry<U>>,
     change_callbacks: RefCell<Vec<ChangeCallback>>,
     default_colour: HCV,
+    harmony_buttons: Vec<gtk::Button>,
+    harmony_colours: RefCell<Vec<HCV>>,
+    hue_dial: Option<Rc<GtkHueDial>>,
+    sv_box: Option<Rc<GtkSVBox>>,
 }
 
 impl<U: Hexable> ColourEditor<U> {
@@ -40,6 +53,29 @@ impl<U: Hexable> ColourEditor<U> {
         self.rgb_entry.set_colour(colour);
         self.colour_manipulator.set_colour(colour);
         self.cads.set_colour(Some(colour));
+        self.update_harmony_buttons(&colour.hcv());
+        self.update_pickers(&colour.hcv());
+    }
+
+    fn update_pickers(&self, colour: &HCV) {
+        if let Some(ref hue_dial) = self.hue_dial {
+            hue_dial.set_colour(Some(colour));
+        }
+        if let Some(ref sv_box) = self.sv_box {
+            sv_box.set_colour(Some(colour));
+        }
+    }
+
+    fn update_harmony_buttons(&self, colour: &HCV) {
+        if self.harmony_buttons.is_empty() {
+            return;
+        }
+        let [first, second] = harmony::split_complements(colour, Angle::from(30));
+        let colours = vec![first, harmony::complement(colour), second];
+        for (button, colour) in self.harmony_buttons.iter().zip(colours.iter()) {
+            button.set_widget_colour(colour);
+        }
+        *self.harmony_colours.borrow_mut() = colours;
     }
 
     pub fn reset(&self) {
@@ -47,6 +83,20 @@ impl<U: Hexable> ColourEditor<U> {
         self.set_colour(&self.default_colour);
     }
 
+    /// Emit an event for each colour operation performed by the user.
+    pub fn set_recorder(&self, recorder: Option<Rc<dyn Recorder>>) {
+        self.colour_manipulator.set_recorder(recorder);
+    }
+
+    // A colour chosen by the user via one of the editor's own widgets
+    fn set_colour_recorded(&self, colour: &HCV) {
+        let before = self.hcv();
+        self.set_colour(colour);
+        self.colour_manipulator
+            .record(Operation::SetColour(*colour), &before);
+        self.inform_change(colour);
+    }
+
     fn inform_change(&self, colour: &impl GdkColour) {
         for callback in self.change_callbacks.borrow().iter() {
             callback(&colour.hcv())
@@ -63,6 +113,11 @@ pub struct ColourEditorBuilder {
     attributes: Vec<ScalarAttribute>,
     extra_buttons: Vec<gtk::Button>,
     default_colour: Option<HCV>,
+    warmth_gradient: bool,
+    harmony_swatches: bool,
+    hue_dial: bool,
+    sv_box: bool,
+    nudge_steps: NudgeSteps,
 }
 
 impl ColourEditorBuilder {
@@ -70,6 +125,18 @@ impl ColourEditorBuilder {
         Self::default()
     }
 
+    /// Set up as specified by the user's preferences.
+    pub fn from_config(config: &UiPreferences) -> Self {
+        Self {
+            attributes: config.attributes.attributes.clone(),
+            warmth_gradient: config.attributes.warmth_gradient,
+            harmony_swatches: config.editor.harmony_swatches,
+            hue_dial: config.editor.hue_dial,
+            sv_box: config.editor.sv_box,
+            ..Self::default()
+        }
+    }
+
     pub fn attributes(&mut self, attributes: &[ScalarAttribute]) -> &mut Self {
         self.attributes = attributes.to_vec();
         self
@@ -85,14 +152,47 @@ impl ColourEditorBuilder {
         self
     }
 
+    pub fn warmth_gradient(&mut self, warmth_gradient: bool) -> &mut Self {
+        self.warmth_gradient = warmth_gradient;
+        self
+    }
+
+    /// Show clickable swatches of the current colour's complement and
+    /// split complements.
+    pub fn harmony_swatches(&mut self, harmony_swatches: bool) -> &mut Self {
+        self.harmony_swatches = harmony_swatches;
+        self
+    }
+
+    /// Show a hue dial which rotates the current colour's hue (greys are
+    /// left unchanged).
+    pub fn hue_dial(&mut self, hue_dial: bool) -> &mut Self {
+        self.hue_dial = hue_dial;
+        self
+    }
+
+    /// Show a saturation/value square for the current colour's hue.
+    pub fn sv_box(&mut self, sv_box: bool) -> &mut Self {
+        self.sv_box = sv_box;
+        self
+    }
+
+    /// Step sizes for keyboard adjustment of the colour in the swatch area.
+    pub fn nudge_steps(&mut self, nudge_steps: NudgeSteps) -> &mut Self {
+        self.nudge_steps = nudge_steps;
+        self
+    }
+
     pub fn build<U: Hexable>(&self) -> Rc<ColourEditor<U>> {
         let cads = ColourAttributeDisplayStackBuilder::new()
             .attributes(&self.attributes)
+            .warmth_gradient(self.warmth_gradient)
             .build();
         let rgb_entry = RGBHexEntryBuilder::<U>::new().editable(true).build();
         let colour_manipulator = ColourManipulatorGUIBuilder::new()
             .clamped(false)
             .extra_buttons(&self.extra_buttons)
+            .nudge_steps(self.nudge_steps)
             .chroma_label(if self.attributes.contains(&ScalarAttribute::Greyness) {
                 if self.attributes.contains(&ScalarAttribute::Chroma) {
                     ChromaLabel::Both
@@ -115,11 +215,76 @@ impl ColourEditorBuilder {
             } else {
                 HCV::new_grey(Value::ONE / 2)
             },
+            harmony_buttons: if self.harmony_swatches {
+                ["Split Complement", "Complement", "Split Complement"]
+                    .iter()
+                    .map(|tooltip| {
+                        let button = gtk::Button::new();
+                        button.set_tooltip_text(Some(tooltip));
+                        button.set_size_request(30, 20);
+                        button
+                    })
+                    .collect()
+            } else {
+                vec![]
+            },
+            harmony_colours: RefCell::new(vec![]),
+            hue_dial: if self.hue_dial {
+                Some(GtkHueDialBuilder::new().build())
+            } else {
+                None
+            },
+            sv_box: if self.sv_box {
+                Some(GtkSVBoxBuilder::new().build())
+            } else {
+                None
+            },
         });
 
         colour_editor
             .vbox
             .pack_start(colour_editor.cads.pwo(), false, false, 0);
+        if !colour_editor.harmony_buttons.is_empty() {
+            let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+            for (index, button) in colour_editor.harmony_buttons.iter().enumerate() {
+                hbox.pack_start(button, true, true, 0);
+                let colour_editor_c = Rc::clone(&colour_editor);
+                button.connect_clicked(move |_| {
+                    let colour = colour_editor_c.harmony_colours.borrow().get(index).copied();
+                    if let Some(colour) = colour {
+                        colour_editor_c.set_colour_recorded(&colour);
+                    }
+                });
+            }
+            colour_editor.vbox.pack_start(&hbox, false, false, 0);
+        }
+        if colour_editor.hue_dial.is_some() || colour_editor.sv_box.is_some() {
+            let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+            if let Some(ref hue_dial) = colour_editor.hue_dial {
+                hbox.pack_start(hue_dial.pwo(), true, false, 0);
+            }
+            if let Some(ref sv_box) = colour_editor.sv_box {
+                hbox.pack_start(sv_box.pwo(), true, false, 0);
+            }
+            colour_editor.vbox.pack_start(&hbox, false, false, 0);
+        }
+        if let Some(ref sv_box) = colour_editor.sv_box {
+            if let Some(ref hue_dial) = colour_editor.hue_dial {
+                sv_box.link_hue_dial(hue_dial);
+            }
+            let colour_editor_c = Rc::clone(&colour_editor);
+            sv_box.connect_changed(move |hcv| colour_editor_c.set_colour_recorded(hcv));
+        }
+        if let Some(ref hue_dial) = colour_editor.hue_dial {
+            let colour_editor_c = Rc::clone(&colour_editor);
+            hue_dial.connect_changed(move |angle| {
+                let hcv = colour_editor_c.hcv();
+                if let Some(hue_angle) = hcv.hue_angle() {
+                    let colour = hcv.rotated(angle - hue_angle);
+                    colour_editor_c.set_colour_recorded(&colour);
+                }
+            });
+        }
         colour_editor
             .vbox
             .pack_start(colour_editor.rgb_entry.pwo(), false, false, 0);
@@ -131,8 +296,14 @@ impl ColourEditorBuilder {
 
         let colour_editor_c = Rc::clone(&colour_editor);
         colour_editor.rgb_entry.connect_colour_changed(move |hcv| {
+            let before = colour_editor_c.hcv();
             colour_editor_c.cads.set_colour(Some(&hcv));
             colour_editor_c.colour_manipulator.set_colour(&hcv);
+            colour_editor_c
+                .colour_manipulator
+                .record(Operation::SetColour(hcv), &before);
+            colour_editor_c.update_harmony_buttons(&hcv);
+            colour_editor_c.update_pickers(&hcv);
             colour_editor_c.inform_change(&hcv);
         });
 
@@ -142,6 +313,8 @@ impl ColourEditorBuilder {
             .connect_changed(move |hcv| {
                 colour_editor_c.cads.set_colour(Some(&hcv));
                 colour_editor_c.rgb_entry.set_colour(&hcv);
+                colour_editor_c.update_harmony_buttons(&hcv);
+                colour_editor_c.update_pickers(&hcv);
                 colour_editor_c.inform_change(&hcv);
             });
 
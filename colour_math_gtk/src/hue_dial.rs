@@ -0,0 +1,156 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{cell::RefCell, rc::Rc};
+
+use pw_gtk_ext::{
+    cairo, gdk,
+    gtk::{self, prelude::*},
+    wrapper::*,
+};
+
+use colour_math::{beigui::hue_dial::HueDial, Angle, Hue};
+use colour_math_cairo::*;
+
+use crate::colour::GdkColour;
+
+type ChangeCallback = Box<dyn Fn(Angle)>;
+
+/// A compact alternative to the hue wheel for choosing a hue by dragging
+/// around a ring.
+#[derive(PWO, Wrapper)]
+pub struct GtkHueDial {
+    drawing_area: gtk::DrawingArea,
+    hue_dial: RefCell<HueDial>,
+    change_callbacks: RefCell<Vec<ChangeCallback>>,
+}
+
+impl GtkHueDial {
+    fn current_transform_matrix(&self) -> cairo::Matrix {
+        CairoCartesian::cartesian_transform_matrix(
+            self.drawing_area.get_allocated_width() as f64,
+            self.drawing_area.get_allocated_height() as f64,
+        )
+    }
+
+    fn device_to_user(&self, x: f64, y: f64) -> Point {
+        let mut ctm = self.current_transform_matrix();
+        ctm.invert();
+        ctm.transform_point(x, y).into()
+    }
+
+    pub fn hue(&self) -> Option<Hue> {
+        self.hue_dial.borrow().hue()
+    }
+
+    pub fn set_hue(&self, hue: Option<Hue>) {
+        self.hue_dial.borrow_mut().set_hue(hue);
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn set_colour(&self, colour: Option<&impl GdkColour>) {
+        self.hue_dial.borrow_mut().set_colour(colour);
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn set_snap(&self, snap: Option<Angle>) {
+        self.hue_dial.borrow_mut().set_snap(snap);
+    }
+
+    fn select_at(&self, x: f64, y: f64) {
+        let point = self.device_to_user(x, y);
+        if let Some(angle) = HueDial::angle_for_point(point.into()) {
+            let angle = self.hue_dial.borrow().snapped(angle);
+            self.set_hue(Some(Hue::from(angle)));
+            self.inform_change(angle);
+        }
+    }
+
+    fn inform_change(&self, angle: Angle) {
+        for callback in self.change_callbacks.borrow().iter() {
+            callback(angle)
+        }
+    }
+
+    pub fn connect_changed<F: Fn(Angle) + 'static>(&self, callback: F) {
+        self.change_callbacks.borrow_mut().push(Box::new(callback))
+    }
+}
+
+#[derive(Default)]
+pub struct GtkHueDialBuilder {
+    size: Option<i32>,
+    snap: Option<Angle>,
+}
+
+impl GtkHueDialBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn size(&mut self, size: i32) -> &mut Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn snap(&mut self, snap: Angle) -> &mut Self {
+        self.snap = Some(snap);
+        self
+    }
+
+    pub fn build(&self) -> Rc<GtkHueDial> {
+        let mut hue_dial = HueDial::new();
+        hue_dial.set_snap(self.snap);
+        let gtk_hue_dial = Rc::new(GtkHueDial {
+            drawing_area: gtk::DrawingArea::new(),
+            hue_dial: RefCell::new(hue_dial),
+            change_callbacks: RefCell::new(vec![]),
+        });
+        let size = self.size.unwrap_or(120);
+        gtk_hue_dial.drawing_area.set_size_request(size, size);
+        gtk_hue_dial.drawing_area.add_events(
+            gdk::EventMask::BUTTON_PRESS_MASK | gdk::EventMask::BUTTON_MOTION_MASK,
+        );
+
+        let gtk_hue_dial_c = Rc::clone(&gtk_hue_dial);
+        gtk_hue_dial
+            .drawing_area
+            .connect_draw(move |da, cairo_context| {
+                cairo_context.transform(gtk_hue_dial_c.current_transform_matrix());
+                let size = Size {
+                    width: da.get_allocated_width() as f64,
+                    height: da.get_allocated_height() as f64,
+                };
+                let cartesian = Drawer::new(cairo_context, size);
+                gtk_hue_dial_c.hue_dial.borrow().draw(&cartesian);
+                gtk::Inhibit(false)
+            });
+
+        let gtk_hue_dial_c = Rc::clone(&gtk_hue_dial);
+        gtk_hue_dial
+            .drawing_area
+            .connect_button_press_event(move |_, event| {
+                if event.get_event_type() == gdk::EventType::ButtonPress && event.get_button() == 1
+                {
+                    let (x, y) = event.get_position();
+                    gtk_hue_dial_c.select_at(x, y);
+                    gtk::Inhibit(true)
+                } else {
+                    gtk::Inhibit(false)
+                }
+            });
+
+        let gtk_hue_dial_c = Rc::clone(&gtk_hue_dial);
+        gtk_hue_dial
+            .drawing_area
+            .connect_motion_notify_event(move |_, event| {
+                if event.get_state().contains(gdk::ModifierType::BUTTON1_MASK) {
+                    let (x, y) = event.get_position();
+                    gtk_hue_dial_c.select_at(x, y);
+                    gtk::Inhibit(true)
+                } else {
+                    gtk::Inhibit(false)
+                }
+            });
+
+        gtk_hue_dial
+    }
+}
@@ -1,13 +1,13 @@
 // Copyright 2020 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
 
 use std::{
-    cell::{Cell, RefCell},
+    cell::{Cell, Ref, RefCell},
     collections::HashMap,
-    rc::Rc,
+    rc::{Rc, Weak},
 };
 
 use pw_gtk_ext::{
-    cairo, gdk,
+    cairo, gdk, glib,
     gtk::{self, prelude::*},
     gtkx::menu::{ManagedMenu, ManagedMenuBuilder, MenuItemSpec},
     sav_state::MaskedCondns,
@@ -15,8 +15,12 @@ use pw_gtk_ext::{
 };
 
 use colour_math::{
-    hue_wheel::{ColouredShape, HueWheel},
-    ScalarAttribute,
+    hue_wheel::{
+        AngularLabels, ColouredShape, HueWheel, HueWheelEvent, LabelStyle, LegendEntry,
+        LevelOfDetail,
+    },
+    preferences::UiPreferences,
+    ColourBasics, Prop, ScalarAttribute, UFDRNumber, RGB,
 };
 use colour_math_cairo::*;
 
@@ -26,18 +30,30 @@ use crate::{
 };
 
 type PopupCallback = Box<dyn Fn(&str)>;
+type Views = Rc<RefCell<Vec<Weak<GtkHueWheel>>>>;
+type ChangedCallback = Box<dyn Fn(&HueWheelEvent)>;
+type PendingEvents = Rc<RefCell<Vec<HueWheelEvent>>>;
 
 #[derive(PWO, Wrapper)]
 pub struct GtkHueWheel {
     vbox: gtk::Box,
     drawing_area: gtk::DrawingArea,
-    hue_wheel: RefCell<HueWheel>,
+    legend_box: gtk::Box,
+    hue_wheel: Rc<RefCell<HueWheel>>,
+    // all views (including this one) of the (shared) hue wheel model
+    views: Views,
+    // the model's events waiting to be passed on (once it's no longer
+    // borrowed) to the views' callbacks
+    pending_events: PendingEvents,
+    changed_callbacks: RefCell<Vec<ChangedCallback>>,
     chosen_item: RefCell<Option<String>>,
     attribute_selector: Rc<AttributeSelector>,
     popup_menu: ManagedMenu,
     callbacks: RefCell<HashMap<String, Vec<PopupCallback>>>,
     origin_offset: Cell<Point>,
     last_xy: Cell<Option<Point>>,
+    #[cfg(feature = "draw_timing")]
+    draw_times: crate::timing::DrawTimes,
 }
 
 impl GtkHueWheel {
@@ -68,23 +84,204 @@ impl GtkHueWheel {
         self.origin_offset.set(self.origin_offset.get() + delta);
     }
 
+    fn linked_views(views: &Views) -> Vec<Rc<GtkHueWheel>> {
+        views.borrow().iter().filter_map(Weak::upgrade).collect()
+    }
+
+    fn queue_draw_views(&self) {
+        for view in Self::linked_views(&self.views) {
+            view.drawing_area.queue_draw();
+        }
+    }
+
+    fn model_changed(&self) {
+        let events: Vec<HueWheelEvent> = self.pending_events.borrow_mut().drain(..).collect();
+        for view in Self::linked_views(&self.views) {
+            view.update_legend();
+            view.drawing_area.queue_draw();
+            for event in events.iter() {
+                for callback in view.changed_callbacks.borrow().iter() {
+                    callback(event)
+                }
+            }
+        }
+    }
+
+    /// Be told about changes to the items (made via this or a linked view).
+    pub fn connect_changed<F: Fn(&HueWheelEvent) + 'static>(&self, callback: F) {
+        self.changed_callbacks.borrow_mut().push(Box::new(callback))
+    }
+
+    /// Read only access to the (shared) model e.g. to enumerate its items.
+    pub fn model(&self) -> Ref<'_, HueWheel> {
+        self.hue_wheel.borrow()
+    }
+
     pub fn add_item(&self, coloured_item: ColouredShape) {
         self.hue_wheel.borrow_mut().add_item(coloured_item);
-        self.drawing_area.queue_draw();
+        self.model_changed();
+    }
+
+    /// Update an item's colour in place (e.g. while its paint is being
+    /// edited) without disturbing the popup menu's chosen item.
+    pub fn set_item_colour(&self, id: &str, colour: &impl ColourBasics) {
+        if self.hue_wheel.borrow_mut().set_item_colour(id, colour) {
+            self.model_changed();
+        }
     }
 
     pub fn remove_item(&self, id: &str) {
-        self.hue_wheel.borrow_mut().remove_item(id);
-        self.drawing_area.queue_draw();
+        let removed = self.hue_wheel.borrow_mut().remove_item(id);
+        if removed.is_some() {
+            self.model_changed();
+        }
     }
 
     pub fn remove_all(&self) {
         self.hue_wheel.borrow_mut().remove_all();
-        self.drawing_area.queue_draw();
+        self.model_changed();
+    }
+
+    pub fn replace_all(&self, coloured_items: impl IntoIterator<Item = ColouredShape>) {
+        self.hue_wheel.borrow_mut().replace_all(coloured_items);
+        self.model_changed();
+    }
+
+    pub fn legend(&self) -> Vec<LegendEntry> {
+        self.hue_wheel.borrow().legend()
+    }
+
+    pub fn set_group_visible(&self, group: &str, visible: bool) {
+        self.hue_wheel
+            .borrow_mut()
+            .set_group_visible(group, visible);
+        self.model_changed();
+    }
+
+    fn update_legend(&self) {
+        for child in self.legend_box.get_children() {
+            self.legend_box.remove(&child);
+        }
+        let legend = self.hue_wheel.borrow().legend();
+        for entry in legend {
+            let label = format!("{} ({})", entry.group, entry.number_of_items);
+            let check_button = gtk::CheckButton::with_label(&label);
+            check_button.set_active(entry.visible);
+            let hue_wheel = Rc::clone(&self.hue_wheel);
+            let views = Rc::clone(&self.views);
+            let drawing_area = self.drawing_area.clone();
+            check_button.connect_toggled(move |button| {
+                hue_wheel
+                    .borrow_mut()
+                    .set_group_visible(&entry.group, button.get_active());
+                // NB: this view's legend is already up to date
+                for view in Self::linked_views(&views) {
+                    if view.drawing_area != drawing_area {
+                        view.update_legend();
+                    }
+                    view.drawing_area.queue_draw();
+                }
+            });
+            self.legend_box.pack_start(&check_button, false, false, 0);
+        }
+        self.legend_box.show_all();
+    }
+
+    pub fn set_merge_distance(&self, merge_distance: Option<Prop>) {
+        self.hue_wheel
+            .borrow_mut()
+            .set_merge_distance(merge_distance);
+        self.queue_draw_views();
+    }
+
+    pub fn set_angular_labels(&self, angular_labels: AngularLabels) {
+        self.hue_wheel
+            .borrow_mut()
+            .set_angular_labels(angular_labels);
+        self.queue_draw_views();
+    }
+
+    pub fn set_label_style(&self, label_style: LabelStyle) {
+        self.hue_wheel.borrow_mut().set_label_style(label_style);
+        self.queue_draw_views();
+    }
+
+    /// The attribute that determines an item's distance from the centre.
+    pub fn radial_attribute(&self) -> ScalarAttribute {
+        self.attribute_selector.attribute()
+    }
+
+    /// Has no effect unless `attribute` is one of the builder's attributes.
+    pub fn set_radial_attribute(&self, attribute: ScalarAttribute) {
+        self.attribute_selector.set_attribute(attribute);
+    }
+
+    /// Centre the view on the item nearest to `colour` (or on the colour's
+    /// own position if there are no items) and briefly highlight it.
+    pub fn locate(&self, colour: &impl ColourBasics) {
+        const LOCATE_ZOOM: f64 = 2.0;
+        const FLASH_MS: u32 = 1500;
+        let attribute = self.radial_attribute();
+        let (point, highlight) = {
+            let mut hue_wheel = self.hue_wheel.borrow_mut();
+            let zoom = hue_wheel.zoom_scale().max(UFDRNumber::from(LOCATE_ZOOM));
+            hue_wheel.set_zoom_scale(zoom);
+            match hue_wheel.nearest_item_to_colour(colour) {
+                Some(item) => (
+                    hue_wheel.colour_position(&item.colour(), attribute),
+                    item.colour(),
+                ),
+                None => (hue_wheel.colour_position(colour, attribute), colour.hcv()),
+            }
+        };
+        let point = Point::from(point);
+        self.origin_offset.set(Point::default() - point);
+        self.hue_wheel.borrow_mut().set_highlight(Some(&highlight));
+        self.queue_draw_views();
+        let hue_wheel = Rc::clone(&self.hue_wheel);
+        let views = Rc::clone(&self.views);
+        glib::timeout_add_local(FLASH_MS, move || {
+            // don't cancel a later search's highlight
+            if hue_wheel.borrow().highlight() == Some(highlight) {
+                hue_wheel.borrow_mut().set_highlight(None::<&RGB<f64>>);
+                for view in Self::linked_views(&views) {
+                    view.drawing_area.queue_draw();
+                }
+            }
+            glib::Continue(false)
+        });
+    }
+
+    /// Locate the colour described by `text` (a hex/RGB specification or
+    /// (part of) an item's id).  Returns `false` if nothing matches.
+    pub fn locate_text(&self, text: &str) -> bool {
+        let text = text.trim();
+        if let Ok(rgb) = text.parse::<RGB<u8>>() {
+            self.locate(&rgb);
+            return true;
+        }
+        let colour = self
+            .hue_wheel
+            .borrow()
+            .find_item(text)
+            .map(|item| item.colour());
+        match colour {
+            Some(colour) => {
+                self.locate(&colour);
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn set_target_colour(&self, colour: Option<&impl GdkColour>) {
         self.hue_wheel.borrow_mut().set_target_colour(colour);
+        self.queue_draw_views();
+    }
+
+    #[cfg(feature = "draw_timing")]
+    pub fn draw_times(&self) -> &crate::timing::DrawTimes {
+        &self.draw_times
     }
 
     pub fn update_popup_condns(&self, changed_condns: MaskedCondns) {
@@ -118,6 +315,13 @@ impl GtkHueWheel {
 pub struct GtkHueWheelBuilder {
     menu_item_specs: Vec<(&'static str, MenuItemSpec, u64)>,
     attributes: Vec<ScalarAttribute>,
+    level_of_detail: LevelOfDetail,
+    merge_distance: Option<Prop>,
+    angular_labels: AngularLabels,
+    label_style: LabelStyle,
+    legend: bool,
+    find_entry: bool,
+    linked_to: Option<(Rc<RefCell<HueWheel>>, Views, PendingEvents)>,
 }
 
 impl GtkHueWheelBuilder {
@@ -125,6 +329,17 @@ impl GtkHueWheelBuilder {
         Self::default()
     }
 
+    /// Set up as specified by the user's preferences.
+    pub fn from_config(config: &UiPreferences) -> Self {
+        Self {
+            attributes: config.wheel.attributes.clone(),
+            angular_labels: config.wheel.angular_labels,
+            legend: config.wheel.legend,
+            find_entry: config.wheel.find_entry,
+            ..Self::default()
+        }
+    }
+
     pub fn menu_item_specs(
         &mut self,
         menu_item_specs: &[(&'static str, MenuItemSpec, u64)],
@@ -133,13 +348,61 @@ impl GtkHueWheelBuilder {
         self
     }
 
+    /// The choices for the radial attribute (the first is the initial
+    /// choice).  Defaults to chroma only.
     pub fn attributes(&mut self, attributes: &[ScalarAttribute]) -> &mut Self {
         self.attributes.extend(attributes.iter());
         self
     }
 
+    pub fn level_of_detail(&mut self, level_of_detail: LevelOfDetail) -> &mut Self {
+        self.level_of_detail = level_of_detail;
+        self
+    }
+
+    /// Merge items whose colours are closer than `merge_distance` into a
+    /// single (click to expand) marker.
+    pub fn merge_distance(&mut self, merge_distance: Prop) -> &mut Self {
+        self.merge_distance = Some(merge_distance);
+        self
+    }
+
+    pub fn angular_labels(&mut self, angular_labels: AngularLabels) -> &mut Self {
+        self.angular_labels = angular_labels;
+        self
+    }
+
+    pub fn label_style(&mut self, label_style: LabelStyle) -> &mut Self {
+        self.label_style = label_style;
+        self
+    }
+
+    /// Show a panel listing the item groups (with visibility toggles).
+    pub fn legend(&mut self, legend: bool) -> &mut Self {
+        self.legend = legend;
+        self
+    }
+
+    /// Include an entry for locating colours (by hex code or item id).
+    pub fn find_entry(&mut self, find_entry: bool) -> &mut Self {
+        self.find_entry = find_entry;
+        self
+    }
+
+    /// Share `other`'s items (and zoom, merging, labels etc.) but display
+    /// them with an independent choice of radial attribute.  The model
+    /// settings of this builder are ignored.
+    pub fn linked_to(&mut self, other: &Rc<GtkHueWheel>) -> &mut Self {
+        self.linked_to = Some((
+            Rc::clone(&other.hue_wheel),
+            Rc::clone(&other.views),
+            Rc::clone(&other.pending_events),
+        ));
+        self
+    }
+
     pub fn build(&self) -> Rc<GtkHueWheel> {
-        let default_attributes = vec![ScalarAttribute::Value];
+        let default_attributes = vec![ScalarAttribute::Chroma];
         let attributes = if self.attributes.is_empty() {
             &default_attributes
         } else {
@@ -166,18 +429,55 @@ impl GtkHueWheelBuilder {
 
         let popup_menu = ManagedMenuBuilder::new().build();
 
+        let (hue_wheel, views, pending_events) = match self.linked_to {
+            Some((ref hue_wheel, ref views, ref pending_events)) => (
+                Rc::clone(hue_wheel),
+                Rc::clone(views),
+                Rc::clone(pending_events),
+            ),
+            None => {
+                let mut hue_wheel = HueWheel::new();
+                hue_wheel.set_level_of_detail(self.level_of_detail);
+                hue_wheel.set_angular_labels(self.angular_labels);
+                hue_wheel.set_label_style(self.label_style);
+                hue_wheel.set_merge_distance(self.merge_distance);
+                let pending_events = PendingEvents::default();
+                let pending_events_c = Rc::clone(&pending_events);
+                hue_wheel.connect_changed(move |event| {
+                    pending_events_c.borrow_mut().push(event.clone())
+                });
+                (
+                    Rc::new(RefCell::new(hue_wheel)),
+                    Views::default(),
+                    pending_events,
+                )
+            }
+        };
+
         let gtk_hue_wheel = Rc::new(GtkHueWheel {
             vbox: gtk::Box::new(gtk::Orientation::Vertical, 0),
             drawing_area,
-            hue_wheel: RefCell::new(HueWheel::new()),
+            legend_box: gtk::Box::new(gtk::Orientation::Vertical, 0),
+            hue_wheel,
+            views,
+            pending_events,
+            changed_callbacks: RefCell::new(vec![]),
             chosen_item: RefCell::new(None),
             attribute_selector,
             popup_menu,
             callbacks: RefCell::new(HashMap::new()),
             origin_offset: Cell::new(Point::default()),
             last_xy: Cell::new(None),
+            #[cfg(feature = "draw_timing")]
+            draw_times: crate::timing::DrawTimes::default(),
         });
 
+        gtk_hue_wheel
+            .views
+            .borrow_mut()
+            .push(Rc::downgrade(&gtk_hue_wheel));
+        gtk_hue_wheel.update_legend();
+
         for (name, menu_item_spec, condns) in self.menu_item_specs.iter() {
             let gtk_hue_wheel_c = Rc::clone(&gtk_hue_wheel);
             let name_c = (*name).to_string();
@@ -195,11 +495,34 @@ impl GtkHueWheelBuilder {
         let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 0);
         hbox.pack_start(&gtk::Label::new(Some("Attribute: ")), false, false, 0);
         hbox.pack_start(gtk_hue_wheel.attribute_selector.pwo(), true, true, 0);
+        if self.find_entry {
+            let entry = gtk::EntryBuilder::new()
+                .placeholder_text("Find: #RRGGBB or name")
+                .build();
+            let gtk_hue_wheel_c = Rc::clone(&gtk_hue_wheel);
+            entry.connect_activate(move |entry| {
+                if !gtk_hue_wheel_c.locate_text(&entry.get_text()) {
+                    entry.error_bell();
+                }
+            });
+            hbox.pack_start(&entry, false, false, 0);
+        }
 
         gtk_hue_wheel.vbox.pack_start(&hbox, false, false, 0);
-        gtk_hue_wheel
-            .vbox
-            .pack_start(&gtk_hue_wheel.drawing_area, true, true, 0);
+        if self.legend {
+            let scrolled_window =
+                gtk::ScrolledWindow::new(None::<&gtk::Adjustment>, None::<&gtk::Adjustment>);
+            scrolled_window.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+            scrolled_window.add(&gtk_hue_wheel.legend_box);
+            let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+            hbox.pack_start(&gtk_hue_wheel.drawing_area, true, true, 0);
+            hbox.pack_start(&scrolled_window, false, false, 0);
+            gtk_hue_wheel.vbox.pack_start(&hbox, true, true, 0);
+        } else {
+            gtk_hue_wheel
+                .vbox
+                .pack_start(&gtk_hue_wheel.drawing_area, true, true, 0);
+        }
 
         let gtk_hue_wheel_c = Rc::clone(&gtk_hue_wheel);
         gtk_hue_wheel
@@ -210,6 +533,8 @@ impl GtkHueWheelBuilder {
         gtk_hue_wheel
             .drawing_area
             .connect_draw(move |da, cairo_context| {
+                #[cfg(feature = "draw_timing")]
+                let start = std::time::Instant::now();
                 cairo_context.transform(gtk_hue_wheel_c.current_transform_matrix());
                 let size = Size {
                     width: da.get_allocated_width() as f64,
@@ -220,6 +545,8 @@ impl GtkHueWheelBuilder {
                     .hue_wheel
                     .borrow()
                     .draw(gtk_hue_wheel_c.attribute_selector.attribute(), &cartesian);
+                #[cfg(feature = "draw_timing")]
+                gtk_hue_wheel_c.draw_times.record(start.elapsed());
                 Inhibit(false)
             });
 
@@ -227,18 +554,18 @@ impl GtkHueWheelBuilder {
         let gtk_hue_wheel_c = Rc::clone(&gtk_hue_wheel);
         gtk_hue_wheel
             .drawing_area
-            .connect_scroll_event(move |da, scroll_event| {
+            .connect_scroll_event(move |_, scroll_event| {
                 if let Some(device) = scroll_event.get_device() {
                     if device.get_source() == gdk::InputSource::Mouse {
                         match scroll_event.get_direction() {
                             gdk::ScrollDirection::Up => {
                                 gtk_hue_wheel_c.hue_wheel.borrow_mut().decr_zoom();
-                                da.queue_draw();
+                                gtk_hue_wheel_c.queue_draw_views();
                                 return Inhibit(true);
                             }
                             gdk::ScrollDirection::Down => {
                                 gtk_hue_wheel_c.hue_wheel.borrow_mut().incr_zoom();
-                                da.queue_draw();
+                                gtk_hue_wheel_c.queue_draw_views();
                                 return Inhibit(true);
                             }
                             _ => (),
@@ -258,9 +585,18 @@ impl GtkHueWheelBuilder {
                 };
                 match event.get_button() {
                     1 => {
-                        gtk_hue_wheel_c
-                            .last_xy
-                            .set(Some(event.get_position().into()));
+                        let device_point: Point = event.get_position().into();
+                        let point = gtk_hue_wheel_c.device_to_user(device_point.x, device_point.y);
+                        let attribute = gtk_hue_wheel_c.attribute_selector.attribute();
+                        if gtk_hue_wheel_c
+                            .hue_wheel
+                            .borrow_mut()
+                            .toggle_expansion_at_point(point.into(), attribute)
+                        {
+                            gtk_hue_wheel_c.queue_draw_views();
+                        } else {
+                            gtk_hue_wheel_c.last_xy.set(Some(device_point));
+                        }
                         Inhibit(true)
                     }
                     3 => {
@@ -1,9 +1,25 @@
 // Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
 
+pub mod accent;
+pub mod autosave;
+pub mod calibration;
+pub mod cell_renderer;
 pub mod colour_edit;
+pub mod hue_dial;
 pub mod hue_wheel;
 pub mod manipulator;
+pub mod paint_match;
+pub mod palette_view;
+pub mod picker;
+pub mod pixbuf;
 pub mod rgb_entry;
+pub mod sparkline;
+pub mod style;
+pub mod sv_box;
+#[cfg(feature = "draw_timing")]
+pub mod timing;
+pub mod value_scale;
+pub mod worker;
 
 pub mod colour {
     use pw_gtk_ext::gdk;
@@ -12,7 +28,7 @@ pub mod colour {
 
     pub trait GdkColour: colour_math::ColourIfce {
         fn gdk_rgba(&self) -> gdk::RGBA {
-            let rgb = self.rgb::<f64>();
+            let rgb = crate::calibration::displayed(&self.rgb::<f64>());
             gdk::RGBA {
                 red: rgb[0],
                 green: rgb[1],
@@ -32,29 +48,108 @@ pub mod colour {
 }
 
 pub mod coloured {
+    use std::cell::RefCell;
+
     use pw_gtk_ext::gtk::{self, prelude::*};
 
+    use colour_math::HCV;
+
     use crate::colour::*;
 
+    const STATE_COLOURS_KEY: &str = "colour_math_gtk::coloured::state_colours";
+
+    type StateColours = RefCell<Vec<(gtk::StateFlags, HCV)>>;
+
+    // The most specific remembered colour applicable to `state`
+    fn remembered_colour<W: ObjectExt>(widget: &W, state: gtk::StateFlags) -> Option<HCV> {
+        let state_colours = unsafe { widget.get_data::<StateColours>(STATE_COLOURS_KEY) }?;
+        let state_colours = state_colours.borrow();
+        state_colours
+            .iter()
+            .filter(|(flags, _)| state.contains(*flags))
+            .max_by_key(|(flags, _)| flags.bits().count_ones())
+            .map(|(_, hcv)| *hcv)
+    }
+
+    // Editable text keeps the theme's colours (for legibility), labels only
+    // get a foreground colour (as they're drawn on their parent's background)
+    // and everything else is coloured in full with containers' descendants
+    // coloured recursively.
+    #[allow(deprecated)]
+    fn set_colour_tree(widget: &gtk::Widget, colour: &impl GdkColour, exclude: &[gtk::Widget]) {
+        if exclude.contains(widget) || widget.is::<gtk::Entry>() || widget.is::<gtk::TextView>() {
+            return;
+        }
+        if widget.is::<gtk::Label>() {
+            let fg_gdk_rgba = colour.best_foreground().gdk_rgba();
+            widget.override_color(gtk::StateFlags::empty(), Some(&fg_gdk_rgba));
+        } else {
+            widget.set_widget_colour(colour);
+        }
+        if let Some(container) = widget.downcast_ref::<gtk::Container>() {
+            for child in container.get_children().iter() {
+                set_colour_tree(child, colour, exclude);
+            }
+        }
+    }
+
     #[allow(deprecated)]
-    pub trait Colourable: WidgetExt {
+    pub trait Colourable: WidgetExt + ObjectExt + IsA<gtk::Widget> {
         fn set_widget_colour(&self, colour: &impl GdkColour) {
+            self.set_widget_colour_for_state(gtk::StateFlags::empty(), colour)
+        }
+
+        /// Colour the widget when its state flags include `state` (the
+        /// colour for `gtk::StateFlags::empty()` is used when no more
+        /// specific one has been set).  The colours are remembered and
+        /// reapplied whenever the widget's state changes e.g. when it becomes
+        /// insensitive and then sensitive again.
+        fn set_widget_colour_for_state(&self, state: gtk::StateFlags, colour: &impl GdkColour) {
+            let hcv = colour.hcv();
+            let state_colours = unsafe { self.get_data::<StateColours>(STATE_COLOURS_KEY) };
+            if let Some(state_colours) = state_colours {
+                let mut state_colours = state_colours.borrow_mut();
+                state_colours.retain(|(flags, _)| *flags != state);
+                state_colours.push((state, hcv));
+            } else {
+                let state_colours: StateColours = RefCell::new(vec![(state, hcv)]);
+                unsafe { self.set_data(STATE_COLOURS_KEY, state_colours) };
+                self.connect_state_flags_changed(|widget, _| widget.reapply_widget_colour());
+            }
+            self.reapply_widget_colour();
+        }
+
+        fn reapply_widget_colour(&self) {
+            let state = self.get_state_flags();
+            if let Some(hcv) = remembered_colour(self, state) {
+                self.apply_widget_colour(state, &hcv);
+            }
+        }
+
+        /// Colour the widget and (if it's a container) its descendants except
+        /// those in `exclude` (and their descendants).
+        fn set_widget_colour_tree(&self, colour: &impl GdkColour, exclude: &[gtk::Widget]) {
+            set_colour_tree(self.upcast_ref::<gtk::Widget>(), colour, exclude)
+        }
+
+        /// Colour the widget for `state` without remembering the colour.
+        fn apply_widget_colour(&self, state: gtk::StateFlags, colour: &impl GdkColour) {
             let bg_gdk_rgba = colour.gdk_rgba();
             let fg_gdk_rgba = colour.best_foreground().gdk_rgba();
-            self.override_background_color(gtk::StateFlags::empty(), Some(&bg_gdk_rgba));
-            self.override_color(gtk::StateFlags::empty(), Some(&fg_gdk_rgba));
+            self.override_background_color(state, Some(&bg_gdk_rgba));
+            self.override_color(state, Some(&fg_gdk_rgba));
         }
     }
 
     #[allow(deprecated)]
     impl Colourable for gtk::Button {
-        fn set_widget_colour(&self, colour: &impl GdkColour) {
+        fn apply_widget_colour(&self, state: gtk::StateFlags, colour: &impl GdkColour) {
             let bg_gdk_rgba = colour.gdk_rgba();
             let fg_gdk_rgba = colour.best_foreground().gdk_rgba();
-            self.override_background_color(gtk::StateFlags::empty(), Some(&bg_gdk_rgba));
-            self.override_color(gtk::StateFlags::empty(), Some(&fg_gdk_rgba));
+            self.override_background_color(state, Some(&bg_gdk_rgba));
+            self.override_color(state, Some(&fg_gdk_rgba));
             for child in self.get_children().iter() {
-                child.set_widget_colour(colour);
+                child.apply_widget_colour(state, colour);
             }
         }
     }
@@ -86,15 +181,23 @@ pub mod coloured {
 pub mod attributes {
     use std::{
         cell::{Cell, RefCell},
+        marker::PhantomData,
         rc::Rc,
     };
 
     use pw_gtk_ext::{
-        gtk::{self, BoxExt, RadioButtonExt, ToggleButtonExt, WidgetExt},
+        gdk,
+        gtk::{self, BoxExt, ContainerExt, RadioButtonExt, ToggleButtonExt, WidgetExt},
         wrapper::*,
     };
 
-    use colour_math::{attr_display, RGB, ScalarAttribute};
+    use colour_math::{
+        attr_display,
+        fdrn::Prop,
+        manipulator::{ColourManipulatorBuilder, Outcome},
+        preferences::UiPreferences,
+        ColourBasics, ScalarAttribute, HCV, RGB,
+    };
     use colour_math_cairo::{Drawer, Size};
 
     use crate::colour::GdkColour;
@@ -105,20 +208,58 @@ pub mod attributes {
     pub type ValueCAD = ColourAttributeDisplay<attr_display::ValueCAD>;
     pub type WarmthCAD = ColourAttributeDisplay<attr_display::WarmthCAD>;
 
+    pub type ChromaCADBuilder = ColourAttributeDisplayBuilder<attr_display::ChromaCAD>;
+    pub type GreynessCADBuilder = ColourAttributeDisplayBuilder<attr_display::GreynessCAD>;
+    pub type HueCADBuilder = ColourAttributeDisplayBuilder<attr_display::HueCAD>;
+    pub type ValueCADBuilder = ColourAttributeDisplayBuilder<attr_display::ValueCAD>;
+    pub type WarmthCADBuilder = ColourAttributeDisplayBuilder<attr_display::WarmthCAD>;
+
+    type ChangeCallback = Box<dyn Fn(HCV)>;
+
     pub trait DynColourAttributeDisplay: PackableWidgetObject<PWT = gtk::DrawingArea> {
         fn set_rgb(&self, rgb: Option<&RGB<f64>>);
         fn set_target_rgb(&self, rgb: Option<&RGB<f64>>);
+        fn set_interactive(&self, interactive: bool);
+        fn connect_changed_boxed(&self, callback: ChangeCallback);
     }
 
+    type DynCAD = Rc<dyn DynColourAttributeDisplay<PWT = gtk::DrawingArea>>;
+
     #[derive(PWO, Wrapper)]
     pub struct ColourAttributeDisplayStack {
         vbox: gtk::Box,
-        cads: Vec<Rc<dyn DynColourAttributeDisplay<PWT = gtk::DrawingArea>>>,
+        hue_cad: RefCell<Option<DynCAD>>,
+        cads: RefCell<Vec<(ScalarAttribute, DynCAD)>>,
+        colour: Cell<Option<HCV>>,
+        target_colour: Cell<Option<HCV>>,
+        warmth_gradient: bool,
+        interactive: bool,
+        change_callbacks: RefCell<Vec<ChangeCallback>>,
     }
 
     impl ColourAttributeDisplayStack {
+        /// Called (with the new colour) when the user edits the colour by
+        /// clicking or dragging in one of the stack's interactive displays.
+        pub fn connect_changed<F: Fn(HCV) + 'static>(&self, callback: F) {
+            self.change_callbacks.borrow_mut().push(Box::new(callback))
+        }
+
+        fn set_colour_and_inform(&self, hcv: &HCV) {
+            self.set_colour(Some(hcv));
+            for callback in self.change_callbacks.borrow().iter() {
+                callback(*hcv)
+            }
+        }
+
+        fn all_cads(&self) -> Vec<DynCAD> {
+            let mut cads: Vec<DynCAD> = self.hue_cad.borrow().iter().cloned().collect();
+            cads.extend(self.cads.borrow().iter().map(|(_, cad)| Rc::clone(cad)));
+            cads
+        }
+
         pub fn set_colour(&self, colour: Option<&impl GdkColour>) {
-            for cad in self.cads.iter() {
+            self.colour.set(colour.map(|colour| colour.hcv()));
+            for cad in self.all_cads().iter() {
                 if let Some(colour) = colour {
                     cad.set_rgb(Some(&colour.rgb()));
                 } else {
@@ -128,7 +269,8 @@ pub mod attributes {
         }
 
         pub fn set_target_colour(&self, colour: Option<&impl GdkColour>) {
-            for cad in self.cads.iter() {
+            self.target_colour.set(colour.map(|colour| colour.hcv()));
+            for cad in self.all_cads().iter() {
                 if let Some(colour) = colour {
                     cad.set_target_rgb(Some(&colour.rgb()));
                 } else {
@@ -136,12 +278,96 @@ pub mod attributes {
                 }
             }
         }
+
+        // Bring a newly created display into line with the rest of the stack
+        fn adopt(self: &Rc<Self>, cad: DynCAD) -> DynCAD {
+            cad.set_interactive(self.interactive);
+            if let Some(colour) = self.colour.get() {
+                cad.set_rgb(Some(&colour.rgb()));
+            }
+            if let Some(target_colour) = self.target_colour.get() {
+                cad.set_target_rgb(Some(&target_colour.rgb()));
+            }
+            // a weak reference as the stack owns the display
+            let stack_w = Rc::downgrade(self);
+            cad.connect_changed_boxed(Box::new(move |hcv| {
+                if let Some(stack) = stack_w.upgrade() {
+                    stack.set_colour_and_inform(&hcv)
+                }
+            }));
+            self.vbox.pack_start(cad.pwo(), true, true, 0);
+            cad.pwo().show();
+            cad
+        }
+
+        pub fn includes_hue(&self) -> bool {
+            self.hue_cad.borrow().is_some()
+        }
+
+        /// Show or hide the hue display (which is always at the top).
+        pub fn set_include_hue(self: &Rc<Self>, include_hue: bool) {
+            if include_hue == self.includes_hue() {
+                return;
+            }
+            if include_hue {
+                let cad = self.adopt(HueCAD::new());
+                self.vbox.reorder_child(cad.pwo(), 0);
+                *self.hue_cad.borrow_mut() = Some(cad);
+            } else if let Some(cad) = self.hue_cad.borrow_mut().take() {
+                self.vbox.remove(cad.pwo());
+            }
+        }
+
+        /// The scalar attributes currently displayed (in display order).
+        pub fn attributes(&self) -> Vec<ScalarAttribute> {
+            self.cads.borrow().iter().map(|(attr, _)| *attr).collect()
+        }
+
+        /// Append a display for `attribute` unless it's already present.
+        pub fn add_attribute(self: &Rc<Self>, attribute: ScalarAttribute) {
+            if self.attributes().contains(&attribute) {
+                return;
+            }
+            let cad: DynCAD = match attribute {
+                ScalarAttribute::Value => ValueCAD::new(),
+                ScalarAttribute::Chroma => ChromaCAD::new(),
+                ScalarAttribute::Warmth => {
+                    let cad = WarmthCAD::new();
+                    cad.set_warmth_gradient(self.warmth_gradient);
+                    cad
+                }
+                ScalarAttribute::Greyness => GreynessCAD::new(),
+            };
+            let cad = self.adopt(cad);
+            self.cads.borrow_mut().push((attribute, cad));
+        }
+
+        pub fn remove_attribute(&self, attribute: ScalarAttribute) {
+            let mut cads = self.cads.borrow_mut();
+            if let Some(index) = cads.iter().position(|(attr, _)| *attr == attribute) {
+                let (_, cad) = cads.remove(index);
+                self.vbox.remove(cad.pwo());
+            }
+        }
     }
 
-    #[derive(Default)]
     pub struct ColourAttributeDisplayStackBuilder {
         // TODO: add orientation as an option for CAD stacks
         attributes: Vec<ScalarAttribute>,
+        include_hue: bool,
+        warmth_gradient: bool,
+        interactive: bool,
+    }
+
+    impl Default for ColourAttributeDisplayStackBuilder {
+        fn default() -> Self {
+            Self {
+                attributes: vec![],
+                include_hue: true,
+                warmth_gradient: false,
+                interactive: false,
+            }
+        }
     }
 
     impl ColourAttributeDisplayStackBuilder {
@@ -149,29 +375,56 @@ pub mod attributes {
             Self::default()
         }
 
+        /// Set up as specified by the user's preferences.
+        pub fn from_config(config: &UiPreferences) -> Self {
+            Self {
+                attributes: config.attributes.attributes.clone(),
+                include_hue: config.attributes.include_hue,
+                warmth_gradient: config.attributes.warmth_gradient,
+                ..Self::default()
+            }
+        }
+
         pub fn attributes(&mut self, attributes: &[ScalarAttribute]) -> &mut Self {
             self.attributes = attributes.to_vec();
             self
         }
 
+        /// Whether to put a hue display above the attribute displays (the
+        /// default).
+        pub fn include_hue(&mut self, include_hue: bool) -> &mut Self {
+            self.include_hue = include_hue;
+            self
+        }
+
+        pub fn warmth_gradient(&mut self, warmth_gradient: bool) -> &mut Self {
+            self.warmth_gradient = warmth_gradient;
+            self
+        }
+
+        /// Allow the colour to be edited by clicking or dragging in the
+        /// displays that support it.
+        pub fn interactive(&mut self, interactive: bool) -> &mut Self {
+            self.interactive = interactive;
+            self
+        }
+
         pub fn build(&self) -> Rc<ColourAttributeDisplayStack> {
-            let vbox = gtk::Box::new(gtk::Orientation::Vertical, 1);
-            let mut cads = vec![];
-            let hue_cad: Rc<dyn DynColourAttributeDisplay<PWT = gtk::DrawingArea>> = HueCAD::new();
-            vbox.pack_start(hue_cad.pwo(), true, true, 0);
-            cads.push(hue_cad);
+            let stack = Rc::new(ColourAttributeDisplayStack {
+                vbox: gtk::Box::new(gtk::Orientation::Vertical, 1),
+                hue_cad: RefCell::new(None),
+                cads: RefCell::new(vec![]),
+                colour: Cell::new(None),
+                target_colour: Cell::new(None),
+                warmth_gradient: self.warmth_gradient,
+                interactive: self.interactive,
+                change_callbacks: RefCell::new(vec![]),
+            });
+            stack.set_include_hue(self.include_hue);
             for scalar_attribute in self.attributes.iter() {
-                let cad: Rc<dyn DynColourAttributeDisplay<PWT = gtk::DrawingArea>> =
-                    match scalar_attribute {
-                        ScalarAttribute::Value => ValueCAD::new(),
-                        ScalarAttribute::Chroma => ChromaCAD::new(),
-                        ScalarAttribute::Warmth => WarmthCAD::new(),
-                        ScalarAttribute::Greyness => GreynessCAD::new(),
-                    };
-                vbox.pack_start(cad.pwo(), true, true, 0);
-                cads.push(cad);
+                stack.add_attribute(*scalar_attribute);
             }
-            Rc::new(ColourAttributeDisplayStack { vbox, cads })
+            stack
         }
     }
 
@@ -179,6 +432,13 @@ pub mod attributes {
     pub struct ColourAttributeDisplay<A: attr_display::ColourAttributeDisplayIfce> {
         drawing_area: gtk::DrawingArea,
         attribute: RefCell<A>,
+        orientation: gtk::Orientation,
+        show_label: bool,
+        colour: Cell<Option<HCV>>,
+        interactive: Cell<bool>,
+        change_callbacks: RefCell<Vec<ChangeCallback>>,
+        #[cfg(feature = "draw_timing")]
+        draw_times: crate::timing::DrawTimes,
     }
 
     impl<A> ColourAttributeDisplay<A>
@@ -186,30 +446,263 @@ pub mod attributes {
         A: attr_display::ColourAttributeDisplayIfce + 'static,
     {
         pub fn new() -> Rc<Self> {
-            let cad = Rc::new(Self {
+            ColourAttributeDisplayBuilder::<A>::new().build()
+        }
+
+        pub fn set_colour(&self, colour: Option<&impl GdkColour>) {
+            if let Some(colour) = colour {
+                self.set_rgb(Some(&colour.rgb()));
+            } else {
+                self.set_rgb(None);
+            }
+        }
+
+        pub fn set_target_colour(&self, colour: Option<&impl GdkColour>) {
+            if let Some(colour) = colour {
+                self.set_target_rgb(Some(&colour.rgb()));
+            } else {
+                self.set_target_rgb(None);
+            }
+        }
+
+        pub fn orientation(&self) -> gtk::Orientation {
+            self.orientation
+        }
+
+        pub fn interactive(&self) -> bool {
+            self.interactive.get()
+        }
+
+        pub fn connect_changed<F: Fn(HCV) + 'static>(&self, callback: F) {
+            self.change_callbacks.borrow_mut().push(Box::new(callback))
+        }
+
+        // Returns false if the event should be passed on.
+        fn select_at(&self, x: f64, y: f64) -> bool {
+            if !self.interactive.get() {
+                return false;
+            }
+            let attr_value = match self.orientation {
+                gtk::Orientation::Vertical => {
+                    let height = self.drawing_area.get_allocated_height() as f64;
+                    A::attr_value_at(height - y, height)
+                }
+                _ => {
+                    let width = self.drawing_area.get_allocated_width() as f64;
+                    A::attr_value_at(x, width)
+                }
+            };
+            self.set_attr_value(attr_value)
+        }
+
+        // Scrolling up (or right) increases the attribute by 1% (10% with
+        // shift and 0.1% with control).  Returns false if the event should
+        // be passed on.
+        fn scroll(&self, event: &gdk::EventScroll) -> bool {
+            if !self.interactive.get() {
+                return false;
+            }
+            let state = event.get_state();
+            let step = if state.contains(gdk::ModifierType::SHIFT_MASK) {
+                0.1
+            } else if state.contains(gdk::ModifierType::CONTROL_MASK) {
+                0.001
+            } else {
+                0.01
+            };
+            let delta = match event.get_direction() {
+                gdk::ScrollDirection::Up | gdk::ScrollDirection::Right => step,
+                gdk::ScrollDirection::Down | gdk::ScrollDirection::Left => -step,
+                _ => return false,
+            };
+            // NB: the attribute mustn't still be borrowed when it's updated
+            let attr_value = self.attribute.borrow().attr_value();
+            match attr_value {
+                Some(attr_value) => {
+                    let attr_value = (f64::from(attr_value) + delta).clamp(0.0, 1.0);
+                    self.set_attr_value(Prop::from(attr_value))
+                }
+                None => false,
+            }
+        }
+
+        // Returns false if the attribute can't be used for editing.
+        fn set_attr_value(&self, attr_value: Prop) -> bool {
+            if let Some(hcv) = self.colour.get() {
+                let mut manipulator = ColourManipulatorBuilder::new().init_hcv(&hcv).build();
+                match A::set_attr_value(&mut manipulator, attr_value) {
+                    None => return false,
+                    Some(Outcome::NoChange) | Some(Outcome::Rejected) => (),
+                    Some(_) => {
+                        let new_hcv = manipulator.hcv();
+                        self.set_rgb(Some(&new_hcv.rgb()));
+                        for callback in self.change_callbacks.borrow().iter() {
+                            callback(new_hcv)
+                        }
+                    }
+                }
+            }
+            true
+        }
+
+        #[cfg(feature = "draw_timing")]
+        pub fn draw_times(&self) -> &crate::timing::DrawTimes {
+            &self.draw_times
+        }
+    }
+
+    /// Builder for a single attribute display for use on its own i.e.
+    /// outside of a `ColourAttributeDisplayStack`.
+    pub struct ColourAttributeDisplayBuilder<A: attr_display::ColourAttributeDisplayIfce> {
+        orientation: gtk::Orientation,
+        show_label: bool,
+        interactive: bool,
+        size_request: Option<(i32, i32)>,
+        phantom: PhantomData<A>,
+    }
+
+    impl<A: attr_display::ColourAttributeDisplayIfce> Default for ColourAttributeDisplayBuilder<A> {
+        fn default() -> Self {
+            Self {
+                orientation: gtk::Orientation::Horizontal,
+                show_label: true,
+                interactive: false,
+                size_request: None,
+                phantom: PhantomData,
+            }
+        }
+    }
+
+    impl<A> ColourAttributeDisplayBuilder<A>
+    where
+        A: attr_display::ColourAttributeDisplayIfce + 'static,
+    {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Vertical displays have their minimum value at the bottom.
+        pub fn orientation(&mut self, orientation: gtk::Orientation) -> &mut Self {
+            self.orientation = orientation;
+            self
+        }
+
+        pub fn show_label(&mut self, show_label: bool) -> &mut Self {
+            self.show_label = show_label;
+            self
+        }
+
+        pub fn interactive(&mut self, interactive: bool) -> &mut Self {
+            self.interactive = interactive;
+            self
+        }
+
+        /// Defaults to 90 x 30 (or 30 x 90 for vertical displays).
+        pub fn size_request(&mut self, width: i32, height: i32) -> &mut Self {
+            self.size_request = Some((width, height));
+            self
+        }
+
+        pub fn build(&self) -> Rc<ColourAttributeDisplay<A>> {
+            let cad = Rc::new(ColourAttributeDisplay {
                 drawing_area: gtk::DrawingArea::new(),
                 attribute: RefCell::new(A::new()),
+                orientation: self.orientation,
+                show_label: self.show_label,
+                colour: Cell::new(None),
+                interactive: Cell::new(self.interactive),
+                change_callbacks: RefCell::new(vec![]),
+                #[cfg(feature = "draw_timing")]
+                draw_times: crate::timing::DrawTimes::default(),
             });
-            cad.drawing_area.set_size_request(90, 30);
+            let (width, height) = match self.size_request {
+                Some(size_request) => size_request,
+                None => match self.orientation {
+                    gtk::Orientation::Vertical => (30, 90),
+                    _ => (90, 30),
+                },
+            };
+            cad.drawing_area.set_size_request(width, height);
+            cad.drawing_area.add_events(
+                gdk::EventMask::BUTTON_PRESS_MASK
+                    | gdk::EventMask::BUTTON_MOTION_MASK
+                    | gdk::EventMask::SCROLL_MASK,
+            );
             let cad_c = Rc::clone(&cad);
             cad.drawing_area.connect_draw(move |da, cairo_context| {
-                let size = Size {
-                    width: da.get_allocated_width() as f64,
-                    height: da.get_allocated_height() as f64,
+                #[cfg(feature = "draw_timing")]
+                let start = std::time::Instant::now();
+                let width = da.get_allocated_width() as f64;
+                let height = da.get_allocated_height() as f64;
+                let size = match cad_c.orientation {
+                    gtk::Orientation::Vertical => {
+                        cairo_context.translate(0.0, height);
+                        cairo_context.rotate(-std::f64::consts::FRAC_PI_2);
+                        Size {
+                            width: height,
+                            height: width,
+                        }
+                    }
+                    _ => Size { width, height },
                 };
                 let drawer = Drawer::new(cairo_context, size);
-                cad_c.attribute.borrow().draw_all(&drawer);
+                if cad_c.show_label {
+                    cad_c.attribute.borrow().draw_all(&drawer);
+                } else {
+                    cad_c.attribute.borrow().draw_all_unlabelled(&drawer);
+                }
+                #[cfg(feature = "draw_timing")]
+                cad_c.draw_times.record(start.elapsed());
                 gtk::Inhibit(false)
             });
+
+            let cad_c = Rc::clone(&cad);
+            cad.drawing_area.connect_button_press_event(move |_, event| {
+                let (x, y) = event.get_position();
+                if event.get_event_type() == gdk::EventType::ButtonPress
+                    && event.get_button() == 1
+                    && cad_c.select_at(x, y)
+                {
+                    gtk::Inhibit(true)
+                } else {
+                    gtk::Inhibit(false)
+                }
+            });
+
+            let cad_c = Rc::clone(&cad);
+            cad.drawing_area.connect_motion_notify_event(move |_, event| {
+                let (x, y) = event.get_position();
+                if event.get_state().contains(gdk::ModifierType::BUTTON1_MASK)
+                    && cad_c.select_at(x, y)
+                {
+                    gtk::Inhibit(true)
+                } else {
+                    gtk::Inhibit(false)
+                }
+            });
+
+            let cad_c = Rc::clone(&cad);
+            cad.drawing_area
+                .connect_scroll_event(move |_, event| gtk::Inhibit(cad_c.scroll(event)));
             cad
         }
     }
 
+    impl WarmthCAD {
+        pub fn set_warmth_gradient(&self, warmth_gradient: bool) {
+            self.attribute
+                .borrow_mut()
+                .set_warmth_gradient(warmth_gradient);
+            self.drawing_area.queue_draw();
+        }
+    }
+
     impl<A> DynColourAttributeDisplay for ColourAttributeDisplay<A>
     where
         A: attr_display::ColourAttributeDisplayIfce + 'static,
     {
         fn set_rgb(&self, rgb: Option<&RGB<f64>>) {
+            self.colour.set(rgb.map(|rgb| rgb.hcv()));
             self.attribute.borrow_mut().set_colour(rgb);
             self.drawing_area.queue_draw();
         }
@@ -218,6 +711,14 @@ pub mod attributes {
             self.attribute.borrow_mut().set_target_colour(rgb);
             self.drawing_area.queue_draw();
         }
+
+        fn set_interactive(&self, interactive: bool) {
+            self.interactive.set(interactive)
+        }
+
+        fn connect_changed_boxed(&self, callback: ChangeCallback) {
+            self.change_callbacks.borrow_mut().push(callback)
+        }
     }
 
     type SelectionCallback = Box<dyn Fn(ScalarAttribute)>;
@@ -226,6 +727,7 @@ pub mod attributes {
     pub struct AttributeSelector {
         gtk_box: gtk::Box,
         attribute: Cell<ScalarAttribute>,
+        buttons: RefCell<Vec<(ScalarAttribute, gtk::RadioButton)>>,
         callbacks: RefCell<Vec<SelectionCallback>>,
     }
 
@@ -234,6 +736,19 @@ pub mod attributes {
             self.attribute.get()
         }
 
+        /// Select `attr` (as if the user had) if it is one of the choices.
+        pub fn set_attribute(&self, attr: ScalarAttribute) {
+            let button = self
+                .buttons
+                .borrow()
+                .iter()
+                .find(|(a, _)| *a == attr)
+                .map(|(_, button)| button.clone());
+            if let Some(button) = button {
+                button.set_active(true);
+            }
+        }
+
         pub fn connect_changed<F: Fn(ScalarAttribute) + 'static>(&self, callback: F) {
             self.callbacks.borrow_mut().push(Box::new(callback))
         }
@@ -279,6 +794,7 @@ pub mod attributes {
             let asrb = Rc::new(AttributeSelector {
                 gtk_box: gtk::Box::new(self.orientation, 0),
                 attribute: Cell::new(*self.attributes.first().expect("programmer error")),
+                buttons: RefCell::new(vec![]),
                 callbacks: RefCell::new(vec![]),
             });
 
@@ -291,6 +807,7 @@ pub mod attributes {
                 } else {
                     first = Some(button.clone())
                 }
+                asrb.buttons.borrow_mut().push((*attr, button.clone()));
                 let asrb_c = Rc::clone(&asrb);
                 let attr = *attr;
                 button.connect_toggled(move |button| {
@@ -4,6 +4,7 @@ use std::{
     rc::Rc,
 };
 
+use atk::prelude::*;
 use pw_gtk_ext::{
     cairo, gdk, gdk_pixbuf,
     gtk::{self, prelude::*, DrawingAreaBuilder},
@@ -13,24 +14,29 @@ use pw_gtk_ext::{
 };
 
 use colour_math::{
+    beigui::monochrome_annotation,
+    code::ColourCode,
     fdrn::Prop,
     hcv::HCV,
     hue::angle::Angle,
     manipulator::{ColourManipulator, ColourManipulatorBuilder},
+    recording::{Event, Operation, Recorder},
     LightLevel, Value, RGB,
 };
-use colour_math_cairo::Point;
+use colour_math_cairo::{CairoSetColour, Point};
 
 use crate::colour::ManipGdkColour;
 use crate::coloured::Colourable;
 
 macro_rules! connect_button {
-    ( $ed:ident, $btn:ident, $delta:ident, $apply:ident ) => {
+    ( $ed:ident, $btn:ident, $delta:ident, $apply:ident, $operation:ident ) => {
         let ced_c = Rc::clone(&$ed);
         $ed.$btn.connect_clicked(move |btn| {
             let delta = ced_c.delta_size.get().$delta();
+            let before = ced_c.colour_manipulator.borrow().hcv();
             let changed = ced_c.colour_manipulator.borrow_mut().$apply(delta);
             if changed {
+                ced_c.record(Operation::$operation(delta), &before);
                 let new_hcv = ced_c.colour_manipulator.borrow().hcv();
                 ced_c.set_colour_and_inform(&new_hcv);
             } else {
@@ -81,6 +87,26 @@ impl DeltaSize {
     }
 }
 
+/// Step sizes for adjusting the colour from the keyboard when the swatch
+/// area has the focus: up/down arrows change the value, left/right arrows
+/// change the chroma and shift+left/right rotate the hue.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct NudgeSteps {
+    pub value: Prop,
+    pub chroma: Prop,
+    pub hue: Angle,
+}
+
+impl Default for NudgeSteps {
+    fn default() -> Self {
+        Self {
+            value: DeltaSize::Normal.for_value(),
+            chroma: DeltaSize::Normal.for_chroma(),
+            hue: DeltaSize::Normal.for_hue_anticlockwise(),
+        }
+    }
+}
+
 struct Sample {
     pixbuf: gdk_pixbuf::Pixbuf,
     position: Point,
@@ -100,12 +126,14 @@ pub struct ColourManipulatorGUI {
     decr_chroma_btn: gtk::Button,
     incr_chroma_btn: gtk::Button,
     delta_size: Cell<DeltaSize>,
+    nudge_steps: Cell<NudgeSteps>,
     samples: RefCell<Vec<Sample>>,
     auto_match_btn: gtk::Button,
     auto_match_on_paste_btn: gtk::CheckButton,
     popup_menu: ManagedMenu,
     popup_menu_posn: Cell<Point>,
     change_callbacks: RefCell<Vec<ChangeCallback>>,
+    recorder: RefCell<Option<Rc<dyn Recorder>>>,
 }
 
 impl ColourManipulatorGUI {
@@ -125,9 +153,77 @@ impl ColourManipulatorGUI {
             .set_widget_colour(&colour.rotated(angle_offset));
         self.hue_right_btn
             .set_widget_colour(&colour.rotated(-angle_offset));
+        // so that screen readers announce keyboard adjustments
+        if let Some(accessible) = self.drawing_area.get_accessible() {
+            accessible.set_description(&monochrome_annotation(&colour.hcv()));
+        }
         self.drawing_area.queue_draw();
     }
 
+    pub fn set_nudge_steps(&self, nudge_steps: NudgeSteps) {
+        self.nudge_steps.set(nudge_steps);
+    }
+
+    // Adjust the colour in response to a key press in the swatch area
+    // returning whether the key was one of ours.
+    fn nudge(&self, key: &gdk::keys::Key, state: gdk::ModifierType) -> bool {
+        use gdk::keys::constants;
+        let steps = self.nudge_steps.get();
+        let operation = if state.contains(gdk::ModifierType::SHIFT_MASK) {
+            if *key == constants::Left {
+                Operation::Rotate(steps.hue)
+            } else if *key == constants::Right {
+                Operation::Rotate(-steps.hue)
+            } else {
+                return false;
+            }
+        } else if *key == constants::Up {
+            Operation::IncrValue(steps.value)
+        } else if *key == constants::Down {
+            Operation::DecrValue(steps.value)
+        } else if *key == constants::Left {
+            Operation::DecrChroma(steps.chroma)
+        } else if *key == constants::Right {
+            Operation::IncrChroma(steps.chroma)
+        } else {
+            return false;
+        };
+        let before = self.hcv();
+        let changed = operation.apply(&mut self.colour_manipulator.borrow_mut());
+        if changed {
+            self.record(operation, &before);
+            self.set_colour_and_inform(&self.hcv());
+        } else {
+            self.drawing_area.error_bell();
+        }
+        true
+    }
+
+    /// Emit an event for each operation performed by the user (including
+    /// colour changes made by containing widgets via `record()`).
+    pub fn set_recorder(&self, recorder: Option<Rc<dyn Recorder>>) {
+        *self.recorder.borrow_mut() = recorder;
+    }
+
+    /// Tell the recorder (if any) that `operation` changed the colour from
+    /// `before` to the current colour.
+    pub fn record(&self, operation: Operation, before: &HCV) {
+        if let Some(ref recorder) = *self.recorder.borrow() {
+            recorder.record(Event {
+                operation,
+                before: *before,
+                after: self.hcv(),
+            });
+        }
+    }
+
+    // A colour change made by the user rather than the program
+    fn set_colour_recorded(&self, colour: &impl ManipGdkColour) {
+        let before = self.hcv();
+        self.set_colour_and_inform(colour);
+        self.record(Operation::SetColour(colour.hcv()), &before);
+    }
+
     fn set_colour_and_inform(&self, colour: &impl ManipGdkColour) {
         self.set_colour(colour);
         for callback in self.change_callbacks.borrow().iter() {
@@ -136,9 +232,19 @@ impl ColourManipulatorGUI {
     }
 
     fn draw(&self, cairo_context: &cairo::Context) {
-        let rgb = self.colour_manipulator.borrow().rgb();
-        cairo_context.set_source_rgb(rgb[0], rgb[1], rgb[2]);
+        let rgb = self.colour_manipulator.borrow().rgb::<f64>();
+        cairo_context.set_source_colour_rgb(&rgb);
         cairo_context.paint();
+        if self.drawing_area.has_focus() {
+            gtk::render_focus(
+                &self.drawing_area.get_style_context(),
+                cairo_context,
+                0.0,
+                0.0,
+                self.drawing_area.get_allocated_width() as f64,
+                self.drawing_area.get_allocated_height() as f64,
+            );
+        }
         for sample in self.samples.borrow().iter() {
             let buffer = sample
                 .pixbuf
@@ -184,7 +290,7 @@ impl ColourManipulatorGUI {
                 (blue / divisor) as u8,
             ];
             let rgb: RGB<u8> = array.into();
-            self.set_colour_and_inform(&rgb);
+            self.set_colour_recorded(&rgb);
         }
     }
 
@@ -223,6 +329,7 @@ pub struct ColourManipulatorGUIBuilder {
     chroma_label: ChromaLabel,
     extra_buttons: Vec<gtk::Button>,
     clamped: bool,
+    nudge_steps: NudgeSteps,
 }
 
 impl ColourManipulatorGUIBuilder {
@@ -245,6 +352,11 @@ impl ColourManipulatorGUIBuilder {
         self
     }
 
+    pub fn nudge_steps(&mut self, nudge_steps: NudgeSteps) -> &mut Self {
+        self.nudge_steps = nudge_steps;
+        self
+    }
+
     pub fn build(&self) -> Rc<ColourManipulatorGUI> {
         let vbox = gtk::BoxBuilder::new()
             .orientation(gtk::Orientation::Vertical)
@@ -261,7 +373,13 @@ impl ColourManipulatorGUIBuilder {
                 .build(),
         );
         let drawing_area = DrawingAreaBuilder::new()
-            .events(gdk::EventMask::BUTTON_PRESS_MASK)
+            .events(
+                gdk::EventMask::BUTTON_PRESS_MASK
+                    | gdk::EventMask::KEY_PRESS_MASK
+                    | gdk::EventMask::FOCUS_CHANGE_MASK,
+            )
+            .can_focus(true)
+            .tooltip_text("Arrow keys adjust value/chroma, shift+left/right rotate hue")
             .height_request(150)
             .width_request(150)
             .build();
@@ -276,12 +394,14 @@ impl ColourManipulatorGUIBuilder {
             decr_chroma_btn: gtk::Button::with_label("Chroma--"),
             incr_chroma_btn: gtk::Button::with_label("Chroma++"),
             delta_size: Cell::new(DeltaSize::Normal),
+            nudge_steps: Cell::new(self.nudge_steps),
             samples: RefCell::new(vec![]),
             auto_match_btn: gtk::Button::with_label("Auto Match"),
             auto_match_on_paste_btn: gtk::CheckButton::with_label("On Paste?"),
             popup_menu: ManagedMenuBuilder::new().build(),
             popup_menu_posn: Cell::new((0.0, 0.0).into()),
             change_callbacks: RefCell::new(Vec::new()),
+            recorder: RefCell::new(None),
         });
 
         rgbm_gui
@@ -361,12 +481,49 @@ impl ColourManipulatorGUIBuilder {
             Inhibit(true)
         });
 
-        connect_button!(rgbm_gui, incr_value_btn, for_value, incr_value);
-        connect_button!(rgbm_gui, decr_value_btn, for_value, decr_value);
-        connect_button!(rgbm_gui, incr_chroma_btn, for_chroma, incr_chroma);
-        connect_button!(rgbm_gui, decr_chroma_btn, for_chroma, decr_chroma);
-        connect_button!(rgbm_gui, hue_left_btn, for_hue_anticlockwise, rotate);
-        connect_button!(rgbm_gui, hue_right_btn, for_hue_clockwise, rotate);
+        let rgbm_gui_c = Rc::clone(&rgbm_gui);
+        rgbm_gui
+            .drawing_area
+            .connect_key_press_event(move |_, event| {
+                Inhibit(rgbm_gui_c.nudge(&event.get_keyval(), event.get_state()))
+            });
+        rgbm_gui
+            .drawing_area
+            .connect_focus_in_event(|drawing_area, _| {
+                drawing_area.queue_draw();
+                Inhibit(false)
+            });
+        rgbm_gui
+            .drawing_area
+            .connect_focus_out_event(|drawing_area, _| {
+                drawing_area.queue_draw();
+                Inhibit(false)
+            });
+
+        connect_button!(rgbm_gui, incr_value_btn, for_value, incr_value, IncrValue);
+        connect_button!(rgbm_gui, decr_value_btn, for_value, decr_value, DecrValue);
+        connect_button!(
+            rgbm_gui,
+            incr_chroma_btn,
+            for_chroma,
+            incr_chroma,
+            IncrChroma
+        );
+        connect_button!(
+            rgbm_gui,
+            decr_chroma_btn,
+            for_chroma,
+            decr_chroma,
+            DecrChroma
+        );
+        connect_button!(
+            rgbm_gui,
+            hue_left_btn,
+            for_hue_anticlockwise,
+            rotate,
+            Rotate
+        );
+        connect_button!(rgbm_gui, hue_right_btn, for_hue_clockwise, rotate, Rotate);
 
         let rgbm_gui_c = Rc::clone(&rgbm_gui);
         rgbm_gui
@@ -417,10 +574,45 @@ impl ColourManipulatorGUIBuilder {
                 rgbm_gui_c.drawing_area.queue_draw();
                 rgbm_gui_c.auto_match_btn.set_sensitive(false);
             });
+        for (name, label, tooltip) in [
+            (
+                "copy_rust",
+                "Copy as Rust const",
+                "Copy a Rust constant definition of the current colour to the clipboard",
+            ),
+            (
+                "copy_css",
+                "Copy as CSS variable",
+                "Copy a CSS custom property definition of the current colour to the clipboard",
+            ),
+            (
+                "copy_gdk_rgba",
+                "Copy as gdk::RGBA",
+                "Copy a gdk::RGBA expression for the current colour to the clipboard",
+            ),
+        ] {
+            let menu_item_spec = MenuItemSpec::from((label, None, Some(tooltip)));
+            let rgbm_gui_c = Rc::clone(&rgbm_gui);
+            rgbm_gui
+                .popup_menu
+                .append_item(name, &menu_item_spec, 0)
+                .expect("Duplicate menu item: copy")
+                .connect_activate(move |_| {
+                    let colour = rgbm_gui_c.hcv();
+                    let text = match name {
+                        "copy_rust" => colour.to_rust_code("COLOUR"),
+                        "copy_css" => colour.to_css_var("colour"),
+                        _ => colour.to_gdk_rgba_code(),
+                    };
+                    let cbd = gtk::Clipboard::get(&gdk::SELECTION_CLIPBOARD);
+                    cbd.set_text(&text);
+                });
+        }
         let rgbm_gui_c = Rc::clone(&rgbm_gui);
         rgbm_gui
             .drawing_area
             .connect_button_press_event(move |_, event| {
+                rgbm_gui_c.drawing_area.grab_focus();
                 if event.get_event_type() == gdk::EventType::ButtonPress && event.get_button() == 3
                 {
                     let position = Point::from(event.get_position());
@@ -0,0 +1,152 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use pw_gtk_ext::gtk::{self, prelude::*};
+
+use colour_math::{
+    distance::DistanceMetric,
+    matching::{PaintMatch, PaintMatcher},
+    project::Paint,
+    ColourBasics,
+};
+
+use crate::coloured::Colourable;
+
+type SelectionCallback = Box<dyn Fn(&Paint)>;
+
+/// A popover listing the paints nearest to a colour (with swatches and
+/// distances) from which the user may select one.
+pub struct PaintMatchPopover {
+    popover: gtk::Popover,
+    list_box: gtk::ListBox,
+    matcher: RefCell<PaintMatcher>,
+    count: Cell<usize>,
+    matches: RefCell<Vec<PaintMatch>>,
+    selection_callbacks: RefCell<Vec<SelectionCallback>>,
+}
+
+impl PaintMatchPopover {
+    pub fn popover(&self) -> &gtk::Popover {
+        &self.popover
+    }
+
+    pub fn metric(&self) -> DistanceMetric {
+        self.matcher.borrow().metric()
+    }
+
+    /// Takes effect at the next `show_matches()`.
+    pub fn set_metric(&self, metric: DistanceMetric) {
+        self.matcher.borrow_mut().set_metric(metric)
+    }
+
+    pub fn count(&self) -> usize {
+        self.count.get()
+    }
+
+    pub fn set_count(&self, count: usize) {
+        self.count.set(count)
+    }
+
+    fn add_row(&self, paint_match: &PaintMatch) {
+        let swatch = gtk::Label::new(Some(paint_match.paint.name()));
+        swatch.set_size_request(160, 24);
+        swatch.set_widget_colour(&paint_match.paint.colour());
+        let distance = gtk::Label::new(Some(&format!("{:.2}", paint_match.distance)));
+        let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 4);
+        hbox.pack_start(&swatch, true, true, 0);
+        hbox.pack_start(&distance, false, false, 0);
+        self.list_box.add(&hbox);
+    }
+
+    /// Fill the list with the paints nearest to `colour` and pop it up.
+    pub fn show_matches(&self, colour: &impl ColourBasics) {
+        for child in self.list_box.get_children() {
+            self.list_box.remove(&child);
+        }
+        let matches = self.matcher.borrow().nearest(colour, self.count.get());
+        for paint_match in matches.iter() {
+            self.add_row(paint_match);
+        }
+        *self.matches.borrow_mut() = matches;
+        self.popover.show_all();
+    }
+
+    pub fn connect_paint_selected<F: Fn(&Paint) + 'static>(&self, callback: F) {
+        self.selection_callbacks
+            .borrow_mut()
+            .push(Box::new(callback))
+    }
+
+    fn inform_paint_selected(&self, index: usize) {
+        let paint = match self.matches.borrow().get(index) {
+            Some(paint_match) => paint_match.paint.clone(),
+            None => return,
+        };
+        self.popover.hide();
+        for callback in self.selection_callbacks.borrow().iter() {
+            callback(&paint)
+        }
+    }
+}
+
+pub struct PaintMatchPopoverBuilder {
+    relative_to: Option<gtk::Widget>,
+    count: usize,
+}
+
+impl Default for PaintMatchPopoverBuilder {
+    fn default() -> Self {
+        Self {
+            relative_to: None,
+            count: 8,
+        }
+    }
+}
+
+impl PaintMatchPopoverBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn relative_to<W: IsA<gtk::Widget>>(&mut self, widget: &W) -> &mut Self {
+        self.relative_to = Some(widget.clone().upcast::<gtk::Widget>());
+        self
+    }
+
+    /// The (maximum) number of paints listed.
+    pub fn count(&mut self, count: usize) -> &mut Self {
+        self.count = count;
+        self
+    }
+
+    pub fn build(&self, matcher: PaintMatcher) -> Rc<PaintMatchPopover> {
+        let popover = gtk::Popover::new(self.relative_to.as_ref());
+        let list_box = gtk::ListBox::new();
+        list_box.set_selection_mode(gtk::SelectionMode::None);
+        list_box.set_activate_on_single_click(true);
+        popover.add(&list_box);
+        let paint_match_popover = Rc::new(PaintMatchPopover {
+            popover,
+            list_box,
+            matcher: RefCell::new(matcher),
+            count: Cell::new(self.count),
+            matches: RefCell::new(vec![]),
+            selection_callbacks: RefCell::new(vec![]),
+        });
+
+        let paint_match_popover_c = Rc::clone(&paint_match_popover);
+        paint_match_popover
+            .list_box
+            .connect_row_activated(move |_, row| {
+                let index = row.get_index();
+                if index >= 0 {
+                    paint_match_popover_c.inform_paint_selected(index as usize);
+                }
+            });
+
+        paint_match_popover
+    }
+}
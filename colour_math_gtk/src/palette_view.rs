@@ -0,0 +1,304 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+//! A list view of a palette's entries whose context menu applies the
+//! palette's batch name and tag operations to the selected entries.
+use std::{cell::RefCell, rc::Rc};
+
+use pw_gtk_ext::{
+    gdk, glib,
+    gtk::{self, prelude::*},
+    gtkx::menu::{ManagedMenu, ManagedMenuBuilder, MenuItemSpec},
+    sav_state::{MaskedCondns, SAV_NEXT_CONDN},
+    wrapper::*,
+};
+use regex::Regex;
+
+use colour_math::{palette::Palette, ColourBasics};
+
+use crate::cell_renderer::CellRendererColour;
+
+pub const HAS_SELECTION: u64 = SAV_NEXT_CONDN;
+
+const COLOUR_COLUMN: i32 = 0;
+const NAME_COLUMN: i32 = 1;
+const TAGS_COLUMN: i32 = 2;
+
+type ChangeCallback = Box<dyn Fn(&Palette)>;
+
+#[derive(PWO, Wrapper)]
+pub struct PaletteListView {
+    scrolled_window: gtk::ScrolledWindow,
+    tree_view: gtk::TreeView,
+    list_store: gtk::ListStore,
+    popup_menu: ManagedMenu,
+    palette: RefCell<Palette>,
+    change_callbacks: RefCell<Vec<ChangeCallback>>,
+}
+
+impl PaletteListView {
+    pub fn palette(&self) -> Palette {
+        self.palette.borrow().clone()
+    }
+
+    pub fn set_palette(&self, palette: &Palette) {
+        *self.palette.borrow_mut() = palette.clone();
+        self.repopulate();
+    }
+
+    pub fn connect_changed<F: Fn(&Palette) + 'static>(&self, callback: F) {
+        self.change_callbacks.borrow_mut().push(Box::new(callback));
+    }
+
+    fn repopulate(&self) {
+        self.list_store.clear();
+        for entry in self.palette.borrow().iter() {
+            let colour = entry.colour().pango_string();
+            let tags = entry.tags().join(", ");
+            self.list_store.insert_with_values(
+                None,
+                &[COLOUR_COLUMN as u32, NAME_COLUMN as u32, TAGS_COLUMN as u32],
+                &[&colour, &entry.name(), &tags],
+            );
+        }
+    }
+
+    fn selection(&self) -> Vec<usize> {
+        let (paths, _) = self.tree_view.get_selection().get_selected_rows();
+        paths
+            .iter()
+            .filter_map(|path| path.get_indices().first().map(|index| *index as usize))
+            .collect()
+    }
+
+    // Apply `operation` to the palette and, if anything changed, show
+    // the result and tell the interested parties.
+    fn apply(&self, operation: impl FnOnce(&mut Palette, &[usize]) -> usize) {
+        let selection = self.selection();
+        let count = operation(&mut self.palette.borrow_mut(), &selection);
+        if count > 0 {
+            self.repopulate();
+            let palette = self.palette.borrow();
+            for callback in self.change_callbacks.borrow().iter() {
+                callback(&palette);
+            }
+        }
+    }
+
+    // The text entered for each of `prompts` (and whether it may be empty)
+    // or None if the user cancelled or left a required one empty.
+    fn ask_for_text(&self, title: &str, prompts: &[(&str, bool)]) -> Option<Vec<String>> {
+        let parent = self
+            .tree_view
+            .get_toplevel()
+            .and_then(|widget| widget.downcast::<gtk::Window>().ok());
+        let dialog = gtk::Dialog::with_buttons(
+            Some(title),
+            parent.as_ref(),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("OK", gtk::ResponseType::Accept),
+            ],
+        );
+        dialog.set_default_response(gtk::ResponseType::Accept);
+        let grid = gtk::Grid::new();
+        grid.set_row_spacing(2);
+        grid.set_column_spacing(4);
+        let entries: Vec<gtk::Entry> = prompts
+            .iter()
+            .enumerate()
+            .map(|(row, (prompt, _))| {
+                let label = gtk::Label::new(Some(*prompt));
+                label.set_halign(gtk::Align::End);
+                let entry = gtk::Entry::new();
+                entry.set_activates_default(true);
+                grid.attach(&label, 0, row as i32, 1, 1);
+                grid.attach(&entry, 1, row as i32, 1, 1);
+                entry
+            })
+            .collect();
+        dialog.get_content_area().pack_start(&grid, true, true, 0);
+        dialog.show_all();
+        let response = dialog.run();
+        // optional text (e.g. a replacement) is kept as is as its spaces
+        // may be significant
+        let texts: Vec<String> = entries
+            .iter()
+            .zip(prompts.iter())
+            .map(|(entry, (_, may_be_empty))| {
+                let text = entry.get_text();
+                if *may_be_empty {
+                    text.to_string()
+                } else {
+                    text.trim().to_string()
+                }
+            })
+            .collect();
+        dialog.close();
+        let required_given = texts
+            .iter()
+            .zip(prompts.iter())
+            .all(|(text, (_, may_be_empty))| *may_be_empty || !text.is_empty());
+        if response == gtk::ResponseType::Accept && required_given {
+            Some(texts)
+        } else {
+            None
+        }
+    }
+
+    fn rename_matching(&self) {
+        if let Some(texts) = self.ask_for_text(
+            "Rename Matching",
+            &[("Pattern:", false), ("Replacement:", true)],
+        ) {
+            match Regex::new(&texts[0]) {
+                Ok(regex) => self.apply(|palette, selection| {
+                    palette.rename_matching(selection, &regex, &texts[1])
+                }),
+                Err(err) => self.inform_user("Invalid pattern.", Some(&err.to_string())),
+            }
+        }
+    }
+
+    fn prefix_names(&self) {
+        if let Some(texts) = self.ask_for_text("Add Prefix", &[("Prefix:", false)]) {
+            self.apply(|palette, selection| palette.prefix_names(selection, &texts[0]));
+        }
+    }
+
+    fn add_tag(&self) {
+        if let Some(texts) = self.ask_for_text("Add Tag", &[("Tag:", false)]) {
+            self.apply(|palette, selection| palette.add_tag(selection, &texts[0]));
+        }
+    }
+
+    fn remove_tag(&self) {
+        if let Some(texts) = self.ask_for_text("Remove Tag", &[("Tag:", false)]) {
+            self.apply(|palette, selection| palette.remove_tag(selection, &texts[0]));
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PaletteListViewBuilder {
+    palette: Palette,
+}
+
+impl PaletteListViewBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn palette(&mut self, palette: &Palette) -> &mut Self {
+        self.palette = palette.clone();
+        self
+    }
+
+    pub fn build(&self) -> Rc<PaletteListView> {
+        let list_store =
+            gtk::ListStore::new(&[glib::Type::String, glib::Type::String, glib::Type::String]);
+        let tree_view = gtk::TreeView::with_model(&list_store);
+        tree_view
+            .get_selection()
+            .set_mode(gtk::SelectionMode::Multiple);
+
+        let column = gtk::TreeViewColumn::new();
+        column.set_title("Colour");
+        CellRendererColour::new(COLOUR_COLUMN, None).pack_into(&column, false);
+        tree_view.append_column(&column);
+        for (title, text_column) in [("Name", NAME_COLUMN), ("Tags", TAGS_COLUMN)] {
+            let column = gtk::TreeViewColumn::new();
+            column.set_title(title);
+            column.set_resizable(true);
+            let cell = gtk::CellRendererText::new();
+            column.pack_start(&cell, true);
+            column.add_attribute(&cell, "text", text_column);
+            tree_view.append_column(&column);
+        }
+
+        let scrolled_window =
+            gtk::ScrolledWindow::new(None::<&gtk::Adjustment>, None::<&gtk::Adjustment>);
+        scrolled_window.add(&tree_view);
+
+        let palette_view = Rc::new(PaletteListView {
+            scrolled_window,
+            tree_view,
+            list_store,
+            popup_menu: ManagedMenuBuilder::new().build(),
+            palette: RefCell::new(self.palette.clone()),
+            change_callbacks: RefCell::new(Vec::new()),
+        });
+        palette_view.repopulate();
+        palette_view.scrolled_window.show_all();
+
+        // POPUP
+        let items: [(&str, &str, &str, fn(&PaletteListView)); 4] = [
+            (
+                "rename_matching",
+                "Rename Matching...",
+                "Replace a pattern in the names of the selected colours",
+                PaletteListView::rename_matching,
+            ),
+            (
+                "prefix_names",
+                "Add Prefix...",
+                "Prefix the names of the selected colours",
+                PaletteListView::prefix_names,
+            ),
+            (
+                "add_tag",
+                "Add Tag...",
+                "Add a tag to the selected colours",
+                PaletteListView::add_tag,
+            ),
+            (
+                "remove_tag",
+                "Remove Tag...",
+                "Remove a tag from the selected colours",
+                PaletteListView::remove_tag,
+            ),
+        ];
+        for (name, label, tooltip, action) in items {
+            let menu_item_spec = MenuItemSpec::from((label, None, Some(tooltip)));
+            let palette_view_c = Rc::clone(&palette_view);
+            palette_view
+                .popup_menu
+                .append_item(name, &menu_item_spec, HAS_SELECTION)
+                .unwrap_or_else(|_| panic!("Duplicate menu item: {name}"))
+                .connect_activate(move |_| action(&palette_view_c));
+        }
+
+        let palette_view_c = Rc::clone(&palette_view);
+        palette_view
+            .tree_view
+            .connect_button_press_event(move |tree_view, event| {
+                if event.get_event_type() == gdk::EventType::ButtonPress && event.get_button() == 3
+                {
+                    // act on the row under the pointer if it isn't selected
+                    let (x, y) = event.get_position();
+                    if let Some((Some(path), _, _, _)) =
+                        tree_view.get_path_at_pos(x as i32, y as i32)
+                    {
+                        let selection = tree_view.get_selection();
+                        if !selection.path_is_selected(&path) {
+                            selection.unselect_all();
+                            selection.select_path(&path);
+                        }
+                    }
+                    let condns = if palette_view_c.selection().is_empty() {
+                        0
+                    } else {
+                        HAS_SELECTION
+                    };
+                    palette_view_c.popup_menu.update_condns(MaskedCondns {
+                        condns,
+                        mask: HAS_SELECTION,
+                    });
+                    palette_view_c.popup_menu.popup_at_event(event);
+                    return Inhibit(true);
+                }
+                Inhibit(false)
+            });
+
+        palette_view
+    }
+}
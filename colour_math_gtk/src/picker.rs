@@ -0,0 +1,274 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use pw_gtk_ext::{
+    gtk::{self, prelude::*},
+    wrapper::*,
+};
+
+use colour_math::{hcv::HCV, ColourBasics, ManipulatedColour, ScalarAttribute, Value};
+
+use crate::{
+    attributes::{ColourAttributeDisplayStack, ColourAttributeDisplayStackBuilder},
+    colour::GdkColour,
+    coloured::Colourable,
+    hue_dial::{GtkHueDial, GtkHueDialBuilder},
+    rgb_entry::{RGBHexEntry, RGBHexEntryBuilder},
+    sv_box::{GtkSVBox, GtkSVBoxBuilder},
+};
+
+/// A ready made colour selection dialog (analogous to
+/// `gtk::ColorChooserDialog`) combining a hue dial, saturation/value square,
+/// hex entry, recently chosen colours and attribute displays.
+pub struct ColourPickerDialog {
+    dialog: gtk::Dialog,
+    hue_dial: Rc<GtkHueDial>,
+    sv_box: Rc<GtkSVBox>,
+    rgb_entry: Rc<RGBHexEntry<u8>>,
+    cads: Rc<ColourAttributeDisplayStack>,
+    swatch: gtk::Label,
+    recent_buttons: Vec<gtk::Button>,
+    recent_colours: RefCell<Vec<HCV>>,
+    colour: Cell<HCV>,
+    updating: Cell<bool>,
+}
+
+impl ColourPickerDialog {
+    pub fn new<W: IsA<gtk::Window>>(title: Option<&str>, parent: Option<&W>) -> Rc<Self> {
+        let mut builder = ColourPickerDialogBuilder::new();
+        if let Some(title) = title {
+            builder.title(title);
+        }
+        if let Some(parent) = parent {
+            builder.parent(parent);
+        }
+        builder.build()
+    }
+
+    pub fn dialog(&self) -> &gtk::Dialog {
+        &self.dialog
+    }
+
+    pub fn hcv(&self) -> HCV {
+        self.colour.get()
+    }
+
+    pub fn set_colour(&self, colour: &impl GdkColour) {
+        self.update_colour(&colour.hcv(), true);
+    }
+
+    fn update_colour(&self, colour: &HCV, update_entry: bool) {
+        self.updating.set(true);
+        self.colour.set(*colour);
+        self.hue_dial.set_colour(Some(colour));
+        self.sv_box.set_colour(Some(colour));
+        if update_entry {
+            self.rgb_entry.set_colour_rounded(colour);
+        }
+        self.cads.set_colour(Some(colour));
+        self.swatch.set_widget_colour(colour);
+        self.updating.set(false);
+    }
+
+    /// Most recent first.
+    pub fn recent_colours(&self) -> Vec<HCV> {
+        self.recent_colours.borrow().clone()
+    }
+
+    pub fn set_recent_colours(&self, colours: &[HCV]) {
+        let mut recent_colours = self.recent_colours.borrow_mut();
+        *recent_colours = colours
+            .iter()
+            .take(self.recent_buttons.len())
+            .copied()
+            .collect();
+        for (index, button) in self.recent_buttons.iter().enumerate() {
+            match recent_colours.get(index) {
+                Some(colour) => {
+                    button.set_widget_colour(colour);
+                    button.set_sensitive(true);
+                }
+                None => {
+                    button.set_widget_colour(&HCV::new_grey(Value::ONE / 2));
+                    button.set_sensitive(false);
+                }
+            }
+        }
+    }
+
+    fn add_recent_colour(&self, colour: &HCV) {
+        let mut colours = vec![*colour];
+        for recent_colour in self.recent_colours.borrow().iter() {
+            if recent_colour != colour {
+                colours.push(*recent_colour);
+            }
+        }
+        self.set_recent_colours(&colours);
+    }
+
+    /// Run the dialog modally returning the selected colour or `None` if the
+    /// user cancelled.
+    pub fn run(&self) -> Option<HCV> {
+        self.dialog.show_all();
+        let response = self.dialog.run();
+        self.dialog.hide();
+        if response == gtk::ResponseType::Accept {
+            let colour = self.hcv();
+            self.add_recent_colour(&colour);
+            Some(colour)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct ColourPickerDialogBuilder {
+    title: Option<String>,
+    parent: Option<gtk::Window>,
+    attributes: Vec<ScalarAttribute>,
+    initial_colour: HCV,
+    number_of_recent_colours: usize,
+}
+
+impl Default for ColourPickerDialogBuilder {
+    fn default() -> Self {
+        Self {
+            title: None,
+            parent: None,
+            attributes: vec![ScalarAttribute::Value, ScalarAttribute::Chroma],
+            initial_colour: HCV::new_grey(Value::ONE / 2),
+            number_of_recent_colours: 8,
+        }
+    }
+}
+
+impl ColourPickerDialogBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(&mut self, title: &str) -> &mut Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn parent<W: IsA<gtk::Window>>(&mut self, parent: &W) -> &mut Self {
+        self.parent = Some(parent.clone().upcast::<gtk::Window>());
+        self
+    }
+
+    pub fn attributes(&mut self, attributes: &[ScalarAttribute]) -> &mut Self {
+        self.attributes = attributes.to_vec();
+        self
+    }
+
+    pub fn initial_colour(&mut self, initial_colour: &impl GdkColour) -> &mut Self {
+        self.initial_colour = initial_colour.hcv();
+        self
+    }
+
+    pub fn number_of_recent_colours(&mut self, number_of_recent_colours: usize) -> &mut Self {
+        self.number_of_recent_colours = number_of_recent_colours;
+        self
+    }
+
+    pub fn build(&self) -> Rc<ColourPickerDialog> {
+        let dialog = gtk::Dialog::with_buttons(
+            Some(self.title.as_deref().unwrap_or("Select Colour")),
+            self.parent.as_ref(),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("Select", gtk::ResponseType::Accept),
+            ],
+        );
+        dialog.set_default_response(gtk::ResponseType::Accept);
+        // the dialog is reused by `run()` so hide rather than destroy it
+        dialog.connect_delete_event(|dialog, _| {
+            dialog.hide();
+            gtk::Inhibit(true)
+        });
+        let recent_buttons = (0..self.number_of_recent_colours)
+            .map(|_| {
+                let button = gtk::Button::new();
+                button.set_size_request(24, 24);
+                button
+            })
+            .collect();
+        let colour_picker = Rc::new(ColourPickerDialog {
+            dialog,
+            hue_dial: GtkHueDialBuilder::new().size(160).build(),
+            sv_box: GtkSVBoxBuilder::new().size(160).build(),
+            rgb_entry: RGBHexEntryBuilder::<u8>::new()
+                .editable(true)
+                .initial_colour(&self.initial_colour)
+                .build(),
+            cads: ColourAttributeDisplayStackBuilder::new()
+                .attributes(&self.attributes)
+                .build(),
+            swatch: gtk::Label::new(None),
+            recent_buttons,
+            recent_colours: RefCell::new(vec![]),
+            colour: Cell::new(self.initial_colour),
+            updating: Cell::new(false),
+        });
+
+        let content_area = colour_picker.dialog.get_content_area();
+        let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 2);
+        hbox.pack_start(colour_picker.hue_dial.pwo(), true, true, 0);
+        hbox.pack_start(colour_picker.sv_box.pwo(), true, true, 0);
+        content_area.pack_start(&hbox, true, true, 0);
+        colour_picker.swatch.set_size_request(-1, 32);
+        content_area.pack_start(&colour_picker.swatch, false, false, 0);
+        content_area.pack_start(colour_picker.rgb_entry.pwo(), false, false, 0);
+        content_area.pack_start(colour_picker.cads.pwo(), false, false, 0);
+        if !colour_picker.recent_buttons.is_empty() {
+            let recent_box = gtk::Box::new(gtk::Orientation::Horizontal, 1);
+            recent_box.pack_start(&gtk::Label::new(Some("Recent:")), false, false, 0);
+            for (index, button) in colour_picker.recent_buttons.iter().enumerate() {
+                recent_box.pack_start(button, false, false, 0);
+                let colour_picker_c = Rc::clone(&colour_picker);
+                button.connect_clicked(move |_| {
+                    let colour = colour_picker_c.recent_colours.borrow().get(index).copied();
+                    if let Some(colour) = colour {
+                        colour_picker_c.set_colour(&colour);
+                    }
+                });
+            }
+            content_area.pack_start(&recent_box, false, false, 0);
+        }
+        colour_picker.set_recent_colours(&[]);
+        colour_picker.set_colour(&self.initial_colour);
+
+        let colour_picker_c = Rc::clone(&colour_picker);
+        colour_picker.hue_dial.connect_changed(move |angle| {
+            if !colour_picker_c.updating.get() {
+                let colour = colour_picker_c.hcv();
+                if let Some(hue_angle) = colour.hue_angle() {
+                    colour_picker_c.set_colour(&colour.rotated(angle - hue_angle));
+                } else {
+                    colour_picker_c.sv_box.set_hue(angle.into());
+                }
+            }
+        });
+
+        let colour_picker_c = Rc::clone(&colour_picker);
+        colour_picker.sv_box.connect_changed(move |colour| {
+            if !colour_picker_c.updating.get() {
+                colour_picker_c.set_colour(colour);
+            }
+        });
+
+        let colour_picker_c = Rc::clone(&colour_picker);
+        colour_picker.rgb_entry.connect_colour_changed(move |colour| {
+            if !colour_picker_c.updating.get() {
+                colour_picker_c.update_colour(&colour, false);
+            }
+        });
+
+        colour_picker
+    }
+}
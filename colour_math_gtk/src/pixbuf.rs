@@ -0,0 +1,66 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use pw_gtk_ext::gdk_pixbuf;
+
+use colour_math::{
+    bulk,
+    transform::{ChainedTransform, PixelTransform},
+    ColourBasics, HCV, RGB,
+};
+
+/// Applies a chain of `PixelTransform`s (in the order they were added) to
+/// whole pixbufs respecting their rowstride and leaving alpha untouched.
+#[derive(Default)]
+pub struct PixbufTransformer {
+    chain: ChainedTransform,
+}
+
+impl From<ChainedTransform> for PixbufTransformer {
+    fn from(chain: ChainedTransform) -> Self {
+        Self { chain }
+    }
+}
+
+impl PixbufTransformer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn then(self, transform: impl PixelTransform + Send + Sync + 'static) -> Self {
+        Self {
+            chain: self.chain.then(transform),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.chain.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chain.is_empty()
+    }
+
+    pub fn transform_rgb(&self, rgb: &RGB<f64>) -> RGB<f64> {
+        self.chain.transform(rgb)
+    }
+
+    /// A transformed copy of `pixbuf` or `None` if it isn't 8 bit RGB(A)
+    /// (or can't be copied).
+    pub fn transform(&self, pixbuf: &gdk_pixbuf::Pixbuf) -> Option<gdk_pixbuf::Pixbuf> {
+        if pixbuf.get_colorspace() != gdk_pixbuf::Colorspace::Rgb
+            || pixbuf.get_bits_per_sample() != 8
+            || pixbuf.get_n_channels() < 3
+        {
+            return None;
+        }
+        let transformed = pixbuf.copy()?;
+        let width = transformed.get_width() as usize;
+        let rowstride = transformed.get_rowstride() as usize;
+        let n_channels = transformed.get_n_channels() as usize;
+        // the copy is ours alone so nothing else can access its pixels
+        let pixels = unsafe { transformed.get_pixels() };
+        bulk::transform_strided(pixels, width, rowstride, n_channels, &|hcv: &HCV| {
+            HCV::from(self.transform_rgb(&hcv.rgb::<f64>()))
+        });
+        Some(transformed)
+    }
+}
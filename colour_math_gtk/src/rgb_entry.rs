@@ -5,12 +5,13 @@ use std::{cell::RefCell, rc::Rc};
 use num_traits::Num;
 use num_traits_plus::NumberConstants;
 use pw_gtk_ext::{
+    gdk,
     gtk::{self, prelude::*},
     gtkx::entry::{HexEntry, HexEntryBuilder},
     wrapper::*,
 };
 
-use colour_math::{HCV, HueConstants, RGB, UnsignedLightLevel};
+use colour_math::{HCV, HueConstants, LightLevel, RGB, UnsignedLightLevel};
 
 use crate::colour::GdkColour;
 use crate::coloured::Colourable;
@@ -60,6 +61,39 @@ impl<U: Hexable> RGBHexEntry<U> {
             .push(Box::new(callback))
     }
 
+    // Scrolling up (or right) increments the channel by one (or 0x10 with
+    // shift).  Returns false if the event should be passed on.
+    fn scroll(&self, index: usize, event: &gdk::EventScroll) -> bool {
+        let step = if event.get_state().contains(gdk::ModifierType::SHIFT_MASK) {
+            U::from_str_radix("10", 16).unwrap_or_else(|_| U::one())
+        } else {
+            U::one()
+        };
+        let value = self.entries[index].value();
+        let new_value = match event.get_direction() {
+            gdk::ScrollDirection::Up | gdk::ScrollDirection::Right => {
+                if <U as LightLevel>::ONE - value > step {
+                    value + step
+                } else {
+                    <U as LightLevel>::ONE
+                }
+            }
+            gdk::ScrollDirection::Down | gdk::ScrollDirection::Left => {
+                if value > step {
+                    value - step
+                } else {
+                    <U as LightLevel>::ZERO
+                }
+            }
+            _ => return false,
+        };
+        if new_value != value {
+            self.entries[index].set_value(new_value);
+            self.inform_colour_changed();
+        }
+        true
+    }
+
     fn inform_colour_changed(&self) {
         let hcv: HCV = self.rgb().into();
         for callback in self.colour_change_callbacks.borrow().iter() {
@@ -68,6 +102,14 @@ impl<U: Hexable> RGBHexEntry<U> {
     }
 }
 
+impl RGBHexEntry<u8> {
+    /// As `set_colour()` but rounding to the nearest byte (rather than
+    /// truncating) so that linked displays don't drift.
+    pub fn set_colour_rounded(&self, colour: &impl GdkColour) {
+        self.set_rgb(&RGB::<u8>::rounded_from(colour))
+    }
+}
+
 #[derive(Default)]
 pub struct RGBHexEntryBuilder<U: Hexable> {
     initial_rgb: RGB<U>,
@@ -119,9 +161,16 @@ impl<U: Hexable> RGBHexEntryBuilder<U> {
             colour_change_callbacks: RefCell::new(vec![]),
         });
 
-        for entry in rgb_hex_entry.entries.iter() {
+        for (index, entry) in rgb_hex_entry.entries.iter().enumerate() {
             let rgb_hex_entry_c = Rc::clone(&rgb_hex_entry);
             entry.connect_value_changed(move |_| rgb_hex_entry_c.inform_colour_changed());
+            if self.editable {
+                entry.pwo().add_events(gdk::EventMask::SCROLL_MASK);
+                let rgb_hex_entry_c = Rc::clone(&rgb_hex_entry);
+                entry.pwo().connect_scroll_event(move |_, event| {
+                    gtk::Inhibit(rgb_hex_entry_c.scroll(index, event))
+                });
+            }
         }
 
         rgb_hex_entry
@@ -0,0 +1,82 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{cell::RefCell, rc::Rc};
+
+use pw_gtk_ext::{
+    gtk::{self, prelude::*},
+    wrapper::*,
+};
+
+use colour_math::{beigui::sparkline::ErrorSparkline, mixing::TargetErrors};
+use colour_math_cairo::{Drawer, Size};
+
+#[derive(PWO, Wrapper)]
+pub struct GtkErrorSparkline {
+    drawing_area: gtk::DrawingArea,
+    sparkline: RefCell<ErrorSparkline>,
+}
+
+impl GtkErrorSparkline {
+    pub fn set_history(&self, history: &[TargetErrors]) {
+        self.sparkline.borrow_mut().set_history(history);
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn push(&self, errors: TargetErrors) {
+        self.sparkline.borrow_mut().push(errors);
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn clear(&self) {
+        self.sparkline.borrow_mut().clear();
+        self.drawing_area.queue_draw();
+    }
+}
+
+pub struct GtkErrorSparklineBuilder {
+    width: i32,
+    height: i32,
+}
+
+impl Default for GtkErrorSparklineBuilder {
+    fn default() -> Self {
+        Self {
+            width: 120,
+            height: 30,
+        }
+    }
+}
+
+impl GtkErrorSparklineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn size(&mut self, width: i32, height: i32) -> &mut Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn build(&self) -> Rc<GtkErrorSparkline> {
+        let gtk_sparkline = Rc::new(GtkErrorSparkline {
+            drawing_area: gtk::DrawingArea::new(),
+            sparkline: RefCell::new(ErrorSparkline::default()),
+        });
+        gtk_sparkline
+            .drawing_area
+            .set_size_request(self.width, self.height);
+        let gtk_sparkline_c = Rc::clone(&gtk_sparkline);
+        gtk_sparkline
+            .drawing_area
+            .connect_draw(move |da, cairo_context| {
+                let size = Size {
+                    width: da.get_allocated_width() as f64,
+                    height: da.get_allocated_height() as f64,
+                };
+                let drawer = Drawer::new(cairo_context, size);
+                gtk_sparkline_c.sparkline.borrow().draw(&drawer);
+                gtk::Inhibit(false)
+            });
+        gtk_sparkline
+    }
+}
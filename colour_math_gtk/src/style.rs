@@ -0,0 +1,131 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::fmt::Write;
+
+use pw_gtk_ext::{
+    gdk, glib,
+    gtk::{self, prelude::*},
+};
+
+use colour_math::{hcv::HCV, Angle, ColourBasics, HueConstants, ManipulatedColour, Prop};
+
+/// A small set of colours with semantic roles from which a GTK style sheet
+/// is generated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SemanticPalette {
+    /// Headers and suggested action buttons.
+    pub primary: HCV,
+    /// Selections.
+    pub accent: HCV,
+    pub warning: HCV,
+    /// Destructive action buttons.
+    pub error: HCV,
+}
+
+impl Default for SemanticPalette {
+    fn default() -> Self {
+        Self::from_primary(&HCV::BLUE)
+    }
+}
+
+impl SemanticPalette {
+    /// Derive the other roles from a (brand) primary colour: the accent is
+    /// its complement.
+    pub fn from_primary(primary: &impl ColourBasics) -> Self {
+        let primary = primary.hcv();
+        Self {
+            primary,
+            accent: primary.rotated(Angle::from(180)),
+            warning: HCV::RED_YELLOW,
+            error: HCV::RED,
+        }
+    }
+
+    /// How much hover shades are lightened and active shades darkened.
+    const SHADE: f64 = 0.15;
+
+    fn hover(colour: &HCV) -> HCV {
+        colour.lightened(Prop::from(Self::SHADE))
+    }
+
+    fn active(colour: &HCV) -> HCV {
+        colour.darkened(Prop::from(Self::SHADE))
+    }
+
+    fn write_rule(css: &mut String, selector: &str, colour: &HCV) {
+        writeln!(
+            css,
+            "{selector} {{ background-image: none; background-color: {}; color: {}; }}",
+            colour.pango_string(),
+            colour.best_foreground().pango_string()
+        )
+        .expect("writing to a String can't fail");
+    }
+
+    fn write_button_rules(css: &mut String, selector: &str, colour: &HCV) {
+        Self::write_rule(css, selector, colour);
+        Self::write_rule(css, &format!("{selector}:hover"), &Self::hover(colour));
+        Self::write_rule(css, &format!("{selector}:active"), &Self::active(colour));
+        Self::write_rule(css, &format!("{selector}:checked"), &Self::active(colour));
+    }
+
+    /// GTK3 CSS for headers, buttons and selections (with hover and active
+    /// shades derived from each colour) plus `@define-color` entries so that
+    /// the palette can be referred to from other style sheets.
+    pub fn css(&self) -> String {
+        let mut css = String::new();
+        for (name, colour) in [
+            ("primary", self.primary),
+            ("accent", self.accent),
+            ("warning", self.warning),
+            ("error", self.error),
+        ] {
+            writeln!(css, "@define-color {name}_color {};", colour.pango_string())
+                .expect("writing to a String can't fail");
+            writeln!(
+                css,
+                "@define-color {name}_fg_color {};",
+                colour.best_foreground().pango_string()
+            )
+            .expect("writing to a String can't fail");
+        }
+        Self::write_rule(&mut css, "headerbar", &self.primary);
+        Self::write_rule(&mut css, ".titlebar", &self.primary);
+        Self::write_button_rules(&mut css, "button.suggested-action", &self.primary);
+        Self::write_button_rules(&mut css, "button.destructive-action", &self.error);
+        writeln!(
+            css,
+            "selection, *:selected {{ background-color: {}; color: {}; }}",
+            self.accent.pango_string(),
+            self.accent.best_foreground().pango_string()
+        )
+        .expect("writing to a String can't fail");
+        writeln!(
+            css,
+            ".warning {{ background-color: {}; color: {}; }}",
+            self.warning.pango_string(),
+            self.warning.best_foreground().pango_string()
+        )
+        .expect("writing to a String can't fail");
+        css
+    }
+
+    pub fn css_provider(&self) -> Result<gtk::CssProvider, glib::Error> {
+        let provider = gtk::CssProvider::new();
+        provider.load_from_data(self.css().as_bytes())?;
+        Ok(provider)
+    }
+
+    /// Style every widget on the default screen with this palette.  Keep the
+    /// returned provider to remove (or replace) the styling later.
+    pub fn apply_to_default_screen(&self) -> Result<gtk::CssProvider, glib::Error> {
+        let provider = self.css_provider()?;
+        if let Some(screen) = gdk::Screen::get_default() {
+            gtk::StyleContext::add_provider_for_screen(
+                &screen,
+                &provider,
+                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+            );
+        }
+        Ok(provider)
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{cell::RefCell, rc::Rc};
+
+use pw_gtk_ext::{
+    gdk,
+    gtk::{self, prelude::*},
+    wrapper::*,
+};
+
+use colour_math::{beigui::sv_box::SVBox, hcv::HCV, Hue};
+use colour_math_cairo::{Drawer, Point, Size};
+
+use crate::{colour::GdkColour, hue_dial::GtkHueDial};
+
+type ChangeCallback = Box<dyn Fn(&HCV)>;
+
+/// A saturation/value square for the current hue.
+#[derive(PWO, Wrapper)]
+pub struct GtkSVBox {
+    drawing_area: gtk::DrawingArea,
+    sv_box: RefCell<SVBox>,
+    change_callbacks: RefCell<Vec<ChangeCallback>>,
+}
+
+impl GtkSVBox {
+    fn size(&self) -> Size {
+        Size {
+            width: self.drawing_area.get_allocated_width() as f64,
+            height: self.drawing_area.get_allocated_height() as f64,
+        }
+    }
+
+    pub fn hue(&self) -> Hue {
+        self.sv_box.borrow().hue()
+    }
+
+    pub fn set_hue(&self, hue: Hue) {
+        self.sv_box.borrow_mut().set_hue(hue);
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn set_colour(&self, colour: Option<&impl GdkColour>) {
+        self.sv_box.borrow_mut().set_colour(colour);
+        self.drawing_area.queue_draw();
+    }
+
+    /// Keep this box's hue in step with `hue_dial`.
+    pub fn link_hue_dial(self: &Rc<Self>, hue_dial: &GtkHueDial) {
+        let self_c = Rc::clone(self);
+        hue_dial.connect_changed(move |angle| self_c.set_hue(Hue::from(angle)));
+    }
+
+    fn select_at(&self, x: f64, y: f64) {
+        let point: Point = (x, y).into();
+        let colour = self
+            .sv_box
+            .borrow()
+            .colour_for_point(point.into(), self.size().into());
+        self.set_colour(Some(&colour));
+        self.inform_change(&colour);
+    }
+
+    fn inform_change(&self, colour: &HCV) {
+        for callback in self.change_callbacks.borrow().iter() {
+            callback(colour)
+        }
+    }
+
+    pub fn connect_changed<F: Fn(&HCV) + 'static>(&self, callback: F) {
+        self.change_callbacks.borrow_mut().push(Box::new(callback))
+    }
+}
+
+#[derive(Default)]
+pub struct GtkSVBoxBuilder {
+    size: Option<i32>,
+    cells: Option<u8>,
+}
+
+impl GtkSVBoxBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn size(&mut self, size: i32) -> &mut Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn cells(&mut self, cells: u8) -> &mut Self {
+        self.cells = Some(cells);
+        self
+    }
+
+    pub fn build(&self) -> Rc<GtkSVBox> {
+        let sv_box = match self.cells {
+            Some(cells) => SVBox::new(cells),
+            None => SVBox::default(),
+        };
+        let gtk_sv_box = Rc::new(GtkSVBox {
+            drawing_area: gtk::DrawingArea::new(),
+            sv_box: RefCell::new(sv_box),
+            change_callbacks: RefCell::new(vec![]),
+        });
+        let size = self.size.unwrap_or(120);
+        gtk_sv_box.drawing_area.set_size_request(size, size);
+        gtk_sv_box.drawing_area.add_events(
+            gdk::EventMask::BUTTON_PRESS_MASK | gdk::EventMask::BUTTON_MOTION_MASK,
+        );
+
+        let gtk_sv_box_c = Rc::clone(&gtk_sv_box);
+        gtk_sv_box
+            .drawing_area
+            .connect_draw(move |_, cairo_context| {
+                let drawer = Drawer::new(cairo_context, gtk_sv_box_c.size());
+                gtk_sv_box_c.sv_box.borrow().draw(&drawer);
+                gtk::Inhibit(false)
+            });
+
+        let gtk_sv_box_c = Rc::clone(&gtk_sv_box);
+        gtk_sv_box
+            .drawing_area
+            .connect_button_press_event(move |_, event| {
+                if event.get_event_type() == gdk::EventType::ButtonPress && event.get_button() == 1
+                {
+                    let (x, y) = event.get_position();
+                    gtk_sv_box_c.select_at(x, y);
+                    gtk::Inhibit(true)
+                } else {
+                    gtk::Inhibit(false)
+                }
+            });
+
+        let gtk_sv_box_c = Rc::clone(&gtk_sv_box);
+        gtk_sv_box
+            .drawing_area
+            .connect_motion_notify_event(move |_, event| {
+                if event.get_state().contains(gdk::ModifierType::BUTTON1_MASK) {
+                    let (x, y) = event.get_position();
+                    gtk_sv_box_c.select_at(x, y);
+                    gtk::Inhibit(true)
+                } else {
+                    gtk::Inhibit(false)
+                }
+            });
+
+        gtk_sv_box
+    }
+}
@@ -0,0 +1,40 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    time::Duration,
+};
+
+/// Records how long a widget's recent draws took.
+#[derive(Debug, Default)]
+pub struct DrawTimes {
+    last: Cell<Option<Duration>>,
+    recent: RefCell<VecDeque<Duration>>,
+}
+
+impl DrawTimes {
+    const WINDOW: usize = 32;
+
+    pub fn record(&self, duration: Duration) {
+        self.last.set(Some(duration));
+        let mut recent = self.recent.borrow_mut();
+        if recent.len() == Self::WINDOW {
+            recent.pop_front();
+        }
+        recent.push_back(duration);
+    }
+
+    pub fn last_draw_duration(&self) -> Option<Duration> {
+        self.last.get()
+    }
+
+    /// The mean of the (up to 32) most recent draw durations.
+    pub fn average_draw_duration(&self) -> Option<Duration> {
+        let recent = self.recent.borrow();
+        if recent.is_empty() {
+            None
+        } else {
+            Some(recent.iter().sum::<Duration>() / recent.len() as u32)
+        }
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{cell::RefCell, rc::Rc};
+
+use pw_gtk_ext::{
+    gtk::{self, prelude::*},
+    wrapper::*,
+};
+
+use colour_math::beigui::value_scale::ValueScale;
+use colour_math_cairo::{Drawer, Size};
+
+use crate::colour::GdkColour;
+
+#[derive(PWO, Wrapper)]
+pub struct GtkValueScale {
+    drawing_area: gtk::DrawingArea,
+    value_scale: RefCell<ValueScale>,
+}
+
+impl GtkValueScale {
+    pub fn set_colour(&self, colour: Option<&impl GdkColour>) {
+        self.value_scale.borrow_mut().set_colour(colour);
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn set_steps(&self, steps: u8) {
+        self.value_scale.borrow_mut().set_steps(steps);
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn current_step(&self) -> Option<u8> {
+        self.value_scale.borrow().current_step()
+    }
+}
+
+pub struct GtkValueScaleBuilder {
+    steps: u8,
+}
+
+impl Default for GtkValueScaleBuilder {
+    fn default() -> Self {
+        Self { steps: 11 }
+    }
+}
+
+impl GtkValueScaleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn steps(&mut self, steps: u8) -> &mut Self {
+        self.steps = steps;
+        self
+    }
+
+    pub fn build(&self) -> Rc<GtkValueScale> {
+        let gtk_value_scale = Rc::new(GtkValueScale {
+            drawing_area: gtk::DrawingArea::new(),
+            value_scale: RefCell::new(ValueScale::new(self.steps)),
+        });
+        gtk_value_scale
+            .drawing_area
+            .set_size_request(30 * self.steps as i32, 30);
+        let gtk_value_scale_c = Rc::clone(&gtk_value_scale);
+        gtk_value_scale
+            .drawing_area
+            .connect_draw(move |da, cairo_context| {
+                let size = Size {
+                    width: da.get_allocated_width() as f64,
+                    height: da.get_allocated_height() as f64,
+                };
+                let drawer = Drawer::new(cairo_context, size);
+                gtk_value_scale_c.value_scale.borrow().draw(&drawer);
+                gtk::Inhibit(false)
+            });
+        gtk_value_scale
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2021 Peter Williams <pwil3058@gmail.com> <pwil3058@bigpond.net.au>
+use std::{cell::RefCell, thread};
+
+use pw_gtk_ext::glib;
+
+use colour_math::{
+    progress::{CancelToken, Cancelled, Progress},
+    Prop,
+};
+
+enum Message<T> {
+    Progress(Prop),
+    Done(Result<T, Cancelled>),
+}
+
+/// The `Progress` handed to a computation running in the background. Its
+/// reports are forwarded to the main loop.
+pub struct WorkerProgress<T> {
+    sender: glib::Sender<Message<T>>,
+    cancel_token: CancelToken,
+}
+
+impl<T> Progress for WorkerProgress<T> {
+    fn report(&self, done: Prop) {
+        // the receiver may have gone if the GUI has been torn down
+        self.sender.send(Message::Progress(done)).ok();
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_token.is_cancelled()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkerHandle {
+    cancel_token: CancelToken,
+}
+
+impl WorkerHandle {
+    pub fn cancel(&self) {
+        self.cancel_token.cancel()
+    }
+
+    pub fn cancel_token(&self) -> &CancelToken {
+        &self.cancel_token
+    }
+}
+
+/// Run `computation` on its own thread.  Progress reports are passed to
+/// `on_progress` and the result to `on_done`: both on the main loop so
+/// they may safely update widgets.
+pub fn spawn_with_progress<T, F, P, D>(computation: F, on_progress: P, on_done: D) -> WorkerHandle
+where
+    T: Send + 'static,
+    F: FnOnce(&WorkerProgress<T>) -> Result<T, Cancelled> + Send + 'static,
+    P: Fn(Prop) + 'static,
+    D: FnOnce(Result<T, Cancelled>) + 'static,
+{
+    let cancel_token = CancelToken::new();
+    let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+    let progress = WorkerProgress {
+        sender,
+        cancel_token: cancel_token.clone(),
+    };
+    thread::spawn(move || {
+        let result = computation(&progress);
+        progress.sender.send(Message::Done(result)).ok();
+    });
+    let on_done = RefCell::new(Some(on_done));
+    receiver.attach(None, move |message| match message {
+        Message::Progress(done) => {
+            on_progress(done);
+            glib::Continue(true)
+        }
+        Message::Done(result) => {
+            if let Some(on_done) = on_done.borrow_mut().take() {
+                on_done(result);
+            }
+            glib::Continue(false)
+        }
+    });
+    WorkerHandle { cancel_token }
+}
+
+/// Run `computation` on its own thread and pass its result to `on_done` on
+/// the main loop.
+pub fn spawn<T, F, D>(computation: F, on_done: D) -> WorkerHandle
+where
+    T: Send + 'static,
+    F: FnOnce(&WorkerProgress<T>) -> Result<T, Cancelled> + Send + 'static,
+    D: FnOnce(Result<T, Cancelled>) + 'static,
+{
+    spawn_with_progress(computation, |_| (), on_done)
+}
@@ -78,15 +78,8 @@ fn main() {
         vbox.pack_start(&btn, false, false, 0);
     }
 
-    let attributes = vec![
-        ScalarAttribute::Value,
-        ScalarAttribute::Chroma,
-        ScalarAttribute::Greyness,
-        ScalarAttribute::Warmth,
-    ];
-
     let cads = ColourAttributeDisplayStackBuilder::new()
-        .attributes(&attributes)
+        .attributes(&ScalarAttribute::ALL)
         .build();
     cads.set_colour(Some(&RGB::from([0.1, 0.4, 0.7])));
     cads.set_target_colour(Some(&RGB::from([0.7, 0.4, 0.7])));
@@ -108,7 +101,7 @@ fn main() {
     // vbox.pack_start(&colour_manipulator.pwo(), true, true, 0);
 
     let gtk_hue_wheel = GtkHueWheelBuilder::new()
-        .attributes(&attributes)
+        .attributes(&ScalarAttribute::ALL)
         .menu_item_specs(&[("add", ("Add", None, Some("Add something")).into(), 0)])
         .build();
     vbox.pack_start(gtk_hue_wheel.pwo(), true, true, 0);
@@ -132,7 +125,7 @@ fn main() {
     ));
 
     let colour_editor = ColourEditorBuilder::new()
-        .attributes(&attributes)
+        .attributes(&ScalarAttribute::ALL)
         .build::<u16>();
     let cads_c = Rc::clone(&cads);
     colour_editor.connect_changed(move |c| cads_c.set_colour(Some(c)));